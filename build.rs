@@ -0,0 +1,38 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "server")]
+    server::generate();
+}
+
+/// Generates the `myosotis.Memory` gRPC stubs from the message types
+/// defined in `src/server.rs`, so no `protoc` is needed at build time.
+#[cfg(feature = "server")]
+mod server {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    const METHODS: &[(&str, &str, &str, &str)] = &[
+        ("get_node", "GetNode", "GetNodeRequest", "NodeReply"),
+        ("mutate", "Mutate", "MutateRequest", "MutateResponse"),
+        ("commit", "Commit", "CommitRequest", "CommitResponse"),
+        ("dry_run", "DryRun", "DryRunRequest", "DryRunResponse"),
+        ("history", "History", "HistoryRequest", "HistoryResponse"),
+        ("state_at", "StateAt", "StateAtRequest", "StateAtResponse"),
+        ("compact", "Compact", "CompactRequest", "CompactResponse"),
+    ];
+
+    pub fn generate() {
+        let mut service = Service::builder().name("Memory").package("myosotis");
+        for (name, route, input, output) in METHODS {
+            service = service.method(
+                Method::builder()
+                    .name(*name)
+                    .route_name(*route)
+                    .input_type(format!("crate::server::proto::{}", input))
+                    .output_type(format!("crate::server::proto::{}", output))
+                    .codec_path("tonic_prost::ProstCodec")
+                    .build(),
+            );
+        }
+        Builder::new().compile(&[service.build()]);
+    }
+}
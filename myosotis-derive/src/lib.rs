@@ -0,0 +1,104 @@
+//! `#[derive(MyoNode)]` for `myosotis::typed::MyoNode`.
+//!
+//! Each named field maps to a node field of the same name, converted with
+//! `myosotis::typed::FieldValue`. The node type defaults to the struct name.
+//!
+//! ```ignore
+//! #[derive(MyoNode)]
+//! #[myo(type = "Agent")]
+//! struct AgentRecord {
+//!     name: String,
+//!     #[myo(rename = "objective")]
+//!     goal: String,
+//!     score: Option<i64>,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, parse_macro_input};
+
+#[proc_macro_derive(MyoNode, attributes(myo))]
+pub fn derive_myo_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let mut ty = name.to_string();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("myo")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type") {
+                ty = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `type = \"...\"`"))
+            }
+        })?;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "MyoNode can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "MyoNode needs a struct with named fields",
+        ));
+    };
+
+    let mut idents = Vec::new();
+    let mut keys = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named field");
+        let mut key = ident.to_string();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("myo")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    key = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"`"))
+                }
+            })?;
+        }
+        idents.push(ident);
+        keys.push(key);
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::myosotis::typed::MyoNode for #name #ty_generics #where_clause {
+            const TYPE: &'static str = #ty;
+
+            fn to_fields(&self) -> ::std::vec::Vec<(&'static str, ::myosotis::node::Value)> {
+                let mut fields = ::std::vec::Vec::new();
+                #(
+                    if let ::std::option::Option::Some(value) =
+                        ::myosotis::typed::FieldValue::to_value(&self.#idents)
+                    {
+                        fields.push((#keys, value));
+                    }
+                )*
+                fields
+            }
+
+            fn from_node(
+                node: &::myosotis::node::Node,
+            ) -> ::std::result::Result<Self, ::myosotis::MyosotisError> {
+                ::myosotis::typed::check_type(node, #ty)?;
+                ::std::result::Result::Ok(Self {
+                    #(
+                        #idents: ::myosotis::typed::field(node, #keys)?,
+                    )*
+                })
+            }
+        }
+    })
+}
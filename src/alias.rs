@@ -0,0 +1,77 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use std::collections::HashMap;
+
+/// Node carrying `alias` in `state`, by scanning. `Memory::resolve` uses
+/// the index instead.
+pub fn find_alias(state: &HashMap<NodeId, Node>, alias: &str) -> Option<NodeId> {
+    state
+        .values()
+        .find(|node| node.alias.as_deref() == Some(alias))
+        .map(|node| node.id)
+}
+
+impl Memory {
+    /// Stages a new node of type `ty` with `alias`, which must be unused.
+    pub fn create_with_alias(&mut self, ty: &str, alias: &str) -> Result<NodeId, MyosotisError> {
+        if let Some(other) = self.resolve(alias) {
+            return Err(MyosotisError::AliasTaken(alias.to_string(), other));
+        }
+        let id = self.create(ty);
+        self.set_alias(id, Some(alias))?;
+        Ok(id)
+    }
+
+    /// Stages setting, or with `None` clearing, the alias of node `id`.
+    pub fn set_alias(&mut self, id: NodeId, alias: Option<&str>) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::SetAlias {
+            id,
+            alias: alias.map(str::to_string),
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.alias_index.retain(|_, node| *node != id);
+        if let Some(alias) = alias {
+            self.alias_index.insert(alias.to_string(), id);
+        }
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Node carrying `alias` in head state, deleted or not. Falls back to
+    /// a scan when the index is stale, e.g. after a batch was applied.
+    pub fn resolve(&self, alias: &str) -> Option<NodeId> {
+        match self.alias_index.get(alias) {
+            Some(id)
+                if self
+                    .head_state
+                    .get(id)
+                    .is_some_and(|node| node.alias.as_deref() == Some(alias)) =>
+            {
+                Some(*id)
+            }
+            _ => find_alias(&self.head_state, alias),
+        }
+    }
+
+    /// Target of a `Ref` or `AliasRef` value; `None` for other values and
+    /// for aliases no node carries.
+    pub fn resolve_ref(&self, value: &Value) -> Option<NodeId> {
+        match value {
+            Value::Ref(id) => Some(*id),
+            Value::AliasRef(alias) => self.resolve(alias),
+            _ => None,
+        }
+    }
+
+    /// Rebuilds the alias index after `head_state` was replaced wholesale.
+    pub(crate) fn reindex_aliases(&mut self) {
+        self.alias_index = self
+            .head_state
+            .values()
+            .filter_map(|node| Some((node.alias.clone()?, node.id)))
+            .collect();
+    }
+}
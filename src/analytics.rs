@@ -0,0 +1,183 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::NodeId;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// What `Memory::report` covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportOptions {
+    /// Width of the commit frequency periods, in seconds.
+    pub period_secs: u64,
+    /// Length of the most-mutated and largest-value lists.
+    pub top: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            period_secs: 86_400,
+            top: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TypeStats {
+    pub live_nodes: usize,
+    pub deleted_nodes: usize,
+    /// Committed mutations to nodes of the type.
+    pub mutations: usize,
+    /// Encoded size of the field values the type holds in head state.
+    pub value_bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeriodActivity {
+    /// Start of the period, in seconds since the Unix epoch.
+    pub start: u64,
+    pub commits: usize,
+    pub mutations: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommitFrequency {
+    pub period_secs: u64,
+    /// Periods with at least one commit, oldest first.
+    pub periods: Vec<PeriodActivity>,
+    /// Commits with no recorded time. Times are only kept while a retention
+    /// policy is set.
+    pub untimed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeActivity {
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub ty: String,
+    pub mutations: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValueSize {
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub key: String,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct KeyCardinality {
+    /// Nodes that have the key.
+    pub nodes: usize,
+    /// Distinct values held under it.
+    pub distinct_values: usize,
+}
+
+/// Where the history and state of a memory are concentrated, from
+/// `Memory::report`. State figures cover every node in head state,
+/// tombstones included, since the file keeps them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub by_type: BTreeMap<String, TypeStats>,
+    pub commit_frequency: CommitFrequency,
+    /// Nodes touched by the most committed mutations, most first.
+    pub most_mutated: Vec<NodeActivity>,
+    /// Largest field values in head state, largest first.
+    pub largest_values: Vec<ValueSize>,
+    pub field_keys: BTreeMap<String, KeyCardinality>,
+}
+
+impl Memory {
+    /// Breaks down committed history and head state by type, time, node,
+    /// value and field key. Ties in the top lists go to the lower node id.
+    pub fn report(&self, options: &ReportOptions) -> Result<Report, MyosotisError> {
+        if options.period_secs == 0 {
+            return Err(MyosotisError::InvalidInput(
+                "report period must be at least one second".to_string(),
+            ));
+        }
+        let mut report = Report::default();
+
+        let mut churn: HashMap<NodeId, usize> = HashMap::new();
+        let mut periods: BTreeMap<u64, PeriodActivity> = BTreeMap::new();
+        for commit in &self.commits {
+            for mutation in &commit.mutations {
+                *churn.entry(mutation.node_id()).or_default() += 1;
+            }
+            match self.commit_times.get(&commit.id) {
+                Some(time) => {
+                    let start = time - time % options.period_secs;
+                    let period = periods.entry(start).or_insert(PeriodActivity {
+                        start,
+                        commits: 0,
+                        mutations: 0,
+                    });
+                    period.commits += 1;
+                    period.mutations += commit.mutations.len();
+                }
+                None => report.commit_frequency.untimed += 1,
+            }
+        }
+        report.commit_frequency.period_secs = options.period_secs;
+        report.commit_frequency.periods = periods.into_values().collect();
+
+        let mut distinct: BTreeMap<&str, HashSet<String>> = BTreeMap::new();
+        let mut values = Vec::new();
+        for node in self.head_state.values() {
+            let stats = report.by_type.entry(node.ty.clone()).or_default();
+            match node.deleted {
+                true => stats.deleted_nodes += 1,
+                false => stats.live_nodes += 1,
+            }
+            stats.mutations += churn.get(&node.id).copied().unwrap_or(0);
+            for (key, value) in &node.fields {
+                let encoded = serde_json::to_string(value)?;
+                stats.value_bytes += encoded.len();
+                values.push(ValueSize {
+                    node_id: node.id,
+                    key: key.clone(),
+                    bytes: encoded.len(),
+                });
+                report.field_keys.entry(key.clone()).or_default().nodes += 1;
+                distinct.entry(key).or_default().insert(encoded);
+            }
+        }
+        for (key, seen) in distinct {
+            if let Some(cardinality) = report.field_keys.get_mut(key) {
+                cardinality.distinct_values = seen.len();
+            }
+        }
+
+        values.sort_by(|a, b| {
+            b.bytes
+                .cmp(&a.bytes)
+                .then(a.node_id.cmp(&b.node_id))
+                .then(a.key.cmp(&b.key))
+        });
+        values.truncate(options.top);
+        report.largest_values = values;
+
+        let mut most_mutated: Vec<NodeActivity> = churn
+            .into_iter()
+            .map(|(node_id, mutations)| NodeActivity {
+                node_id,
+                ty: self
+                    .head_state
+                    .get(&node_id)
+                    .map(|n| n.ty.clone())
+                    .unwrap_or_default(),
+                mutations,
+            })
+            .collect();
+        most_mutated.sort_by(|a, b| {
+            b.mutations
+                .cmp(&a.mutations)
+                .then(a.node_id.cmp(&b.node_id))
+        });
+        most_mutated.truncate(options.top);
+        report.most_mutated = most_mutated;
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,361 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "tsa")]
+use std::time::Duration;
+
+/// Proof obtained from an external party that a commit hash existed.
+///
+/// Receipts live outside the hash chain; `receipt` is opaque to the engine
+/// (for RFC 3161 it is the DER-encoded `TimeStampResp`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnchorReceipt {
+    pub commit_id: u64,
+    pub commit_hash: [u8; 32],
+    pub authority: String,
+    pub receipt: Vec<u8>,
+}
+
+/// Fingerprint of a file's committed history, small enough to publish to
+/// an external timestamping service. Any later rewrite of the commits up
+/// to `head_commit_id`, or of the state they produce, fails
+/// `Memory::verify_anchor`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub head_commit_id: u64,
+    pub head_hash: [u8; 32],
+    pub state_hash: [u8; 32],
+}
+
+pub trait Anchorer {
+    /// Identifies the authority in stored receipts.
+    fn authority(&self) -> String;
+
+    /// Submits `commit_hash` and returns the authority's receipt bytes.
+    fn submit(&self, commit_hash: &[u8; 32]) -> Result<Vec<u8>, MyosotisError>;
+}
+
+/// Anchors through a user-provided function, e.g. a KMS or ledger client.
+pub struct CallbackAnchorer<F>
+where
+    F: Fn(&[u8; 32]) -> Result<Vec<u8>, MyosotisError>,
+{
+    authority: String,
+    callback: F,
+}
+
+impl<F> CallbackAnchorer<F>
+where
+    F: Fn(&[u8; 32]) -> Result<Vec<u8>, MyosotisError>,
+{
+    pub fn new(authority: &str, callback: F) -> Self {
+        Self {
+            authority: authority.to_string(),
+            callback,
+        }
+    }
+}
+
+impl<F> Anchorer for CallbackAnchorer<F>
+where
+    F: Fn(&[u8; 32]) -> Result<Vec<u8>, MyosotisError>,
+{
+    fn authority(&self) -> String {
+        self.authority.clone()
+    }
+
+    fn submit(&self, commit_hash: &[u8; 32]) -> Result<Vec<u8>, MyosotisError> {
+        (self.callback)(commit_hash)
+    }
+}
+
+// DER encoding of the SHA-256 AlgorithmIdentifier (OID 2.16.840.1.101.3.4.2.1, NULL).
+const SHA256_ALGORITHM_ID: [u8; 15] = [
+    0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00,
+];
+
+fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|b| *b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// Builds a DER `TimeStampReq` (RFC 3161 section 2.4.1) for a SHA-256
+/// imprint, requesting the TSA certificate in the response.
+pub fn timestamp_request(hash: &[u8; 32]) -> Vec<u8> {
+    let mut imprint = SHA256_ALGORITHM_ID.to_vec();
+    imprint.extend(der_tlv(0x04, hash));
+
+    let mut body = der_tlv(0x02, &[0x01]);
+    body.extend(der_tlv(0x30, &imprint));
+    body.extend(der_tlv(0x01, &[0xff]));
+    der_tlv(0x30, &body)
+}
+
+fn der_read(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, mut rest) = rest.split_first()?;
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > 8 || rest.len() < n {
+            return None;
+        }
+        let len = rest[..n]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        rest = &rest[n..];
+        len
+    };
+    if rest.len() < len {
+        return None;
+    }
+    Some((tag, &rest[..len], &rest[len..]))
+}
+
+/// Reads the `PKIStatus` of a DER `TimeStampResp`.
+pub fn timestamp_response_status(response: &[u8]) -> Option<u64> {
+    let (0x30, resp, _) = der_read(response)? else {
+        return None;
+    };
+    let (0x30, status_info, _) = der_read(resp)? else {
+        return None;
+    };
+    let (0x02, status, _) = der_read(status_info)? else {
+        return None;
+    };
+    if status.is_empty() || status.len() > 8 {
+        return None;
+    }
+    Some(status.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+// OID contents of id-signedData (1.2.840.113549.1.7.2) and id-ct-TSTInfo
+// (1.2.840.113549.1.9.16.1.4).
+const SIGNED_DATA_OID: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+const TST_INFO_OID: [u8; 11] = [
+    0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x01, 0x04,
+];
+
+/// Reads the element tagged `tag` at the start of `data`, returning its
+/// content and what follows it.
+fn der_expect(data: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    match der_read(data)? {
+        (t, content, rest) if t == tag => Some((content, rest)),
+        _ => None,
+    }
+}
+
+/// Reads the SHA-256 `messageImprint` of the `TSTInfo` in a DER
+/// `TimeStampResp` (RFC 3161 section 2.4.2): the hash the TSA says it
+/// stamped. `None` without a token or for another hash algorithm. The
+/// token's signature is not checked.
+pub fn timestamp_response_imprint(response: &[u8]) -> Option<[u8; 32]> {
+    let (resp, _) = der_expect(response, 0x30)?;
+    let (_status, token) = der_expect(resp, 0x30)?;
+
+    // ContentInfo { contentType, [0] EXPLICIT SignedData }
+    let (content_info, _) = der_expect(token, 0x30)?;
+    let (content_type, rest) = der_expect(content_info, 0x06)?;
+    if content_type != SIGNED_DATA_OID {
+        return None;
+    }
+    let (explicit, _) = der_expect(rest, 0xa0)?;
+    let (signed_data, _) = der_expect(explicit, 0x30)?;
+
+    // SignedData { version, digestAlgorithms, encapContentInfo, .. }
+    let (_version, rest) = der_expect(signed_data, 0x02)?;
+    let (_digest_algorithms, rest) = der_expect(rest, 0x31)?;
+    let (encap, _) = der_expect(rest, 0x30)?;
+    let (content_type, rest) = der_expect(encap, 0x06)?;
+    if content_type != TST_INFO_OID {
+        return None;
+    }
+    let (explicit, _) = der_expect(rest, 0xa0)?;
+    let (tst_info, _) = der_expect(explicit, 0x04)?;
+
+    // TSTInfo { version, policy, messageImprint, .. }
+    let (tst_info, _) = der_expect(tst_info, 0x30)?;
+    let (_version, rest) = der_expect(tst_info, 0x02)?;
+    let (_policy, rest) = der_expect(rest, 0x06)?;
+    let (imprint, _) = der_expect(rest, 0x30)?;
+    let (algorithm, rest) = der_expect(imprint, 0x30)?;
+    // The NULL parameters are optional for SHA-256.
+    let (oid, _) = der_expect(algorithm, 0x06)?;
+    if oid != &SHA256_ALGORITHM_ID[4..13] {
+        return None;
+    }
+    let (hashed, _) = der_expect(rest, 0x04)?;
+    hashed.try_into().ok()
+}
+
+/// Checks a TSA reply: a granted `PKIStatus` and a token stamping
+/// `commit_hash`.
+pub fn check_timestamp_response(
+    response: &[u8],
+    commit_hash: &[u8; 32],
+) -> Result<(), MyosotisError> {
+    match timestamp_response_status(response) {
+        // granted (0) or grantedWithMods (1)
+        Some(0) | Some(1) => {}
+        Some(status) => {
+            return Err(MyosotisError::AnchorFailed(format!(
+                "TSA rejected request with status {}",
+                status
+            )));
+        }
+        None => {
+            return Err(MyosotisError::AnchorFailed(
+                "malformed TimeStampResp".to_string(),
+            ));
+        }
+    }
+    if timestamp_response_imprint(response) != Some(*commit_hash) {
+        return Err(MyosotisError::AnchorFailed(
+            "TimeStampResp does not stamp the commit hash".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// RFC 3161 time-stamp authority reached over HTTP(S) (`tsa` feature).
+#[cfg(feature = "tsa")]
+pub struct Rfc3161Anchorer {
+    pub url: String,
+    /// Bound on the whole exchange, connecting included.
+    pub timeout: Duration,
+}
+
+#[cfg(feature = "tsa")]
+impl Rfc3161Anchorer {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    fn post(&self, body: &[u8]) -> Result<Vec<u8>, MyosotisError> {
+        let failed =
+            |e: ureq::Error| MyosotisError::AnchorFailed(format!("TSA request failed: {}", e));
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(self.timeout))
+            .build()
+            .into();
+        let mut response = agent
+            .post(&self.url)
+            .header("Content-Type", "application/timestamp-query")
+            .header("Accept", "application/timestamp-reply")
+            .send(body)
+            .map_err(failed)?;
+        response.body_mut().read_to_vec().map_err(failed)
+    }
+}
+
+#[cfg(feature = "tsa")]
+impl Anchorer for Rfc3161Anchorer {
+    fn authority(&self) -> String {
+        self.url.clone()
+    }
+
+    fn submit(&self, commit_hash: &[u8; 32]) -> Result<Vec<u8>, MyosotisError> {
+        let response = self.post(&timestamp_request(commit_hash))?;
+        check_timestamp_response(&response, commit_hash)?;
+        Ok(response)
+    }
+}
+
+impl Memory {
+    /// Anchor for the head commit. Pending mutations are not covered.
+    pub fn anchor(&self) -> Result<Anchor, MyosotisError> {
+        let head = self
+            .commits
+            .last()
+            .ok_or_else(|| MyosotisError::InvalidInput("no commits to anchor".to_string()))?;
+        Ok(Anchor {
+            head_commit_id: head.id,
+            head_hash: head.hash,
+            state_hash: self.hash_state(&self.committed_state()?),
+        })
+    }
+
+    /// Checks that the commit `anchor` names is still in the chain with the
+    /// same hash and replays to the same state. Since each commit hash
+    /// covers its parent, this proves nothing up to that commit has been
+    /// rewritten; later commits are allowed.
+    pub fn verify_anchor(&self, anchor: &Anchor) -> Result<(), MyosotisError> {
+        let mismatch = MyosotisError::AnchorMismatch(anchor.head_commit_id);
+        let Some(commit) = self.commits.iter().find(|c| c.id == anchor.head_commit_id) else {
+            return Err(mismatch);
+        };
+        if commit.hash != anchor.head_hash
+            || self.hash_state(&self.state_at_commit(commit.id)?) != anchor.state_hash
+        {
+            return Err(mismatch);
+        }
+        Ok(())
+    }
+
+    /// Submits the head commit hash to `anchorer` and stores the receipt.
+    pub fn anchor_head(
+        &mut self,
+        anchorer: &dyn Anchorer,
+    ) -> Result<&AnchorReceipt, MyosotisError> {
+        let head = self
+            .commits
+            .last()
+            .ok_or_else(|| MyosotisError::InvalidInput("no commits to anchor".to_string()))?;
+        let receipt = AnchorReceipt {
+            commit_id: head.id,
+            commit_hash: head.hash,
+            authority: anchorer.authority(),
+            receipt: anchorer.submit(&head.hash)?,
+        };
+        tracing::info!(
+            commit_id = receipt.commit_id,
+            authority = %receipt.authority,
+            "anchored head commit"
+        );
+        self.anchors.push(receipt);
+        Ok(&self.anchors[self.anchors.len() - 1])
+    }
+
+    /// Anchors the head commit when at least `every` commits have been made
+    /// since the last stored receipt. Returns `None` when not yet due.
+    pub fn anchor_if_due(
+        &mut self,
+        anchorer: &dyn Anchorer,
+        every: u64,
+    ) -> Result<Option<&AnchorReceipt>, MyosotisError> {
+        let Some(head_id) = self.commits.last().map(|c| c.id) else {
+            return Ok(None);
+        };
+        let last_anchored = self.anchors.iter().map(|a| a.commit_id).max().unwrap_or(0);
+        if head_id < last_anchored + every.max(1) {
+            return Ok(None);
+        }
+        self.anchor_head(anchorer).map(Some)
+    }
+
+    /// Whether a receipt still refers to a commit in the current chain.
+    /// Compaction rewrites commit hashes, which orphans earlier receipts.
+    pub fn anchor_matches_chain(&self, receipt: &AnchorReceipt) -> bool {
+        self.commits
+            .iter()
+            .any(|c| c.id == receipt.commit_id && c.hash == receipt.commit_hash)
+    }
+}
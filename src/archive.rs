@@ -0,0 +1,42 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+
+impl Memory {
+    /// Stages archiving node `id`: it stays live, readable, writable and
+    /// referenceable, but listings such as `nodes_in` and searches such as
+    /// `similar` skip it. Undo with `unarchive`.
+    pub fn archive(&mut self, id: NodeId) -> Result<(), MyosotisError> {
+        self.set_archived(id, true)
+    }
+
+    /// Stages un-archiving node `id`, returning it to default listings.
+    pub fn unarchive(&mut self, id: NodeId) -> Result<(), MyosotisError> {
+        self.set_archived(id, false)
+    }
+
+    fn set_archived(&mut self, id: NodeId, archived: bool) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::SetArchived { id, archived };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Whether node `id` is archived in head state.
+    pub fn is_archived(&self, id: NodeId) -> bool {
+        self.head_state.get(&id).is_some_and(|node| node.archived)
+    }
+
+    /// Archived nodes that are not deleted, by id.
+    pub fn archived_nodes(&self) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self
+            .head_state
+            .values()
+            .filter(|node| node.archived && !node.deleted)
+            .collect();
+        nodes.sort_unstable_by_key(|node| node.id);
+        nodes
+    }
+}
@@ -0,0 +1,39 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::storage;
+
+/// Where a memory is persisted. `load` returns a fully validated memory;
+/// `save` stores every commit and checkpoint of `memory`, replacing what
+/// was there before.
+pub trait StorageBackend {
+    fn load(&self) -> Result<Memory, MyosotisError>;
+    fn save(&mut self, memory: &Memory) -> Result<(), MyosotisError>;
+}
+
+/// The JSON memory file of `storage::save` and `storage::load`.
+#[derive(Debug, Clone)]
+pub struct FileBackend {
+    path: String,
+}
+
+impl FileBackend {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn load(&self) -> Result<Memory, MyosotisError> {
+        storage::load(&self.path)
+    }
+
+    fn save(&mut self, memory: &Memory) -> Result<(), MyosotisError> {
+        storage::save(&self.path, memory)
+    }
+}
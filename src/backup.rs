@@ -0,0 +1,197 @@
+use crate::blob;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::storage::{self, Durability};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory backups go to when none is given, next to the file.
+pub const DEFAULT_DIR: &str = "backups";
+
+/// A copy of a memory file made by `backup`.
+///
+/// Copies are named `<file name>.<seconds since the Unix epoch>.bak`, with
+/// `-<n>` after the seconds when several are made within one second.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Backup {
+    pub path: String,
+    /// Seconds since the Unix epoch when the copy was made.
+    pub created: u64,
+    #[serde(skip)]
+    seq: u32,
+}
+
+/// Which backups `rotate` keeps. The newest backup is always kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationPolicy {
+    /// Keep at most this many backups.
+    pub keep_last: Option<usize>,
+    /// Remove backups older than this many seconds.
+    pub max_age: Option<u64>,
+}
+
+fn backup_dir(path: &str, dir: Option<&str>) -> PathBuf {
+    match dir {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(DEFAULT_DIR),
+    }
+}
+
+fn file_name(path: &str) -> Result<String, MyosotisError> {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| MyosotisError::InvalidInput(format!("not a file path: {}", path)))
+}
+
+fn head_hash(mem: &Memory) -> Option<[u8; 32]> {
+    mem.commits.last().map(|c| c.hash)
+}
+
+/// Copies the segment log of `path`, if any, next to `target`.
+fn copy_segments(path: &str, target: &str) -> Result<(), MyosotisError> {
+    let segments = storage::segment_path(path);
+    match fs::read(&segments) {
+        Ok(data) => storage::write_atomic(&storage::segment_path(target), &data, Durability::Sync),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(MyosotisError::ReadFailed(segments, e)),
+    }
+}
+
+/// Copies `path`, its segment log and the blobs it refers to, into `dir` (default
+/// `backups/` next to it) and checks that the copy loads to the same
+/// head. Both files are fully validated; a copy that fails the check is
+/// removed.
+pub fn backup(path: &str, dir: Option<&str>) -> Result<Backup, MyosotisError> {
+    let source = storage::load(path)?;
+    let data = fs::read(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+
+    let dir = backup_dir(path, dir);
+    fs::create_dir_all(&dir)
+        .map_err(|e| MyosotisError::WriteFailed(dir.display().to_string(), e))?;
+
+    let name = file_name(path)?;
+    let created = crate::lock::now_secs();
+    let mut seq = 0;
+    let target = loop {
+        let stamp = match seq {
+            0 => created.to_string(),
+            n => format!("{}-{}", created, n),
+        };
+        let target = dir.join(format!("{}.{}.bak", name, stamp));
+        if !target.exists() {
+            break target.display().to_string();
+        }
+        seq += 1;
+    };
+
+    storage::write_atomic(&target, &data, Durability::Sync)?;
+    let copied = copy_segments(path, &target)
+        .and_then(|()| blob::copy_blobs(path, &target))
+        .and_then(|()| storage::load(&target));
+    match copied {
+        Ok(copy) if head_hash(&copy) == head_hash(&source) => {}
+        copy => {
+            let _ = fs::remove_file(&target);
+            let _ = fs::remove_file(storage::segment_path(&target));
+            let _ = fs::remove_dir_all(blob::blob_dir(&target));
+            return Err(copy.err().unwrap_or_else(|| {
+                MyosotisError::Invariant(format!("backup {} does not match {}", target, path))
+            }));
+        }
+    }
+    tracing::info!(backup = %target, commits = source.commits.len(), "backed up memory");
+
+    Ok(Backup {
+        path: target,
+        created,
+        seq,
+    })
+}
+
+fn parse_backup(name: &str, prefix: &str) -> Option<(u64, u32)> {
+    let stamp = name.strip_prefix(prefix)?.strip_suffix(".bak")?;
+    match stamp.split_once('-') {
+        Some((secs, seq)) => Some((secs.parse().ok()?, seq.parse().ok()?)),
+        None => Some((stamp.parse().ok()?, 0)),
+    }
+}
+
+/// Backups of `path` in `dir` (default `backups/` next to it), oldest
+/// first. A missing directory has none.
+pub fn list(path: &str, dir: Option<&str>) -> Result<Vec<Backup>, MyosotisError> {
+    let dir = backup_dir(path, dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(MyosotisError::ReadFailed(dir.display().to_string(), e)),
+    };
+
+    let prefix = format!("{}.", file_name(path)?);
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| MyosotisError::ReadFailed(dir.display().to_string(), e))?;
+        let name = entry.file_name();
+        let Some((created, seq)) = name.to_str().and_then(|n| parse_backup(n, &prefix)) else {
+            continue;
+        };
+        backups.push(Backup {
+            path: entry.path().display().to_string(),
+            created,
+            seq,
+        });
+    }
+    backups.sort_by_key(|b| (b.created, b.seq));
+    Ok(backups)
+}
+
+/// Removes the backups of `path` that `policy` does not keep. Returns the
+/// removed backups, oldest first.
+pub fn rotate(
+    path: &str,
+    dir: Option<&str>,
+    policy: &RotationPolicy,
+) -> Result<Vec<Backup>, MyosotisError> {
+    let mut backups = list(path, dir)?;
+    backups.pop();
+
+    let mut removed = Vec::new();
+    if let Some(keep_last) = policy.keep_last {
+        let excess = backups.len().saturating_sub(keep_last.saturating_sub(1));
+        removed.extend(backups.drain(..excess));
+    }
+    if let Some(max_age) = policy.max_age {
+        let cutoff = crate::lock::now_secs().saturating_sub(max_age);
+        let old = backups.iter().take_while(|b| b.created < cutoff).count();
+        removed.extend(backups.drain(..old));
+    }
+
+    for backup in &removed {
+        fs::remove_file(&backup.path)
+            .map_err(|e| MyosotisError::WriteFailed(backup.path.clone(), e))?;
+        let _ = fs::remove_file(storage::segment_path(&backup.path));
+        let blobs = blob::blob_dir(&backup.path);
+        match fs::remove_dir_all(&blobs) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(MyosotisError::WriteFailed(blobs, e));
+            }
+            _ => {}
+        }
+    }
+    Ok(removed)
+}
+
+/// Replaces `path` with the backup at `backup`, after validating the
+/// backup. Goes through `storage::save`, so restoring an older copy over
+/// a write-once file is refused. Returns the restored memory.
+pub fn restore(backup: &str, path: &str) -> Result<Memory, MyosotisError> {
+    let mem = storage::load(backup)?;
+    storage::save(path, &mem)?;
+    tracing::info!(backup, commits = mem.commits.len(), "restored memory");
+    Ok(mem)
+}
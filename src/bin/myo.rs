@@ -1,93 +1,697 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 use myosotis::Memory;
 use myosotis::MyosotisError;
-use myosotis::node::Value;
+use myosotis::analytics;
+use myosotis::anchor::Anchor;
+#[cfg(feature = "tsa")]
+use myosotis::anchor::Rfc3161Anchorer;
+use myosotis::backup;
+use myosotis::blob;
+use myosotis::canonical::Canonicalization;
+use myosotis::classify::Classifier;
+use myosotis::export;
+use myosotis::import;
+use myosotis::maintenance;
+use myosotis::memory::PendingSummary;
+use myosotis::node::{IdMode, Node, NodeId, Value};
+use myosotis::render;
+use myosotis::retention::RetentionPolicy;
+use myosotis::script;
+use myosotis::shell::{Outcome, Session};
 use myosotis::storage;
+use myosotis::sync;
+use myosotis::verify;
+use myosotis::wal::{self, WalRecovery};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::{Context, Helper, Highlighter, Hinter, Validator};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, ExitCode, Stdio};
+use tracing_subscriber::EnvFilter;
+
+#[path = "myo/messages.rs"]
+mod messages;
+
+use messages::{Messages, tr};
 
 #[derive(Parser)]
 #[command(name = "myo")]
 struct Cli {
+    /// Increase log verbosity (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Result format on stdout
+    #[arg(long, value_enum, default_value = "text", global = true)]
+    output: OutputFormat,
+
+    /// Language of text output (en, de)
+    #[arg(long, default_value = "en", global = true)]
+    lang: String,
+
+    /// JSON file of message template overrides, keyed like the built-in catalog
+    #[arg(long, global = true)]
+    messages: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SessionAction {
+    Begin,
+    End,
+}
+
+/// Prints `text` in text mode, or `value` as a single JSON line.
+/// Runs the `--backup` option of a destructive command.
+fn backup_first(file: &str, dir: Option<Option<String>>) -> Result<Option<String>, MyosotisError> {
+    dir.map(|dir| backup::backup(file, dir.as_deref()).map(|b| b.path))
+        .transpose()
+}
+
+fn ids_json(ids: &[NodeId]) -> serde_json::Value {
+    ids.iter().map(|id| render::id_json(*id)).collect()
+}
+
+fn emit(output: OutputFormat, text: &str, value: serde_json::Value) {
+    match output {
+        OutputFormat::Text if text.is_empty() => {}
+        OutputFormat::Text => println!("{}", text),
+        OutputFormat::Json => println!("{}", value),
+    }
+}
+
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct ShellHelper {
+    session: Session,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+        let start = before
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let first_word = before[..start].trim().is_empty();
+        Ok((
+            start,
+            self.session.completions(first_word, &before[start..]),
+        ))
+    }
+}
+
+fn hex(hash: &[u8; 32]) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 #[derive(Subcommand)]
 enum Commands {
     Init {
         file: String,
+        #[arg(long, default_value = "binary")]
+        canonicalization: String,
+        /// Only allow appending commits; compaction and rewrites are refused
+        #[arg(long)]
+        write_once: bool,
+        /// Allocate node ids as random UUIDs instead of counting up
+        #[arg(long)]
+        uuid_ids: bool,
     },
     History {
         file: String,
     },
+    /// Print new commits as they are written to the file, until interrupted
+    Watch {
+        file: String,
+    },
+    Log {
+        file: String,
+        /// Only show commits labelled with this class
+        #[arg(long)]
+        class: Option<String>,
+        /// JSON rules file: {"rules": [{"class", "message", "mutation"}]}
+        #[arg(long)]
+        rules: Option<String>,
+    },
     Create {
         file: String,
         ty: String,
+        /// Create the node in this namespace instead of the default one
+        #[arg(long)]
+        namespace: Option<String>,
     },
     Set {
         file: String,
-        id: u64,
+        id: NodeId,
         key: String,
         value: String,
     },
     DeleteNode {
         file: String,
-        id: u64,
+        id: NodeId,
+    },
+    /// Change a node's type, keeping its fields
+    SetType {
+        file: String,
+        id: NodeId,
+        ty: String,
+    },
+    /// Set or clear the alias of a node
+    Alias {
+        file: String,
+        id: NodeId,
+        #[arg(required_unless_present = "clear")]
+        alias: Option<String>,
+        /// Remove the node's alias
+        #[arg(long, conflicts_with = "alias")]
+        clear: bool,
+    },
+    /// Look up the node an alias names
+    Resolve {
+        file: String,
+        alias: String,
+    },
+    /// Undelete a node, with the fields it had when deleted
+    RestoreNode {
+        file: String,
+        id: NodeId,
+        /// Restore the node without its old fields
+        #[arg(long)]
+        clear_fields: bool,
     },
     DeleteField {
         file: String,
-        id: u64,
+        id: NodeId,
         key: String,
     },
+    /// Rename a field on one node, or on every node of --type
+    RenameField {
+        file: String,
+        from: String,
+        to: String,
+        #[arg(long, conflicts_with = "ty", required_unless_present = "ty")]
+        id: Option<NodeId>,
+        #[arg(long = "type")]
+        ty: Option<String>,
+    },
+    /// Commit a session begin or end marker
+    Session {
+        file: String,
+        action: SessionAction,
+    },
+    /// List namespaces with their live node counts
+    Namespaces {
+        file: String,
+    },
+    /// List sessions, or the commits of one session
+    Sessions {
+        file: String,
+        #[arg(long)]
+        id: Option<u64>,
+    },
+    /// Fetch missing commits from another file sharing this history
+    Sync {
+        file: String,
+        remote: String,
+        /// Send this file's extra commits to the remote instead
+        #[arg(long)]
+        push: bool,
+    },
+    /// Write the commits after --since to a bundle file
+    Bundle {
+        file: String,
+        out: String,
+        #[arg(long, default_value_t = 0)]
+        since: u64,
+    },
+    /// Append the commits of a bundle file
+    Unbundle {
+        file: String,
+        bundle: String,
+    },
+    /// Drop staged, uncommitted mutations
+    Discard {
+        file: String,
+    },
+    /// Check a file and report every problem found
+    Fsck {
+        file: String,
+        /// Also recompute all hashes and checkpoint states
+        #[arg(long)]
+        deep: bool,
+    },
+    /// Verify a file and optionally write a signed JSON report
+    Verify {
+        file: String,
+        /// Also recompute all hashes and checkpoint states
+        #[arg(long)]
+        deep: bool,
+        /// Write the report to this path
+        #[arg(long)]
+        report: Option<String>,
+        /// Sign the report with HMAC-SHA256 using this key file
+        #[arg(long)]
+        key: Option<String>,
+    },
     Compact {
         file: String,
         #[arg(long)]
         at: Option<u64>,
+        /// Back the file up first, to DIR or backups/ next to it
+        #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true)]
+        backup: Option<Option<String>>,
+    },
+    /// Replace every historical value of a field with a salted hash
+    Redact {
+        file: String,
+        id: NodeId,
+        key: String,
+        /// Back the file up first, to DIR or backups/ next to it
+        #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true)]
+        backup: Option<Option<String>>,
+    },
+    /// Drop every commit after N (0 for genesis); without --hard, only show
+    /// what would be dropped
+    Reset {
+        file: String,
+        commit: u64,
+        #[arg(long)]
+        hard: bool,
+        /// Back the file up first, to DIR or backups/ next to it
+        #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true)]
+        backup: Option<Option<String>>,
+    },
+    /// List checkpoints, or thin them out or rebuild them
+    Checkpoints {
+        file: String,
+        /// Drop checkpoints that do not match the chain and keep the newest N
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+        /// Replace all checkpoints with one every N commits
+        #[arg(long, value_name = "N", conflicts_with = "keep")]
+        rebuild: Option<usize>,
+    },
+    /// Show or set the retention policy stored in the file
+    Retention {
+        file: String,
+        /// Keep at most this many commits
+        #[arg(long)]
+        max_commits: Option<usize>,
+        /// Prune commits older than this many seconds
+        #[arg(long)]
+        max_age: Option<u64>,
+        /// Never prune commits tagged with this (repeatable)
+        #[arg(long = "keep-tag")]
+        keep_tags: Vec<String>,
+        /// Remove the policy
+        #[arg(long, conflicts_with_all = ["max_commits", "max_age", "keep_tags"])]
+        clear: bool,
+    },
+    /// Compact history according to the retention policy
+    Prune {
+        file: String,
+        /// Back the file up first, to DIR or backups/ next to it
+        #[arg(long, value_name = "DIR", num_args = 0..=1, require_equals = true)]
+        backup: Option<Option<String>>,
+    },
+    /// Delete fields whose TTL has passed, in one commit
+    Expire {
+        file: String,
+        /// Expire as of this time, in seconds since the Unix epoch
+        #[arg(long, value_name = "SECS")]
+        now: Option<u64>,
+    },
+    /// Seal the compacted genesis state, or check or remove the seal
+    Seal {
+        file: String,
+        /// Sign with HMAC-SHA256 using this key file
+        #[arg(long)]
+        key: Option<String>,
+        /// Check the seal instead of writing one
+        #[arg(long, conflicts_with = "clear")]
+        check: bool,
+        /// Remove the seal
+        #[arg(long, conflicts_with = "key")]
+        clear: bool,
+    },
+    /// Print an anchor of the head commit to publish externally, or check one
+    Anchor {
+        file: String,
+        /// Check the anchor in this JSON file instead of printing one
+        #[arg(long, value_name = "ANCHOR")]
+        verify: Option<String>,
+    },
+    /// Start the file with a checksum of the whole file, or stop
+    Checksum {
+        file: String,
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Keep large values in FILE.blobs instead of the file, or show the store
+    Blobs {
+        file: String,
+        /// Move values over this many encoded bytes out of the file
+        #[arg(long, value_name = "BYTES", conflicts_with = "off")]
+        threshold: Option<usize>,
+        /// Keep every value in the file again
+        #[arg(long)]
+        off: bool,
+        /// Remove blobs the file no longer refers to
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Copy the file to a backup directory and verify the copy
+    Backup {
+        file: String,
+        /// Defaults to backups/ next to the file
+        #[arg(long)]
+        dir: Option<String>,
+        /// Then keep at most this many backups
+        #[arg(long)]
+        keep: Option<usize>,
+        /// Then remove backups older than this many seconds
+        #[arg(long)]
+        max_age: Option<u64>,
+    },
+    /// Replace the file with a backup, by default the newest one
+    Restore {
+        file: String,
+        backup: Option<String>,
+        #[arg(long)]
+        dir: Option<String>,
+    },
+    /// Find the first commit where a field starts or stops having a value
+    Bisect {
+        file: String,
+        #[arg(long, num_args = 2, value_names = ["ID", "KEY"], required = true)]
+        field: Vec<String>,
+        /// JSON value to compare with; anything that is not JSON is a string
+        #[arg(long)]
+        equals: String,
+    },
+    /// Analyze history and recommend a compaction target
+    Advise {
+        file: String,
     },
     Commit {
         file: String,
         message: String,
+        /// Record a commit even when nothing is staged
+        #[arg(long)]
+        allow_empty: bool,
+    },
+    /// Anchor the head commit with an RFC 3161 time-stamp authority
+    #[cfg(feature = "tsa")]
+    Timestamp {
+        file: String,
+        #[arg(long)]
+        url: String,
+        #[arg(long)]
+        every: Option<u64>,
+    },
+    /// Interactive session; saves once on exit
+    Shell {
+        file: String,
+        /// Log staged mutations to FILE.wal and offer to restage them
+        /// after a crash
+        #[arg(long)]
+        wal: bool,
+    },
+    /// Node, commit and mutation counts
+    Stats {
+        file: String,
+    },
+    /// What the next commit would record: the mutations staged in FILE.staged
+    Status {
+        file: String,
+    },
+    /// Where history and state are concentrated: per type, over time, by
+    /// node, value and field key
+    Report {
+        file: String,
+        /// Commit frequency period, in seconds
+        #[arg(long, default_value_t = 86_400)]
+        period: u64,
+        /// Entries in the most-mutated and largest-value lists
+        #[arg(long, default_value_t = 10)]
+        top: usize,
     },
     Show {
         file: String,
-        id: u64,
+        id: NodeId,
+        #[arg(long)]
+        at: Option<u64>,
+        /// Print values without truncation
+        #[arg(long, conflicts_with = "max_len")]
+        full: bool,
+        /// Characters per string and items per list or map before truncating
+        #[arg(long, default_value_t = render::DEFAULT_MAX_LEN)]
+        max_len: usize,
+    },
+    ExportGit {
+        file: String,
+        dir: String,
+    },
+    /// Write nodes.csv and relationships.csv for `neo4j-admin import`
+    ExportNeo4j {
+        file: String,
+        #[arg(long)]
+        out: String,
         #[arg(long)]
         at: Option<u64>,
     },
+    Graph {
+        file: String,
+        /// dot, graphml, ntriples or jsonld
+        #[arg(long, default_value = "dot")]
+        format: String,
+        #[arg(long)]
+        at: Option<u64>,
+        /// JSON ontology mapping for ntriples/jsonld: {"base", "vocab", "types", "properties"}
+        #[arg(long)]
+        ontology: Option<String>,
+    },
+    Import {
+        file: String,
+        data: String,
+        #[arg(long = "type")]
+        ty: String,
+    },
+    /// Apply a JSON array of mutations as one commit, or none on error
+    Apply {
+        file: String,
+        /// Reads stdin when omitted or "-"
+        script: Option<String>,
+        #[arg(long)]
+        message: Option<String>,
+    },
+    #[cfg(feature = "fuse")]
+    Mount {
+        file: String,
+        dir: String,
+    },
+    /// Serve the file over gRPC; every commit is saved back to it
+    #[cfg(feature = "server")]
+    Serve {
+        file: String,
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        addr: std::net::SocketAddr,
+    },
 }
 
-fn main() -> Result<()> {
+fn init_logging(verbose: u8, quiet: bool) {
+    let default_level = if quiet {
+        "error"
+    } else {
+        match verbose {
+            0 => "warn",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        }
+    };
+    // RUST_LOG takes precedence over -v/-q.
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    init_logging(cli.verbose, cli.quiet);
+
+    let result = Messages::load(&cli.lang, cli.messages.as_deref())
+        .and_then(|messages| run(cli.command, cli.output, &messages));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            tracing::error!(error = %format!("{:#}", e), "command failed");
+            if cli.output == OutputFormat::Json {
+                println!("{}", json!({ "error": format!("{:#}", e) }));
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    match cli.command {
-        Commands::Init { file } => {
+fn run(command: Commands, output: OutputFormat, m: &Messages) -> Result<()> {
+    match command {
+        Commands::Init {
+            file,
+            canonicalization,
+            write_once,
+            uuid_ids,
+        } => {
             if storage::exists(&file) {
-                println!("File already exists: {}", file);
+                emit(
+                    output,
+                    &tr!(m, "init.exists", file = file),
+                    json!({ "file": file, "created": false }),
+                );
                 return Ok(());
             }
 
-            let mem = Memory::new();
+            let canonicalization = match canonicalization.as_str() {
+                "binary" => Canonicalization::Binary,
+                "jcs" => Canonicalization::Jcs,
+                other => {
+                    return Err(anyhow::anyhow!(MyosotisError::InvalidInput(format!(
+                        "unknown canonicalization '{}', expected 'binary' or 'jcs'",
+                        other
+                    ))));
+                }
+            };
+            let mut mem = Memory::with_canonicalization(canonicalization);
+            mem.write_once = write_once;
+            if uuid_ids {
+                mem.id_mode = IdMode::Uuid;
+            }
             storage::save(&file, &mem)?;
-            println!("Initialized new memory at {}", file);
+            emit(
+                output,
+                &tr!(m, "init.done", file = file),
+                json!({ "file": file, "created": true }),
+            );
         }
 
         Commands::History { file } => {
+            let commits = storage::stream_commits(&file)?;
+            let mut text = tr!(m, "history.header");
+            let mut entries = Vec::new();
+            for commit in commits {
+                let message = format!("{:?}", commit.message);
+                text.push('\n');
+                text.push_str(&tr!(m, "history.line", id = commit.id, message = message));
+                entries.push(json!({
+                    "id": commit.id,
+                    "hash": hex(&commit.hash),
+                    "message": commit.message,
+                    "mutations_bytes": commit.mutations_len(),
+                }));
+            }
+            emit(output, &text, json!({ "commits": entries }));
+        }
+        Commands::Watch { file } => {
+            storage::watch(&file, |update| {
+                if update.rewritten {
+                    emit(
+                        output,
+                        &tr!(m, "watch.rewritten", file = file),
+                        json!({ "rewritten": true }),
+                    );
+                }
+                for commit in update.new_commits {
+                    let message = format!("{:?}", commit.message);
+                    emit(
+                        output,
+                        &tr!(m, "history.line", id = commit.id, message = message),
+                        json!({
+                            "id": commit.id,
+                            "hash": hex(&commit.hash),
+                            "message": commit.message,
+                            "mutations": commit.mutations.len(),
+                        }),
+                    );
+                }
+                true
+            })?;
+        }
+        Commands::Log { file, class, rules } => {
             let mem = storage::load(&file)?;
-            println!("Commit history:");
+            let classifier = match rules {
+                Some(path) => Classifier::from_rules_json(&fs::read_to_string(path)?)?,
+                None => Classifier::new(),
+            };
+
+            let mut lines = Vec::new();
+            let mut entries = Vec::new();
             for commit in &mem.commits {
-                println!("Commit {} - {:?}", commit.id, commit.message);
+                let classes = classifier.classify(commit);
+                if let Some(wanted) = &class
+                    && !classes.contains(wanted)
+                {
+                    continue;
+                }
+                lines.push(tr!(
+                    m,
+                    "log.line",
+                    id = commit.id,
+                    classes = classes.join(","),
+                    message = format!("{:?}", commit.message),
+                ));
+                entries.push(json!({
+                    "id": commit.id,
+                    "message": commit.message,
+                    "classes": classes,
+                }));
             }
+            emit(output, &lines.join("\n"), json!({ "commits": entries }));
         }
-        Commands::Create { file, ty } => {
-            let mut mem = if storage::exists(&file) {
-                storage::load(&file)?
-            } else {
-                Memory::new()
-            };
+        Commands::Create {
+            file,
+            ty,
+            namespace,
+        } => {
+            if !storage::exists(&file) {
+                storage::save(&file, &Memory::new())?;
+            }
+            let mut mem = wal::load_staged(&file)?;
 
-            let id = mem.create(&ty);
-            storage::save(&file, &mem)?;
-            println!("Created node {} of type '{}' in {}", id, ty, file);
+            let id = match &namespace {
+                Some(namespace) => mem.namespace(namespace).create(&ty)?,
+                None => mem.create(&ty),
+            };
+            emit(
+                output,
+                &tr!(m, "create.done", id = id, type = ty, file = file),
+                json!({ "file": file, "id": render::id_json(id), "type": ty, "namespace": namespace }),
+            );
         }
         Commands::Set {
             file,
@@ -95,86 +699,1254 @@ fn main() -> Result<()> {
             key,
             value,
         } => {
-            let mut mem = storage::load(&file)?;
+            let mut mem = wal::load_staged(&file)?;
 
-            if !mem.head_state.contains_key(&id) {
+            if !mem.head_state().contains_key(&id) {
                 return Err(anyhow::anyhow!(MyosotisError::NodeNotFound(id)));
             }
 
             mem.set(id, &key, Value::Str(value.clone()))?;
+            emit(
+                output,
+                &tr!(m, "set.done", id = id, key = key, value = value),
+                json!({ "id": render::id_json(id), "key": key, "value": value }),
+            );
+        }
+        Commands::Commit {
+            file,
+            message,
+            allow_empty,
+        } => {
+            // Commit a copy, which does not write to the staging area, so
+            // the staged mutations stay there until the commit is saved.
+            let mut mem = wal::load_staged(&file)?.clone();
+
+            if allow_empty {
+                mem.commit_allow_empty(Some(message.clone()))?;
+            } else {
+                mem.commit(Some(message.clone()))?;
+            }
 
             storage::save(&file, &mem)?;
-            println!("Set node {} field '{}' = '{}'", id, key, value);
+            wal::clear_staged(&file)?;
+            let (commit_id, hash) = mem
+                .commits
+                .last()
+                .map(|c| (c.id, hex(&c.hash)))
+                .unwrap_or_default();
+            emit(
+                output,
+                &tr!(
+                    m,
+                    "commit.done",
+                    id = commit_id,
+                    message = format!("{:?}", message),
+                ),
+                json!({ "id": commit_id, "hash": hash, "message": message }),
+            );
         }
-        Commands::Commit { file, message } => {
+        #[cfg(feature = "tsa")]
+        Commands::Timestamp { file, url, every } => {
             let mut mem = storage::load(&file)?;
+            let anchorer = Rfc3161Anchorer::new(&url);
 
-            mem.commit(Some(message.clone()))?;
+            let anchored = match every {
+                Some(n) => mem.anchor_if_due(&anchorer, n)?.map(|r| r.commit_id),
+                None => Some(mem.anchor_head(&anchorer)?.commit_id),
+            };
 
-            storage::save(&file, &mem)?;
-            println!(
-                "Committed {} with message {:?}",
-                mem.commits.last().map(|c| c.id).unwrap_or(0),
-                message
-            );
+            match anchored {
+                Some(commit_id) => {
+                    storage::save(&file, &mem)?;
+                    emit(
+                        output,
+                        &tr!(m, "timestamp.anchored", id = commit_id, url = url),
+                        json!({ "anchored": true, "commit_id": commit_id, "authority": url }),
+                    );
+                }
+                None => emit(
+                    output,
+                    &tr!(m, "timestamp.not_due", file = file),
+                    json!({ "anchored": false }),
+                ),
+            }
         }
         Commands::DeleteNode { file, id } => {
-            let mut mem = storage::load(&file)?;
+            let mut mem = wal::load_staged(&file)?;
             mem.delete_node(id)?;
-            storage::save(&file, &mem)?;
-            println!("Staged delete-node for node {}", id);
+            emit(
+                output,
+                &tr!(m, "delete_node.done", id = id),
+                json!({ "id": render::id_json(id) }),
+            );
+        }
+        Commands::SetType { file, id, ty } => {
+            let mut mem = wal::load_staged(&file)?;
+            mem.set_type(id, &ty)?;
+            emit(
+                output,
+                &tr!(m, "set_type.done", id = id, type = ty),
+                json!({ "id": render::id_json(id), "type": ty }),
+            );
+        }
+        Commands::Alias {
+            file,
+            id,
+            alias,
+            clear,
+        } => {
+            let alias = if clear { None } else { alias };
+            let mut mem = wal::load_staged(&file)?;
+            mem.set_alias(id, alias.as_deref())?;
+            let text = match &alias {
+                Some(alias) => tr!(m, "alias.set", id = id, alias = alias),
+                None => tr!(m, "alias.cleared", id = id),
+            };
+            emit(
+                output,
+                &text,
+                json!({ "id": render::id_json(id), "alias": alias }),
+            );
+        }
+        Commands::Resolve { file, alias } => {
+            let mem = storage::load(&file)?;
+            let id = mem
+                .resolve(&alias)
+                .ok_or_else(|| anyhow::anyhow!(tr!(m, "resolve.missing", alias = alias)))?;
+            emit(
+                output,
+                &tr!(m, "resolve.found", alias = alias, id = id),
+                json!({ "alias": alias, "id": render::id_json(id) }),
+            );
+        }
+        Commands::RestoreNode {
+            file,
+            id,
+            clear_fields,
+        } => {
+            let mut mem = wal::load_staged(&file)?;
+            mem.restore_node(id, !clear_fields)?;
+            emit(
+                output,
+                &tr!(m, "restore_node.done", id = id),
+                json!({ "id": render::id_json(id), "keep_fields": !clear_fields }),
+            );
         }
         Commands::DeleteField { file, id, key } => {
-            let mut mem = storage::load(&file)?;
+            let mut mem = wal::load_staged(&file)?;
             mem.delete_field(id, &key)?;
+            emit(
+                output,
+                &tr!(m, "delete_field.done", key = key, id = id),
+                json!({ "id": render::id_json(id), "key": key }),
+            );
+        }
+        Commands::RenameField {
+            file,
+            from,
+            to,
+            id,
+            ty,
+        } => {
+            let mut mem = wal::load_staged(&file)?;
+            let ids = match (id, ty) {
+                (Some(id), _) => {
+                    mem.rename_field(id, &from, &to)?;
+                    vec![id]
+                }
+                (None, Some(ty)) => mem.rename_field_for_type(&ty, &from, &to)?,
+                (None, None) => unreachable!("clap requires --id or --type"),
+            };
+            emit(
+                output,
+                &tr!(
+                    m,
+                    "rename_field.done",
+                    from = from,
+                    to = to,
+                    count = ids.len(),
+                ),
+                json!({ "from": from, "to": to, "ids": ids_json(&ids) }),
+            );
+        }
+        Commands::Session { file, action } => {
+            let mut mem = storage::load(&file)?;
+            let (id, key) = match action {
+                SessionAction::Begin => (mem.begin_session()?, "session.begin"),
+                SessionAction::End => (mem.end_session()?, "session.end"),
+            };
             storage::save(&file, &mem)?;
-            println!("Staged delete-field '{}' on node {}", key, id);
+            let commit_id = mem.commits.last().map(|c| c.id);
+            emit(
+                output,
+                &tr!(m, key, id = id, commit = commit_id.unwrap_or(0)),
+                json!({ "session": id, "commit": commit_id }),
+            );
+        }
+        Commands::Sessions { file, id } => {
+            let mem = storage::load(&file)?;
+            match id {
+                Some(id) => {
+                    let commits = mem.commits_in_session(id)?;
+                    let mut text = tr!(m, "sessions.commits", id = id, count = commits.len());
+                    for commit in commits {
+                        let message = format!("{:?}", commit.message);
+                        text.push('\n');
+                        text.push_str(&tr!(
+                            m,
+                            "sessions.commit",
+                            id = commit.id,
+                            message = message
+                        ));
+                    }
+                    let ids: Vec<u64> = commits.iter().map(|c| c.id).collect();
+                    emit(output, &text, json!({ "session": id, "commits": ids }));
+                }
+                None => {
+                    let sessions = mem.sessions();
+                    let mut text = tr!(m, "sessions.count", count = sessions.len());
+                    for s in &sessions {
+                        let end = s
+                            .end_commit
+                            .map(|c| c.to_string())
+                            .unwrap_or_else(|| tr!(m, "sessions.open"));
+                        text.push('\n');
+                        text.push_str(&tr!(
+                            m,
+                            "sessions.line",
+                            id = s.id,
+                            begin = s.begin_commit,
+                            end = end,
+                        ));
+                    }
+                    emit(output, &text, json!({ "sessions": sessions }));
+                }
+            }
+        }
+        Commands::Namespaces { file } => {
+            let mem = storage::load(&file)?;
+            let counts: Vec<(&str, usize)> = mem
+                .namespaces()
+                .into_iter()
+                .map(|name| (name, mem.nodes_in(Some(name)).len()))
+                .collect();
+            let mut text = tr!(
+                m,
+                "namespaces.count",
+                count = counts.len(),
+                default = mem.nodes_in(None).len(),
+            );
+            for (name, nodes) in &counts {
+                text.push('\n');
+                text.push_str(&tr!(m, "namespaces.line", name = name, count = nodes));
+            }
+            let namespaces: serde_json::Map<String, serde_json::Value> = counts
+                .iter()
+                .map(|(name, nodes)| (name.to_string(), json!(nodes)))
+                .collect();
+            emit(output, &text, json!({ "namespaces": namespaces }));
+        }
+        Commands::Sync { file, remote, push } => {
+            let (from, to) = if push {
+                (&file, &remote)
+            } else {
+                (&remote, &file)
+            };
+            let transferred = sync::pull(to, from)?;
+            emit(
+                output,
+                &tr!(m, "sync.done", count = transferred, from = from, to = to),
+                json!({ "from": from, "to": to, "commits": transferred }),
+            );
+        }
+        Commands::Bundle { file, out, since } => {
+            let written = sync::write_bundle(&file, &out, since)?;
+            emit(
+                output,
+                &tr!(m, "bundle.done", count = written, since = since, out = out),
+                json!({ "file": file, "out": out, "since": since, "commits": written }),
+            );
+        }
+        Commands::Unbundle { file, bundle } => {
+            let added = sync::apply_bundle(&file, &bundle)?;
+            emit(
+                output,
+                &tr!(m, "unbundle.done", count = added, bundle = bundle),
+                json!({ "file": file, "bundle": bundle, "commits": added }),
+            );
+        }
+        Commands::Discard { file } => {
+            storage::load(&file)?;
+            let dropped = wal::clear_staged(&file)?;
+            emit(
+                output,
+                &tr!(m, "discard.done", count = dropped, file = file),
+                json!({ "file": file, "discarded": dropped }),
+            );
+        }
+        Commands::Fsck { file, deep } => {
+            let report = maintenance::fsck(&file, deep)?;
+            let text = if report.issues.is_empty() {
+                tr!(m, "fsck.clean", file = file)
+            } else {
+                report
+                    .issues
+                    .iter()
+                    .map(|i| {
+                        tr!(
+                            m,
+                            "fsck.issue",
+                            severity = format!("{:?}", i.severity),
+                            check = i.check,
+                            message = i.message,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            emit(output, &text, serde_json::to_value(&report)?);
+            if report.has_errors() {
+                return Err(anyhow::anyhow!(tr!(m, "fsck.failed", file = file)));
+            }
+        }
+        Commands::Verify {
+            file,
+            deep,
+            report: report_path,
+            key,
+        } => {
+            let mut report = verify::verify(&file, deep)?;
+            let key = key.map(fs::read).transpose()?;
+            report.sign(key.as_deref())?;
+            if let Some(path) = &report_path {
+                fs::write(path, serde_json::to_string_pretty(&report)?)?;
+            }
+
+            let mut text = tr!(
+                m,
+                "verify.summary",
+                file = file,
+                checks = report.checks.len(),
+                failures = report.failures.len(),
+            );
+            for failure in &report.failures {
+                text.push('\n');
+                text.push_str(&tr!(
+                    m,
+                    "verify.failure",
+                    check = failure.check,
+                    message = failure.message,
+                ));
+            }
+            if let Some(path) = &report_path {
+                text.push('\n');
+                text.push_str(&tr!(m, "verify.written", path = path));
+            }
+            emit(output, &text, serde_json::to_value(&report)?);
+            if !report.passed() {
+                return Err(anyhow::anyhow!(tr!(m, "verify.failed", file = file)));
+            }
+        }
+        Commands::Redact {
+            file,
+            id,
+            key,
+            backup,
+        } => {
+            let backup = backup_first(&file, backup)?;
+            let redaction = maintenance::redact(&file, id, &key)?;
+            let mut text = tr!(
+                m,
+                "redact.done",
+                id = id,
+                key = key,
+                mutations = redaction.mutations,
+                snapshots = redaction.snapshots,
+                commit = redaction.marker_commit,
+            );
+            if let Some(path) = &backup {
+                text = format!(
+                    "{}\n{}",
+                    tr!(m, "backup.done", file = file, path = path),
+                    text
+                );
+            }
+            let mut value = serde_json::to_value(&redaction)?;
+            value["backup"] = json!(backup);
+            emit(output, &text, value);
+        }
+        Commands::Reset {
+            file,
+            commit,
+            hard,
+            backup,
+        } => {
+            let mut mem = storage::load(&file)?;
+            let backup = match hard {
+                true => backup_first(&file, backup)?,
+                false => None,
+            };
+            let dropped = match hard {
+                true => {
+                    let dropped = mem.reset_to(commit)?;
+                    storage::save(&file, &mem)?;
+                    dropped
+                }
+                false => {
+                    let mut preview = mem.clone();
+                    preview.reset_to(commit)?
+                }
+            };
+            let key = if hard { "reset.done" } else { "reset.preview" };
+            let mut text = tr!(m, key, file = file, commit = commit, count = dropped.len());
+            if let Some(path) = &backup {
+                text = format!(
+                    "{}\n{}",
+                    tr!(m, "backup.done", file = file, path = path),
+                    text
+                );
+            }
+            let ids: Vec<u64> = dropped.iter().map(|c| c.id).collect();
+            emit(
+                output,
+                &text,
+                json!({ "commit": commit, "dropped": ids, "applied": hard, "backup": backup }),
+            );
+        }
+        Commands::Checkpoints {
+            file,
+            keep,
+            rebuild,
+        } => {
+            let text = match (keep, rebuild) {
+                (Some(keep), _) => {
+                    let removed = maintenance::prune_checkpoints(&file, keep)?;
+                    tr!(m, "checkpoints.pruned", file = file, count = removed)
+                }
+                (None, Some(interval)) => {
+                    let mut mem = storage::load(&file)?;
+                    let count = mem.rebuild_checkpoints(interval)?;
+                    storage::save(&file, &mem)?;
+                    tr!(
+                        m,
+                        "checkpoints.rebuilt",
+                        file = file,
+                        count = count,
+                        interval = interval
+                    )
+                }
+                (None, None) => String::new(),
+            };
+            let mem = storage::load(&file)?;
+            let ids: Vec<u64> = mem.checkpoints.iter().map(|cp| cp.commit_id).collect();
+            let list = ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let listed = tr!(m, "checkpoints.list", count = ids.len(), commits = list);
+            let text = match text.is_empty() {
+                true => listed,
+                false => format!("{}\n{}", text, listed),
+            };
+            emit(output, &text, json!({ "file": file, "checkpoints": ids }));
+        }
+        Commands::Retention {
+            file,
+            max_commits,
+            max_age,
+            keep_tags,
+            clear,
+        } => {
+            let mut mem = storage::load(&file)?;
+            let changed =
+                clear || max_commits.is_some() || max_age.is_some() || !keep_tags.is_empty();
+            if changed {
+                mem.retention = (!clear).then_some(RetentionPolicy {
+                    max_commits,
+                    max_age,
+                    keep_tags,
+                });
+                storage::save(&file, &mem)?;
+            }
+            let text = match &mem.retention {
+                Some(policy) => tr!(
+                    m,
+                    "retention.policy",
+                    file = file,
+                    max_commits = policy
+                        .max_commits
+                        .map_or("-".to_string(), |n| n.to_string()),
+                    max_age = policy
+                        .max_age
+                        .map_or("-".to_string(), |s| format!("{}s", s)),
+                    keep_tags = policy.keep_tags.join(", "),
+                ),
+                None => tr!(m, "retention.none", file = file),
+            };
+            emit(
+                output,
+                &text,
+                json!({ "file": file, "retention": mem.retention }),
+            );
+        }
+        Commands::Prune { file, backup } => {
+            let backup = backup_first(&file, backup)?;
+            let pruned = maintenance::apply_retention(&file)?;
+            let mut text = match pruned {
+                Some(commit) => tr!(m, "prune.done", file = file, commit = commit),
+                None => tr!(m, "prune.none", file = file),
+            };
+            if let Some(path) = &backup {
+                text = format!(
+                    "{}\n{}",
+                    tr!(m, "backup.done", file = file, path = path),
+                    text
+                );
+            }
+            emit(
+                output,
+                &text,
+                json!({ "file": file, "pruned_through": pruned, "backup": backup }),
+            );
+        }
+        Commands::Expire { file, now } => {
+            let now = now.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+            });
+            let expired = maintenance::expire(&file, now)?;
+            let text = match expired.len() {
+                0 => tr!(m, "expire.none", file = file),
+                count => tr!(m, "expire.done", file = file, count = count),
+            };
+            emit(output, &text, json!({ "file": file, "expired": expired }));
+        }
+        Commands::Seal {
+            file,
+            key,
+            check,
+            clear,
+        } => {
+            let mut mem = storage::load(&file)?;
+            let key = key.map(fs::read).transpose()?;
+            if check {
+                mem.verify_seal(key.as_deref())?;
+            } else if clear {
+                mem.unseal();
+                storage::save(&file, &mem)?;
+            } else {
+                mem.seal(key.as_deref())?;
+                storage::save(&file, &mem)?;
+            }
+            let algorithm = mem.seal.as_ref().map(|s| s.algorithm.clone());
+            let text = match &algorithm {
+                None => tr!(m, "seal.cleared", file = file),
+                Some(algorithm) if check => {
+                    tr!(m, "seal.valid", file = file, algorithm = algorithm)
+                }
+                Some(algorithm) => tr!(m, "seal.done", file = file, algorithm = algorithm),
+            };
+            emit(output, &text, json!({ "file": file, "seal": mem.seal }));
+        }
+        Commands::Anchor { file, verify } => {
+            let mem = storage::load(&file)?;
+            let (anchor, key) = match verify {
+                Some(path) => {
+                    let anchor: Anchor = serde_json::from_str(&fs::read_to_string(path)?)?;
+                    mem.verify_anchor(&anchor)?;
+                    (anchor, "anchor.valid")
+                }
+                None => (mem.anchor()?, "anchor.done"),
+            };
+            emit(
+                output,
+                &tr!(
+                    m,
+                    key,
+                    file = file,
+                    id = anchor.head_commit_id,
+                    hash = hex(&anchor.head_hash),
+                    state = hex(&anchor.state_hash),
+                ),
+                json!(anchor),
+            );
+        }
+        Commands::Checksum { file, remove } => {
+            let mut mem = storage::load(&file)?;
+            mem.set_file_checksum(!remove);
+            storage::save(&file, &mem)?;
+            let text = match remove {
+                false => tr!(m, "checksum.added", file = file),
+                true => tr!(m, "checksum.removed", file = file),
+            };
+            emit(output, &text, json!({ "file": file, "checksum": !remove }));
+        }
+        Commands::Blobs {
+            file,
+            threshold,
+            off,
+            prune,
+        } => {
+            let mut mem = storage::load(&file)?;
+            if threshold.is_some() || off {
+                mem.set_blob_threshold(threshold);
+                storage::save(&file, &mem)?;
+            }
+            let pruned = match prune {
+                true => Some(blob::prune_blobs(&file)?),
+                false => None,
+            };
+            let count = blob::referenced(&file)?.len();
+            let dir = blob::blob_dir(&file);
+            let mut text = match mem.blob_threshold() {
+                Some(bytes) => {
+                    tr!(
+                        m,
+                        "blobs.info",
+                        file = file,
+                        count = count,
+                        dir = dir,
+                        bytes = bytes
+                    )
+                }
+                None => tr!(m, "blobs.off", file = file),
+            };
+            if let Some(pruned) = pruned {
+                text.push('\n');
+                text.push_str(&tr!(m, "blobs.pruned", count = pruned));
+            }
+            emit(
+                output,
+                &text,
+                json!({
+                    "file": file,
+                    "threshold": mem.blob_threshold(),
+                    "blobs": count,
+                    "dir": dir,
+                    "pruned": pruned,
+                }),
+            );
+        }
+        Commands::Backup {
+            file,
+            dir,
+            keep,
+            max_age,
+        } => {
+            let made = backup::backup(&file, dir.as_deref())?;
+            let mut text = tr!(m, "backup.done", file = file, path = made.path);
+            let mut removed = Vec::new();
+            if keep.is_some() || max_age.is_some() {
+                let policy = backup::RotationPolicy {
+                    keep_last: keep,
+                    max_age,
+                };
+                removed = backup::rotate(&file, dir.as_deref(), &policy)?;
+                if !removed.is_empty() {
+                    text.push('\n');
+                    text.push_str(&tr!(m, "backup.rotated", count = removed.len()));
+                }
+            }
+            emit(
+                output,
+                &text,
+                json!({
+                    "file": file,
+                    "backup": made,
+                    "removed": removed.iter().map(|b| &b.path).collect::<Vec<_>>(),
+                }),
+            );
+        }
+        Commands::Restore { file, backup, dir } => {
+            let path = match backup {
+                Some(path) => path,
+                None => backup::list(&file, dir.as_deref())?
+                    .pop()
+                    .map(|b| b.path)
+                    .ok_or_else(|| anyhow::anyhow!(tr!(m, "restore.none", file = file)))?,
+            };
+            let mem = backup::restore(&path, &file)?;
+            emit(
+                output,
+                &tr!(
+                    m,
+                    "restore.done",
+                    file = file,
+                    path = path,
+                    commits = mem.commits.len(),
+                ),
+                json!({ "file": file, "backup": path, "commits": mem.commits.len() }),
+            );
+        }
+        Commands::Bisect {
+            file,
+            field,
+            equals,
+        } => {
+            let id: NodeId = field[0].parse().map_err(|_| {
+                MyosotisError::InvalidInput(format!("invalid node id '{}'", field[0]))
+            })?;
+            let key = &field[1];
+            let expected: serde_json::Value = serde_json::from_str(&equals)
+                .unwrap_or_else(|_| serde_json::Value::String(equals.clone()));
+            let matches = |state: &HashMap<NodeId, Node>| {
+                state
+                    .get(&id)
+                    .filter(|node| !node.deleted)
+                    .and_then(|node| node.fields.get(key))
+                    .is_some_and(|value| render::to_json(value, None) == expected)
+            };
+
+            let mem = storage::load(&file)?;
+            let commit = mem.bisect(matches)?;
+            let text = match commit {
+                Some(commit_id) => {
+                    let now = matches(&mem.state_at_commit(commit_id)?);
+                    tr!(
+                        m,
+                        "bisect.found",
+                        commit = commit_id,
+                        id = id,
+                        key = key,
+                        value = expected,
+                        now = now,
+                    )
+                }
+                None => tr!(m, "bisect.none", id = id, key = key, value = expected),
+            };
+            emit(
+                output,
+                &text,
+                json!({
+                    "file": file,
+                    "id": render::id_json(id),
+                    "key": key,
+                    "equals": expected,
+                    "commit": commit,
+                }),
+            );
         }
-        Commands::Compact { file, at } => {
+        Commands::Compact { file, at, backup } => {
+            let backup = backup_first(&file, backup)?;
             storage::compact(&file, at)?;
-            println!("Compacted log in {}", file);
+            let mut text = tr!(m, "compact.done", file = file);
+            if let Some(path) = &backup {
+                text = format!(
+                    "{}\n{}",
+                    tr!(m, "backup.done", file = file, path = path),
+                    text
+                );
+            }
+            emit(output, &text, json!({ "file": file, "backup": backup }));
         }
-        Commands::Show { file, id, at } => {
+        Commands::ExportGit { file, dir } => {
             let mem = storage::load(&file)?;
+            fs::create_dir_all(&dir)?;
 
-            if let Some(commit_id) = at {
-                let state = mem
-                    .state_at_commit(commit_id)
-                    .map_err(|e| anyhow::anyhow!(e))?;
+            let status = Command::new("git")
+                .args(["init", "--quiet"])
+                .current_dir(&dir)
+                .status()?;
+            if !status.success() {
+                return Err(anyhow::anyhow!("git init failed in {}", dir));
+            }
 
-                let node = state
-                    .get(&id)
-                    .ok_or_else(|| anyhow::anyhow!(MyosotisError::NodeNotFound(id)))?;
-                if node.deleted {
-                    return Err(anyhow::anyhow!(MyosotisError::NodeDeleted(id)));
+            let mut child = Command::new("git")
+                .args(["fast-import", "--quiet"])
+                .current_dir(&dir)
+                .stdin(Stdio::piped())
+                .spawn()?;
+            {
+                let stdin = child
+                    .stdin
+                    .as_mut()
+                    .ok_or_else(|| anyhow::anyhow!("failed to open git fast-import stdin"))?;
+                export::git_fast_import(&mem, stdin)?;
+                stdin.flush()?;
+            }
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(anyhow::anyhow!("git fast-import failed in {}", dir));
+            }
+
+            emit(
+                output,
+                &tr!(
+                    m,
+                    "export_git.done",
+                    count = mem.commits.len(),
+                    file = file,
+                    dir = dir,
+                ),
+                json!({ "commits": mem.commits.len(), "dir": dir }),
+            );
+        }
+        Commands::ExportNeo4j { file, out, at } => {
+            let mem = storage::load(&file)?;
+            let (nodes, relationships) = mem.to_neo4j_csv(at)?;
+            fs::create_dir_all(&out)?;
+            let dir = std::path::Path::new(&out);
+            fs::write(dir.join("nodes.csv"), nodes)?;
+            fs::write(dir.join("relationships.csv"), relationships)?;
+            emit(
+                output,
+                &tr!(m, "export_neo4j.done", dir = out),
+                json!({ "dir": out }),
+            );
+        }
+        Commands::Graph {
+            file,
+            format,
+            at,
+            ontology,
+        } => {
+            let mem = storage::load(&file)?;
+            let ontology: export::Ontology = match ontology {
+                Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+                None => export::Ontology::default(),
+            };
+            let rendered = match format.as_str() {
+                "dot" => mem.to_dot(at)?,
+                "graphml" => mem.to_graphml(at)?,
+                "ntriples" => mem.to_ntriples(at, &ontology)?,
+                "jsonld" => format!(
+                    "{}\n",
+                    serde_json::to_string_pretty(&mem.to_json_ld(at, &ontology)?)?
+                ),
+                other => {
+                    return Err(anyhow::anyhow!(MyosotisError::InvalidInput(format!(
+                        "unknown graph format '{}', expected 'dot', 'graphml', 'ntriples' or 'jsonld'",
+                        other
+                    ))));
                 }
+            };
+            match output {
+                OutputFormat::Text => print!("{}", rendered),
+                OutputFormat::Json => {
+                    println!("{}", json!({ "format": format, "graph": rendered }))
+                }
+            }
+        }
+        Commands::Import { file, data, ty } => {
+            let mut mem = if storage::exists(&file) {
+                storage::load(&file)?
+            } else {
+                Memory::new()
+            };
+
+            let contents = fs::read_to_string(&data)?;
+            let rows = if data.to_ascii_lowercase().ends_with(".csv") {
+                import::rows_from_csv(&contents)?
+            } else {
+                import::rows_from_json(&contents)?
+            };
+
+            let ids = import::stage_rows(&mut mem, &ty, rows)?;
+            mem.commit(Some(format!("import {}", data)))?;
+            storage::save(&file, &mem)?;
+            let commit_id = mem.commits.last().map(|c| c.id).unwrap_or(0);
+            emit(
+                output,
+                &tr!(
+                    m,
+                    "import.done",
+                    count = ids.len(),
+                    type = ty,
+                    file = file,
+                    commit = commit_id,
+                ),
+                json!({ "ids": ids_json(&ids), "type": ty, "commit_id": commit_id }),
+            );
+        }
+        Commands::Apply {
+            file,
+            script,
+            message,
+        } => {
+            let mut mem = if storage::exists(&file) {
+                storage::load(&file)?
+            } else {
+                Memory::new()
+            };
 
-                println!("Node {} @ commit {}:", id, commit_id);
-                println!("  type: {}", node.ty);
-                println!("  fields:");
-                let mut keys: Vec<&String> = node.fields.keys().collect();
-                keys.sort();
-                for k in keys {
-                    println!("    {}: {:?}", k, node.fields.get(k).unwrap());
+            let source = script.filter(|s| s != "-");
+            let contents = match &source {
+                Some(path) => fs::read_to_string(path)?,
+                None => std::io::read_to_string(std::io::stdin())?,
+            };
+            let steps = script::parse(&contents)?;
+            let names = mem.apply_script(&steps)?;
+            let message = message
+                .unwrap_or_else(|| format!("apply {}", source.as_deref().unwrap_or("stdin")));
+            mem.commit(Some(message))?;
+            storage::save(&file, &mem)?;
+            let commit_id = mem.commits.last().map(|c| c.id).unwrap_or(0);
+            emit(
+                output,
+                &tr!(
+                    m,
+                    "apply.done",
+                    count = steps.len(),
+                    file = file,
+                    commit = commit_id
+                ),
+                json!({
+                    "steps": steps.len(),
+                    "created": names
+                        .iter()
+                        .map(|(name, id)| (name.clone(), render::id_json(*id)))
+                        .collect::<serde_json::Map<_, _>>(),
+                    "commit_id": commit_id,
+                }),
+            );
+        }
+        #[cfg(feature = "fuse")]
+        Commands::Mount { file, dir } => {
+            let mem = storage::load(&file)?;
+            emit(
+                output,
+                &tr!(m, "mount.start", file = file, dir = dir),
+                json!({ "file": file, "mountpoint": dir }),
+            );
+            myosotis::mount::mount(&mem, &dir)?;
+        }
+        #[cfg(feature = "server")]
+        Commands::Serve { file, addr } => {
+            let mem = storage::load(&file)?;
+            let service = myosotis::server::MemoryService::new(mem.into()).persist_to(&file);
+            emit(
+                output,
+                &tr!(m, "serve.start", file = file, addr = addr),
+                json!({ "file": file, "addr": addr.to_string() }),
+            );
+            tokio::runtime::Runtime::new()?.block_on(myosotis::server::serve(service, addr))?;
+        }
+        Commands::Shell { file, wal } => {
+            let mut editor = rustyline::Editor::new()?;
+            let mem = if wal {
+                let leftover = wal::pending(&file)?;
+                let mut recovery = WalRecovery::Discard;
+                if !leftover.is_empty() {
+                    let prompt = tr!(
+                        m,
+                        "shell.wal_prompt",
+                        count = leftover.len(),
+                        wal = wal::wal_path(&file),
+                    );
+                    if let Ok(answer) = editor.readline(&prompt)
+                        && matches!(
+                            answer.trim().to_lowercase().as_str(),
+                            "y" | "yes" | "j" | "ja"
+                        )
+                    {
+                        recovery = WalRecovery::Restage;
+                    }
                 }
+                wal::load(&file, recovery)?
             } else {
-                let node = mem
-                    .head_state
-                    .get(&id)
-                    .ok_or_else(|| anyhow::anyhow!(MyosotisError::NodeNotFound(id)))?;
-                if node.deleted {
-                    return Err(anyhow::anyhow!(MyosotisError::NodeDeleted(id)));
+                storage::load(&file)?
+            };
+            let commits_before = mem.commits.len();
+
+            editor.set_helper(Some(ShellHelper {
+                session: Session::new(mem),
+            }));
+
+            loop {
+                let line = match editor.readline("myo> ") {
+                    Ok(line) => line,
+                    Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+                    Err(e) => return Err(e.into()),
+                };
+                let _ = editor.add_history_entry(line.as_str());
+                let Some(helper) = editor.helper_mut() else {
+                    break;
+                };
+                match helper.session.execute(&line) {
+                    Ok(Outcome::Output(text)) if !text.is_empty() => println!("{}", text),
+                    Ok(Outcome::Output(_)) => {}
+                    Ok(Outcome::Exit) => break,
+                    Err(e) => eprintln!("{}", tr!(m, "shell.error", error = e)),
                 }
+            }
+
+            let Some(helper) = editor.helper_mut() else {
+                return Ok(());
+            };
+            let mem = &mut helper.session.mem;
+            if let Some(wal) = mem.wal_path()
+                && !mem.pending_mutations().is_empty()
+            {
+                tracing::warn!(
+                    pending = mem.pending_mutations().len(),
+                    wal,
+                    "keeping uncommitted mutations in the write-ahead log"
+                );
+            } else if !mem.pending_mutations().is_empty() {
+                tracing::warn!(
+                    pending = mem.pending_mutations().len(),
+                    "discarding uncommitted mutations"
+                );
+            }
+            if mem.commits.len() != commits_before {
+                storage::save(&file, mem)?;
+                emit(
+                    output,
+                    &tr!(
+                        m,
+                        "shell.saved",
+                        count = mem.commits.len() - commits_before,
+                        file = file,
+                    ),
+                    json!({ "file": file, "commits": mem.commits.len() - commits_before }),
+                );
+            }
+            if mem.pending_mutations().is_empty() {
+                mem.disable_wal()?;
+            }
+        }
+        Commands::Report { file, period, top } => {
+            let mem = storage::load(&file)?;
+            let options = analytics::ReportOptions {
+                period_secs: period,
+                top,
+            };
+            let report = mem.report(&options)?;
 
-                println!("Node {} (current):", id);
-                println!("  type: {}", node.ty);
-                println!("  fields:");
-                let mut keys: Vec<&String> = node.fields.keys().collect();
-                keys.sort();
-                for k in keys {
-                    println!("    {}: {:?}", k, node.fields.get(k).unwrap());
+            let mut lines = vec![tr!(m, "report.types")];
+            for (ty, stats) in &report.by_type {
+                lines.push(tr!(
+                    m,
+                    "report.type",
+                    name = ty,
+                    live = stats.live_nodes,
+                    deleted = stats.deleted_nodes,
+                    mutations = stats.mutations,
+                    bytes = stats.value_bytes,
+                ));
+            }
+            lines.push(tr!(m, "report.frequency", period = period));
+            for p in &report.commit_frequency.periods {
+                lines.push(tr!(
+                    m,
+                    "report.period",
+                    start = p.start,
+                    commits = p.commits,
+                    mutations = p.mutations,
+                ));
+            }
+            if report.commit_frequency.untimed > 0 {
+                let count = report.commit_frequency.untimed;
+                lines.push(tr!(m, "report.untimed", count = count));
+            }
+            lines.push(tr!(m, "report.mutated"));
+            for node in &report.most_mutated {
+                lines.push(tr!(
+                    m,
+                    "report.node",
+                    id = node.node_id,
+                    ty = node.ty,
+                    count = node.mutations,
+                ));
+            }
+            lines.push(tr!(m, "report.values"));
+            for value in &report.largest_values {
+                lines.push(tr!(
+                    m,
+                    "report.value",
+                    id = value.node_id,
+                    key = value.key,
+                    bytes = value.bytes,
+                ));
+            }
+            lines.push(tr!(m, "report.keys"));
+            for (key, card) in &report.field_keys {
+                lines.push(tr!(
+                    m,
+                    "report.key",
+                    key = key,
+                    nodes = card.nodes,
+                    distinct = card.distinct_values,
+                ));
+            }
+            emit(output, &lines.join("\n"), serde_json::to_value(&report)?);
+        }
+        Commands::Status { file } => {
+            storage::load(&file)?;
+            let summary = PendingSummary::of(&wal::staged(&file)?);
+            let staged = wal::staged_path(&file);
+            let mut lines = Vec::new();
+            if summary.mutations == 0 {
+                lines.push(tr!(m, "status.clean", file = file));
+            } else {
+                lines.push(tr!(
+                    m,
+                    "status.pending",
+                    count = summary.mutations,
+                    path = staged
+                ));
+                for (kind, count) in &summary.by_kind {
+                    lines.push(tr!(m, "stats.entry", name = kind, count = count));
                 }
+                let nodes: Vec<String> = summary.nodes.iter().map(|id| id.to_string()).collect();
+                lines.push(tr!(m, "status.nodes", nodes = nodes.join(", ")));
             }
+            let mut value = serde_json::to_value(&summary)?;
+            value["dirty"] = json!(summary.mutations > 0);
+            value["staged"] = json!(staged);
+            emit(output, &lines.join("\n"), value);
+        }
+        Commands::Stats { file } => {
+            let mem = storage::load(&file)?;
+            let stats = mem.stats()?;
+
+            let mut lines = vec![tr!(
+                m,
+                "stats.nodes",
+                live = stats.live_nodes,
+                deleted = stats.deleted_nodes,
+            )];
+            for (ty, count) in &stats.nodes_by_type {
+                lines.push(tr!(m, "stats.entry", name = ty, count = count));
+            }
+            lines.push(tr!(m, "stats.commits", count = stats.commits));
+            lines.push(tr!(m, "stats.checkpoints", count = stats.checkpoints));
+            lines.push(tr!(m, "stats.mutations"));
+            for (kind, count) in &stats.mutations_by_kind {
+                lines.push(tr!(m, "stats.entry", name = kind, count = count));
+            }
+            lines.push(tr!(m, "stats.size", bytes = stats.approx_size_bytes));
+            for (key, inputs) in [
+                ("stats.commit_inputs", &stats.commit_hash_inputs),
+                ("stats.checkpoint_inputs", &stats.checkpoint_hash_inputs),
+            ] {
+                if let Some(commit_id) = inputs.max_commit_id {
+                    lines.push(tr!(
+                        m,
+                        key,
+                        total = inputs.total_bytes,
+                        max = inputs.max_bytes,
+                        commit = commit_id,
+                    ));
+                }
+            }
+            let text = lines.join("\n");
+            emit(output, &text, serde_json::to_value(&stats)?);
+        }
+        Commands::Advise { file } => {
+            let advice = maintenance::advise(&file)?;
+
+            let mut lines = vec![
+                tr!(
+                    m,
+                    "advise.file",
+                    bytes = advice.file_bytes,
+                    commits = advice.commits,
+                    mutations = advice.mutations,
+                ),
+                tr!(
+                    m,
+                    "advise.nodes",
+                    nodes = advice.nodes,
+                    tombstones = advice.tombstones,
+                    percent = format!("{:.0}", advice.tombstone_ratio * 100.0),
+                ),
+                tr!(
+                    m,
+                    "advise.churn",
+                    ratio = format!("{:.1}", advice.mutations_per_node),
+                ),
+                tr!(
+                    m,
+                    "advise.checkpoints",
+                    count = advice.checkpoints,
+                    redundant = advice.redundant_checkpoints,
+                ),
+            ];
+            if !advice.hotspots.is_empty() {
+                lines.push(tr!(m, "advise.hotspots"));
+                for spot in &advice.hotspots {
+                    lines.push(tr!(
+                        m,
+                        "advise.hotspot",
+                        id = spot.node_id,
+                        count = spot.mutations,
+                    ));
+                }
+            }
+            match &advice.recommendation {
+                Some(rec) => {
+                    lines.push(tr!(
+                        m,
+                        "advise.recommend",
+                        file = file,
+                        commit = rec.target_commit_id,
+                    ));
+                    lines.push(tr!(
+                        m,
+                        "advise.reason",
+                        reason = rec.reason,
+                        saved = rec.estimated_savings_bytes,
+                        before = advice.file_bytes,
+                        after = rec.estimated_bytes_after,
+                    ));
+                }
+                None => lines.push(tr!(m, "advise.none")),
+            }
+            let text = lines.join("\n");
+            emit(output, &text, serde_json::to_value(&advice)?);
+        }
+        Commands::Show {
+            file,
+            id,
+            at,
+            full,
+            max_len,
+        } => {
+            let mem = storage::load(&file)?;
+
+            let state = match at {
+                Some(commit_id) => mem
+                    .state_at_commit(commit_id)
+                    .map_err(|e| anyhow::anyhow!(e))?,
+                None => mem.head_state().clone(),
+            };
+            let node = state
+                .get(&id)
+                .ok_or_else(|| anyhow::anyhow!(MyosotisError::NodeNotFound(id)))?;
+            if node.deleted {
+                return Err(anyhow::anyhow!(MyosotisError::NodeDeleted(id)));
+            }
+
+            let mut text = match at {
+                Some(commit_id) => tr!(m, "show.at", id = id, commit = commit_id),
+                None => tr!(m, "show.current", id = id),
+            };
+            text.push('\n');
+            text.push_str(&tr!(m, "show.type", type = node.ty));
+            text.push('\n');
+            text.push_str(&tr!(m, "show.fields"));
+            let mut keys: Vec<&String> = node.fields.keys().collect();
+            keys.sort();
+            let limit = (!full).then_some(max_len);
+            for k in keys {
+                let value = render::pretty(node.fields.get(k).unwrap(), limit, "    ");
+                text.push('\n');
+                text.push_str(&tr!(m, "show.field", key = k, value = value));
+            }
+
+            emit(
+                output,
+                &text,
+                json!({
+                    "id": render::id_json(id),
+                    "at": at,
+                    "type": node.ty,
+                    "fields": serde_json::to_value(&node.fields)?,
+                }),
+            );
         }
     }
 
@@ -0,0 +1,568 @@
+//! Text-mode message templates. Logic picks a key and supplies named
+//! arguments; wording lives here, per language, and can be overridden
+//! from a JSON file of `{"key": "template"}` without rebuilding.
+
+use anyhow::{Context, Result};
+use myosotis::MyosotisError;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+
+const EN: &[(&str, &str)] = &[
+    ("init.exists", "File already exists: {file}"),
+    ("init.done", "Initialized new memory at {file}"),
+    ("history.header", "Commit history:"),
+    ("history.line", "Commit {id} - {message}"),
+    (
+        "watch.rewritten",
+        "History of {file} was rewritten; showing all commits",
+    ),
+    ("log.line", "Commit {id} [{classes}] - {message}"),
+    ("create.done", "Staged node {id} of type '{type}' in {file}"),
+    ("set.done", "Staged node {id} field '{key}' = '{value}'"),
+    ("commit.done", "Committed {id} with message {message}"),
+    ("timestamp.anchored", "Anchored commit {id} with {url}"),
+    ("timestamp.not_due", "Anchoring not due for {file}"),
+    ("delete_node.done", "Staged delete-node for node {id}"),
+    ("restore_node.done", "Staged restore-node for node {id}"),
+    ("set_type.done", "Staged set-type '{type}' on node {id}"),
+    ("alias.set", "Staged alias '{alias}' for node {id}"),
+    ("alias.cleared", "Staged alias removal for node {id}"),
+    ("resolve.found", "Alias '{alias}' names node {id}"),
+    ("resolve.missing", "No node has alias '{alias}'"),
+    (
+        "delete_field.done",
+        "Staged delete-field '{key}' on node {id}",
+    ),
+    (
+        "rename_field.done",
+        "Staged rename '{from}' -> '{to}' on {count} nodes",
+    ),
+    ("session.begin", "Began session {id} at commit {commit}"),
+    ("session.end", "Ended session {id} at commit {commit}"),
+    (
+        "namespaces.count",
+        "{count} namespaces, {default} nodes in the default namespace",
+    ),
+    ("namespaces.line", "{name}: {count} nodes"),
+    ("sessions.count", "{count} sessions"),
+    ("sessions.line", "Session {id}: commits {begin}..{end}"),
+    ("sessions.open", "open"),
+    ("sessions.commits", "Session {id}: {count} commits"),
+    ("sessions.commit", "Commit {id} - {message}"),
+    (
+        "sync.done",
+        "Transferred {count} commits from {from} to {to}",
+    ),
+    (
+        "bundle.done",
+        "Bundled {count} commits after {since} into {out}",
+    ),
+    ("unbundle.done", "Added {count} commits from {bundle}"),
+    (
+        "discard.done",
+        "Discarded {count} staged mutations in {file}",
+    ),
+    ("fsck.clean", "{file}: no issues found"),
+    ("fsck.issue", "[{severity}] {check}: {message}"),
+    ("fsck.failed", "fsck found errors in {file}"),
+    (
+        "verify.summary",
+        "{file}: {checks} checks, {failures} failures",
+    ),
+    ("verify.failure", "  {check}: {message}"),
+    ("verify.written", "Report written to {path}"),
+    ("verify.failed", "verification failed for {file}"),
+    (
+        "redact.done",
+        "Redacted node {id} field '{key}': {mutations} mutations, {snapshots} snapshots (marker commit {commit})",
+    ),
+    ("compact.done", "Compacted log in {file}"),
+    (
+        "checkpoints.list",
+        "{count} checkpoints at commits: {commits}",
+    ),
+    (
+        "checkpoints.pruned",
+        "Removed {count} checkpoints from {file}",
+    ),
+    (
+        "checkpoints.rebuilt",
+        "Rebuilt {count} checkpoints in {file}, one every {interval} commits",
+    ),
+    (
+        "reset.preview",
+        "Would drop {count} commits after commit {commit}; rerun with --hard",
+    ),
+    (
+        "reset.done",
+        "Reset {file} to commit {commit}, dropped {count} commits",
+    ),
+    ("backup.done", "Backed up {file} to {path}"),
+    (
+        "seal.done",
+        "Sealed the genesis state of {file} ({algorithm})",
+    ),
+    ("seal.valid", "Seal of {file} is valid ({algorithm})"),
+    ("seal.cleared", "Removed the seal from {file}"),
+    (
+        "anchor.done",
+        "Anchor of {file}: commit {id} hash {hash} state {state}",
+    ),
+    ("anchor.valid", "{file} matches the anchor at commit {id}"),
+    (
+        "checksum.added",
+        "{file} now starts with a checksum of the whole file",
+    ),
+    ("checksum.removed", "Removed the file checksum from {file}"),
+    (
+        "blobs.info",
+        "{file} keeps {count} values over {bytes} bytes in {dir}",
+    ),
+    ("blobs.off", "{file} keeps every value in the file"),
+    ("blobs.pruned", "Removed {count} unused blobs"),
+    ("backup.rotated", "Removed {count} old backups"),
+    (
+        "restore.done",
+        "Restored {file} from {path} ({commits} commits)",
+    ),
+    ("restore.none", "No backups of {file} found"),
+    (
+        "retention.policy",
+        "Retention for {file}: max commits {max_commits}, max age {max_age}, keep tags [{keep_tags}]",
+    ),
+    ("retention.none", "No retention policy in {file}"),
+    ("prune.done", "Pruned {file} through commit {commit}"),
+    ("prune.none", "Nothing to prune in {file}"),
+    ("expire.done", "Deleted {count} expired fields in {file}"),
+    ("expire.none", "Nothing has expired in {file}"),
+    (
+        "export_git.done",
+        "Exported {count} commits from {file} to {dir}",
+    ),
+    (
+        "export_neo4j.done",
+        "Wrote nodes.csv and relationships.csv to {dir} (neo4j-admin database import full --nodes={dir}/nodes.csv --relationships={dir}/relationships.csv)",
+    ),
+    (
+        "import.done",
+        "Imported {count} nodes of type '{type}' into {file} (commit {commit})",
+    ),
+    (
+        "apply.done",
+        "Applied {count} steps to {file} (commit {commit})",
+    ),
+    (
+        "mount.start",
+        "Mounting {file} read-only at {dir} (unmount to exit)",
+    ),
+    (
+        "serve.start",
+        "Serving {file} over gRPC on {addr} (interrupt to exit)",
+    ),
+    ("shell.error", "error: {error}"),
+    ("shell.saved", "Saved {count} new commits to {file}"),
+    (
+        "shell.wal_prompt",
+        "{wal} holds {count} uncommitted mutations; restage them? [y/N] ",
+    ),
+    ("status.clean", "Nothing staged for {file}"),
+    ("status.pending", "{count} mutations staged in {path}:"),
+    ("status.nodes", "Nodes: {nodes}"),
+    ("stats.nodes", "Nodes: {live} live, {deleted} deleted"),
+    ("stats.entry", "  {name}: {count}"),
+    ("stats.commits", "Commits: {count}"),
+    ("stats.checkpoints", "Checkpoints: {count}"),
+    ("stats.mutations", "Mutations:"),
+    ("stats.size", "Approx. size: {bytes} bytes"),
+    (
+        "stats.commit_inputs",
+        "Commit hash input: {total} bytes total, max {max} bytes at commit {commit}",
+    ),
+    (
+        "stats.checkpoint_inputs",
+        "Checkpoint hash input: {total} bytes total, max {max} bytes at commit {commit}",
+    ),
+    ("report.types", "Types:"),
+    (
+        "report.type",
+        "  {name}: {live} live, {deleted} deleted, {mutations} mutations, {bytes} bytes of values",
+    ),
+    ("report.frequency", "Commits per {period}s:"),
+    (
+        "report.period",
+        "  {start}: {commits} commits, {mutations} mutations",
+    ),
+    (
+        "report.untimed",
+        "  {count} commits without a recorded time",
+    ),
+    ("report.mutated", "Most mutated nodes:"),
+    ("report.node", "  node {id} ({ty}): {count} mutations"),
+    ("report.values", "Largest values:"),
+    ("report.value", "  node {id} field '{key}': {bytes} bytes"),
+    ("report.keys", "Field keys:"),
+    (
+        "report.key",
+        "  {key}: {nodes} nodes, {distinct} distinct values",
+    ),
+    (
+        "advise.file",
+        "File: {bytes} bytes, {commits} commits, {mutations} mutations",
+    ),
+    (
+        "advise.nodes",
+        "Nodes: {nodes} ({tombstones} tombstones, {percent}%)",
+    ),
+    ("advise.churn", "Mutations per node: {ratio}"),
+    (
+        "advise.checkpoints",
+        "Checkpoints: {count} ({redundant} redundant)",
+    ),
+    ("advise.hotspots", "Churn hotspots:"),
+    ("advise.hotspot", "  node {id}: {count} mutations"),
+    (
+        "advise.recommend",
+        "Recommended: myo compact {file} --at {commit}",
+    ),
+    (
+        "advise.reason",
+        "  {reason}; ~{saved} bytes saved ({before} -> {after})",
+    ),
+    ("advise.none", "No compaction recommended"),
+    (
+        "bisect.found",
+        "Commit {commit} is the first where node {id} field '{key}' = {value} is {now}",
+    ),
+    (
+        "bisect.none",
+        "No commit changes whether node {id} field '{key}' = {value}",
+    ),
+    ("show.at", "Node {id} @ commit {commit}:"),
+    ("show.current", "Node {id} (current):"),
+    ("show.type", "  type: {type}"),
+    ("show.fields", "  fields:"),
+    ("show.field", "    {key}: {value}"),
+];
+
+/// German. Keys missing here fall back to English.
+const DE: &[(&str, &str)] = &[
+    ("init.exists", "Datei existiert bereits: {file}"),
+    ("init.done", "Neuer Speicher angelegt in {file}"),
+    ("history.header", "Commit-Verlauf:"),
+    (
+        "watch.rewritten",
+        "Verlauf von {file} wurde umgeschrieben; alle Commits werden angezeigt",
+    ),
+    (
+        "create.done",
+        "Knoten {id} vom Typ '{type}' in {file} vorgemerkt",
+    ),
+    (
+        "set.done",
+        "Knoten {id} Feld '{key}' = '{value}' vorgemerkt",
+    ),
+    (
+        "commit.done",
+        "Commit {id} mit Nachricht {message} erstellt",
+    ),
+    ("timestamp.anchored", "Commit {id} bei {url} verankert"),
+    (
+        "timestamp.not_due",
+        "Verankerung für {file} noch nicht fällig",
+    ),
+    ("delete_node.done", "Löschen von Knoten {id} vorgemerkt"),
+    (
+        "restore_node.done",
+        "Wiederherstellen von Knoten {id} vorgemerkt",
+    ),
+    ("set_type.done", "Typ '{type}' für Knoten {id} vorgemerkt"),
+    ("alias.set", "Alias '{alias}' für Knoten {id} vorgemerkt"),
+    (
+        "alias.cleared",
+        "Entfernen des Alias von Knoten {id} vorgemerkt",
+    ),
+    ("resolve.found", "Alias '{alias}' bezeichnet Knoten {id}"),
+    ("resolve.missing", "Kein Knoten hat den Alias '{alias}'"),
+    (
+        "delete_field.done",
+        "Löschen von Feld '{key}' an Knoten {id} vorgemerkt",
+    ),
+    (
+        "rename_field.done",
+        "Umbenennung '{from}' -> '{to}' an {count} Knoten vorgemerkt",
+    ),
+    ("session.begin", "Sitzung {id} bei Commit {commit} begonnen"),
+    ("session.end", "Sitzung {id} bei Commit {commit} beendet"),
+    (
+        "namespaces.count",
+        "{count} Namensräume, {default} Knoten im Standard-Namensraum",
+    ),
+    ("namespaces.line", "{name}: {count} Knoten"),
+    ("sessions.count", "{count} Sitzungen"),
+    ("sessions.line", "Sitzung {id}: Commits {begin}..{end}"),
+    ("sessions.open", "offen"),
+    ("sessions.commits", "Sitzung {id}: {count} Commits"),
+    (
+        "sync.done",
+        "{count} Commits von {from} nach {to} übertragen",
+    ),
+    (
+        "bundle.done",
+        "{count} Commits nach {since} in {out} gebündelt",
+    ),
+    ("unbundle.done", "{count} Commits aus {bundle} hinzugefügt"),
+    (
+        "discard.done",
+        "{count} vorgemerkte Änderungen in {file} verworfen",
+    ),
+    ("fsck.clean", "{file}: keine Probleme gefunden"),
+    ("fsck.failed", "fsck hat Fehler in {file} gefunden"),
+    (
+        "verify.summary",
+        "{file}: {checks} Prüfungen, {failures} Fehler",
+    ),
+    ("verify.written", "Bericht geschrieben nach {path}"),
+    ("verify.failed", "Prüfung von {file} fehlgeschlagen"),
+    (
+        "redact.done",
+        "Knoten {id} Feld '{key}' geschwärzt: {mutations} Änderungen, {snapshots} Snapshots (Markierungs-Commit {commit})",
+    ),
+    ("compact.done", "Log in {file} kompaktiert"),
+    (
+        "checkpoints.list",
+        "{count} Checkpoints bei Commits: {commits}",
+    ),
+    (
+        "checkpoints.pruned",
+        "{count} Checkpoints aus {file} entfernt",
+    ),
+    (
+        "checkpoints.rebuilt",
+        "{count} Checkpoints in {file} neu erstellt, einer alle {interval} Commits",
+    ),
+    (
+        "reset.preview",
+        "Würde {count} Commits nach Commit {commit} verwerfen; mit --hard erneut ausführen",
+    ),
+    (
+        "reset.done",
+        "{file} auf Commit {commit} zurückgesetzt, {count} Commits verworfen",
+    ),
+    (
+        "backup.done",
+        "Sicherung von {file} nach {path} geschrieben",
+    ),
+    (
+        "seal.done",
+        "Genesis-Zustand von {file} versiegelt ({algorithm})",
+    ),
+    ("seal.valid", "Siegel von {file} ist gültig ({algorithm})"),
+    ("seal.cleared", "Siegel von {file} entfernt"),
+    (
+        "anchor.done",
+        "Anker von {file}: Commit {id} Hash {hash} Zustand {state}",
+    ),
+    (
+        "anchor.valid",
+        "{file} stimmt mit dem Anker bei Commit {id} überein",
+    ),
+    (
+        "checksum.added",
+        "{file} beginnt jetzt mit einer Prüfsumme der ganzen Datei",
+    ),
+    ("checksum.removed", "Dateiprüfsumme aus {file} entfernt"),
+    (
+        "blobs.info",
+        "{file} hält {count} Werte über {bytes} Bytes in {dir}",
+    ),
+    ("blobs.off", "{file} hält alle Werte in der Datei"),
+    ("blobs.pruned", "{count} ungenutzte Blobs entfernt"),
+    ("backup.rotated", "{count} alte Sicherungen entfernt"),
+    (
+        "restore.done",
+        "{file} aus {path} wiederhergestellt ({commits} Commits)",
+    ),
+    ("restore.none", "Keine Sicherungen von {file} gefunden"),
+    (
+        "retention.policy",
+        "Aufbewahrung für {file}: höchstens {max_commits} Commits, höchstens {max_age} alt, Tags behalten [{keep_tags}]",
+    ),
+    ("retention.none", "Keine Aufbewahrungsregel in {file}"),
+    ("prune.done", "{file} bis Commit {commit} bereinigt"),
+    ("prune.none", "Nichts zu bereinigen in {file}"),
+    (
+        "expire.done",
+        "{count} abgelaufene Felder in {file} gelöscht",
+    ),
+    ("expire.none", "In {file} ist nichts abgelaufen"),
+    (
+        "export_git.done",
+        "{count} Commits aus {file} nach {dir} exportiert",
+    ),
+    (
+        "import.done",
+        "{count} Knoten vom Typ '{type}' in {file} importiert (Commit {commit})",
+    ),
+    (
+        "apply.done",
+        "{count} Schritte auf {file} angewendet (Commit {commit})",
+    ),
+    (
+        "mount.start",
+        "{file} wird schreibgeschützt unter {dir} eingehängt (zum Beenden aushängen)",
+    ),
+    (
+        "serve.start",
+        "{file} wird per gRPC unter {addr} bereitgestellt (zum Beenden unterbrechen)",
+    ),
+    ("shell.error", "Fehler: {error}"),
+    ("shell.saved", "{count} neue Commits in {file} gespeichert"),
+    (
+        "shell.wal_prompt",
+        "{wal} enthält {count} nicht committete Mutationen; erneut vormerken? [j/N] ",
+    ),
+    ("status.clean", "Nichts vorgemerkt für {file}"),
+    ("status.pending", "{count} Änderungen vorgemerkt in {path}:"),
+    ("status.nodes", "Knoten: {nodes}"),
+    ("stats.nodes", "Knoten: {live} aktiv, {deleted} gelöscht"),
+    ("stats.commits", "Commits: {count}"),
+    ("stats.checkpoints", "Checkpoints: {count}"),
+    ("stats.mutations", "Änderungen:"),
+    ("stats.size", "Ungefähre Größe: {bytes} Bytes"),
+    ("report.types", "Typen:"),
+    (
+        "report.type",
+        "  {name}: {live} aktiv, {deleted} gelöscht, {mutations} Änderungen, {bytes} Bytes an Werten",
+    ),
+    ("report.frequency", "Commits pro {period}s:"),
+    (
+        "report.period",
+        "  {start}: {commits} Commits, {mutations} Änderungen",
+    ),
+    ("report.untimed", "  {count} Commits ohne erfasste Zeit"),
+    ("report.mutated", "Am häufigsten geänderte Knoten:"),
+    ("report.node", "  Knoten {id} ({ty}): {count} Änderungen"),
+    ("report.values", "Größte Werte:"),
+    ("report.value", "  Knoten {id} Feld '{key}': {bytes} Bytes"),
+    ("report.keys", "Feldschlüssel:"),
+    (
+        "report.key",
+        "  {key}: {nodes} Knoten, {distinct} verschiedene Werte",
+    ),
+    (
+        "advise.file",
+        "Datei: {bytes} Bytes, {commits} Commits, {mutations} Änderungen",
+    ),
+    (
+        "advise.nodes",
+        "Knoten: {nodes} ({tombstones} Grabsteine, {percent}%)",
+    ),
+    ("advise.churn", "Änderungen pro Knoten: {ratio}"),
+    (
+        "advise.checkpoints",
+        "Checkpoints: {count} ({redundant} redundant)",
+    ),
+    ("advise.hotspots", "Häufig geänderte Knoten:"),
+    ("advise.hotspot", "  Knoten {id}: {count} Änderungen"),
+    (
+        "advise.recommend",
+        "Empfohlen: myo compact {file} --at {commit}",
+    ),
+    ("advise.none", "Keine Kompaktierung empfohlen"),
+    (
+        "bisect.found",
+        "Commit {commit} ist der erste, bei dem Knoten {id} Feld '{key}' = {value} {now} ist",
+    ),
+    (
+        "bisect.none",
+        "Kein Commit ändert, ob Knoten {id} Feld '{key}' = {value} ist",
+    ),
+    ("show.at", "Knoten {id} @ Commit {commit}:"),
+    ("show.current", "Knoten {id} (aktuell):"),
+    ("show.type", "  Typ: {type}"),
+    ("show.fields", "  Felder:"),
+];
+
+pub struct Messages {
+    templates: HashMap<String, String>,
+}
+
+impl Messages {
+    /// Built-in catalog for `lang` (`en` or `de`), with templates from the
+    /// JSON file at `overrides` on top. Override keys must exist in the
+    /// English catalog so typos are caught.
+    pub fn load(lang: &str, overrides: Option<&str>) -> Result<Self> {
+        let mut templates: HashMap<String, String> = EN
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let catalog = match lang {
+            "en" => &[][..],
+            "de" => DE,
+            other => {
+                return Err(anyhow::anyhow!(MyosotisError::InvalidInput(format!(
+                    "unknown language '{}', expected 'en' or 'de'",
+                    other
+                ))));
+            }
+        };
+        for (key, template) in catalog {
+            templates.insert(key.to_string(), template.to_string());
+        }
+
+        if let Some(path) = overrides {
+            let data = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read file: {}", path))?;
+            let custom: HashMap<String, String> = serde_json::from_str(&data)?;
+            for (key, template) in custom {
+                if !templates.contains_key(&key) {
+                    return Err(anyhow::anyhow!(MyosotisError::InvalidInput(format!(
+                        "unknown message key '{}' in {}",
+                        key, path
+                    ))));
+                }
+                templates.insert(key, template);
+            }
+        }
+        Ok(Self { templates })
+    }
+
+    /// Fills `{name}` placeholders in the template for `key`. Placeholders
+    /// without a matching argument are left as they are.
+    pub fn render(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let template = self.templates.get(key).map(String::as_str).unwrap_or(key);
+        let mut text = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            text.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let arg = after.find('}').and_then(|close| {
+                let name = &after[..close];
+                args.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, value)| (close, value))
+            });
+            match arg {
+                Some((close, value)) => {
+                    text.push_str(&value.to_string());
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    text.push('{');
+                    rest = after;
+                }
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+}
+
+/// `tr!(messages, "key", name = value, ...)` renders a message template.
+macro_rules! tr {
+    ($messages:expr, $key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $messages.render($key, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*])
+    };
+}
+pub(crate) use tr;
@@ -0,0 +1,39 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use std::collections::HashMap;
+
+impl Memory {
+    /// First commit whose state gives a different `predicate` result than
+    /// the state before the first commit (genesis). Returns `None` if head
+    /// gives the same result. Binary search, so the result is only
+    /// meaningful when the predicate flips at most once; each probe
+    /// rebuilds state from the nearest checkpoint.
+    pub fn bisect<F>(&self, mut predicate: F) -> Result<Option<u64>, MyosotisError>
+    where
+        F: FnMut(&HashMap<NodeId, Node>) -> bool,
+    {
+        let Some(last) = self.commits.last() else {
+            return Ok(None);
+        };
+        let initial = match &self.genesis_state {
+            Some(state) => predicate(state),
+            None => predicate(&HashMap::new()),
+        };
+        if predicate(&self.state_at_commit(last.id)?) == initial {
+            return Ok(None);
+        }
+
+        let (mut lo, mut hi) = (0, self.commits.len() - 1);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if predicate(&self.state_at_commit(self.commits[mid].id)?) == initial {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        tracing::debug!(commit_id = self.commits[lo].id, "bisect found flip");
+        Ok(Some(self.commits[lo].id))
+    }
+}
@@ -0,0 +1,289 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{NodeId, PathSeg, Value};
+use crate::storage::{self, Durability};
+use crate::verify::to_hex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Where the blob store of the memory file `path` lives.
+pub fn blob_dir(path: &str) -> String {
+    format!("{}.blobs", path)
+}
+
+/// Caps on the encoded size of the values `set`, `set_path`, `list_push`
+/// and `list_insert` write. Path and list writes are capped by the field
+/// their path starts at, and count only the value they write.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueLimits {
+    /// Cap for every field.
+    pub max_bytes: Option<usize>,
+    /// Caps for single fields, overriding `max_bytes`.
+    pub fields: BTreeMap<String, usize>,
+}
+
+impl ValueLimits {
+    pub fn limit_for(&self, key: &str) -> Option<usize> {
+        self.fields.get(key).copied().or(self.max_bytes)
+    }
+}
+
+/// Where in the file an offloaded value was.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) enum BlobSite {
+    Genesis {
+        #[serde(with = "crate::node::wide_id")]
+        node_id: NodeId,
+        key: String,
+    },
+    Commit {
+        commit_id: u64,
+        /// Index into the commit's mutations.
+        mutation: usize,
+    },
+    Checkpoint {
+        commit_id: u64,
+        #[serde(with = "crate::node::wide_id")]
+        node_id: NodeId,
+        key: String,
+    },
+}
+
+/// A value the file holds in its blob store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BlobRef {
+    pub(crate) site: BlobSite,
+    /// Hex SHA-256 of the encoded value, which is also the blob's name.
+    pub(crate) hash: String,
+}
+
+/// What the file holds in place of an offloaded value.
+pub(crate) fn placeholder(hash: &str) -> Value {
+    Value::Str(format!("sha256:{}", hash))
+}
+
+fn is_hash(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Content-addressed values of one memory file, one file per value.
+pub(crate) struct BlobStore {
+    dir: String,
+}
+
+impl BlobStore {
+    pub(crate) fn for_file(path: &str) -> Self {
+        Self {
+            dir: blob_dir(path),
+        }
+    }
+
+    fn blob_path(&self, hash: &str) -> String {
+        format!("{}/{}", self.dir, hash)
+    }
+
+    /// Writes `value` under its hash unless it is already stored. Returns
+    /// the hash.
+    fn put(&self, value: &Value, durability: Durability) -> Result<String, MyosotisError> {
+        let data = serde_json::to_vec(value)?;
+        let hash = to_hex(&Sha256::digest(&data));
+        let target = self.blob_path(&hash);
+        if !Path::new(&target).exists() {
+            fs::create_dir_all(&self.dir)
+                .map_err(|e| MyosotisError::WriteFailed(self.dir.clone(), e))?;
+            storage::write_atomic(&target, &data, durability)?;
+        }
+        Ok(hash)
+    }
+
+    /// Reads blob `hash` and checks it against its name. A blob over
+    /// `max_bytes` is refused before it is read.
+    fn get(&self, hash: &str, max_bytes: Option<usize>) -> Result<Value, MyosotisError> {
+        if !is_hash(hash) {
+            return Err(MyosotisError::MalformedFileStructure);
+        }
+        let target = self.blob_path(hash);
+        if let Some(max) = max_bytes {
+            let size = fs::metadata(&target)
+                .map_err(|e| MyosotisError::ReadFailed(target.clone(), e))?
+                .len();
+            if size > max as u64 {
+                return Err(MyosotisError::LoadLimitExceeded(
+                    "value bytes",
+                    size,
+                    max as u64,
+                ));
+            }
+        }
+        let data = fs::read(&target).map_err(|e| MyosotisError::ReadFailed(target, e))?;
+        if to_hex(&Sha256::digest(&data)) != hash {
+            return Err(MyosotisError::CorruptBlob(hash.to_string()));
+        }
+        serde_json::from_slice(&data).map_err(|_| MyosotisError::MalformedFileStructure)
+    }
+}
+
+/// Moves values above a threshold into a `BlobStore` as `save` encodes a
+/// file, collecting the `BlobRef`s the file records.
+pub(crate) struct Offloader {
+    store: BlobStore,
+    threshold: usize,
+    durability: Durability,
+    pub(crate) blobs: Vec<BlobRef>,
+}
+
+impl Offloader {
+    pub(crate) fn new(path: &str, threshold: usize, durability: Durability) -> Self {
+        Self {
+            store: BlobStore::for_file(path),
+            threshold,
+            durability,
+            blobs: Vec::new(),
+        }
+    }
+
+    pub(crate) fn offload(
+        &mut self,
+        value: &mut Value,
+        site: BlobSite,
+    ) -> Result<(), MyosotisError> {
+        if storage::encoded_value_len(value)? <= self.threshold {
+            return Ok(());
+        }
+        let hash = self.store.put(value, self.durability)?;
+        *value = placeholder(&hash);
+        self.blobs.push(BlobRef { site, hash });
+        Ok(())
+    }
+}
+
+/// Reads offloaded values back as a file is loaded. Each blob is read and
+/// checked once, however often the file refers to it.
+pub(crate) struct Restorer {
+    store: BlobStore,
+    max_bytes: Option<usize>,
+    read: HashMap<String, Value>,
+}
+
+impl Restorer {
+    pub(crate) fn new(path: &str, max_bytes: Option<usize>) -> Self {
+        Self {
+            store: BlobStore::for_file(path),
+            max_bytes,
+            read: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn value(&mut self, hash: &str) -> Result<Value, MyosotisError> {
+        if let Some(value) = self.read.get(hash) {
+            return Ok(value.clone());
+        }
+        let value = self.store.get(hash, self.max_bytes)?;
+        self.read.insert(hash.to_string(), value.clone());
+        Ok(value)
+    }
+}
+
+#[derive(Deserialize)]
+struct BlobRefs {
+    #[serde(default)]
+    blobs: Vec<BlobRef>,
+}
+
+/// Hashes of the blobs the file at `path` refers to.
+pub fn referenced(path: &str) -> Result<BTreeSet<String>, MyosotisError> {
+    storage::verify_file_checksum(path)?;
+    let data =
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    let refs: BlobRefs =
+        serde_json::from_str(&data).map_err(|_| MyosotisError::MalformedFileStructure)?;
+    Ok(refs.blobs.into_iter().map(|blob| blob.hash).collect())
+}
+
+/// Removes the blobs of `path` its file no longer refers to, such as
+/// values dropped by compaction or redaction. Returns how many went.
+pub fn prune_blobs(path: &str) -> Result<usize, MyosotisError> {
+    let keep = referenced(path)?;
+    let dir = blob_dir(path);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(MyosotisError::ReadFailed(dir, e)),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let entry = entry.map_err(|e| MyosotisError::ReadFailed(dir.clone(), e))?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str().filter(|name| is_hash(name)) else {
+            continue;
+        };
+        if !keep.contains(name) {
+            let target = entry.path().display().to_string();
+            fs::remove_file(&target).map_err(|e| MyosotisError::WriteFailed(target, e))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Copies the blobs the file at `to`, a copy of `from`, refers to.
+pub(crate) fn copy_blobs(from: &str, to: &str) -> Result<(), MyosotisError> {
+    let source = BlobStore::for_file(from);
+    let target = BlobStore::for_file(to);
+    for hash in referenced(to)? {
+        let value = source.get(&hash, None)?;
+        target.put(&value, Durability::Sync)?;
+    }
+    Ok(())
+}
+
+impl Memory {
+    /// Makes `save` move field values whose encoded size is above
+    /// `threshold` bytes into the blob store next to the file (see
+    /// `blob_dir`), leaving their hash in the file. `None` keeps every
+    /// value in the file. Memories loaded from a file keep its threshold.
+    pub fn set_blob_threshold(&mut self, threshold: Option<usize>) {
+        self.blob_threshold = threshold;
+    }
+
+    pub fn blob_threshold(&self) -> Option<usize> {
+        self.blob_threshold
+    }
+
+    /// Caps the size of values written from now on. Not stored in the file.
+    pub fn set_value_limits(&mut self, limits: ValueLimits) {
+        self.value_limits = limits;
+    }
+
+    pub fn value_limits(&self) -> &ValueLimits {
+        &self.value_limits
+    }
+
+    /// Refuses `mutation` if the value it writes is over the limit of its
+    /// field.
+    pub(crate) fn check_value_limit(&self, mutation: &Mutation) -> Result<(), MyosotisError> {
+        let key = match mutation {
+            Mutation::SetField { key, .. } => key.as_str(),
+            _ => match mutation.path().and_then(|path| path.first()) {
+                Some(PathSeg::Key(key)) => key.as_str(),
+                _ => return Ok(()),
+            },
+        };
+        let (Some(limit), Some(value)) = (self.value_limits.limit_for(key), mutation.value())
+        else {
+            return Ok(());
+        };
+        let size = storage::encoded_value_len(value)?;
+        if size > limit {
+            return Err(MyosotisError::ValueTooLarge(key.to_string(), size, limit));
+        }
+        Ok(())
+    }
+}
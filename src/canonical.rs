@@ -0,0 +1,472 @@
+use crate::commit::Mutation;
+use crate::node::{Node, NodeId, PathSeg, Value};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Byte encoding fed to SHA-256 for commit and state hashes.
+///
+/// `Binary` is the original length-prefixed encoding. `Jcs` renders the
+/// same data as RFC 8785 canonical JSON so hashes can be checked with any
+/// JCS implementation. The mode is fixed per file and recorded in the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Canonicalization {
+    #[default]
+    Binary,
+    Jcs,
+}
+
+impl Canonicalization {
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Canonicalization::Binary)
+    }
+}
+
+fn utf16_cmp(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+fn write_jcs_string(buf: &mut Vec<u8>, s: &str) {
+    buf.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.extend_from_slice(b"\\\""),
+            '\\' => buf.extend_from_slice(b"\\\\"),
+            '\u{08}' => buf.extend_from_slice(b"\\b"),
+            '\t' => buf.extend_from_slice(b"\\t"),
+            '\n' => buf.extend_from_slice(b"\\n"),
+            '\u{0c}' => buf.extend_from_slice(b"\\f"),
+            '\r' => buf.extend_from_slice(b"\\r"),
+            c if (c as u32) < 0x20 => {
+                buf.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => {
+                let mut tmp = [0u8; 4];
+                buf.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+            }
+        }
+    }
+    buf.push(b'"');
+}
+
+/// Formats a finite double the way ECMAScript `Number.prototype.toString`
+/// does, as required by RFC 8785 section 3.2.2.3.
+pub fn format_jcs_number(v: f64) -> String {
+    if v == 0.0 {
+        return "0".to_string();
+    }
+
+    // `{:e}` yields the shortest round-trip digits, e.g. "-1.2345e-7".
+    let sci = format!("{:e}", v);
+    let (mantissa, exponent) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let exp_sign = if n - 1 < 0 { '-' } else { '+' };
+        let exp = (n - 1).abs();
+        if k == 1 {
+            format!("{}e{}{}", digits, exp_sign, exp)
+        } else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], exp_sign, exp)
+        }
+    };
+    format!("{}{}", sign, body)
+}
+
+fn write_jcs_u64(buf: &mut Vec<u8>, v: u64) {
+    write_jcs_string(buf, &v.to_string());
+}
+
+fn write_jcs_id(buf: &mut Vec<u8>, id: NodeId) {
+    write_jcs_string(buf, &id.to_string());
+}
+
+fn write_jcs_hash(buf: &mut Vec<u8>, hash: Option<[u8; 32]>) {
+    match hash {
+        Some(h) => {
+            let hex: String = h.iter().map(|b| format!("{:02x}", b)).collect();
+            write_jcs_string(buf, &hex);
+        }
+        None => buf.extend_from_slice(b"null"),
+    }
+}
+
+fn write_jcs_map<'a, I>(buf: &mut Vec<u8>, entries: I)
+where
+    I: Iterator<Item = (&'a String, &'a Value)>,
+{
+    let mut entries: Vec<(&String, &Value)> = entries.collect();
+    entries.sort_by(|a, b| utf16_cmp(a.0, b.0));
+    buf.push(b'{');
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            buf.push(b',');
+        }
+        write_jcs_string(buf, key);
+        buf.push(b':');
+        write_jcs_value(buf, value);
+    }
+    buf.push(b'}');
+}
+
+fn write_jcs_float(buf: &mut Vec<u8>, v: f64) {
+    if v.is_finite() {
+        buf.extend_from_slice(format_jcs_number(v).as_bytes());
+    } else if v.is_nan() {
+        write_jcs_string(buf, "NaN");
+    } else if v > 0.0 {
+        write_jcs_string(buf, "Infinity");
+    } else {
+        write_jcs_string(buf, "-Infinity");
+    }
+}
+
+/// Values are single-member objects tagged by kind. Integers and ids are
+/// decimal strings because JCS numbers are IEEE doubles. Non-finite floats
+/// are the strings `"NaN"`, `"Infinity"` and `"-Infinity"`. Vector
+/// components are widened to doubles, which is exact.
+pub fn write_jcs_value(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Int(v) => {
+            buf.extend_from_slice(b"{\"int\":");
+            write_jcs_string(buf, &v.to_string());
+        }
+        Value::Float(v) => {
+            buf.extend_from_slice(b"{\"float\":");
+            write_jcs_float(buf, *v);
+        }
+        Value::Vector(v) => {
+            buf.extend_from_slice(b"{\"vector\":[");
+            for (i, component) in v.iter().enumerate() {
+                if i > 0 {
+                    buf.push(b',');
+                }
+                write_jcs_float(buf, f64::from(*component));
+            }
+            buf.push(b']');
+        }
+        Value::Bool(v) => {
+            buf.extend_from_slice(b"{\"bool\":");
+            buf.extend_from_slice(if *v { b"true" } else { b"false" });
+        }
+        Value::Str(v) => {
+            buf.extend_from_slice(b"{\"str\":");
+            write_jcs_string(buf, v);
+        }
+        Value::Ref(v) => {
+            buf.extend_from_slice(b"{\"ref\":");
+            write_jcs_id(buf, *v);
+        }
+        Value::AliasRef(v) => {
+            buf.extend_from_slice(b"{\"alias_ref\":");
+            write_jcs_string(buf, v);
+        }
+        Value::ExternalRef {
+            file_alias,
+            node_id,
+        } => {
+            buf.extend_from_slice(b"{\"external_ref\":{\"file_alias\":");
+            write_jcs_string(buf, file_alias);
+            buf.extend_from_slice(b",\"node_id\":");
+            write_jcs_id(buf, *node_id);
+            buf.push(b'}');
+        }
+        Value::List(values) => {
+            buf.extend_from_slice(b"{\"list\":[");
+            for (i, item) in values.iter().enumerate() {
+                if i > 0 {
+                    buf.push(b',');
+                }
+                write_jcs_value(buf, item);
+            }
+            buf.push(b']');
+        }
+        Value::Map(map) => {
+            buf.extend_from_slice(b"{\"map\":");
+            write_jcs_map(buf, map.iter());
+        }
+    }
+    buf.push(b'}');
+}
+
+fn write_jcs_mutation(buf: &mut Vec<u8>, mutation: &Mutation) {
+    // "from" sorts before "id", so renames are written whole.
+    if let Mutation::RenameField { id, from, to } = mutation {
+        buf.extend_from_slice(b"{\"from\":");
+        write_jcs_string(buf, from);
+        buf.extend_from_slice(b",\"id\":");
+        write_jcs_id(buf, *id);
+        buf.extend_from_slice(b",\"op\":\"rename_field\",\"to\":");
+        write_jcs_string(buf, to);
+        buf.push(b'}');
+        return;
+    }
+    // Likewise "alias".
+    if let Mutation::SetAlias { id, alias } = mutation {
+        buf.extend_from_slice(b"{\"alias\":");
+        match alias {
+            Some(alias) => write_jcs_string(buf, alias),
+            None => buf.extend_from_slice(b"null"),
+        }
+        buf.extend_from_slice(b",\"id\":");
+        write_jcs_id(buf, *id);
+        buf.extend_from_slice(b",\"op\":\"set_alias\"}");
+        return;
+    }
+
+    // And "archived".
+    if let Mutation::SetArchived { id, archived } = mutation {
+        buf.extend_from_slice(if *archived {
+            b"{\"archived\":true,\"id\":"
+        } else {
+            b"{\"archived\":false,\"id\":"
+        });
+        write_jcs_id(buf, *id);
+        buf.extend_from_slice(b",\"op\":\"set_archived\"}");
+        return;
+    }
+
+    buf.extend_from_slice(b"{\"id\":");
+    write_jcs_id(buf, mutation.node_id());
+    match mutation {
+        Mutation::CreateNode { ty, .. } => {
+            buf.extend_from_slice(b",\"op\":\"create_node\",\"ty\":");
+            write_jcs_string(buf, ty);
+        }
+        Mutation::SetField { key, value, .. } => {
+            buf.extend_from_slice(b",\"key\":");
+            write_jcs_string(buf, key);
+            buf.extend_from_slice(b",\"op\":\"set_field\",\"value\":");
+            write_jcs_value(buf, value);
+        }
+        Mutation::DeleteField { key, .. } => {
+            buf.extend_from_slice(b",\"key\":");
+            write_jcs_string(buf, key);
+            buf.extend_from_slice(b",\"op\":\"delete_field\"");
+        }
+        Mutation::DeleteNode { .. } => {
+            buf.extend_from_slice(b",\"op\":\"delete_node\"");
+        }
+        Mutation::AddLabel { label, .. } => {
+            buf.extend_from_slice(b",\"label\":");
+            write_jcs_string(buf, label);
+            buf.extend_from_slice(b",\"op\":\"add_label\"");
+        }
+        Mutation::RemoveLabel { label, .. } => {
+            buf.extend_from_slice(b",\"label\":");
+            write_jcs_string(buf, label);
+            buf.extend_from_slice(b",\"op\":\"remove_label\"");
+        }
+        Mutation::SetNamespace { namespace, .. } => {
+            buf.extend_from_slice(b",\"namespace\":");
+            match namespace {
+                Some(namespace) => write_jcs_string(buf, namespace),
+                None => buf.extend_from_slice(b"null"),
+            }
+            buf.extend_from_slice(b",\"op\":\"set_namespace\"");
+        }
+        Mutation::SetType { ty, .. } => {
+            buf.extend_from_slice(b",\"op\":\"set_type\",\"ty\":");
+            write_jcs_string(buf, ty);
+        }
+        Mutation::RestoreNode { keep_fields, .. } => {
+            buf.extend_from_slice(if *keep_fields {
+                b",\"keep_fields\":true,\"op\":\"restore_node\""
+            } else {
+                b",\"keep_fields\":false,\"op\":\"restore_node\""
+            });
+        }
+        Mutation::SetPath { path, value, .. } => {
+            buf.extend_from_slice(b",\"op\":\"set_path\",\"path\":");
+            write_jcs_path(buf, path);
+            buf.extend_from_slice(b",\"value\":");
+            write_jcs_value(buf, value);
+        }
+        Mutation::DeletePath { path, .. } => {
+            buf.extend_from_slice(b",\"op\":\"delete_path\",\"path\":");
+            write_jcs_path(buf, path);
+        }
+        Mutation::ListPush { path, value, .. } => {
+            buf.extend_from_slice(b",\"op\":\"list_push\",\"path\":");
+            write_jcs_path(buf, path);
+            buf.extend_from_slice(b",\"value\":");
+            write_jcs_value(buf, value);
+        }
+        Mutation::ListInsert {
+            path, index, value, ..
+        } => {
+            buf.extend_from_slice(b",\"index\":");
+            write_jcs_u64(buf, *index as u64);
+            buf.extend_from_slice(b",\"op\":\"list_insert\",\"path\":");
+            write_jcs_path(buf, path);
+            buf.extend_from_slice(b",\"value\":");
+            write_jcs_value(buf, value);
+        }
+        Mutation::ListRemove { path, index, .. } => {
+            buf.extend_from_slice(b",\"index\":");
+            write_jcs_u64(buf, *index as u64);
+            buf.extend_from_slice(b",\"op\":\"list_remove\",\"path\":");
+            write_jcs_path(buf, path);
+        }
+        Mutation::RenameField { .. } | Mutation::SetAlias { .. } | Mutation::SetArchived { .. } => {
+        }
+    }
+    buf.push(b'}');
+}
+
+/// Paths are arrays of `{"key":..}` and `{"index":"<decimal>"}`.
+fn write_jcs_path(buf: &mut Vec<u8>, path: &[PathSeg]) {
+    buf.push(b'[');
+    for (i, seg) in path.iter().enumerate() {
+        if i > 0 {
+            buf.push(b',');
+        }
+        match seg {
+            PathSeg::Key(key) => {
+                buf.extend_from_slice(b"{\"key\":");
+                write_jcs_string(buf, key);
+            }
+            PathSeg::Index(index) => {
+                buf.extend_from_slice(b"{\"index\":");
+                write_jcs_u64(buf, *index as u64);
+            }
+        }
+        buf.push(b'}');
+    }
+    buf.push(b']');
+}
+
+/// Canonical JSON for a commit:
+/// `{"message":..,"mutations":[..],"parent_hash":..}`.
+pub fn jcs_commit_bytes(
+    parent_hash: Option<[u8; 32]>,
+    message: &Option<String>,
+    mutations: &[Mutation],
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"{\"message\":");
+    match message {
+        Some(msg) => write_jcs_string(&mut buf, msg),
+        None => buf.extend_from_slice(b"null"),
+    }
+    buf.extend_from_slice(b",\"mutations\":[");
+    for (i, mutation) in mutations.iter().enumerate() {
+        if i > 0 {
+            buf.push(b',');
+        }
+        write_jcs_mutation(&mut buf, mutation);
+    }
+    buf.extend_from_slice(b"],\"parent_hash\":");
+    write_jcs_hash(&mut buf, parent_hash);
+    buf.push(b'}');
+    buf
+}
+
+/// Adds a commit's `source` to its hash input: in binary mode a `0xff`
+/// tag, which no mutation uses, and the length-prefixed source after the
+/// mutations; in JCS mode a trailing `"source"` key, which sorts last.
+pub(crate) fn append_source(canonicalization: Canonicalization, bytes: &mut Vec<u8>, source: &str) {
+    match canonicalization {
+        Canonicalization::Binary => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&(source.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(source.as_bytes());
+        }
+        Canonicalization::Jcs => {
+            bytes.pop();
+            bytes.extend_from_slice(b",\"source\":");
+            write_jcs_string(bytes, source);
+            bytes.push(b'}');
+        }
+    }
+}
+
+/// Adds a commit's recorded `state_hash` to its hash input, after any
+/// source: in binary mode a `0xfe` tag and the 32 hash bytes; in JCS mode
+/// a trailing `"state_hash"` key, which sorts after `"source"`.
+pub(crate) fn append_state_hash(
+    canonicalization: Canonicalization,
+    bytes: &mut Vec<u8>,
+    state_hash: &[u8; 32],
+) {
+    match canonicalization {
+        Canonicalization::Binary => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(state_hash);
+        }
+        Canonicalization::Jcs => {
+            bytes.pop();
+            bytes.extend_from_slice(b",\"state_hash\":");
+            write_jcs_hash(bytes, Some(*state_hash));
+            bytes.push(b'}');
+        }
+    }
+}
+
+/// Canonical JSON for a state: `{"nodes":[..]}` with nodes ordered by id.
+pub fn jcs_state_bytes(state: &HashMap<NodeId, Node>) -> Vec<u8> {
+    let mut node_ids: Vec<NodeId> = state.keys().copied().collect();
+    node_ids.sort_unstable();
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"{\"nodes\":[");
+    let mut first = true;
+    for node_id in node_ids {
+        let Some(node) = state.get(&node_id) else {
+            continue;
+        };
+        if !first {
+            buf.push(b',');
+        }
+        first = false;
+
+        buf.push(b'{');
+        if let Some(alias) = &node.alias {
+            buf.extend_from_slice(b"\"alias\":");
+            write_jcs_string(&mut buf, alias);
+            buf.push(b',');
+        }
+        if node.archived {
+            buf.extend_from_slice(b"\"archived\":true,");
+        }
+        buf.extend_from_slice(b"\"deleted\":");
+        buf.extend_from_slice(if node.deleted { b"true" } else { b"false" });
+        buf.extend_from_slice(b",\"fields\":");
+        write_jcs_map(&mut buf, node.fields.iter());
+        buf.extend_from_slice(b",\"id\":");
+        write_jcs_id(&mut buf, node_id);
+        if !node.labels.is_empty() {
+            buf.extend_from_slice(b",\"labels\":[");
+            for (i, label) in node.labels.iter().enumerate() {
+                if i > 0 {
+                    buf.push(b',');
+                }
+                write_jcs_string(&mut buf, label);
+            }
+            buf.push(b']');
+        }
+        if let Some(namespace) = &node.namespace {
+            buf.extend_from_slice(b",\"namespace\":");
+            write_jcs_string(&mut buf, namespace);
+        }
+        buf.extend_from_slice(b",\"ty\":");
+        write_jcs_string(&mut buf, &node.ty);
+        buf.push(b'}');
+    }
+    buf.extend_from_slice(b"]}");
+    buf
+}
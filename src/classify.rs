@@ -0,0 +1,103 @@
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use regex::Regex;
+use serde::Deserialize;
+
+type ClassifierFn = Box<dyn Fn(&Commit) -> bool + Send + Sync>;
+
+/// Labels commits at read time. Nothing is stored on the commits
+/// themselves, so rules apply equally to historical and new commits.
+#[derive(Default)]
+pub struct Classifier {
+    rules: Vec<(String, ClassifierFn)>,
+}
+
+/// One entry of a JSON rules file. All given conditions must match.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClassRule {
+    pub class: String,
+    /// Regex matched against the commit message.
+    pub message: Option<String>,
+    /// Mutation kind (e.g. `delete_node`) that must occur in the commit.
+    pub mutation: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClassRules {
+    rules: Vec<ClassRule>,
+}
+
+impl Classifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a predicate; commits for which it returns true get `class`.
+    pub fn register<F>(&mut self, class: &str, predicate: F)
+    where
+        F: Fn(&Commit) -> bool + Send + Sync + 'static,
+    {
+        self.rules.push((class.to_string(), Box::new(predicate)));
+    }
+
+    pub fn add_rule(&mut self, rule: ClassRule) -> Result<(), MyosotisError> {
+        let message = rule
+            .message
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| MyosotisError::InvalidInput(format!("invalid class regex: {}", e)))?;
+        let mutation = rule.mutation;
+        self.register(&rule.class, move |commit| {
+            let message_ok = message.as_ref().is_none_or(|re| {
+                commit
+                    .message
+                    .as_deref()
+                    .is_some_and(|msg| re.is_match(msg))
+            });
+            let mutation_ok = mutation
+                .as_deref()
+                .is_none_or(|kind| commit.mutations.iter().any(|m| m.kind() == kind));
+            message_ok && mutation_ok
+        });
+        Ok(())
+    }
+
+    /// Parses `{"rules": [{"class": .., "message": .., "mutation": ..}]}`.
+    pub fn from_rules_json(data: &str) -> Result<Self, MyosotisError> {
+        let parsed: ClassRules = serde_json::from_str(data)?;
+        let mut classifier = Self::new();
+        for rule in parsed.rules {
+            classifier.add_rule(rule)?;
+        }
+        Ok(classifier)
+    }
+
+    /// Classes of `commit` in registration order, without duplicates.
+    pub fn classify(&self, commit: &Commit) -> Vec<String> {
+        let mut classes: Vec<String> = Vec::new();
+        for (class, predicate) in &self.rules {
+            if !classes.contains(class) && predicate(commit) {
+                classes.push(class.clone());
+            }
+        }
+        classes
+    }
+}
+
+impl Memory {
+    /// Commits labelled `class` by `classifier`, in log order.
+    pub fn commits_with_class<'a>(
+        &'a self,
+        classifier: &Classifier,
+        class: &str,
+    ) -> Vec<&'a Commit> {
+        self.commits
+            .iter()
+            .filter(|c| classifier.classify(c).iter().any(|l| l == class))
+            .collect()
+    }
+}
@@ -1,24 +1,112 @@
-use crate::node::{NodeId, Value};
+use crate::canonical::{self, Canonicalization};
+use crate::memory::Memory;
+use crate::node::{NodeId, PathSeg, Value};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Mutation {
     CreateNode {
+        #[serde(with = "crate::node::wide_id")]
         id: NodeId,
         ty: String,
     },
     SetField {
+        #[serde(with = "crate::node::wide_id")]
         id: NodeId,
         key: String,
         value: Value,
     },
     DeleteField {
+        #[serde(with = "crate::node::wide_id")]
         id: NodeId,
         key: String,
     },
     DeleteNode {
+        #[serde(with = "crate::node::wide_id")]
         id: NodeId,
     },
+    /// Clears the tombstone of a deleted node. Tombstones keep their
+    /// fields; unless `keep_fields` is set they are cleared too.
+    RestoreNode {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        keep_fields: bool,
+    },
+    /// Sets or, with `None`, clears the alias of a live node. Aliases are
+    /// unique across all nodes, deleted ones included.
+    SetAlias {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        alias: Option<String>,
+    },
+    /// Moves a live node into a namespace or, with `None`, back to the
+    /// default one.
+    SetNamespace {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        namespace: Option<String>,
+    },
+    /// Archives or un-archives a live node. Unlike deletion this keeps the
+    /// node fully usable; it only hides it from default queries.
+    SetArchived {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        archived: bool,
+    },
+    /// Adds a label, which the live node must not have yet.
+    AddLabel {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        label: String,
+    },
+    /// Removes a label the live node has.
+    RemoveLabel {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        label: String,
+    },
+    /// Changes the type of a live node; its fields are kept.
+    SetType {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        ty: String,
+    },
+    RenameField {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        from: String,
+        to: String,
+    },
+    SetPath {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        path: Vec<PathSeg>,
+        value: Value,
+    },
+    DeletePath {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        path: Vec<PathSeg>,
+    },
+    ListPush {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        path: Vec<PathSeg>,
+        value: Value,
+    },
+    ListInsert {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        path: Vec<PathSeg>,
+        index: usize,
+        value: Value,
+    },
+    ListRemove {
+        #[serde(with = "crate::node::wide_id")]
+        id: NodeId,
+        path: Vec<PathSeg>,
+        index: usize,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,5 +116,125 @@ pub struct Commit {
     pub parent_hash: Option<[u8; 32]>,
     pub hash: [u8; 32],
     pub message: Option<String>,
+    /// Component that made the commit, such as a tool name or model call
+    /// id. Hashed when set; commits without one hash as they always have.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Hash of the state right after this commit, recorded while
+    /// `Memory::record_state_hashes` is on. Hashed when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_hash: Option<[u8; 32]>,
     pub mutations: Vec<Mutation>,
 }
+
+impl Commit {
+    /// Exact bytes fed to SHA-256 for this commit's hash in a binary-mode
+    /// file, so `sha256(canonical_bytes())` equals `hash`.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        self.canonical_bytes_with(Canonicalization::Binary)
+    }
+
+    /// Exact bytes fed to SHA-256 for this commit's hash under
+    /// `canonicalization`.
+    pub fn canonical_bytes_with(&self, canonicalization: Canonicalization) -> Vec<u8> {
+        let mut bytes = Memory::commit_hash_input(
+            canonicalization,
+            self.parent_hash,
+            &self.message,
+            &self.mutations,
+        );
+        if let Some(source) = &self.source {
+            canonical::append_source(canonicalization, &mut bytes, source);
+        }
+        if let Some(state_hash) = &self.state_hash {
+            canonical::append_state_hash(canonicalization, &mut bytes, state_hash);
+        }
+        bytes
+    }
+
+    /// This commit's hash recomputed from its contents under
+    /// `canonicalization`.
+    pub fn compute_hash_with(&self, canonicalization: Canonicalization) -> [u8; 32] {
+        Memory::sha256(&self.canonical_bytes_with(canonicalization))
+    }
+}
+
+impl Mutation {
+    /// Stable snake_case name of the mutation kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Mutation::CreateNode { .. } => "create_node",
+            Mutation::SetField { .. } => "set_field",
+            Mutation::DeleteField { .. } => "delete_field",
+            Mutation::DeleteNode { .. } => "delete_node",
+            Mutation::RestoreNode { .. } => "restore_node",
+            Mutation::SetType { .. } => "set_type",
+            Mutation::SetAlias { .. } => "set_alias",
+            Mutation::SetNamespace { .. } => "set_namespace",
+            Mutation::SetArchived { .. } => "set_archived",
+            Mutation::AddLabel { .. } => "add_label",
+            Mutation::RemoveLabel { .. } => "remove_label",
+            Mutation::RenameField { .. } => "rename_field",
+            Mutation::SetPath { .. } => "set_path",
+            Mutation::DeletePath { .. } => "delete_path",
+            Mutation::ListPush { .. } => "list_push",
+            Mutation::ListInsert { .. } => "list_insert",
+            Mutation::ListRemove { .. } => "list_remove",
+        }
+    }
+
+    pub fn node_id(&self) -> NodeId {
+        match self {
+            Mutation::CreateNode { id, .. }
+            | Mutation::SetField { id, .. }
+            | Mutation::DeleteField { id, .. }
+            | Mutation::DeleteNode { id }
+            | Mutation::RestoreNode { id, .. }
+            | Mutation::SetType { id, .. }
+            | Mutation::SetAlias { id, .. }
+            | Mutation::SetNamespace { id, .. }
+            | Mutation::SetArchived { id, .. }
+            | Mutation::AddLabel { id, .. }
+            | Mutation::RemoveLabel { id, .. }
+            | Mutation::RenameField { id, .. }
+            | Mutation::SetPath { id, .. }
+            | Mutation::DeletePath { id, .. }
+            | Mutation::ListPush { id, .. }
+            | Mutation::ListInsert { id, .. }
+            | Mutation::ListRemove { id, .. } => *id,
+        }
+    }
+
+    /// Path within the node, for path and list mutations.
+    pub fn path(&self) -> Option<&[PathSeg]> {
+        match self {
+            Mutation::SetPath { path, .. }
+            | Mutation::DeletePath { path, .. }
+            | Mutation::ListPush { path, .. }
+            | Mutation::ListInsert { path, .. }
+            | Mutation::ListRemove { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Value written, for set, path-set and list-add mutations.
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            Mutation::SetField { value, .. }
+            | Mutation::SetPath { value, .. }
+            | Mutation::ListPush { value, .. }
+            | Mutation::ListInsert { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn value_mut(&mut self) -> Option<&mut Value> {
+        match self {
+            Mutation::SetField { value, .. }
+            | Mutation::SetPath { value, .. }
+            | Mutation::ListPush { value, .. }
+            | Mutation::ListInsert { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+/// Computes a derived field from the node it is read on.
+pub type DerivedField = Arc<dyn Fn(&Node) -> Value + Send + Sync>;
+
+/// Registered derived fields by type and key. Not serialized, hashed or
+/// recorded in history; like pre-commit hooks they are carried over when a
+/// `Memory` is cloned.
+#[derive(Default, Clone)]
+pub struct DerivedFields(HashMap<String, BTreeMap<String, DerivedField>>);
+
+impl fmt::Debug for DerivedFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count: usize = self.0.values().map(BTreeMap::len).sum();
+        write!(f, "DerivedFields({})", count)
+    }
+}
+
+impl Memory {
+    /// Registers `key` as a field of every node of type `ty` whose value
+    /// `compute` works out from the node each time it is read. Replaces a
+    /// previous definition of the same field.
+    pub fn define_derived<F>(&mut self, ty: &str, key: &str, compute: F)
+    where
+        F: Fn(&Node) -> Value + Send + Sync + 'static,
+    {
+        self.derived_fields
+            .0
+            .entry(ty.to_string())
+            .or_default()
+            .insert(key.to_string(), Arc::new(compute));
+    }
+
+    /// Drops the derived field `key` of type `ty`. Returns whether there
+    /// was one.
+    pub fn remove_derived(&mut self, ty: &str, key: &str) -> bool {
+        let Some(fields) = self.derived_fields.0.get_mut(ty) else {
+            return false;
+        };
+        let removed = fields.remove(key).is_some();
+        if fields.is_empty() {
+            self.derived_fields.0.remove(ty);
+        }
+        removed
+    }
+
+    fn live_node(&self, id: NodeId) -> Result<&Node, MyosotisError> {
+        let node = self
+            .head_state
+            .get(&id)
+            .ok_or(MyosotisError::NodeNotFound(id))?;
+        if node.deleted {
+            return Err(MyosotisError::NodeDeleted(id));
+        }
+        Ok(node)
+    }
+
+    /// Computes derived field `key` of live node `id` from head state.
+    pub fn get_derived(&self, id: NodeId, key: &str) -> Result<Value, MyosotisError> {
+        let node = self.live_node(id)?;
+        let compute = self
+            .derived_fields
+            .0
+            .get(&node.ty)
+            .and_then(|fields| fields.get(key))
+            .ok_or_else(|| MyosotisError::FieldNotFound(key.to_string()))?;
+        Ok(compute(node))
+    }
+
+    /// Every derived field of live node `id`, computed from head state.
+    pub fn derived_values(&self, id: NodeId) -> Result<BTreeMap<String, Value>, MyosotisError> {
+        let node = self.live_node(id)?;
+        Ok(self
+            .derived_fields
+            .0
+            .get(&node.ty)
+            .into_iter()
+            .flatten()
+            .map(|(key, compute)| (key.clone(), compute(node)))
+            .collect())
+    }
+
+    /// Field `key` of live node `id`: the stored value if the node has
+    /// one, otherwise the derived field of that name.
+    pub fn read_field(&self, id: NodeId, key: &str) -> Result<Value, MyosotisError> {
+        match self.get_field(id, key) {
+            Ok(value) => Ok(value.clone()),
+            Err(MyosotisError::FieldNotFound(_)) => self.get_derived(id, key),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    NodeCreated {
+        id: NodeId,
+        ty: String,
+    },
+    NodeDeleted {
+        id: NodeId,
+    },
+    FieldSet {
+        id: NodeId,
+        key: String,
+        old: Option<Value>,
+        new: Value,
+    },
+    FieldRemoved {
+        id: NodeId,
+        key: String,
+        old: Value,
+    },
+}
+
+/// Changes turning `from` into `to`, ordered by node id then field key.
+/// A node tombstoned in `to` yields a single `NodeDeleted`.
+pub fn diff_states(from: &HashMap<NodeId, Node>, to: &HashMap<NodeId, Node>) -> Vec<Change> {
+    let ids: BTreeSet<NodeId> = from.keys().chain(to.keys()).copied().collect();
+    let mut changes = Vec::new();
+
+    for id in ids {
+        let before = from.get(&id).filter(|n| !n.deleted);
+        let after = to.get(&id).filter(|n| !n.deleted);
+        match (before, after) {
+            (None, None) => {}
+            (Some(_), None) => changes.push(Change::NodeDeleted { id }),
+            (before, Some(node)) => {
+                if before.is_none() {
+                    changes.push(Change::NodeCreated {
+                        id,
+                        ty: node.ty.clone(),
+                    });
+                }
+                let empty = HashMap::new();
+                let old_fields = before.map(|n| &n.fields).unwrap_or(&empty);
+                let keys: BTreeSet<&String> = old_fields.keys().chain(node.fields.keys()).collect();
+                for key in keys {
+                    match (old_fields.get(key), node.fields.get(key)) {
+                        (old, Some(new)) if old != Some(new) => changes.push(Change::FieldSet {
+                            id,
+                            key: key.clone(),
+                            old: old.cloned(),
+                            new: new.clone(),
+                        }),
+                        (Some(old), None) => changes.push(Change::FieldRemoved {
+                            id,
+                            key: key.clone(),
+                            old: old.clone(),
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    changes
+}
+
+impl Memory {
+    /// Changes between the states at two commits. `to = None` compares
+    /// against the head state, including pending mutations. Commit 0
+    /// stands for genesis.
+    pub fn diff(&self, from: u64, to: Option<u64>) -> Result<Vec<Change>, MyosotisError> {
+        match to {
+            Some(to) if from <= to => self.replay_range(from, to),
+            Some(to) => Ok(diff_states(
+                &self.state_after(from)?,
+                &self.state_after(to)?,
+            )),
+            None => Ok(diff_states(&self.state_after(from)?, &self.head_state)),
+        }
+    }
+
+    fn state_after(&self, commit_id: u64) -> Result<HashMap<NodeId, Node>, MyosotisError> {
+        match commit_id {
+            0 => Ok(self.genesis_state.clone().unwrap_or_default()),
+            commit_id => self.state_at_commit(commit_id),
+        }
+    }
+
+    /// Indices in `commits` of the commits after `from` up to and including
+    /// `to`. Commit 0 stands for genesis.
+    fn commit_range(&self, from: u64, to: u64) -> Result<(usize, usize), MyosotisError> {
+        let after = |id: u64| match id {
+            0 => Ok(0),
+            id => self
+                .commit_index(id)
+                .map(|i| i + 1)
+                .ok_or(MyosotisError::CommitNotFound(id)),
+        };
+        let (start, end) = (after(from)?, after(to)?);
+        if start > end {
+            return Err(MyosotisError::InvalidInput(format!(
+                "commit {} comes after commit {}",
+                from, to
+            )));
+        }
+        Ok((start, end))
+    }
+
+    /// Mutations recorded by the commits after `from` up to and including
+    /// `to`, in commit order. Applied to the state at `from` they give the
+    /// state at `to`. Commit 0 stands for genesis.
+    pub fn mutations_between(&self, from: u64, to: u64) -> Result<Vec<Mutation>, MyosotisError> {
+        let (start, end) = self.commit_range(from, to)?;
+        Ok(self.commits[start..end]
+            .iter()
+            .flat_map(|c| c.mutations.iter().cloned())
+            .collect())
+    }
+
+    /// Replays the commits after `from` up to and including `to` onto the
+    /// state at `from` and returns the net effect, as `diff_states` reports
+    /// it. Commit 0 stands for genesis.
+    pub fn replay_range(&self, from: u64, to: u64) -> Result<Vec<Change>, MyosotisError> {
+        let (start, end) = self.commit_range(from, to)?;
+        let before = self.state_after(from)?;
+        let after = Memory::replay_from(before.clone(), &self.commits[start..end])?;
+        Ok(diff_states(&before, &after))
+    }
+}
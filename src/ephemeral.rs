@@ -0,0 +1,76 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::storage;
+
+/// Whether a `Memory` may touch the disk. Not serialized; a loaded memory
+/// is `Unbound`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Persistence {
+    /// Saved wherever the caller asks.
+    #[default]
+    Unbound,
+    /// Never written: `storage::save` and `enable_wal` refuse it.
+    Ephemeral,
+    /// Written only by `attach` and `persist`, to this path.
+    Attached(String),
+}
+
+impl Memory {
+    /// An empty memory that never writes to disk until `attach`ed.
+    pub fn open_ephemeral() -> Self {
+        Self {
+            persistence: Persistence::Ephemeral,
+            ..Self::new()
+        }
+    }
+
+    pub fn persistence(&self) -> &Persistence {
+        &self.persistence
+    }
+
+    pub fn is_ephemeral(&self) -> bool {
+        self.persistence == Persistence::Ephemeral
+    }
+
+    /// Path `persist` writes to, while attached.
+    pub fn attached_path(&self) -> Option<&str> {
+        match &self.persistence {
+            Persistence::Attached(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Saves the committed history to `path` and binds the memory to it.
+    /// Pending mutations stay in memory. On failure nothing changes.
+    pub fn attach(&mut self, path: &str) -> Result<(), MyosotisError> {
+        let previous = std::mem::replace(
+            &mut self.persistence,
+            Persistence::Attached(path.to_string()),
+        );
+        if let Err(e) = storage::save(path, self) {
+            self.persistence = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Saves to the attached path.
+    pub fn persist(&self) -> Result<(), MyosotisError> {
+        match &self.persistence {
+            Persistence::Attached(path) => storage::save(path, self),
+            _ => Err(MyosotisError::Ephemeral),
+        }
+    }
+
+    /// Makes the memory ephemeral, closing and removing its write-ahead log
+    /// if one is open. Returns the path it was attached to. The file itself
+    /// is left as last persisted.
+    pub fn detach(&mut self) -> Result<Option<String>, MyosotisError> {
+        self.disable_wal()?;
+        let previous = std::mem::replace(&mut self.persistence, Persistence::Ephemeral);
+        Ok(match previous {
+            Persistence::Attached(path) => Some(path),
+            _ => None,
+        })
+    }
+}
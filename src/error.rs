@@ -1,3 +1,4 @@
+use crate::node::NodeId;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,6 +6,15 @@ pub enum MyosotisError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Failed to read file: {0}")]
+    ReadFailed(String, #[source] std::io::Error),
+
+    #[error("Failed to write to file: {0}")]
+    WriteFailed(String, #[source] std::io::Error),
+
+    #[error("Failed to atomically replace file: {0}")]
+    ReplaceFailed(String, #[source] std::io::Error),
+
     #[error("Format error: {0}")]
     Serde(#[from] serde_json::Error),
 
@@ -12,7 +22,7 @@ pub enum MyosotisError {
     Invariant(String),
 
     #[error("Node not found: {0}")]
-    NodeNotFound(u64),
+    NodeNotFound(NodeId),
 
     #[error("Commit not found: {0}")]
     CommitNotFound(u64),
@@ -38,16 +48,19 @@ pub enum MyosotisError {
     CheckpointCommitMismatch,
 
     #[error("Node is deleted: {0}")]
-    NodeDeleted(u64),
+    NodeDeleted(NodeId),
 
     #[error("Field not found: {0}")]
     FieldNotFound(String),
 
     #[error("Delete on already deleted node: {0}")]
-    DeleteOnDeletedNode(u64),
+    DeleteOnDeletedNode(NodeId),
 
     #[error("Delete on non-existent node: {0}")]
-    DeleteNonexistentNode(u64),
+    DeleteNonexistentNode(NodeId),
+
+    #[error("Restore on node that is not deleted: {0}")]
+    RestoreLiveNode(NodeId),
 
     #[error("Invalid compaction target")]
     InvalidCompactionTarget,
@@ -78,4 +91,93 @@ pub enum MyosotisError {
 
     #[error("Malformed file structure")]
     MalformedFileStructure,
+
+    #[error("Embedded memory checksum mismatch")]
+    EmbedChecksumMismatch,
+
+    #[error("Head snapshot hash mismatch")]
+    HeadSnapshotHashMismatch,
+
+    #[error("Head state hash mismatch")]
+    HeadStateMismatch,
+
+    #[error("Histories diverge: {0}")]
+    HistoriesDiverged(String),
+
+    #[error("Session not found: {0}")]
+    SessionNotFound(u64),
+
+    #[error("Node {0} is locked by {1}")]
+    NodeLocked(NodeId, String),
+
+    #[error("File is write-once; {0} is not allowed")]
+    WriteOnceViolation(&'static str),
+
+    #[error("Anchoring failed: {0}")]
+    AnchorFailed(String),
+
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+
+    #[error("Alias {0} is already used by node {1}")]
+    AliasTaken(String, NodeId),
+
+    #[error("Node {0} has no label {1}")]
+    LabelNotFound(NodeId, String),
+
+    #[error("Unique field {0} has the same value on nodes {1} and {2}")]
+    UniqueViolation(String, NodeId, NodeId),
+
+    #[error("State hash recorded in commit {0} does not match the replayed state")]
+    CommitStateHashMismatch(u64),
+
+    #[error("Float {0} is not allowed under the {1} float policy")]
+    FloatPolicyViolation(String, String),
+
+    #[error("External reference to node {1} in {0} does not resolve")]
+    DanglingExternalRef(String, NodeId),
+
+    #[error("Commit rejected by policy: {0}")]
+    PolicyViolation(String),
+
+    #[error("Watching failed: {0}")]
+    WatchFailed(String),
+
+    #[error("Server failed: {0}")]
+    ServerFailed(String),
+
+    #[error("Write-ahead log {0} was written on top of a different head")]
+    StaleWal(String),
+
+    #[error("Genesis state does not match its seal")]
+    SealMismatch,
+
+    #[error("File checksum mismatch: the file is truncated or corrupt")]
+    FileChecksumMismatch,
+
+    #[error("Load limit exceeded: {0} is {1}, at most {2} allowed")]
+    LoadLimitExceeded(&'static str, u64, u64),
+
+    #[error("Memory is ephemeral; attach it to a file to persist it")]
+    Ephemeral,
+
+    #[error("Value for field {0} is {1} bytes, at most {2} allowed")]
+    ValueTooLarge(String, usize, usize),
+
+    #[error("Blob {0} does not match its hash")]
+    CorruptBlob(String),
+
+    #[error("Anchor for commit {0} does not match the file")]
+    AnchorMismatch(u64),
+
+    #[error("Save conflict: {0} was changed since it was last read")]
+    SaveConflict(String),
+
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[cfg(feature = "object-store")]
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
 }
@@ -0,0 +1,90 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{NodeId, Value};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// When a field set with `Memory::set_with_ttl` expires. Like locks,
+/// expiries live outside the hash chain; `Memory::expire` carries them out
+/// with ordinary commits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldExpiry {
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub key: String,
+    /// Expiry as seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+impl Memory {
+    /// Stages `set` and records that the field expires `ttl` from now,
+    /// replacing any expiry it had. The expiry is recorded at once, not on
+    /// commit; a plain `set` of the field keeps it.
+    pub fn set_with_ttl(
+        &mut self,
+        id: NodeId,
+        key: &str,
+        value: Value,
+        ttl: Duration,
+    ) -> Result<(), MyosotisError> {
+        self.set(id, key, value)?;
+        self.clear_ttl(id, key);
+        self.expiries.push(FieldExpiry {
+            node_id: id,
+            key: key.to_string(),
+            expires_at: crate::lock::now_secs().saturating_add(ttl.as_secs()),
+        });
+        Ok(())
+    }
+
+    /// Keeps the field from expiring. Returns whether it had an expiry.
+    pub fn clear_ttl(&mut self, id: NodeId, key: &str) -> bool {
+        let before = self.expiries.len();
+        self.expiries.retain(|e| e.node_id != id || e.key != key);
+        self.expiries.len() != before
+    }
+
+    pub fn expiry(&self, id: NodeId, key: &str) -> Option<&FieldExpiry> {
+        self.expiries
+            .iter()
+            .find(|e| e.node_id == id && e.key == key)
+    }
+
+    /// Deletes every field whose expiry is at or before `now`, in seconds
+    /// since the Unix epoch, in one commit. Expiries of fields that are
+    /// already gone are dropped. Returns the fields deleted; with none,
+    /// nothing is committed. Refused while mutations are pending.
+    pub fn expire(&mut self, now: u64) -> Result<Vec<FieldExpiry>, MyosotisError> {
+        if !self.pending_mutations.is_empty() {
+            return Err(MyosotisError::InvalidInput(
+                "commit or discard pending mutations before expiring".to_string(),
+            ));
+        }
+        let (mut due, rest): (Vec<FieldExpiry>, Vec<FieldExpiry>) = self
+            .expiries
+            .iter()
+            .cloned()
+            .partition(|e| e.expires_at <= now);
+        due.sort_by(|a, b| (a.node_id, &a.key).cmp(&(b.node_id, &b.key)));
+        due.retain(|e| {
+            self.head_state
+                .get(&e.node_id)
+                .is_some_and(|node| !node.deleted && node.get(&e.key).is_some())
+        });
+
+        if !due.is_empty() {
+            let staged = due
+                .iter()
+                .try_for_each(|e| self.delete_field(e.node_id, &e.key))
+                .and_then(|()| {
+                    self.commit(Some(format!("expire {} fields past their TTL", due.len())))
+                });
+            if let Err(e) = staged {
+                self.discard_pending()?;
+                return Err(e);
+            }
+        }
+        self.expiries = rest;
+        Ok(due)
+    }
+}
@@ -0,0 +1,713 @@
+use crate::canonical::Canonicalization;
+use crate::commit::{Commit, Mutation};
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::io::Write;
+
+pub const GIT_BRANCH: &str = "refs/heads/main";
+const GIT_COMMITTER: &str = "Myosotis <myosotis@localhost>";
+
+fn node_path(id: NodeId) -> String {
+    format!("nodes/{}.json", id)
+}
+
+fn node_json(node: &Node) -> Result<String, MyosotisError> {
+    // Round-trip through serde_json::Value so map keys are emitted sorted.
+    let value = serde_json::to_value(node)?;
+    let mut out = serde_json::to_string_pretty(&value)?;
+    out.push('\n');
+    Ok(out)
+}
+
+fn write_data<W: Write>(out: &mut W, data: &[u8]) -> Result<(), MyosotisError> {
+    writeln!(out, "data {}", data.len())?;
+    out.write_all(data)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn write_git_commit<W: Write>(
+    out: &mut W,
+    mark: u64,
+    timestamp: u64,
+    message: &str,
+    state: &HashMap<NodeId, Node>,
+    touched: &BTreeSet<NodeId>,
+) -> Result<(), MyosotisError> {
+    writeln!(out, "commit {}", GIT_BRANCH)?;
+    writeln!(out, "mark :{}", mark)?;
+    writeln!(out, "committer {} {} +0000", GIT_COMMITTER, timestamp)?;
+    write_data(out, message.as_bytes())?;
+    if mark > 1 {
+        writeln!(out, "from :{}", mark - 1)?;
+    }
+
+    for id in touched {
+        match state.get(id) {
+            Some(node) if !node.deleted => {
+                writeln!(out, "M 644 inline {}", node_path(*id))?;
+                write_data(out, node_json(node)?.as_bytes())?;
+            }
+            _ => writeln!(out, "D {}", node_path(*id))?,
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// RFC 8785 canonical JSON for `commit`: the bytes a JCS-mode file hashes,
+/// so any JCS implementation can re-derive and check or co-sign the hash.
+/// Binary-mode files hash `Commit::canonical_bytes` instead.
+pub fn commit_json(commit: &Commit) -> String {
+    String::from_utf8_lossy(&commit.canonical_bytes_with(Canonicalization::Jcs)).into_owned()
+}
+
+/// Writes the commit chain as a `git fast-import` stream.
+///
+/// Every live node is stored as `nodes/<id>.json`; tombstoned nodes are
+/// removed from the tree. A compacted genesis state becomes the first git
+/// commit. Committer timestamps are derived from commit ids so the stream
+/// is deterministic.
+pub fn git_fast_import<W: Write>(mem: &Memory, out: &mut W) -> Result<(), MyosotisError> {
+    let mut state = mem.genesis_state.clone().unwrap_or_default();
+    let mut mark = 0u64;
+
+    if !state.is_empty() {
+        mark += 1;
+        let touched: BTreeSet<NodeId> = state.keys().copied().collect();
+        write_git_commit(out, mark, 0, "genesis", &state, &touched)?;
+    }
+
+    for commit in &mem.commits {
+        let mut touched = BTreeSet::new();
+        for mutation in &commit.mutations {
+            Memory::apply_mutation(&mut state, mutation)?;
+            touched.insert(mutation.node_id());
+        }
+
+        mark += 1;
+        let message = commit
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("commit {}", commit.id));
+        write_git_commit(out, mark, commit.id, &message, &state, &touched)?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// First `Ref` in `value` to a node not in `keep`.
+fn ref_outside(value: &Value, keep: &BTreeSet<NodeId>) -> Option<NodeId> {
+    match value {
+        Value::Ref(id) if !keep.contains(id) => Some(*id),
+        Value::List(items) => items.iter().find_map(|v| ref_outside(v, keep)),
+        Value::Map(map) => map.values().find_map(|v| ref_outside(v, keep)),
+        _ => None,
+    }
+}
+
+fn check_refs_kept(
+    from: NodeId,
+    value: &Value,
+    keep: &BTreeSet<NodeId>,
+) -> Result<(), MyosotisError> {
+    match ref_outside(value, keep) {
+        Some(to) => Err(MyosotisError::InvalidInput(format!(
+            "node {} refers to node {}, which is not extracted",
+            from, to
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// A new memory holding only `node_ids` and their history: genesis keeps
+/// just those nodes, each commit keeps just the mutations to them, and
+/// commits left empty are dropped. The remaining commits are renumbered
+/// from 1 and rehashed, so the result validates on its own.
+///
+/// Settings carry over, as do the locks, expiries and commit times of
+/// what is kept.
+/// Checkpoints, anchors and the seal pin hashes of the full history and
+/// are left out. Fails with `InvalidInput` when a kept node ever refers
+/// to a node outside `node_ids`, since the result could not replay it.
+/// Only committed history is read; pending mutations are ignored.
+pub fn extract(mem: &Memory, node_ids: &[NodeId]) -> Result<Memory, MyosotisError> {
+    let committed = mem.committed_state()?;
+    for id in node_ids {
+        if !committed.contains_key(id) {
+            return Err(MyosotisError::NodeNotFound(*id));
+        }
+    }
+    let keep: BTreeSet<NodeId> = node_ids.iter().copied().collect();
+
+    let mut out = Memory::new();
+    out.canonicalization = mem.canonicalization;
+    out.float_policy = mem.float_policy;
+    out.id_mode = mem.id_mode;
+    out.next_node_id = mem.next_node_id;
+    out.retention = mem.retention.clone();
+    out.unique_constraints = mem.unique_constraints.clone();
+    out.locks = mem
+        .locks
+        .iter()
+        .filter(|lock| keep.contains(&lock.node_id))
+        .cloned()
+        .collect();
+    out.expiries = mem
+        .expiries
+        .iter()
+        .filter(|expiry| keep.contains(&expiry.node_id))
+        .cloned()
+        .collect();
+
+    if let Some(genesis) = &mem.genesis_state {
+        let state: HashMap<NodeId, Node> = genesis
+            .iter()
+            .filter(|(id, _)| keep.contains(id))
+            .map(|(id, node)| (*id, node.clone()))
+            .collect();
+        for node in state.values() {
+            for value in node.fields.values() {
+                check_refs_kept(node.id, value, &keep)?;
+            }
+        }
+        out.genesis_state_hash = Some(out.hash_state(&state));
+        out.genesis_state = Some(state);
+    }
+
+    let mut prev: Option<(u64, [u8; 32])> = None;
+    for commit in &mem.commits {
+        let mutations: Vec<_> = commit
+            .mutations
+            .iter()
+            .filter(|m| keep.contains(&m.node_id()))
+            .cloned()
+            .collect();
+        if mutations.is_empty() {
+            continue;
+        }
+        for mutation in &mutations {
+            if let Mutation::SetField { id, value, .. }
+            | Mutation::SetPath { id, value, .. }
+            | Mutation::ListPush { id, value, .. }
+            | Mutation::ListInsert { id, value, .. } = mutation
+            {
+                check_refs_kept(*id, value, &keep)?;
+            }
+        }
+        let id = prev.map_or(1, |(id, _)| id + 1);
+        let parent_hash = prev.map(|(_, hash)| hash).or(out.genesis_state_hash);
+        if let Some(time) = mem.commit_times.get(&commit.id) {
+            out.commit_times.insert(id, *time);
+        }
+        let mut extracted = Commit {
+            id,
+            parent: prev.map(|(id, _)| id),
+            parent_hash,
+            hash: [0; 32],
+            message: commit.message.clone(),
+            source: commit.source.clone(),
+            state_hash: None,
+            mutations,
+        };
+        extracted.hash = extracted.compute_hash_with(out.canonicalization);
+        prev = Some((id, extracted.hash));
+        out.commits.push(extracted);
+    }
+
+    out.head_state =
+        Memory::replay_from(out.genesis_state.clone().unwrap_or_default(), &out.commits)?;
+    out.reindex_aliases();
+    out.reindex_labels();
+    out.reindex_unique()?;
+    let expected: HashMap<NodeId, Node> = committed
+        .into_iter()
+        .filter(|(id, _)| keep.contains(id))
+        .collect();
+    if out.head_state != expected {
+        return Err(MyosotisError::Invariant(
+            "extracted history does not replay to the extracted nodes".to_string(),
+        ));
+    }
+    out.validate()?;
+    tracing::debug!(
+        nodes = keep.len(),
+        commits = out.commits.len(),
+        "extracted memory"
+    );
+    Ok(out)
+}
+
+fn collect_refs(value: &Value, state: &HashMap<NodeId, Node>, out: &mut Vec<NodeId>) {
+    match value {
+        Value::Ref(id) => out.push(*id),
+        Value::AliasRef(alias) => out.extend(crate::alias::find_alias(state, alias)),
+        Value::List(items) => {
+            for item in items {
+                collect_refs(item, state, out);
+            }
+        }
+        Value::Map(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                if let Some(item) = map.get(key) {
+                    collect_refs(item, state, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Live nodes sorted by id, plus `(from, to, field)` edges between them.
+/// References nested inside lists and maps count as edges of the field
+/// that contains them.
+pub(crate) fn graph_of(state: &HashMap<NodeId, Node>) -> (Vec<&Node>, Vec<(NodeId, NodeId, &str)>) {
+    let mut nodes: Vec<&Node> = state.values().filter(|n| !n.deleted).collect();
+    nodes.sort_by_key(|n| n.id);
+
+    let mut edges = Vec::new();
+    for node in &nodes {
+        let mut keys: Vec<&String> = node.fields.keys().collect();
+        keys.sort();
+        for key in keys {
+            let mut targets = Vec::new();
+            if let Some(value) = node.fields.get(key) {
+                collect_refs(value, state, &mut targets);
+            }
+            for target in targets {
+                if state.get(&target).is_some_and(|t| !t.deleted) {
+                    edges.push((node.id, target, key.as_str()));
+                }
+            }
+        }
+    }
+    (nodes, edges)
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Renders live nodes and their references as a Graphviz digraph.
+pub fn dot(state: &HashMap<NodeId, Node>) -> String {
+    let (nodes, edges) = graph_of(state);
+    let mut out = String::from("digraph myosotis {\n");
+    for node in nodes {
+        let _ = writeln!(
+            out,
+            "  n{} [label=\"{}: {}\", type=\"{}\"];",
+            node.id,
+            node.id,
+            dot_escape(&node.ty),
+            dot_escape(&node.ty)
+        );
+    }
+    for (from, to, field) in edges {
+        let _ = writeln!(
+            out,
+            "  n{} -> n{} [label=\"{}\"];",
+            from,
+            to,
+            dot_escape(field)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Renders live nodes and their references as GraphML.
+pub fn graphml(state: &HashMap<NodeId, Node>) -> String {
+    let (nodes, edges) = graph_of(state);
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"field\" for=\"edge\" attr.name=\"field\" attr.type=\"string\"/>\n");
+    out.push_str("  <graph id=\"myosotis\" edgedefault=\"directed\">\n");
+    for node in nodes {
+        let _ = writeln!(
+            out,
+            "    <node id=\"n{}\"><data key=\"type\">{}</data></node>",
+            node.id,
+            xml_escape(&node.ty)
+        );
+    }
+    for (i, (from, to, field)) in edges.into_iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"><data key=\"field\">{}</data></edge>",
+            i,
+            from,
+            to,
+            xml_escape(field)
+        );
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// How nodes, types and fields map onto IRIs for RDF export. Unmapped
+/// types and fields fall back to `vocab` followed by the escaped name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ontology {
+    /// Prefix for node IRIs; the node id is appended.
+    pub base: String,
+    pub vocab: String,
+    pub types: HashMap<String, String>,
+    pub properties: HashMap<String, String>,
+}
+
+impl Default for Ontology {
+    fn default() -> Self {
+        Self {
+            base: "urn:myosotis:node:".to_string(),
+            vocab: "urn:myosotis:vocab:".to_string(),
+            types: HashMap::new(),
+            properties: HashMap::new(),
+        }
+    }
+}
+
+fn iri_escape(s: &str) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            let _ = write!(out, "%{:02X}", b);
+        }
+    }
+    out
+}
+
+impl Ontology {
+    pub fn node_iri(&self, id: NodeId) -> String {
+        format!("{}{}", self.base, id)
+    }
+
+    pub fn class_iri(&self, ty: &str) -> String {
+        self.types
+            .get(ty)
+            .cloned()
+            .unwrap_or_else(|| format!("{}{}", self.vocab, iri_escape(ty)))
+    }
+
+    pub fn property_iri(&self, key: &str) -> String {
+        self.properties
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| format!("{}{}", self.vocab, iri_escape(key)))
+    }
+}
+
+fn xsd_double(v: f64) -> String {
+    if v.is_nan() {
+        "NaN".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 { "INF" } else { "-INF" }.to_string()
+    } else {
+        format!("{:e}", v)
+    }
+}
+
+fn nt_literal(lexical: &str, datatype: Option<&str>) -> String {
+    let mut out = String::from("\"");
+    for c in lexical.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    if let Some(datatype) = datatype {
+        let _ = write!(out, "^^<{}>", datatype);
+    }
+    out
+}
+
+/// N-Triples objects for a field value. Lists contribute one object per
+/// item; maps become `rdf:JSON` literals; references to missing or deleted
+/// nodes are dropped.
+fn nt_objects(
+    value: &Value,
+    state: &HashMap<NodeId, Node>,
+    ontology: &Ontology,
+    out: &mut Vec<String>,
+) -> Result<(), MyosotisError> {
+    match value {
+        Value::Str(v) => out.push(nt_literal(v, None)),
+        Value::Int(v) => out.push(nt_literal(&v.to_string(), Some(&format!("{}integer", XSD)))),
+        Value::Float(v) => out.push(nt_literal(&xsd_double(*v), Some(&format!("{}double", XSD)))),
+        Value::Bool(v) => out.push(nt_literal(&v.to_string(), Some(&format!("{}boolean", XSD)))),
+        Value::Ref(id) => {
+            if state.get(id).is_some_and(|n| !n.deleted) {
+                out.push(format!("<{}>", ontology.node_iri(*id)));
+            }
+        }
+        Value::AliasRef(alias) => {
+            if let Some(id) = crate::alias::find_alias(state, alias) {
+                nt_objects(&Value::Ref(id), state, ontology, out)?;
+            }
+        }
+        // The target lives in another file, outside this graph.
+        Value::ExternalRef { .. } => {}
+        Value::List(items) => {
+            for item in items {
+                nt_objects(item, state, ontology, out)?;
+            }
+        }
+        Value::Map(_) | Value::Vector(_) => {
+            let json = serde_json::to_string(&serde_json::to_value(value)?)?;
+            out.push(nt_literal(&json, Some(RDF_JSON)));
+        }
+    }
+    Ok(())
+}
+
+/// Renders live nodes as N-Triples, ordered by node id then field key.
+pub fn ntriples(
+    state: &HashMap<NodeId, Node>,
+    ontology: &Ontology,
+) -> Result<String, MyosotisError> {
+    let (nodes, _) = graph_of(state);
+    let mut out = String::new();
+    for node in nodes {
+        let subject = ontology.node_iri(node.id);
+        let _ = writeln!(
+            out,
+            "<{}> <{}> <{}> .",
+            subject,
+            RDF_TYPE,
+            ontology.class_iri(&node.ty)
+        );
+
+        let mut keys: Vec<&String> = node.fields.keys().collect();
+        keys.sort();
+        for key in keys {
+            let Some(value) = node.fields.get(key) else {
+                continue;
+            };
+            let mut objects = Vec::new();
+            nt_objects(value, state, ontology, &mut objects)?;
+            let predicate = ontology.property_iri(key);
+            for object in objects {
+                let _ = writeln!(out, "<{}> <{}> {} .", subject, predicate, object);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn json_ld_value(
+    value: &Value,
+    state: &HashMap<NodeId, Node>,
+    ontology: &Ontology,
+) -> Result<Option<serde_json::Value>, MyosotisError> {
+    use serde_json::json;
+    Ok(Some(match value {
+        Value::Str(v) => json!(v),
+        Value::Bool(v) => json!(v),
+        Value::Int(v) => json!({ "@value": v.to_string(), "@type": format!("{}integer", XSD) }),
+        Value::Float(v) => json!({ "@value": xsd_double(*v), "@type": format!("{}double", XSD) }),
+        Value::Ref(id) => {
+            if state.get(id).is_none_or(|n| n.deleted) {
+                return Ok(None);
+            }
+            json!({ "@id": ontology.node_iri(*id) })
+        }
+        Value::AliasRef(alias) => match crate::alias::find_alias(state, alias) {
+            Some(id) => return json_ld_value(&Value::Ref(id), state, ontology),
+            None => return Ok(None),
+        },
+        Value::ExternalRef { .. } => return Ok(None),
+        Value::List(items) => {
+            let mut values = Vec::new();
+            for item in items {
+                values.extend(json_ld_value(item, state, ontology)?);
+            }
+            serde_json::Value::Array(values)
+        }
+        Value::Map(_) | Value::Vector(_) => {
+            json!({ "@value": serde_json::to_value(value)?, "@type": "@json" })
+        }
+    }))
+}
+
+/// Renders live nodes as an expanded-IRI JSON-LD document with one
+/// `@graph` entry per node.
+pub fn json_ld(
+    state: &HashMap<NodeId, Node>,
+    ontology: &Ontology,
+) -> Result<serde_json::Value, MyosotisError> {
+    let (nodes, _) = graph_of(state);
+    let mut graph = Vec::new();
+    for node in nodes {
+        let mut entry = serde_json::Map::new();
+        entry.insert("@id".to_string(), ontology.node_iri(node.id).into());
+        entry.insert("@type".to_string(), ontology.class_iri(&node.ty).into());
+        for (key, value) in &node.fields {
+            if let Some(value) = json_ld_value(value, state, ontology)? {
+                entry.insert(ontology.property_iri(key), value);
+            }
+        }
+        graph.push(serde_json::Value::Object(entry));
+    }
+    Ok(serde_json::json!({ "@graph": graph }))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn contains_ref(value: &Value) -> bool {
+    match value {
+        Value::Ref(_) | Value::AliasRef(_) => true,
+        Value::List(items) => items.iter().any(contains_ref),
+        Value::Map(map) => map.values().any(contains_ref),
+        _ => false,
+    }
+}
+
+/// `neo4j-admin import` column type shared by every value of a property,
+/// or `None` when values differ and the column falls back to string.
+fn neo4j_type(values: &[&Value]) -> Option<&'static str> {
+    let ty = |v: &Value| match v {
+        Value::Int(_) => Some("long"),
+        Value::Float(_) => Some("double"),
+        Value::Bool(_) => Some("boolean"),
+        _ => None,
+    };
+    let first = ty(values.first()?)?;
+    values.iter().all(|v| ty(v) == Some(first)).then_some(first)
+}
+
+fn neo4j_cell(value: &Value) -> Result<String, MyosotisError> {
+    Ok(match value {
+        Value::Str(v) => v.clone(),
+        Value::Int(v) => v.to_string(),
+        Value::Float(v) => v.to_string(),
+        Value::Bool(v) => v.to_string(),
+        _ => serde_json::to_string(&serde_json::to_value(value)?)?,
+    })
+}
+
+/// Renders live nodes and reference edges as `neo4j-admin import` CSVs,
+/// returned as `(nodes.csv, relationships.csv)`.
+///
+/// Node types become labels. Fields holding references become
+/// relationships typed by the field name; other fields become properties,
+/// typed when all values of a field share a scalar type. Lists and maps
+/// are stored as JSON strings.
+pub fn neo4j_csv(state: &HashMap<NodeId, Node>) -> Result<(String, String), MyosotisError> {
+    let (nodes, edges) = graph_of(state);
+
+    let mut columns: Vec<&str> = nodes
+        .iter()
+        .flat_map(|n| {
+            n.fields
+                .iter()
+                .filter(|(_, v)| !contains_ref(v))
+                .map(|(k, _)| k.as_str())
+        })
+        .collect();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let mut header = vec!["id:ID".to_string(), ":LABEL".to_string()];
+    for column in &columns {
+        let values: Vec<&Value> = nodes.iter().filter_map(|n| n.fields.get(*column)).collect();
+        header.push(match neo4j_type(&values) {
+            Some(ty) => csv_field(&format!("{}:{}", column, ty)),
+            None => csv_field(column),
+        });
+    }
+
+    let mut node_csv = header.join(",");
+    node_csv.push('\n');
+    for node in &nodes {
+        let mut row = vec![node.id.to_string(), csv_field(&node.ty)];
+        for column in &columns {
+            row.push(match node.fields.get(*column) {
+                Some(value) if !contains_ref(value) => csv_field(&neo4j_cell(value)?),
+                _ => String::new(),
+            });
+        }
+        node_csv.push_str(&row.join(","));
+        node_csv.push('\n');
+    }
+
+    let mut rel_csv = String::from(":START_ID,:END_ID,:TYPE\n");
+    for (from, to, field) in edges {
+        let _ = writeln!(rel_csv, "{},{},{}", from, to, csv_field(field));
+    }
+    Ok((node_csv, rel_csv))
+}
+
+impl Memory {
+    fn graph_state(&self, at: Option<u64>) -> Result<HashMap<NodeId, Node>, MyosotisError> {
+        match at {
+            Some(commit_id) => self.state_at_commit(commit_id),
+            None => Ok(self.head_state.clone()),
+        }
+    }
+
+    /// Graphviz rendering of head state, or of the state at commit `at`.
+    pub fn to_dot(&self, at: Option<u64>) -> Result<String, MyosotisError> {
+        Ok(dot(&self.graph_state(at)?))
+    }
+
+    /// GraphML rendering of head state, or of the state at commit `at`.
+    pub fn to_graphml(&self, at: Option<u64>) -> Result<String, MyosotisError> {
+        Ok(graphml(&self.graph_state(at)?))
+    }
+
+    /// `neo4j-admin import` CSVs of head state, or of the state at commit `at`.
+    pub fn to_neo4j_csv(&self, at: Option<u64>) -> Result<(String, String), MyosotisError> {
+        neo4j_csv(&self.graph_state(at)?)
+    }
+
+    /// N-Triples rendering of head state, or of the state at commit `at`.
+    pub fn to_ntriples(
+        &self,
+        at: Option<u64>,
+        ontology: &Ontology,
+    ) -> Result<String, MyosotisError> {
+        ntriples(&self.graph_state(at)?, ontology)
+    }
+
+    /// JSON-LD rendering of head state, or of the state at commit `at`.
+    pub fn to_json_ld(
+        &self,
+        at: Option<u64>,
+        ontology: &Ontology,
+    ) -> Result<serde_json::Value, MyosotisError> {
+        json_ld(&self.graph_state(at)?, ontology)
+    }
+}
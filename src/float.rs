@@ -0,0 +1,201 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// How float values are canonicalized before they are recorded.
+///
+/// `binary` hashes write a float's bits as they are, so `-0.0` and `0.0`,
+/// or two NaNs with different payloads, hash differently even though they
+/// compare (or fail to compare) alike. `Normalize` and `Strict` rule those
+/// out when a value is written, which makes the hashed bits the same on
+/// every platform. The policy is fixed per file and recorded in the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FloatPolicy {
+    /// Floats are recorded and hashed bit for bit.
+    #[default]
+    BitExact,
+    /// `-0.0` is recorded as `0.0` and every NaN as the one canonical NaN.
+    Normalize,
+    /// Like `Normalize`, but a NaN is refused.
+    Strict,
+}
+
+impl fmt::Display for FloatPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FloatPolicy::BitExact => "bit_exact",
+            FloatPolicy::Normalize => "normalize",
+            FloatPolicy::Strict => "strict",
+        })
+    }
+}
+
+impl FloatPolicy {
+    pub fn is_bit_exact(&self) -> bool {
+        matches!(self, FloatPolicy::BitExact)
+    }
+
+    fn violation(self, v: f64) -> MyosotisError {
+        MyosotisError::FloatPolicyViolation(format!("{:?}", v), self.to_string())
+    }
+
+    /// `v` as this policy records it.
+    pub fn canonical_f64(self, v: f64) -> Result<f64, MyosotisError> {
+        match self {
+            FloatPolicy::BitExact => Ok(v),
+            FloatPolicy::Strict if v.is_nan() => Err(self.violation(v)),
+            _ if v.is_nan() => Ok(f64::NAN),
+            _ => Ok(v + 0.0),
+        }
+    }
+
+    fn canonical_f32(self, v: f32) -> Result<f32, MyosotisError> {
+        match self {
+            FloatPolicy::BitExact => Ok(v),
+            FloatPolicy::Strict if v.is_nan() => Err(self.violation(f64::from(v))),
+            _ if v.is_nan() => Ok(f32::NAN),
+            _ => Ok(v + 0.0),
+        }
+    }
+
+    /// Rewrites every float in `value`, nested ones included, as this
+    /// policy records it.
+    pub fn canonicalize(self, value: &mut Value) -> Result<(), MyosotisError> {
+        match value {
+            Value::Float(v) => *v = self.canonical_f64(*v)?,
+            Value::Vector(v) => {
+                for component in v {
+                    *component = self.canonical_f32(*component)?;
+                }
+            }
+            Value::List(items) => {
+                for item in items {
+                    self.canonicalize(item)?;
+                }
+            }
+            Value::Map(map) => {
+                for item in map.values_mut() {
+                    self.canonicalize(item)?;
+                }
+            }
+            Value::Int(_)
+            | Value::Bool(_)
+            | Value::Str(_)
+            | Value::Ref(_)
+            | Value::AliasRef(_)
+            | Value::ExternalRef { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Fails unless every float in `value` is already recorded the way
+    /// this policy would record it, bit for bit.
+    pub fn check(self, value: &Value) -> Result<(), MyosotisError> {
+        match value {
+            Value::Float(v) => {
+                if self.canonical_f64(*v)?.to_bits() != v.to_bits() {
+                    return Err(self.violation(*v));
+                }
+            }
+            Value::Vector(v) => {
+                for &component in v {
+                    if self.canonical_f32(component)?.to_bits() != component.to_bits() {
+                        return Err(self.violation(f64::from(component)));
+                    }
+                }
+            }
+            Value::List(items) => {
+                for item in items {
+                    self.check(item)?;
+                }
+            }
+            Value::Map(map) => {
+                for item in map.values() {
+                    self.check(item)?;
+                }
+            }
+            Value::Int(_)
+            | Value::Bool(_)
+            | Value::Str(_)
+            | Value::Ref(_)
+            | Value::AliasRef(_)
+            | Value::ExternalRef { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn check_mutations<'a>(
+        self,
+        mutations: impl IntoIterator<Item = &'a Mutation>,
+    ) -> Result<(), MyosotisError> {
+        mutations
+            .into_iter()
+            .filter_map(Mutation::value)
+            .try_for_each(|value| self.check(value))
+    }
+
+    fn check_state(self, state: &HashMap<NodeId, Node>) -> Result<(), MyosotisError> {
+        state
+            .values()
+            .flat_map(|node| node.fields.values())
+            .try_for_each(|value| self.check(value))
+    }
+}
+
+impl Memory {
+    pub fn float_policy(&self) -> FloatPolicy {
+        self.float_policy
+    }
+
+    /// Switches the float policy. Fails, changing nothing, if genesis
+    /// state, a commit or a pending mutation holds a float the policy
+    /// would record differently: recorded values are never rewritten, as
+    /// that would change their hashes.
+    pub fn set_float_policy(&mut self, policy: FloatPolicy) -> Result<(), MyosotisError> {
+        self.check_recorded_floats(policy)?;
+        if !policy.is_bit_exact() {
+            policy.check_mutations(&self.pending_mutations)?;
+        }
+        self.float_policy = policy;
+        Ok(())
+    }
+
+    fn check_recorded_floats(&self, policy: FloatPolicy) -> Result<(), MyosotisError> {
+        if policy.is_bit_exact() {
+            return Ok(());
+        }
+        if let Some(genesis) = &self.genesis_state {
+            policy.check_state(genesis)?;
+        }
+        policy.check_mutations(self.commits.iter().flat_map(|c| &c.mutations))
+    }
+
+    /// Applies the float policy to the value a mutation about to be staged
+    /// writes.
+    pub(crate) fn canonicalize_floats(&self, mutation: &mut Mutation) -> Result<(), MyosotisError> {
+        match mutation.value_mut() {
+            Some(value) => self.float_policy.canonicalize(value),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks `mutations` against the float policy, for those that reach a
+    /// commit without being staged through `set` and friends.
+    pub(crate) fn check_floats(&self, mutations: &[Mutation]) -> Result<(), MyosotisError> {
+        if self.float_policy.is_bit_exact() {
+            return Ok(());
+        }
+        self.float_policy.check_mutations(mutations)
+    }
+
+    /// Checks genesis state and every commit against the float policy
+    /// after a load.
+    pub(crate) fn check_float_history(&self) -> Result<(), MyosotisError> {
+        self.check_recorded_floats(self.float_policy)
+    }
+}
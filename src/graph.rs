@@ -0,0 +1,144 @@
+use crate::error::MyosotisError;
+use crate::export::graph_of;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Outgoing and incoming references between the live nodes of one state,
+/// including refs nested in lists and maps and resolved alias refs. Each
+/// list is sorted and free of duplicates.
+#[derive(Debug, Default)]
+pub struct RefIndex {
+    outgoing: HashMap<NodeId, Vec<NodeId>>,
+    incoming: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl RefIndex {
+    pub fn build(state: &HashMap<NodeId, Node>) -> Self {
+        let (_, edges) = graph_of(state);
+        let mut outgoing: HashMap<NodeId, BTreeSet<NodeId>> = HashMap::new();
+        let mut incoming: HashMap<NodeId, BTreeSet<NodeId>> = HashMap::new();
+        for (from, to, _) in edges {
+            outgoing.entry(from).or_default().insert(to);
+            incoming.entry(to).or_default().insert(from);
+        }
+        let flatten = |map: HashMap<NodeId, BTreeSet<NodeId>>| {
+            map.into_iter()
+                .map(|(id, set)| (id, set.into_iter().collect()))
+                .collect()
+        };
+        Self {
+            outgoing: flatten(outgoing),
+            incoming: flatten(incoming),
+        }
+    }
+
+    /// Nodes `id` refers to.
+    pub fn refs_from(&self, id: NodeId) -> &[NodeId] {
+        self.outgoing.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Nodes referring to `id`.
+    pub fn refs_to(&self, id: NodeId) -> &[NodeId] {
+        self.incoming.get(&id).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Nodes reachable from `from` by following refs forwards, sorted, not
+/// including `from` itself unless it lies on a cycle.
+pub fn reachable_from(
+    state: &HashMap<NodeId, Node>,
+    from: NodeId,
+) -> Result<Vec<NodeId>, MyosotisError> {
+    check_live(state, from)?;
+    let index = RefIndex::build(state);
+    let mut seen = BTreeSet::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(id) = queue.pop_front() {
+        for next in index.refs_from(id) {
+            if seen.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+    Ok(seen.into_iter().collect())
+}
+
+/// Shortest chain of nodes from `a` to `b`, both included, where each step
+/// follows a ref in either direction. `None` if they are not connected.
+/// Among equally short paths the one through lower ids wins.
+pub fn path_between(
+    state: &HashMap<NodeId, Node>,
+    a: NodeId,
+    b: NodeId,
+) -> Result<Option<Vec<NodeId>>, MyosotisError> {
+    check_live(state, a)?;
+    check_live(state, b)?;
+    let index = RefIndex::build(state);
+    let mut previous: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut queue = VecDeque::from([a]);
+    while let Some(id) = queue.pop_front() {
+        if id == b {
+            let mut path = vec![b];
+            let mut current = b;
+            while let Some(prev) = previous.get(&current) {
+                path.push(*prev);
+                current = *prev;
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+        let neighbours: BTreeSet<NodeId> = index
+            .refs_from(id)
+            .iter()
+            .chain(index.refs_to(id))
+            .copied()
+            .collect();
+        for next in neighbours {
+            if next != a && !previous.contains_key(&next) {
+                previous.insert(next, id);
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn check_live(state: &HashMap<NodeId, Node>, id: NodeId) -> Result<(), MyosotisError> {
+    match state.get(&id) {
+        None => Err(MyosotisError::NodeNotFound(id)),
+        Some(node) if node.deleted => Err(MyosotisError::NodeDeleted(id)),
+        Some(_) => Ok(()),
+    }
+}
+
+impl Memory {
+    /// See `graph::reachable_from`; evaluated at head.
+    pub fn reachable_from(&self, from: NodeId) -> Result<Vec<NodeId>, MyosotisError> {
+        reachable_from(&self.head_state, from)
+    }
+
+    /// See `graph::path_between`; evaluated at head.
+    pub fn path_between(&self, a: NodeId, b: NodeId) -> Result<Option<Vec<NodeId>>, MyosotisError> {
+        path_between(&self.head_state, a, b)
+    }
+
+    /// `reachable_from` as of commit `commit_id`.
+    pub fn reachable_from_at(
+        &self,
+        from: NodeId,
+        commit_id: u64,
+    ) -> Result<Vec<NodeId>, MyosotisError> {
+        reachable_from(&self.state_at_commit(commit_id)?, from)
+    }
+
+    /// `path_between` as of commit `commit_id`.
+    pub fn path_between_at(
+        &self,
+        a: NodeId,
+        b: NodeId,
+        commit_id: u64,
+    ) -> Result<Option<Vec<NodeId>>, MyosotisError> {
+        path_between(&self.state_at_commit(commit_id)?, a, b)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Validation policy run before a commit is hashed and appended. It gets
+/// the commit's mutations and the state they produce; returning an error
+/// rejects the commit and leaves the mutations pending.
+pub type PrecommitHook =
+    Arc<dyn Fn(&[Mutation], &HashMap<NodeId, Node>) -> Result<(), MyosotisError> + Send + Sync>;
+
+/// Registered pre-commit hooks. Not serialized. Unlike observers they are
+/// carried over when a `Memory` is cloned, so copies enforce the same rules.
+#[derive(Default, Clone)]
+pub struct PrecommitHooks(Vec<PrecommitHook>);
+
+impl fmt::Debug for PrecommitHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PrecommitHooks({})", self.0.len())
+    }
+}
+
+impl PrecommitHooks {
+    /// Runs hooks in registration order, stopping at the first error.
+    pub(crate) fn check(
+        &self,
+        mutations: &[Mutation],
+        state: &HashMap<NodeId, Node>,
+    ) -> Result<(), MyosotisError> {
+        self.0.iter().try_for_each(|hook| hook(mutations, state))
+    }
+}
+
+impl Memory {
+    pub fn add_precommit_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&[Mutation], &HashMap<NodeId, Node>) -> Result<(), MyosotisError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.precommit_hooks.0.push(Arc::new(hook));
+    }
+
+    pub fn clear_precommit_hooks(&mut self) {
+        self.precommit_hooks.0.clear();
+    }
+}
@@ -0,0 +1,309 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::graph::RefIndex;
+use crate::memory::Memory;
+use crate::node::{NodeId, Value, is_sequential_id};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+pub type Row = BTreeMap<String, Value>;
+
+/// `None` if `value` is or holds a `null`, which has no `Value`.
+fn value_from_json(value: &serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(b) => Some(Value::Bool(*b)),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(Value::Int)
+            .or_else(|| n.as_f64().map(Value::Float)),
+        serde_json::Value::String(s) => Some(Value::Str(s.clone())),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(value_from_json)
+            .collect::<Option<_>>()
+            .map(Value::List),
+        serde_json::Value::Object(obj) => obj
+            .iter()
+            .map(|(k, v)| value_from_json(v).map(|v| (k.clone(), v)))
+            .collect::<Option<_>>()
+            .map(Value::Map),
+    }
+}
+
+/// Parses a JSON array of objects. Object keys become field names; `null`
+/// members are skipped, and a `null` nested in a list or map is refused.
+pub fn rows_from_json(data: &str) -> Result<Vec<Row>, MyosotisError> {
+    let root: serde_json::Value = serde_json::from_str(data)?;
+    let items = root.as_array().ok_or_else(|| {
+        MyosotisError::InvalidInput("import data must be a JSON array".to_string())
+    })?;
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let obj = item.as_object().ok_or_else(|| {
+                MyosotisError::InvalidInput(format!("import row {} is not an object", i))
+            })?;
+            obj.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| {
+                    let value = value_from_json(v).ok_or_else(|| {
+                        MyosotisError::InvalidInput(format!(
+                            "import row {} field '{}' holds a null",
+                            i, k
+                        ))
+                    })?;
+                    Ok((k.clone(), value))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn parse_csv_records(data: &str) -> Result<Vec<Vec<String>>, MyosotisError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(MyosotisError::InvalidInput(
+            "unterminated quoted CSV field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records.retain(|r| !(r.len() == 1 && r[0].is_empty()));
+    Ok(records)
+}
+
+/// Parses CSV with a header row. Columns become string fields; empty cells
+/// are skipped.
+pub fn rows_from_csv(data: &str) -> Result<Vec<Row>, MyosotisError> {
+    let mut records = parse_csv_records(data)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| MyosotisError::InvalidInput("CSV header row missing".to_string()))?;
+
+    records
+        .enumerate()
+        .map(|(i, record)| {
+            if record.len() != header.len() {
+                return Err(MyosotisError::InvalidInput(format!(
+                    "CSV row {} has {} columns, expected {}",
+                    i + 1,
+                    record.len(),
+                    header.len()
+                )));
+            }
+            Ok(header
+                .iter()
+                .zip(record)
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(|(column, cell)| (column.clone(), Value::Str(cell)))
+                .collect())
+        })
+        .collect()
+}
+
+/// Stages one node of type `ty` per row, in row order, with fields set in
+/// sorted key order. IDs are therefore assigned deterministically from
+/// `next_node_id`. Nothing is committed. If a row fails, the rows before
+/// it are unstaged again, so a failed import stages nothing.
+pub fn stage_rows(
+    mem: &mut Memory,
+    ty: &str,
+    rows: Vec<Row>,
+) -> Result<Vec<NodeId>, MyosotisError> {
+    let staged = mem.pending_mutations.len();
+    stage_each(mem, ty, rows).or_else(|e| {
+        mem.truncate_pending(staged)?;
+        Err(e)
+    })
+}
+
+fn stage_each(mem: &mut Memory, ty: &str, rows: Vec<Row>) -> Result<Vec<NodeId>, MyosotisError> {
+    let mut ids = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id = mem.create(ty);
+        for (key, value) in row {
+            mem.set(id, &key, value)?;
+        }
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+/// How `Memory::import_subgraph` picks ids for the nodes it copies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RemapPolicy {
+    /// Every node gets a new id from this memory's allocator.
+    #[default]
+    Fresh,
+    /// A node keeps its id when this memory has no node with it and, for
+    /// sequential ids, has not handed it out yet. The rest get new ids
+    /// after all kept ones are placed.
+    KeepIfFree,
+}
+
+fn remap_value(
+    value: &Value,
+    mapping: &BTreeMap<NodeId, NodeId>,
+    from: NodeId,
+) -> Result<Value, MyosotisError> {
+    Ok(match value {
+        Value::Ref(id) => Value::Ref(*mapping.get(id).ok_or_else(|| {
+            MyosotisError::InvalidInput(format!(
+                "node {} refers to node {}, which is not live",
+                from, id
+            ))
+        })?),
+        Value::List(items) => Value::List(
+            items
+                .iter()
+                .map(|v| remap_value(v, mapping, from))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(map) => Value::Map(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), remap_value(v, mapping, from)?)))
+                .collect::<Result<_, MyosotisError>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+impl Memory {
+    /// Copies `roots` and every node reachable from them by refs out of
+    /// `other`'s head state into this memory, giving them ids by `policy`.
+    /// Refs between copied nodes are rewritten to the new ids; aliases and
+    /// namespaces are copied, alias refs and external refs are kept as
+    /// they are.
+    ///
+    /// Nodes are copied breadth-first from the roots in the order given,
+    /// following refs in id order, and fields are set in sorted key order,
+    /// so on a sequential file the same import always assigns the same
+    /// ids. Everything is checked before anything is staged: a root that
+    /// is not live, a ref to a node that is not live, or an alias already
+    /// taken here fails without staging. Nothing is committed, so one
+    /// commit records the whole import. Returns the old id to new id map.
+    pub fn import_subgraph(
+        &mut self,
+        other: &Memory,
+        roots: &[NodeId],
+        policy: RemapPolicy,
+    ) -> Result<BTreeMap<NodeId, NodeId>, MyosotisError> {
+        let source = other.head_state();
+        let index = RefIndex::build(source);
+        let mut seen = BTreeSet::new();
+        let mut order = Vec::new();
+        let mut queue = VecDeque::new();
+        for root in roots {
+            match source.get(root) {
+                None => return Err(MyosotisError::NodeNotFound(*root)),
+                Some(node) if node.deleted => return Err(MyosotisError::NodeDeleted(*root)),
+                Some(_) => {}
+            }
+            if seen.insert(*root) {
+                queue.push_back(*root);
+            }
+            while let Some(id) = queue.pop_front() {
+                order.push(id);
+                for next in index.refs_from(id) {
+                    if seen.insert(*next) {
+                        queue.push_back(*next);
+                    }
+                }
+            }
+        }
+
+        for id in &order {
+            if let Some(alias) = &source[id].alias
+                && let Some(taken) = self.resolve(alias)
+            {
+                return Err(MyosotisError::AliasTaken(alias.clone(), taken));
+            }
+        }
+        let placeholder: BTreeMap<NodeId, NodeId> = order.iter().map(|id| (*id, *id)).collect();
+        for id in &order {
+            for value in source[id].fields.values() {
+                remap_value(value, &placeholder, *id)?;
+            }
+        }
+
+        let mut mapping = BTreeMap::new();
+        if policy == RemapPolicy::KeepIfFree {
+            for id in &seen {
+                if self.head_state.contains_key(id)
+                    || (is_sequential_id(*id) && *id < self.next_node_id)
+                {
+                    continue;
+                }
+                if is_sequential_id(*id) {
+                    self.next_node_id = id + 1;
+                }
+                let m = Mutation::CreateNode {
+                    id: *id,
+                    ty: source[id].ty.clone(),
+                };
+                Self::apply_mutation(&mut self.head_state, &m)?;
+                self.stage(m);
+                mapping.insert(*id, *id);
+            }
+        }
+        for id in &order {
+            if !mapping.contains_key(id) {
+                let new_id = self.create(&source[id].ty);
+                mapping.insert(*id, new_id);
+            }
+        }
+
+        for id in &order {
+            let node = &source[id];
+            let new_id = mapping[id];
+            let mut keys: Vec<&String> = node.fields.keys().collect();
+            keys.sort();
+            for key in keys {
+                self.set(new_id, key, remap_value(&node.fields[key], &mapping, *id)?)?;
+            }
+            if node.alias.is_some() {
+                self.set_alias(new_id, node.alias.as_deref())?;
+            }
+            if node.namespace.is_some() {
+                self.set_namespace(new_id, node.namespace.as_deref())?;
+            }
+        }
+        tracing::debug!(nodes = order.len(), "imported subgraph");
+        Ok(mapping)
+    }
+}
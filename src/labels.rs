@@ -0,0 +1,84 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+
+impl Memory {
+    /// Stages adding `label` to node `id`. Labels sit beside the fields,
+    /// so categorizing a node never touches a field such as `tags`.
+    pub fn add_label(&mut self, id: NodeId, label: &str) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::AddLabel {
+            id,
+            label: label.to_string(),
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.label_index
+            .entry(label.to_string())
+            .or_default()
+            .insert(id);
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Stages removing `label` from node `id`.
+    pub fn remove_label(&mut self, id: NodeId, label: &str) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::RemoveLabel {
+            id,
+            label: label.to_string(),
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        if let Some(ids) = self.label_index.get_mut(label) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.label_index.remove(label);
+            }
+        }
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Whether node `id` carries `label` in head state.
+    pub fn has_label(&self, id: NodeId, label: &str) -> bool {
+        self.head_state
+            .get(&id)
+            .is_some_and(|node| node.labels.contains(label))
+    }
+
+    /// Live nodes carrying `label`, by id, looked up in the label index.
+    pub fn nodes_with_label(&self, label: &str) -> Vec<&Node> {
+        let Some(ids) = self.label_index.get(label) else {
+            return Vec::new();
+        };
+        ids.iter()
+            .filter_map(|id| self.head_state.get(id))
+            .filter(|node| !node.deleted && node.labels.contains(label))
+            .collect()
+    }
+
+    /// Labels carried by live nodes, sorted.
+    pub fn labels(&self) -> Vec<&str> {
+        let mut labels: Vec<&str> = self
+            .label_index
+            .keys()
+            .filter(|label| !self.nodes_with_label(label).is_empty())
+            .map(String::as_str)
+            .collect();
+        labels.sort_unstable();
+        labels
+    }
+
+    /// Rebuilds the label index after `head_state` was replaced wholesale.
+    pub(crate) fn reindex_labels(&mut self) {
+        self.label_index.clear();
+        for node in self.head_state.values() {
+            for label in &node.labels {
+                self.label_index
+                    .entry(label.clone())
+                    .or_default()
+                    .insert(node.id);
+            }
+        }
+    }
+}
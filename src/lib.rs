@@ -1,9 +1,62 @@
+pub mod alias;
+pub mod analytics;
+pub mod anchor;
+pub mod archive;
+pub mod backend;
+pub mod backup;
+pub mod bisect;
+pub mod blob;
+pub mod canonical;
+pub mod classify;
 pub mod commit;
+pub mod derived;
+pub mod diff;
+pub mod ephemeral;
 pub mod error;
+pub mod expiry;
+pub mod export;
+pub mod float;
+pub mod graph;
+pub mod hook;
+pub mod import;
+pub mod labels;
+pub mod lock;
 pub mod maintenance;
 pub mod memory;
+pub mod metrics;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod mount;
+pub mod namespace;
 pub mod node;
+pub mod normalize;
+pub mod observer;
+pub mod paged;
+pub mod prelude;
+pub mod provenance;
+#[cfg(feature = "object-store")]
+pub mod remote;
+pub mod render;
+pub mod retention;
+pub mod script;
+pub mod seal;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod session;
+pub mod shared;
+pub mod shell;
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
 pub mod storage;
+pub mod sync;
+pub mod typed;
+pub mod unique;
+pub mod vector;
+pub mod verify;
+pub mod wal;
+pub mod workspace;
 
 pub use error::MyosotisError;
 pub use memory::Memory;
@@ -0,0 +1,92 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::NodeId;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Advisory lock on a node. Locks live outside the hash chain and are only
+/// enforced for writers that identify themselves with `Memory::act_as`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeLock {
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub owner: String,
+    /// Expiry as seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl NodeLock {
+    pub fn is_active(&self) -> bool {
+        self.expires_at > now_secs()
+    }
+}
+
+impl Memory {
+    /// Sets the owner name used for lock enforcement. With no owner set,
+    /// writes ignore locks (single-writer use).
+    pub fn act_as(&mut self, owner: Option<&str>) {
+        self.acting_owner = owner.map(str::to_string);
+    }
+
+    /// Takes or refreshes the lock on `id` for `owner` for `ttl`.
+    pub fn lock_node(
+        &mut self,
+        id: NodeId,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<&NodeLock, MyosotisError> {
+        match self.head_state.get(&id) {
+            None => return Err(MyosotisError::NodeNotFound(id)),
+            Some(node) if node.deleted => return Err(MyosotisError::NodeDeleted(id)),
+            Some(_) => {}
+        }
+        if let Some(held) = self.active_lock(id)
+            && held.owner != owner
+        {
+            return Err(MyosotisError::NodeLocked(id, held.owner.clone()));
+        }
+
+        self.locks.retain(|l| l.node_id != id && l.is_active());
+        self.locks.push(NodeLock {
+            node_id: id,
+            owner: owner.to_string(),
+            expires_at: now_secs().saturating_add(ttl.as_secs()),
+        });
+        Ok(&self.locks[self.locks.len() - 1])
+    }
+
+    /// Releases `owner`'s lock on `id`. Releasing an expired or missing lock
+    /// is not an error.
+    pub fn unlock_node(&mut self, id: NodeId, owner: &str) -> Result<(), MyosotisError> {
+        if let Some(held) = self.active_lock(id)
+            && held.owner != owner
+        {
+            return Err(MyosotisError::NodeLocked(id, held.owner.clone()));
+        }
+        self.locks.retain(|l| l.node_id != id);
+        Ok(())
+    }
+
+    pub fn active_lock(&self, id: NodeId) -> Option<&NodeLock> {
+        self.locks.iter().find(|l| l.node_id == id && l.is_active())
+    }
+
+    pub(crate) fn check_lock(&self, id: NodeId) -> Result<(), MyosotisError> {
+        let Some(owner) = &self.acting_owner else {
+            return Ok(());
+        };
+        match self.active_lock(id) {
+            Some(held) if &held.owner != owner => {
+                Err(MyosotisError::NodeLocked(id, held.owner.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
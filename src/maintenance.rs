@@ -1,68 +1,938 @@
+use crate::commit::{Commit, Mutation};
 use crate::error::MyosotisError;
-use crate::memory::Memory;
-use anyhow::{Context, Result};
+use crate::expiry::FieldExpiry;
+use crate::memory::{Checkpoint, Memory};
+use crate::node::{Node, NodeId, PathSeg, Value, is_sequential_id};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 
-pub fn compact(path: &str, at: Option<u64>) -> Result<()> {
-    let mut mem = crate::storage::load(path)?;
-    let before_state_hash = Memory::compute_state_hash(&mem.head_state);
-
-    let target_commit_id = if let Some(target) = at {
+/// Commit folded into genesis by default: `at` if given, else the latest
+/// checkpoint, else the last commit.
+fn compaction_target(mem: &Memory, at: Option<u64>) -> Result<u64, MyosotisError> {
+    if let Some(target) = at {
         if mem.commits.iter().any(|c| c.id == target) {
-            target
+            Ok(target)
         } else {
-            return Err(anyhow::anyhow!(MyosotisError::InvalidCompactionTarget));
+            Err(MyosotisError::InvalidCompactionTarget)
         }
     } else if let Some(cp) = mem.checkpoints.iter().max_by_key(|c| c.commit_id) {
-        cp.commit_id
+        Ok(cp.commit_id)
     } else if let Some(last) = mem.commits.last() {
-        last.id
+        Ok(last.id)
     } else {
-        return Err(anyhow::anyhow!(MyosotisError::InvalidCompactionTarget));
-    };
+        Err(MyosotisError::InvalidCompactionTarget)
+    }
+}
+
+/// Hooks for `Memory::compact_with` and `compact_with`. Every method has a
+/// default, so implementors override only what they need.
+pub trait CompactionStrategy {
+    /// Commit to fold into genesis, with everything before it.
+    fn select_target(&self, mem: &Memory) -> Result<u64, MyosotisError> {
+        compaction_target(mem, None)
+    }
+
+    /// Receives the commits about to be folded before anything changes,
+    /// e.g. to copy them to cold storage. An error aborts compaction.
+    fn archive(&mut self, _folded: &[Commit]) -> Result<(), MyosotisError> {
+        Ok(())
+    }
+
+    /// Checks the compacted memory against the original. The default
+    /// requires identical head state hashes.
+    fn verify(&self, before: &Memory, after: &Memory) -> Result<(), MyosotisError> {
+        if before.hash_state(&before.head_state) == after.hash_state(&after.head_state) {
+            Ok(())
+        } else {
+            Err(MyosotisError::CompactionIntegrityMismatch)
+        }
+    }
+}
+
+/// Strategy behind `compact`: folds up to `at`, or the default target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultCompaction {
+    pub at: Option<u64>,
+}
+
+impl CompactionStrategy for DefaultCompaction {
+    fn select_target(&self, mem: &Memory) -> Result<u64, MyosotisError> {
+        compaction_target(mem, self.at)
+    }
+}
+
+impl Memory {
+    /// Relinks `commits` onto `genesis_state_hash`, re-records the state
+    /// hashes of commits that have one, recomputes every commit hash and
+    /// points `checkpoints` at the new hashes.
+    fn rehash_chain(
+        &self,
+        genesis_state: Option<&HashMap<NodeId, Node>>,
+        genesis_state_hash: Option<[u8; 32]>,
+        commits: &mut [Commit],
+        checkpoints: &mut [Checkpoint],
+    ) -> Result<(), MyosotisError> {
+        let mut state = commits
+            .iter()
+            .any(|c| c.state_hash.is_some())
+            .then(|| genesis_state.cloned().unwrap_or_default());
+        let mut prev_hash = genesis_state_hash;
+        let mut prev_id: Option<u64> = None;
+        for commit in commits.iter_mut() {
+            if let Some(state) = state.as_mut() {
+                for mutation in &commit.mutations {
+                    Self::apply_mutation(state, mutation)?;
+                }
+                if commit.state_hash.is_some() {
+                    commit.state_hash = Some(self.hash_state(state));
+                }
+            }
+            commit.parent = prev_id;
+            commit.parent_hash = prev_hash;
+            commit.hash = commit.compute_hash_with(self.canonicalization);
+            prev_hash = Some(commit.hash);
+            prev_id = Some(commit.id);
+        }
+        for checkpoint in checkpoints.iter_mut() {
+            let commit = commits
+                .iter()
+                .find(|c| c.id == checkpoint.commit_id)
+                .ok_or(MyosotisError::CheckpointCommitMismatch)?;
+            checkpoint.commit_hash = commit.hash;
+        }
+        Ok(())
+    }
+
+    /// Folds `target_commit_id` and every commit before it into a genesis
+    /// snapshot and rehashes the remaining chain and its checkpoints. The
+    /// replayed committed state is checked against the original before
+    /// anything is replaced; pending mutations are kept.
+    pub fn compact_in_memory(&mut self, target_commit_id: u64) -> Result<(), MyosotisError> {
+        if self.write_once {
+            return Err(MyosotisError::WriteOnceViolation("compaction"));
+        }
+        if self.commit_index(target_commit_id).is_none() {
+            return Err(MyosotisError::InvalidCompactionTarget);
+        }
+        let before = self.committed_state()?;
+
+        let genesis_state = self.state_at_commit(target_commit_id)?;
+        let state_hash = self.hash_state(&genesis_state);
+        let seal = self.renewed_seal(state_hash, true)?;
+        let genesis_state_hash = Some(state_hash);
+
+        let mut commits: Vec<_> = self
+            .commits
+            .iter()
+            .filter(|c| c.id > target_commit_id)
+            .cloned()
+            .collect();
+        let mut checkpoints: Vec<_> = self
+            .checkpoints
+            .iter()
+            .filter(|cp| cp.commit_id > target_commit_id)
+            .cloned()
+            .collect();
+        self.rehash_chain(
+            Some(&genesis_state),
+            genesis_state_hash,
+            &mut commits,
+            &mut checkpoints,
+        )?;
 
-    let genesis_state = mem
-        .state_at_commit(target_commit_id)
-        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
-    let genesis_state_hash = Memory::compute_state_hash(&genesis_state);
+        if Memory::replay_from(genesis_state.clone(), &commits)? != before {
+            return Err(MyosotisError::CompactionIntegrityMismatch);
+        }
 
-    mem.genesis_state = Some(genesis_state);
-    mem.genesis_state_hash = Some(genesis_state_hash);
+        self.genesis_state = Some(genesis_state);
+        self.genesis_state_hash = genesis_state_hash;
+        self.seal = seal;
+        self.commits = commits;
+        self.checkpoints = checkpoints;
+        self.commit_times.retain(|id, _| *id > target_commit_id);
+        tracing::debug!(target_commit_id, "compacted in memory");
+        Ok(())
+    }
 
-    mem.commits.retain(|c| c.id > target_commit_id);
+    /// Compacts using `strategy` for target selection, archiving and
+    /// verification. Returns the commit folded into genesis.
+    pub fn compact_with(
+        &mut self,
+        strategy: &mut dyn CompactionStrategy,
+    ) -> Result<u64, MyosotisError> {
+        let target_commit_id = strategy.select_target(self)?;
+        let folded = self.commits.partition_point(|c| c.id <= target_commit_id);
+        strategy.archive(&self.commits[..folded])?;
 
-    let mut prev_hash = mem.genesis_state_hash;
-    let mut prev_id: Option<u64> = None;
-    for commit in &mut mem.commits {
-        commit.parent = prev_id;
-        commit.parent_hash = prev_hash;
-        commit.hash =
-            Memory::compute_commit_hash(commit.parent_hash, &commit.message, &commit.mutations);
-        prev_hash = Some(commit.hash);
-        prev_id = Some(commit.id);
+        let before = self.clone();
+        self.compact_in_memory(target_commit_id)?;
+        if let Err(e) = strategy.verify(&before, self) {
+            self.genesis_state = before.genesis_state;
+            self.genesis_state_hash = before.genesis_state_hash;
+            self.seal = before.seal;
+            self.commits = before.commits;
+            self.checkpoints = before.checkpoints;
+            self.commit_times = before.commit_times;
+            return Err(e);
+        }
+        Ok(target_commit_id)
     }
 
-    mem.checkpoints.retain(|cp| cp.commit_id > target_commit_id);
-    for checkpoint in &mut mem.checkpoints {
-        let commit = mem
+    /// Takes the history of `compacted`, a copy of this memory made when
+    /// its last commit had hash `base` and compacted since, e.g. on another
+    /// thread. Commits made in the meantime are rebased onto the compacted
+    /// chain; pending mutations are kept. Returns how many commits were
+    /// rebased.
+    pub fn adopt_compacted(
+        &mut self,
+        compacted: Memory,
+        base: [u8; 32],
+    ) -> Result<usize, MyosotisError> {
+        let since = self
             .commits
             .iter()
-            .find(|c| c.id == checkpoint.commit_id)
-            .ok_or_else(|| anyhow::anyhow!(MyosotisError::CheckpointCommitMismatch))?;
-        checkpoint.commit_hash = commit.hash;
+            .position(|c| c.hash == base)
+            .ok_or(MyosotisError::CompactionIntegrityMismatch)?
+            + 1;
+        let base_id = self.commits[since - 1].id;
+        let mut commits = compacted.commits;
+        let mut checkpoints = compacted.checkpoints;
+        let mut commit_times = compacted.commit_times;
+        let rebased = self.commits.len() - since;
+        if rebased > 0 {
+            commits.extend_from_slice(&self.commits[since..]);
+            checkpoints.extend(
+                self.checkpoints
+                    .iter()
+                    .filter(|cp| cp.commit_id > base_id)
+                    .cloned(),
+            );
+            commit_times.extend(
+                self.commit_times
+                    .iter()
+                    .filter(|(id, _)| **id > base_id)
+                    .map(|(id, time)| (*id, *time)),
+            );
+            self.rehash_chain(
+                compacted.genesis_state.as_ref(),
+                compacted.genesis_state_hash,
+                &mut commits,
+                &mut checkpoints,
+            )?;
+            let genesis = compacted.genesis_state.clone().unwrap_or_default();
+            if Memory::replay_from(genesis, &commits)? != self.committed_state()? {
+                return Err(MyosotisError::CompactionIntegrityMismatch);
+            }
+        }
+
+        self.genesis_state = compacted.genesis_state;
+        self.genesis_state_hash = compacted.genesis_state_hash;
+        self.seal = compacted.seal;
+        self.commits = commits;
+        self.checkpoints = checkpoints;
+        self.commit_times = commit_times;
+        Ok(rebased)
     }
+}
+
+/// What `redact` replaced. Keep `salt` only if placeholders may later need
+/// to be matched against candidate values; anyone holding it can test
+/// guesses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Redaction {
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub key: String,
+    /// `SetField` mutations rewritten.
+    pub mutations: usize,
+    /// Genesis and checkpoint snapshots rewritten.
+    pub snapshots: usize,
+    /// Hex-encoded random salt mixed into the placeholders.
+    pub salt: String,
+    /// Empty commit recording the redaction.
+    pub marker_commit: u64,
+}
+
+fn redacted_placeholder(salt: &[u8], value: &Value) -> Value {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(serde_json::to_vec(value).unwrap_or_default());
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Value::Str(format!("redacted:{}", digest))
+}
+
+fn redact_state(state: &mut HashMap<NodeId, Node>, id: NodeId, key: &str, salt: &[u8]) -> bool {
+    match state.get_mut(&id).and_then(|n| n.fields.get_mut(key)) {
+        Some(value) => {
+            *value = redacted_placeholder(salt, value);
+            true
+        }
+        None => false,
+    }
+}
+
+impl Memory {
+    /// Replaces every value field `key` of node `id` ever held, in commits,
+    /// genesis and checkpoints, with a salted SHA-256 placeholder, rehashes
+    /// the chain, and commits an empty marker recording the redaction.
+    /// Path and list writes into the field are replaced by placeholder sets
+    /// of the whole field. Values later set under a renamed key are not touched.
+    /// Refused for write-once files and while mutations are pending.
+    pub fn redact_in_memory(&mut self, id: NodeId, key: &str) -> Result<Redaction, MyosotisError> {
+        if self.write_once {
+            return Err(MyosotisError::WriteOnceViolation("redaction"));
+        }
+        if !self.pending_mutations.is_empty() {
+            return Err(MyosotisError::InvalidInput(
+                "commit or discard pending mutations before redacting".to_string(),
+            ));
+        }
+        let salt = *uuid::Uuid::new_v4().as_bytes();
+
+        let field = PathSeg::Key(key.to_string());
+        let mut mutations = 0;
+        let mut commits = self.commits.clone();
+        for mutation in commits.iter_mut().flat_map(|c| c.mutations.iter_mut()) {
+            if let Mutation::SetField {
+                id: m_id,
+                key: m_key,
+                value,
+            } = mutation
+                && *m_id == id
+                && m_key == key
+            {
+                *value = redacted_placeholder(&salt, value);
+                mutations += 1;
+            }
+            // Values under a redacted field are placeholder strings, so path
+            // writes into it can no longer be replayed and are turned into
+            // whole-field placeholder sets.
+            // Deleting the whole field still works on a placeholder.
+            let whole_field =
+                matches!(mutation, Mutation::DeletePath { path, .. } if path.len() == 1);
+            let path_write = mutation.node_id() == id
+                && !whole_field
+                && mutation.path().and_then(|p| p.first()) == Some(&field);
+            if path_write {
+                let original = Value::Str(serde_json::to_string(mutation).unwrap_or_default());
+                *mutation = Mutation::SetField {
+                    id,
+                    key: key.to_string(),
+                    value: redacted_placeholder(&salt, &original),
+                };
+                mutations += 1;
+            }
+        }
+
+        let mut snapshots = 0;
+        let mut genesis_state = self.genesis_state.clone();
+        let mut genesis_state_hash = self.genesis_state_hash;
+        let mut seal = self.seal.clone();
+        if let Some(state) = genesis_state.as_mut()
+            && redact_state(state, id, key, &salt)
+        {
+            let state_hash = self.hash_state(state);
+            seal = self.renewed_seal(state_hash, false)?;
+            genesis_state_hash = Some(state_hash);
+            snapshots += 1;
+        }
+        let mut checkpoints = self.checkpoints.clone();
+        for checkpoint in &mut checkpoints {
+            if redact_state(&mut checkpoint.state, id, key, &salt) {
+                checkpoint.state_hash = self.hash_state(&checkpoint.state);
+                snapshots += 1;
+            }
+        }
+        if mutations == 0 && snapshots == 0 {
+            return Err(MyosotisError::FieldNotFound(key.to_string()));
+        }
+
+        self.rehash_chain(
+            genesis_state.as_ref(),
+            genesis_state_hash,
+            &mut commits,
+            &mut checkpoints,
+        )?;
+        let head_state = Memory::replay_from(genesis_state.clone().unwrap_or_default(), &commits)?;
+
+        self.genesis_state = genesis_state;
+        self.genesis_state_hash = genesis_state_hash;
+        self.seal = seal;
+        self.commits = commits;
+        self.checkpoints = checkpoints;
+        self.head_state = head_state;
+        self.reindex_unique()?;
+        self.commit_allow_empty(Some(format!("redacted: node {} field '{}'", id, key)))?;
+        let marker_commit = self.commits.last().map(|c| c.id).unwrap_or(0);
+
+        tracing::info!(id, key, mutations, snapshots, "redacted field history");
+        Ok(Redaction {
+            node_id: id,
+            key: key.to_string(),
+            mutations,
+            snapshots,
+            salt: salt.iter().map(|b| format!("{:02x}", b)).collect(),
+            marker_commit,
+        })
+    }
+}
+
+/// File-level `Memory::redact_in_memory`. The rewritten file is saved to a
+/// temporary path and fully validated before it replaces `path`; blobs it
+/// no longer refers to are then removed.
+#[tracing::instrument]
+pub fn redact(path: &str, id: NodeId, key: &str) -> Result<Redaction, MyosotisError> {
+    let mut mem = crate::storage::load(path)?;
+    let redaction = mem.redact_in_memory(id, key)?;
 
     let tmp_path = format!("{}.tmp", path);
-    crate::storage::save(&tmp_path, &mem)?;
+    crate::storage::save_replacement(&tmp_path, path, &mem)?;
+    if let Err(e) = crate::storage::load_replacement(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::rename(&tmp_path, path).map_err(|e| MyosotisError::ReplaceFailed(path.to_string(), e))?;
+    // A redacted value must not survive in the blob store.
+    crate::blob::prune_blobs(path)?;
+    Ok(redaction)
+}
 
-    let reloaded = crate::storage::load(&tmp_path)?;
-    let after_state_hash = Memory::compute_state_hash(&reloaded.head_state);
-    if after_state_hash != before_state_hash {
+#[tracing::instrument]
+pub fn compact(path: &str, at: Option<u64>) -> Result<(), MyosotisError> {
+    compact_with(path, &mut DefaultCompaction { at }).map(|_| ())
+}
+
+/// File-level `Memory::compact_with`. The result is written to a temporary
+/// file, reloaded and verified again before it replaces `path`.
+#[tracing::instrument(skip(strategy))]
+pub fn compact_with(
+    path: &str,
+    strategy: &mut dyn CompactionStrategy,
+) -> Result<u64, MyosotisError> {
+    let mut mem = crate::storage::load(path)?;
+    let before = mem.clone();
+    let target_commit_id = mem.compact_with(strategy)?;
+
+    let tmp_path = format!("{}.tmp", path);
+    crate::storage::save_replacement(&tmp_path, path, &mem)?;
+
+    let reloaded = crate::storage::load_replacement(&tmp_path, path)?;
+    if let Err(e) = strategy.verify(&before, &reloaded) {
+        tracing::error!("compacted file failed verification; keeping original file");
         let _ = fs::remove_file(&tmp_path);
-        return Err(anyhow::anyhow!(MyosotisError::CompactionIntegrityMismatch));
+        return Err(e);
+    }
+
+    fs::rename(&tmp_path, path).map_err(|e| MyosotisError::ReplaceFailed(path.to_string(), e))?;
+    tracing::info!(
+        target_commit_id,
+        remaining_commits = reloaded.commits.len(),
+        "compacted history"
+    );
+    Ok(target_commit_id)
+}
+
+impl Memory {
+    /// Whether `checkpoint` belongs to this chain: its commit is present
+    /// with the hash it records, and its state matches its state hash.
+    fn checkpoint_matches(&self, checkpoint: &Checkpoint) -> bool {
+        self.commit_index(checkpoint.commit_id)
+            .is_some_and(|i| self.commits[i].hash == checkpoint.commit_hash)
+            && self.hash_state(&checkpoint.state) == checkpoint.state_hash
     }
 
-    fs::rename(&tmp_path, path)
-        .with_context(|| format!("Failed to atomically replace file: {}", path))?;
-    Ok(())
+    /// Removes checkpoints that do not match the chain, then all but the
+    /// newest `keep_last_n`. Returns how many were removed.
+    pub fn prune_checkpoints(&mut self, keep_last_n: usize) -> Result<usize, MyosotisError> {
+        let before = self.checkpoints.len();
+        let checkpoints = std::mem::take(&mut self.checkpoints);
+        let mut kept: Vec<_> = checkpoints
+            .into_iter()
+            .filter(|cp| self.checkpoint_matches(cp))
+            .collect();
+        let orphaned = before - kept.len();
+        kept.drain(..kept.len().saturating_sub(keep_last_n));
+        self.checkpoints = kept;
+        self.validate_snapshot_integrity()?;
+        tracing::debug!(
+            orphaned,
+            kept = self.checkpoints.len(),
+            "pruned checkpoints"
+        );
+        Ok(before - self.checkpoints.len())
+    }
+
+    /// Replaces every checkpoint with one after each `interval`th commit,
+    /// counted the way `commit` counts them, by replaying the chain once.
+    /// Returns how many checkpoints there are now.
+    pub fn rebuild_checkpoints(&mut self, interval: usize) -> Result<usize, MyosotisError> {
+        if interval == 0 {
+            return Err(MyosotisError::InvalidInput(
+                "checkpoint interval must be at least 1".to_string(),
+            ));
+        }
+        let mut state = self.genesis_state.clone().unwrap_or_default();
+        let mut checkpoints = Vec::new();
+        for (i, commit) in self.commits.iter().enumerate() {
+            for mutation in &commit.mutations {
+                Memory::apply_mutation(&mut state, mutation)?;
+            }
+            if (i + 1).is_multiple_of(interval) {
+                checkpoints.push(Checkpoint {
+                    commit_id: commit.id,
+                    commit_hash: commit.hash,
+                    state_hash: self.hash_state(&state),
+                    state: state.clone(),
+                });
+            }
+        }
+        self.checkpoints = checkpoints;
+        self.validate_snapshot_integrity()?;
+        tracing::debug!(
+            interval,
+            checkpoints = self.checkpoints.len(),
+            "rebuilt checkpoints"
+        );
+        Ok(self.checkpoints.len())
+    }
+}
+
+/// File-level `Memory::prune_checkpoints`. The file is read without
+/// validation, so checkpoints left behind by a history rewrite can be
+/// dropped; the result is saved to a temporary path and fully validated
+/// before it replaces `path`.
+#[tracing::instrument]
+pub fn prune_checkpoints(path: &str, keep_last_n: usize) -> Result<usize, MyosotisError> {
+    let mut mem = crate::storage::load_unvalidated(path)?;
+    let removed = mem.prune_checkpoints(keep_last_n)?;
+
+    let tmp_path = format!("{}.tmp", path);
+    crate::storage::save_replacement(&tmp_path, path, &mem)?;
+    if let Err(e) = crate::storage::load_replacement(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::rename(&tmp_path, path).map_err(|e| MyosotisError::ReplaceFailed(path.to_string(), e))?;
+    Ok(removed)
+}
+
+/// File-level `Memory::expire`: commits the deletion of every field of
+/// `path` whose expiry is at or before `now` and saves the file. Returns
+/// the fields deleted.
+#[tracing::instrument]
+pub fn expire(path: &str, now: u64) -> Result<Vec<FieldExpiry>, MyosotisError> {
+    let mut mem = crate::storage::load(path)?;
+    let before = mem.expiries.len();
+    let expired = mem.expire(now)?;
+    if mem.expiries.len() != before {
+        crate::storage::save(path, &mem)?;
+    }
+    Ok(expired)
+}
+
+/// Applies the retention policy stored in `path`, compacting through
+/// `compact_with`. Returns the commit folded into genesis, or `None` when
+/// the file has no policy or nothing is due.
+#[tracing::instrument]
+pub fn apply_retention(path: &str) -> Result<Option<u64>, MyosotisError> {
+    let mem = crate::storage::load(path)?;
+    match mem.retention_target(crate::lock::now_secs()) {
+        Some(target) => {
+            compact_with(path, &mut DefaultCompaction { at: Some(target) })?;
+            Ok(Some(target))
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckIssue {
+    pub severity: Severity,
+    /// Short identifier of the check that raised the issue.
+    pub check: &'static str,
+    pub message: String,
+    /// Commit the issue was found at, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<u64>,
+    /// Node the issue concerns, if any.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::node::wide_id::option::serialize"
+    )]
+    pub node: Option<NodeId>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckReport {
+    fn push(&mut self, severity: Severity, check: &'static str, message: String) {
+        self.push_at(severity, check, None, None, message);
+    }
+
+    fn push_at(
+        &mut self,
+        severity: Severity,
+        check: &'static str,
+        commit: Option<u64>,
+        node: Option<NodeId>,
+        message: String,
+    ) {
+        self.issues.push(FsckIssue {
+            severity,
+            check,
+            message,
+            commit,
+            node,
+        });
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+}
+
+fn collect_refs(value: &Value, out: &mut Vec<NodeId>) {
+    match value {
+        Value::Ref(id) => out.push(*id),
+        Value::List(items) => items.iter().for_each(|v| collect_refs(v, out)),
+        Value::Map(map) => map.values().for_each(|v| collect_refs(v, out)),
+        _ => {}
+    }
+}
+
+/// Checks `fsck` always runs.
+pub const FSCK_CHECKS: &[&str] = &[
+    "read",
+    "validate",
+    "duplicate-id",
+    "orphan-checkpoint",
+    "next-node-id",
+    "dangling-ref",
+    "replay",
+];
+/// Checks `fsck` adds when `deep` is set.
+pub const FSCK_DEEP_CHECKS: &[&str] = &["checkpoint-state"];
+
+/// Checks a memory file and reports every problem found instead of
+/// stopping at the first. `deep` additionally recomputes all commit and
+/// checkpoint hashes and re-derives each checkpoint state by replay.
+#[tracing::instrument]
+pub fn fsck(path: &str, deep: bool) -> Result<FsckReport, MyosotisError> {
+    let mut report = FsckReport::default();
+    if let Err(e @ MyosotisError::FileChecksumMismatch) = crate::storage::verify_file_checksum(path)
+    {
+        report.push(Severity::Error, "checksum", e.to_string());
+    }
+    let mem = match crate::storage::load_unvalidated(path) {
+        Ok(mem) => mem,
+        Err(e) => {
+            report.push(Severity::Error, "read", format!("{:#}", e));
+            return Ok(report);
+        }
+    };
+
+    if let Err(e) = mem.validate_with_mode(deep) {
+        report.push(Severity::Error, "validate", e.to_string());
+    }
+
+    // Duplicate node ids: a node created twice, or created over genesis.
+    let mut created: HashSet<NodeId> = mem
+        .genesis_state
+        .as_ref()
+        .map(|state| state.keys().copied().collect())
+        .unwrap_or_default();
+    for (key, node) in mem.genesis_state.iter().flatten() {
+        if *key != node.id {
+            report.push_at(
+                Severity::Error,
+                "duplicate-id",
+                None,
+                Some(*key),
+                format!("genesis entry {} holds node {}", key, node.id),
+            );
+        }
+    }
+    for commit in &mem.commits {
+        for mutation in &commit.mutations {
+            if let Mutation::CreateNode { id, .. } = mutation
+                && !created.insert(*id)
+            {
+                report.push_at(
+                    Severity::Error,
+                    "duplicate-id",
+                    Some(commit.id),
+                    Some(*id),
+                    format!("node {} created again in commit {}", id, commit.id),
+                );
+            }
+        }
+    }
+
+    // Orphaned checkpoints: no matching commit in the chain.
+    for checkpoint in &mem.checkpoints {
+        match mem.commits.iter().find(|c| c.id == checkpoint.commit_id) {
+            None => report.push_at(
+                Severity::Error,
+                "orphan-checkpoint",
+                Some(checkpoint.commit_id),
+                None,
+                format!("checkpoint at missing commit {}", checkpoint.commit_id),
+            ),
+            Some(commit) if commit.hash != checkpoint.commit_hash => report.push_at(
+                Severity::Error,
+                "orphan-checkpoint",
+                Some(checkpoint.commit_id),
+                None,
+                format!(
+                    "checkpoint at commit {} does not match its commit hash",
+                    checkpoint.commit_id
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    // next_node_id must exceed every sequential id; unused ids are only
+    // informational. UUID ids are outside the counter's range.
+    let max_id = created
+        .iter()
+        .copied()
+        .filter(|id| is_sequential_id(*id))
+        .max()
+        .unwrap_or(0);
+    if mem.next_node_id <= max_id {
+        report.push(
+            Severity::Error,
+            "next-node-id",
+            format!(
+                "next_node_id {} <= max node id {}",
+                mem.next_node_id, max_id
+            ),
+        );
+    } else {
+        let unused = (1..mem.next_node_id)
+            .filter(|id| !created.contains(id))
+            .count();
+        if unused > 0 {
+            report.push(
+                Severity::Info,
+                "next-node-id",
+                format!("{} node ids below {} are unused", unused, mem.next_node_id),
+            );
+        }
+    }
+
+    // Dangling refs in head state: targets that are missing or deleted.
+    match Memory::replay_from(mem.genesis_state.clone().unwrap_or_default(), &mem.commits) {
+        Ok(state) => {
+            let mut ids: Vec<NodeId> = state.keys().copied().collect();
+            ids.sort_unstable();
+            for id in ids {
+                let Some(node) = state.get(&id).filter(|n| !n.deleted) else {
+                    continue;
+                };
+                let mut keys: Vec<&String> = node.fields.keys().collect();
+                keys.sort();
+                for key in keys {
+                    let mut targets = Vec::new();
+                    if let Some(value) = node.fields.get(key) {
+                        collect_refs(value, &mut targets);
+                    }
+                    for target in targets {
+                        let problem = match state.get(&target) {
+                            None => "missing",
+                            Some(t) if t.deleted => "deleted",
+                            Some(_) => continue,
+                        };
+                        report.push_at(
+                            Severity::Warning,
+                            "dangling-ref",
+                            None,
+                            Some(id),
+                            format!(
+                                "node {} field '{}' refers to {} node {}",
+                                id, key, problem, target
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        Err(e) => report.push(Severity::Error, "replay", e.to_string()),
+    }
+
+    if deep {
+        for checkpoint in &mem.checkpoints {
+            let Some(index) = mem
+                .commits
+                .iter()
+                .position(|c| c.id == checkpoint.commit_id)
+            else {
+                continue;
+            };
+            let replayed = Memory::replay_from(
+                mem.genesis_state.clone().unwrap_or_default(),
+                &mem.commits[..=index],
+            );
+            if !replayed.is_ok_and(|state| state == checkpoint.state) {
+                report.push_at(
+                    Severity::Error,
+                    "checkpoint-state",
+                    Some(checkpoint.commit_id),
+                    None,
+                    format!(
+                        "checkpoint at commit {} differs from replayed state",
+                        checkpoint.commit_id
+                    ),
+                );
+            }
+        }
+    }
+
+    tracing::info!(issues = report.issues.len(), "fsck finished");
+    Ok(report)
+}
+
+/// Files smaller than this gain little from compaction.
+const ADVISE_MIN_SAVINGS_BYTES: usize = 4096;
+/// Compaction is recommended once it would remove this share of the file.
+const ADVISE_MIN_SAVINGS_RATIO: f64 = 0.25;
+const ADVISE_HOTSPOTS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChurnHotspot {
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub mutations: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompactionRecommendation {
+    /// Pass as `myo compact --at`.
+    pub target_commit_id: u64,
+    pub estimated_bytes_after: usize,
+    pub estimated_savings_bytes: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompactionAdvice {
+    pub file_bytes: usize,
+    pub commits: usize,
+    pub mutations: usize,
+    pub nodes: usize,
+    pub tombstones: usize,
+    /// Tombstones over all nodes. Compaction keeps tombstones, so this
+    /// share of the state cannot be reclaimed.
+    pub tombstone_ratio: f64,
+    /// Mutations in history per node in head state.
+    pub mutations_per_node: f64,
+    pub checkpoints: usize,
+    /// Checkpoints whose state equals the previous checkpoint (or genesis).
+    pub redundant_checkpoints: usize,
+    /// Nodes touched by the most mutations, most first.
+    pub hotspots: Vec<ChurnHotspot>,
+    pub recommendation: Option<CompactionRecommendation>,
+}
+
+/// Analyzes a memory file and recommends a compaction target when folding
+/// history into genesis would shrink the file noticeably. The estimate
+/// comes from compacting a copy in memory; the file is not modified.
+/// Write-once files never get a recommendation.
+#[tracing::instrument]
+pub fn advise(path: &str) -> Result<CompactionAdvice, MyosotisError> {
+    let mem = crate::storage::load(path)?;
+    let file_bytes = crate::storage::encoded_len(&mem)?;
+
+    let mutations = mem.commits.iter().map(|c| c.mutations.len()).sum();
+    let nodes = mem.head_state.len();
+    let tombstones = mem.head_state.values().filter(|n| n.deleted).count();
+
+    let mut redundant_checkpoints = 0;
+    let mut previous = mem.genesis_state.clone().unwrap_or_default();
+    let mut checkpoints: Vec<_> = mem.checkpoints.iter().collect();
+    checkpoints.sort_by_key(|cp| cp.commit_id);
+    for checkpoint in checkpoints {
+        if checkpoint.state == previous {
+            redundant_checkpoints += 1;
+        }
+        previous = checkpoint.state.clone();
+    }
+
+    let mut churn: HashMap<NodeId, usize> = HashMap::new();
+    for mutation in mem.commits.iter().flat_map(|c| &c.mutations) {
+        *churn.entry(mutation.node_id()).or_default() += 1;
+    }
+    let mut hotspots: Vec<ChurnHotspot> = churn
+        .into_iter()
+        .map(|(node_id, mutations)| ChurnHotspot { node_id, mutations })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        b.mutations
+            .cmp(&a.mutations)
+            .then(a.node_id.cmp(&b.node_id))
+    });
+    hotspots.truncate(ADVISE_HOTSPOTS);
+
+    let mut recommendation = None;
+    if let Ok(target_commit_id) = compaction_target(&mem, None)
+        && !mem.write_once
+    {
+        let mut compacted = mem.clone();
+        compacted.compact_in_memory(target_commit_id)?;
+        let estimated_bytes_after = crate::storage::encoded_len(&compacted)?;
+        let estimated_savings_bytes = file_bytes.saturating_sub(estimated_bytes_after);
+        let folded = mem.commits.len() - compacted.commits.len();
+        if estimated_savings_bytes >= ADVISE_MIN_SAVINGS_BYTES
+            && estimated_savings_bytes as f64 >= file_bytes as f64 * ADVISE_MIN_SAVINGS_RATIO
+        {
+            recommendation = Some(CompactionRecommendation {
+                target_commit_id,
+                estimated_bytes_after,
+                estimated_savings_bytes,
+                reason: format!(
+                    "folding {} commits into genesis removes {:.0}% of the file",
+                    folded,
+                    estimated_savings_bytes as f64 * 100.0 / file_bytes as f64
+                ),
+            });
+        }
+    }
+
+    let ratio = |a: usize, b: usize| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+    let advice = CompactionAdvice {
+        file_bytes,
+        commits: mem.commits.len(),
+        mutations,
+        nodes,
+        tombstones,
+        tombstone_ratio: ratio(tombstones, nodes),
+        mutations_per_node: ratio(mutations, nodes),
+        checkpoints: mem.checkpoints.len(),
+        redundant_checkpoints,
+        hotspots,
+        recommendation,
+    };
+    tracing::info!(
+        recommended = advice.recommendation.is_some(),
+        "compaction advice ready"
+    );
+    Ok(advice)
 }
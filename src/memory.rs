@@ -1,9 +1,26 @@
+use crate::anchor::AnchorReceipt;
+use crate::blob::ValueLimits;
+use crate::canonical::{self, Canonicalization};
 use crate::commit::{Commit, Mutation};
+use crate::derived::DerivedFields;
+use crate::ephemeral::Persistence;
 use crate::error::MyosotisError;
-use crate::node::{Node, NodeId, Value};
+use crate::expiry::FieldExpiry;
+use crate::float::FloatPolicy;
+use crate::hook::PrecommitHooks;
+use crate::lock::NodeLock;
+use crate::metrics::MetricsRecorder;
+use crate::node::{IdMode, Node, NodeId, PathSeg, Value, is_sequential_id, parse_path};
+use crate::observer::{CommitOrigin, Observers};
+use crate::retention::RetentionPolicy;
+use crate::seal::{Seal, SealKey};
+use crate::snapshot::SnapshotCache;
+use crate::storage::Durability;
+use crate::unique::{UniqueConstraint, UniqueIndex, UniqueUpdate};
+use crate::wal::WalHandle;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 type Hash = [u8; 32];
 type State = HashMap<NodeId, Node>;
@@ -26,19 +43,180 @@ pub struct Checkpoint {
     pub state: HashMap<NodeId, Node>,
 }
 
+/// A pending mutation that `commit` would reject, from `check_pending`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutationIssue {
+    /// Position in `pending_mutations`.
+    pub index: usize,
+    /// See `Mutation::kind`.
+    pub kind: &'static str,
+    #[serde(with = "crate::node::wide_id")]
+    pub node_id: NodeId,
+    pub message: String,
+}
+
+/// What the next commit would record, from `Memory::pending_summary`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PendingSummary {
+    pub mutations: usize,
+    /// Mutations per kind (see `Mutation::kind`).
+    pub by_kind: BTreeMap<String, usize>,
+    /// Nodes the mutations create, in staging order.
+    #[serde(with = "crate::node::wide_id::list")]
+    pub created: Vec<NodeId>,
+    /// Every node the mutations touch, sorted.
+    #[serde(with = "crate::node::wide_id::list")]
+    pub nodes: Vec<NodeId>,
+}
+
+impl PendingSummary {
+    pub fn of(mutations: &[Mutation]) -> Self {
+        let mut summary = Self {
+            mutations: mutations.len(),
+            ..Self::default()
+        };
+        for mutation in mutations {
+            *summary
+                .by_kind
+                .entry(mutation.kind().to_string())
+                .or_default() += 1;
+            if let Mutation::CreateNode { id, .. } = mutation {
+                summary.created.push(*id);
+            }
+            summary.nodes.push(mutation.node_id());
+        }
+        summary.nodes.sort_unstable();
+        summary.nodes.dedup();
+        summary
+    }
+}
+
+/// What `commit` would produce, as computed by `dry_run_commit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitPreview {
+    pub id: u64,
+    pub parent_hash: Option<Hash>,
+    pub hash: Hash,
+    pub state_hash: Hash,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
     pub genesis_state: Option<HashMap<NodeId, Node>>,
     pub genesis_state_hash: Option<[u8; 32]>,
+    /// Ordered by id; ids are sequential, so lookups binary-search it.
     pub commits: Vec<Commit>,
+    /// Ordered by commit id, which lookups rely on. Loads sort them.
     pub checkpoints: Vec<Checkpoint>,
     pub next_node_id: NodeId,
+    #[serde(default)]
+    pub canonicalization: Canonicalization,
+    /// See `FloatPolicy`; change it with `set_float_policy`.
+    #[serde(default)]
+    pub(crate) float_policy: FloatPolicy,
+    /// Write-once (WORM) mode: commits may only be appended. Compaction and
+    /// any save that drops or changes existing commits is refused, and the
+    /// flag cannot be cleared once a file has been saved with it.
+    #[serde(default)]
+    pub write_once: bool,
+    /// Record in each new commit the hash of the state right after it, so
+    /// any reconstructed historical state can be checked, not only those
+    /// at checkpoints. Commits made before it was turned on have none.
+    #[serde(default)]
+    pub record_state_hashes: bool,
+    /// How `create` allocates ids. In `Uuid` mode `next_node_id` is left
+    /// alone and only bounds sequential ids that arrive by sync.
+    #[serde(default)]
+    pub id_mode: IdMode,
+    #[serde(default)]
+    pub anchors: Vec<AnchorReceipt>,
+    #[serde(default)]
+    pub locks: Vec<NodeLock>,
+    #[serde(default)]
+    pub expiries: Vec<FieldExpiry>,
+    #[serde(default)]
+    pub retention: Option<RetentionPolicy>,
+    /// When each commit was made here, in seconds since the Unix epoch.
+    /// Recorded only while a retention policy is set; kept outside the
+    /// hash chain.
+    #[serde(default)]
+    pub commit_times: BTreeMap<u64, u64>,
+    /// Pins the genesis state; see `Seal`.
+    #[serde(default)]
+    pub seal: Option<Seal>,
+    /// See `add_unique_constraint`; kept outside the hash chain.
+    #[serde(default)]
+    pub(crate) unique_constraints: Vec<UniqueConstraint>,
+
+    /// Latest state, pending mutations included; see `head_state()`.
+    #[serde(skip)]
+    pub(crate) head_state: HashMap<NodeId, Node>,
 
+    /// Staged, not yet committed; see `pending_mutations()`.
     #[serde(skip)]
-    pub head_state: HashMap<NodeId, Node>,
+    pub(crate) pending_mutations: Vec<Mutation>,
 
+    /// How many of `pending_mutations` were staged by the methods that
+    /// apply them to `head_state` first. When that is all of them, `commit`
+    /// needs no replay to know they apply.
     #[serde(skip)]
-    pub pending_mutations: Vec<Mutation>,
+    pub(crate) applied_pending: usize,
+
+    /// Alias to node id for `head_state`; see `reindex_aliases`.
+    #[serde(skip)]
+    pub(crate) alias_index: HashMap<String, NodeId>,
+
+    /// Label to the nodes carrying it in `head_state`, deleted ones
+    /// included; see `reindex_labels`.
+    #[serde(skip)]
+    pub(crate) label_index: HashMap<String, BTreeSet<NodeId>>,
+
+    /// Holder of each constrained value in committed state; see
+    /// `reindex_unique`.
+    #[serde(skip)]
+    pub(crate) unique_index: UniqueIndex,
+
+    #[serde(skip)]
+    pub acting_owner: Option<String>,
+
+    #[serde(skip)]
+    pub(crate) footprint_limit: Option<usize>,
+
+    #[serde(skip)]
+    pub(crate) durability: Durability,
+
+    #[serde(skip)]
+    pub(crate) file_checksum: bool,
+
+    #[serde(skip)]
+    pub(crate) blob_threshold: Option<usize>,
+
+    #[serde(skip)]
+    pub(crate) value_limits: ValueLimits,
+
+    #[serde(skip)]
+    pub(crate) seal_key: SealKey,
+
+    #[serde(skip)]
+    pub(crate) observers: Observers,
+
+    #[serde(skip)]
+    pub(crate) precommit_hooks: PrecommitHooks,
+
+    #[serde(skip)]
+    pub(crate) derived_fields: DerivedFields,
+
+    #[serde(skip)]
+    pub(crate) wal: WalHandle,
+
+    #[serde(skip)]
+    pub(crate) persistence: Persistence,
+
+    #[serde(skip)]
+    pub(crate) snapshot_cache: SnapshotCache,
+
+    #[serde(skip)]
+    pub(crate) metrics: MetricsRecorder,
 }
 
 impl Memory {
@@ -49,11 +227,75 @@ impl Memory {
             commits: Vec::new(),
             checkpoints: Vec::new(),
             next_node_id: 1,
+            canonicalization: Canonicalization::Binary,
+            float_policy: FloatPolicy::BitExact,
+            write_once: false,
+            record_state_hashes: false,
+            id_mode: IdMode::Sequential,
+            anchors: Vec::new(),
+            locks: Vec::new(),
+            expiries: Vec::new(),
+            retention: None,
+            commit_times: BTreeMap::new(),
+            seal: None,
+            unique_constraints: Vec::new(),
             head_state: HashMap::new(),
             pending_mutations: Vec::new(),
+            applied_pending: 0,
+            alias_index: HashMap::new(),
+            label_index: HashMap::new(),
+            unique_index: UniqueIndex::default(),
+            acting_owner: None,
+            footprint_limit: None,
+            durability: Durability::default(),
+            file_checksum: false,
+            blob_threshold: None,
+            value_limits: ValueLimits::default(),
+            seal_key: SealKey::default(),
+            observers: Observers::default(),
+            precommit_hooks: PrecommitHooks::default(),
+            derived_fields: DerivedFields::default(),
+            wal: WalHandle::default(),
+            persistence: Persistence::default(),
+            snapshot_cache: SnapshotCache::default(),
+            metrics: MetricsRecorder::default(),
+        }
+    }
+
+    pub fn with_canonicalization(canonicalization: Canonicalization) -> Self {
+        Self {
+            canonicalization,
+            ..Self::new()
         }
     }
 
+    pub(crate) fn sha256(bytes: &[u8]) -> [u8; 32] {
+        let digest = Sha256::digest(bytes);
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&digest);
+        out
+    }
+
+    /// Ids that fit in a u64 are written as 8 bytes, as before ids were
+    /// widened. Wider ids (UUIDs) follow an all-ones u64, which sequential
+    /// allocation never reaches.
+    fn write_id_canonical(buf: &mut Vec<u8>, id: NodeId) {
+        match u64::try_from(id) {
+            Ok(narrow) if narrow != u64::MAX => buf.extend_from_slice(&narrow.to_be_bytes()),
+            _ => {
+                buf.extend_from_slice(&u64::MAX.to_be_bytes());
+                buf.extend_from_slice(&id.to_be_bytes());
+            }
+        }
+    }
+
+    /// Binary canonical encoding of `value` on its own.
+    pub(crate) fn value_bytes(value: &Value) -> Vec<u8> {
+        let mut buf = Vec::new();
+        Self::write_value_canonical(&mut buf, value);
+        buf
+    }
+
     fn write_value_canonical(buf: &mut Vec<u8>, value: &Value) {
         match value {
             Value::Int(v) => {
@@ -61,6 +303,8 @@ impl Memory {
                 buf.extend_from_slice(&v.to_be_bytes());
             }
             Value::Float(v) => {
+                // Bits as they are; under a `FloatPolicy` other than
+                // `BitExact` they were made canonical when recorded.
                 buf.push(0x02);
                 buf.extend_from_slice(&v.to_bits().to_be_bytes());
             }
@@ -76,7 +320,7 @@ impl Memory {
             }
             Value::Ref(v) => {
                 buf.push(0x05);
-                buf.extend_from_slice(&v.to_be_bytes());
+                Self::write_id_canonical(buf, *v);
             }
             Value::List(values) => {
                 buf.push(0x06);
@@ -101,6 +345,49 @@ impl Memory {
                     }
                 }
             }
+            Value::AliasRef(alias) => {
+                buf.push(0x08);
+                let len = alias.len() as u64;
+                buf.extend_from_slice(&len.to_be_bytes());
+                buf.extend_from_slice(alias.as_bytes());
+            }
+            Value::Vector(v) => {
+                buf.push(0x0a);
+                let len = v.len() as u64;
+                buf.extend_from_slice(&len.to_be_bytes());
+                for component in v {
+                    buf.extend_from_slice(&component.to_bits().to_be_bytes());
+                }
+            }
+            Value::ExternalRef {
+                file_alias,
+                node_id,
+            } => {
+                buf.push(0x09);
+                let len = file_alias.len() as u64;
+                buf.extend_from_slice(&len.to_be_bytes());
+                buf.extend_from_slice(file_alias.as_bytes());
+                Self::write_id_canonical(buf, *node_id);
+            }
+        }
+    }
+
+    /// Segment count, then per segment 0x01 and a length-prefixed key, or
+    /// 0x02 and an index.
+    fn write_path_canonical(buf: &mut Vec<u8>, path: &[PathSeg]) {
+        buf.extend_from_slice(&(path.len() as u64).to_be_bytes());
+        for seg in path {
+            match seg {
+                PathSeg::Key(key) => {
+                    buf.push(0x01);
+                    buf.extend_from_slice(&(key.len() as u64).to_be_bytes());
+                    buf.extend_from_slice(key.as_bytes());
+                }
+                PathSeg::Index(index) => {
+                    buf.push(0x02);
+                    buf.extend_from_slice(&(*index as u64).to_be_bytes());
+                }
+            }
         }
     }
 
@@ -109,6 +396,14 @@ impl Memory {
         message: &Option<String>,
         mutations: &[Mutation],
     ) -> [u8; 32] {
+        Self::sha256(&Self::binary_commit_bytes(parent_hash, message, mutations))
+    }
+
+    fn binary_commit_bytes(
+        parent_hash: Option<[u8; 32]>,
+        message: &Option<String>,
+        mutations: &[Mutation],
+    ) -> Vec<u8> {
         let mut bytes = Vec::new();
 
         match parent_hash {
@@ -128,14 +423,14 @@ impl Memory {
             match m {
                 Mutation::CreateNode { id, ty } => {
                     bytes.push(0x01);
-                    bytes.extend_from_slice(&id.to_be_bytes());
+                    Self::write_id_canonical(&mut bytes, *id);
                     let tlen = ty.len() as u64;
                     bytes.extend_from_slice(&tlen.to_be_bytes());
                     bytes.extend_from_slice(ty.as_bytes());
                 }
                 Mutation::SetField { id, key, value } => {
                     bytes.push(0x02);
-                    bytes.extend_from_slice(&id.to_be_bytes());
+                    Self::write_id_canonical(&mut bytes, *id);
                     let klen = key.len() as u64;
                     bytes.extend_from_slice(&klen.to_be_bytes());
                     bytes.extend_from_slice(key.as_bytes());
@@ -143,38 +438,162 @@ impl Memory {
                 }
                 Mutation::DeleteField { id, key } => {
                     bytes.push(0x03);
-                    bytes.extend_from_slice(&id.to_be_bytes());
+                    Self::write_id_canonical(&mut bytes, *id);
                     let klen = key.len() as u64;
                     bytes.extend_from_slice(&klen.to_be_bytes());
                     bytes.extend_from_slice(key.as_bytes());
                 }
                 Mutation::DeleteNode { id } => {
                     bytes.push(0x04);
-                    bytes.extend_from_slice(&id.to_be_bytes());
+                    Self::write_id_canonical(&mut bytes, *id);
+                }
+                Mutation::RestoreNode { id, keep_fields } => {
+                    bytes.push(0x0b);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    bytes.push(u8::from(*keep_fields));
+                }
+                Mutation::SetAlias { id, alias } => {
+                    bytes.push(0x0d);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    match alias {
+                        Some(alias) => {
+                            bytes.push(0x01);
+                            let alen = alias.len() as u64;
+                            bytes.extend_from_slice(&alen.to_be_bytes());
+                            bytes.extend_from_slice(alias.as_bytes());
+                        }
+                        None => bytes.push(0x00),
+                    }
+                }
+                Mutation::SetNamespace { id, namespace } => {
+                    bytes.push(0x0e);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    match namespace {
+                        Some(namespace) => {
+                            bytes.push(0x01);
+                            let nlen = namespace.len() as u64;
+                            bytes.extend_from_slice(&nlen.to_be_bytes());
+                            bytes.extend_from_slice(namespace.as_bytes());
+                        }
+                        None => bytes.push(0x00),
+                    }
+                }
+                Mutation::SetArchived { id, archived } => {
+                    bytes.push(0x0f);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    bytes.push(u8::from(*archived));
+                }
+                Mutation::AddLabel { id, label } => {
+                    bytes.push(0x10);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    let llen = label.len() as u64;
+                    bytes.extend_from_slice(&llen.to_be_bytes());
+                    bytes.extend_from_slice(label.as_bytes());
+                }
+                Mutation::RemoveLabel { id, label } => {
+                    bytes.push(0x11);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    let llen = label.len() as u64;
+                    bytes.extend_from_slice(&llen.to_be_bytes());
+                    bytes.extend_from_slice(label.as_bytes());
+                }
+                Mutation::SetType { id, ty } => {
+                    bytes.push(0x0c);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    let tlen = ty.len() as u64;
+                    bytes.extend_from_slice(&tlen.to_be_bytes());
+                    bytes.extend_from_slice(ty.as_bytes());
+                }
+                Mutation::RenameField { id, from, to } => {
+                    bytes.push(0x05);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    for key in [from, to] {
+                        let klen = key.len() as u64;
+                        bytes.extend_from_slice(&klen.to_be_bytes());
+                        bytes.extend_from_slice(key.as_bytes());
+                    }
+                }
+                Mutation::SetPath { id, path, value } => {
+                    bytes.push(0x06);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    Self::write_path_canonical(&mut bytes, path);
+                    Self::write_value_canonical(&mut bytes, value);
+                }
+                Mutation::DeletePath { id, path } => {
+                    bytes.push(0x07);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    Self::write_path_canonical(&mut bytes, path);
+                }
+                Mutation::ListPush { id, path, value } => {
+                    bytes.push(0x08);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    Self::write_path_canonical(&mut bytes, path);
+                    Self::write_value_canonical(&mut bytes, value);
+                }
+                Mutation::ListInsert {
+                    id,
+                    path,
+                    index,
+                    value,
+                } => {
+                    bytes.push(0x09);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    Self::write_path_canonical(&mut bytes, path);
+                    bytes.extend_from_slice(&(*index as u64).to_be_bytes());
+                    Self::write_value_canonical(&mut bytes, value);
+                }
+                Mutation::ListRemove { id, path, index } => {
+                    bytes.push(0x0a);
+                    Self::write_id_canonical(&mut bytes, *id);
+                    Self::write_path_canonical(&mut bytes, path);
+                    bytes.extend_from_slice(&(*index as u64).to_be_bytes());
                 }
             }
         }
 
-        let digest = Sha256::digest(bytes);
-        let mut out = [0u8; 32];
-        out.copy_from_slice(&digest);
-        out
+        bytes
     }
 
     pub fn compute_state_hash(state: &HashMap<NodeId, Node>) -> [u8; 32] {
+        Self::sha256(&Self::binary_state_bytes(state))
+    }
+
+    fn binary_state_bytes(state: &HashMap<NodeId, Node>) -> Vec<u8> {
         let mut bytes = Vec::new();
         let mut node_ids: Vec<NodeId> = state.keys().copied().collect();
         node_ids.sort_unstable();
 
         for node_id in node_ids {
             if let Some(node) = state.get(&node_id) {
-                bytes.extend_from_slice(&node_id.to_be_bytes());
+                Self::write_id_canonical(&mut bytes, node_id);
 
                 let ty_len = node.ty.len() as u64;
                 bytes.extend_from_slice(&ty_len.to_be_bytes());
                 bytes.extend_from_slice(node.ty.as_bytes());
 
-                bytes.push(if node.deleted { 1 } else { 0 });
+                // Bits 1 and 2 mark a length-prefixed alias and namespace
+                // after the flags, bit 3 an archived node and bit 4 a
+                // counted list of labels, so states without them hash as
+                // they always have.
+                let flags = u8::from(node.deleted)
+                    | if node.alias.is_some() { 2 } else { 0 }
+                    | if node.namespace.is_some() { 4 } else { 0 }
+                    | if node.archived { 8 } else { 0 }
+                    | if node.labels.is_empty() { 0 } else { 16 };
+                bytes.push(flags);
+                for extra in [&node.alias, &node.namespace].into_iter().flatten() {
+                    let extra_len = extra.len() as u64;
+                    bytes.extend_from_slice(&extra_len.to_be_bytes());
+                    bytes.extend_from_slice(extra.as_bytes());
+                }
+                if !node.labels.is_empty() {
+                    bytes.extend_from_slice(&(node.labels.len() as u64).to_be_bytes());
+                    for label in &node.labels {
+                        let label_len = label.len() as u64;
+                        bytes.extend_from_slice(&label_len.to_be_bytes());
+                        bytes.extend_from_slice(label.as_bytes());
+                    }
+                }
 
                 let mut field_keys: Vec<&String> = node.fields.keys().collect();
                 field_keys.sort();
@@ -191,21 +610,75 @@ impl Memory {
             }
         }
 
-        let digest = Sha256::digest(bytes);
-        let mut out = [0u8; 32];
-        out.copy_from_slice(&digest);
-        out
+        bytes
+    }
+
+    /// Exact bytes fed to SHA-256 for a commit hash.
+    pub fn commit_hash_input(
+        canonicalization: Canonicalization,
+        parent_hash: Option<[u8; 32]>,
+        message: &Option<String>,
+        mutations: &[Mutation],
+    ) -> Vec<u8> {
+        match canonicalization {
+            Canonicalization::Binary => Self::binary_commit_bytes(parent_hash, message, mutations),
+            Canonicalization::Jcs => canonical::jcs_commit_bytes(parent_hash, message, mutations),
+        }
+    }
+
+    /// Exact bytes fed to SHA-256 for a state hash.
+    pub fn state_hash_input(
+        canonicalization: Canonicalization,
+        state: &HashMap<NodeId, Node>,
+    ) -> Vec<u8> {
+        match canonicalization {
+            Canonicalization::Binary => Self::binary_state_bytes(state),
+            Canonicalization::Jcs => canonical::jcs_state_bytes(state),
+        }
+    }
+
+    pub fn compute_commit_hash_with(
+        canonicalization: Canonicalization,
+        parent_hash: Option<[u8; 32]>,
+        message: &Option<String>,
+        mutations: &[Mutation],
+    ) -> [u8; 32] {
+        let bytes = Self::commit_hash_input(canonicalization, parent_hash, message, mutations);
+        tracing::trace!(bytes = bytes.len(), "hashing commit input");
+        Self::sha256(&bytes)
+    }
+
+    pub fn compute_state_hash_with(
+        canonicalization: Canonicalization,
+        state: &HashMap<NodeId, Node>,
+    ) -> [u8; 32] {
+        let bytes = Self::state_hash_input(canonicalization, state);
+        tracing::trace!(bytes = bytes.len(), "hashing state input");
+        Self::sha256(&bytes)
+    }
+
+    /// Commit hash under this memory's canonicalization mode.
+    pub fn hash_commit(
+        &self,
+        parent_hash: Option<[u8; 32]>,
+        message: &Option<String>,
+        mutations: &[Mutation],
+    ) -> [u8; 32] {
+        Self::compute_commit_hash_with(self.canonicalization, parent_hash, message, mutations)
+    }
+
+    /// State hash under this memory's canonicalization mode.
+    pub fn hash_state(&self, state: &HashMap<NodeId, Node>) -> [u8; 32] {
+        Self::compute_state_hash_with(self.canonicalization, state)
     }
 
     fn check_value_refs(value: &Value, state: &State) -> Result<(), MyosotisError> {
         match value {
-            Value::Ref(rid) => {
-                if !state.contains_key(rid) {
-                    return Err(MyosotisError::Invariant(format!(
-                        "reference to missing node {}",
-                        rid
-                    )));
-                }
+            Value::Ref(rid) if !state.contains_key(rid) => {
+                return Err(MyosotisError::Invariant(format!(
+                    "reference to missing node {}",
+                    rid
+                )));
             }
             Value::List(vec) => {
                 for item in vec {
@@ -222,7 +695,10 @@ impl Memory {
         Ok(())
     }
 
-    fn apply_mutation(state: &mut State, mutation: &Mutation) -> Result<(), MyosotisError> {
+    pub(crate) fn apply_mutation(
+        state: &mut State,
+        mutation: &Mutation,
+    ) -> Result<(), MyosotisError> {
         match mutation {
             Mutation::CreateNode { id, ty } => {
                 if state.contains_key(id) {
@@ -238,6 +714,10 @@ impl Memory {
                         ty: ty.clone(),
                         fields: HashMap::new(),
                         deleted: false,
+                        alias: None,
+                        namespace: None,
+                        archived: false,
+                        labels: BTreeSet::new(),
                     },
                 );
                 Ok(())
@@ -280,15 +760,161 @@ impl Memory {
                 node.deleted = true;
                 Ok(())
             }
+            Mutation::SetAlias { id, alias } => {
+                if let Some(alias) = alias {
+                    if alias.is_empty() {
+                        return Err(MyosotisError::InvalidInput(
+                            "alias must not be empty".to_string(),
+                        ));
+                    }
+                    if let Some(other) = state
+                        .values()
+                        .find(|n| n.id != *id && n.alias.as_ref() == Some(alias))
+                    {
+                        return Err(MyosotisError::AliasTaken(alias.clone(), other.id));
+                    }
+                }
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                node.alias = alias.clone();
+                Ok(())
+            }
+            Mutation::SetNamespace { id, namespace } => {
+                if namespace.as_ref().is_some_and(String::is_empty) {
+                    return Err(MyosotisError::InvalidInput(
+                        "namespace must not be empty".to_string(),
+                    ));
+                }
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                node.namespace = namespace.clone();
+                Ok(())
+            }
+            Mutation::SetArchived { id, archived } => {
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                node.archived = *archived;
+                Ok(())
+            }
+            Mutation::AddLabel { id, label } => {
+                if label.is_empty() {
+                    return Err(MyosotisError::InvalidInput(
+                        "label must not be empty".to_string(),
+                    ));
+                }
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                if !node.labels.insert(label.clone()) {
+                    return Err(MyosotisError::InvalidInput(format!(
+                        "node {} already has label '{}'",
+                        id, label
+                    )));
+                }
+                Ok(())
+            }
+            Mutation::RemoveLabel { id, label } => {
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                if !node.labels.remove(label) {
+                    return Err(MyosotisError::LabelNotFound(*id, label.clone()));
+                }
+                Ok(())
+            }
+            Mutation::SetType { id, ty } => {
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                node.ty = ty.clone();
+                Ok(())
+            }
+            Mutation::RestoreNode { id, keep_fields } => {
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if !node.deleted {
+                    return Err(MyosotisError::RestoreLiveNode(*id));
+                }
+                node.deleted = false;
+                if !keep_fields {
+                    node.fields.clear();
+                }
+                Ok(())
+            }
+            Mutation::RenameField { id, from, to } => {
+                let node = state.get_mut(id).ok_or(MyosotisError::NodeNotFound(*id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(*id));
+                }
+                if from == to || node.fields.contains_key(to) {
+                    return Err(MyosotisError::InvalidInput(format!(
+                        "cannot rename '{}' to existing field '{}' on node {}",
+                        from, to, id
+                    )));
+                }
+                let value = node
+                    .fields
+                    .remove(from)
+                    .ok_or_else(|| MyosotisError::FieldNotFound(from.clone()))?;
+                node.fields.insert(to.clone(), value);
+                Ok(())
+            }
+            Mutation::SetPath { id, path, value } => {
+                Self::live_node_for(state, *id, Some(value))?.set_path(path, value.clone())
+            }
+            Mutation::DeletePath { id, path } => {
+                Self::live_node_for(state, *id, None)?.delete_path(path)
+            }
+            Mutation::ListPush { id, path, value } => {
+                Self::live_node_for(state, *id, Some(value))?.list_push(path, value.clone())
+            }
+            Mutation::ListInsert {
+                id,
+                path,
+                index,
+                value,
+            } => Self::live_node_for(state, *id, Some(value))?.list_insert(
+                path,
+                *index,
+                value.clone(),
+            ),
+            Mutation::ListRemove { id, path, index } => {
+                Self::live_node_for(state, *id, None)?.list_remove(path, *index)
+            }
         }
     }
 
+    /// The node a path or list mutation writes to, which must exist and
+    /// not be deleted, after checking the refs in the value it writes.
+    fn live_node_for<'a>(
+        state: &'a mut State,
+        id: NodeId,
+        value: Option<&Value>,
+    ) -> Result<&'a mut Node, MyosotisError> {
+        if let Some(value) = value {
+            Self::check_value_refs(value, state)?;
+        }
+        let node = state.get_mut(&id).ok_or(MyosotisError::NodeNotFound(id))?;
+        if node.deleted {
+            return Err(MyosotisError::NodeDeleted(id));
+        }
+        Ok(node)
+    }
+
     fn snapshot_from_genesis(&self) -> Option<Snapshot> {
         self.genesis_state.as_ref().map(|state| Snapshot {
             state: state.clone(),
             state_hash: self
                 .genesis_state_hash
-                .unwrap_or_else(|| Self::compute_state_hash(state)),
+                .unwrap_or_else(|| self.hash_state(state)),
             commit_id: None,
             commit_hash: None,
         })
@@ -303,8 +929,21 @@ impl Memory {
         }
     }
 
+    /// Position of commit `commit_id` in `commits`.
+    pub(crate) fn commit_index(&self, commit_id: u64) -> Option<usize> {
+        self.commits.binary_search_by_key(&commit_id, |c| c.id).ok()
+    }
+
+    /// The newest checkpoint taken at or before commit `commit_id`.
+    pub(crate) fn checkpoint_at_or_before(&self, commit_id: u64) -> Option<&Checkpoint> {
+        let end = self
+            .checkpoints
+            .partition_point(|cp| cp.commit_id <= commit_id);
+        end.checked_sub(1).map(|i| &self.checkpoints[i])
+    }
+
     fn latest_snapshot_for_head(&self) -> Option<Snapshot> {
-        if let Some(cp) = self.checkpoints.iter().max_by_key(|c| c.commit_id) {
+        if let Some(cp) = self.checkpoints.last() {
             Some(Self::snapshot_from_checkpoint(cp))
         } else {
             self.snapshot_from_genesis()
@@ -312,12 +951,7 @@ impl Memory {
     }
 
     fn snapshot_for_commit(&self, commit_id: u64) -> Option<Snapshot> {
-        if let Some(cp) = self
-            .checkpoints
-            .iter()
-            .filter(|c| c.commit_id <= commit_id)
-            .max_by_key(|c| c.commit_id)
-        {
+        if let Some(cp) = self.checkpoint_at_or_before(commit_id) {
             Some(Self::snapshot_from_checkpoint(cp))
         } else {
             self.snapshot_from_genesis()
@@ -350,25 +984,47 @@ impl Memory {
             && let Some(cid) = s.commit_id
         {
             let pos = self
-                .commits
-                .iter()
-                .position(|c| c.id == cid)
+                .commit_index(cid)
                 .ok_or(MyosotisError::InvalidCheckpoint)?;
             return Ok(pos + 1);
         }
         Ok(0)
     }
 
+    pub(crate) fn stage(&mut self, mutation: Mutation) {
+        self.observers.mutation(&mutation);
+        self.wal.append(&mutation);
+        if self.applied_pending == self.pending_mutations.len() {
+            self.applied_pending += 1;
+        }
+        self.pending_mutations.push(mutation);
+    }
+
+    /// Stages `mutation` as is, without checking it or applying it to head
+    /// state; `commit` replays and checks it. For tooling that forwards
+    /// mutations recorded elsewhere; `check_pending` reports bad ones.
+    pub fn stage_unchecked(&mut self, mutation: Mutation) {
+        self.observers.mutation(&mutation);
+        self.wal.append(&mutation);
+        self.pending_mutations.push(mutation);
+    }
+
     pub fn create(&mut self, ty: &str) -> NodeId {
-        let id = self.next_node_id;
-        self.next_node_id += 1;
+        let id = match self.id_mode {
+            IdMode::Sequential => {
+                let id = self.next_node_id;
+                self.next_node_id += 1;
+                id
+            }
+            IdMode::Uuid => uuid::Uuid::new_v4().as_u128(),
+        };
 
         let m = Mutation::CreateNode {
             id,
             ty: ty.to_string(),
         };
         let _ = Self::apply_mutation(&mut self.head_state, &m);
-        self.pending_mutations.push(m);
+        self.stage(m);
         id
     }
 
@@ -379,14 +1035,134 @@ impl Memory {
         if self.head_state.get(&id).map(|n| n.deleted).unwrap_or(false) {
             return Err(MyosotisError::NodeDeleted(id));
         }
+        self.check_lock(id)?;
 
-        let m = Mutation::SetField {
+        let mut m = Mutation::SetField {
             id,
             key: key.to_string(),
             value,
         };
+        self.canonicalize_floats(&mut m)?;
+        self.check_value_limit(&m)?;
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Latest state: the last commit plus pending mutations. Deleted nodes
+    /// stay in it as tombstones.
+    pub fn head_state(&self) -> &HashMap<NodeId, Node> {
+        &self.head_state
+    }
+
+    /// Mutations staged since the last commit, oldest first.
+    pub fn pending_mutations(&self) -> &[Mutation] {
+        &self.pending_mutations
+    }
+
+    /// Whether mutations are staged that the next commit would record.
+    pub fn is_dirty(&self) -> bool {
+        !self.pending_mutations.is_empty()
+    }
+
+    pub fn pending_summary(&self) -> PendingSummary {
+        PendingSummary::of(&self.pending_mutations)
+    }
+
+    /// Reads a field of a live node in head state. Use the `Value::as_*`
+    /// helpers or `Node::get_str` and friends for a typed view.
+    pub fn get_field(&self, id: NodeId, key: &str) -> Result<&Value, MyosotisError> {
+        let node = self
+            .head_state
+            .get(&id)
+            .ok_or(MyosotisError::NodeNotFound(id))?;
+        if node.deleted {
+            return Err(MyosotisError::NodeDeleted(id));
+        }
+        node.get(key)
+            .ok_or_else(|| MyosotisError::FieldNotFound(key.to_string()))
+    }
+
+    /// Stages a write inside a field value, e.g. `profile.settings.theme`
+    /// (see `node::parse_path`). Only the path and the new value are
+    /// recorded, not the whole field.
+    pub fn set_path(&mut self, id: NodeId, path: &str, value: Value) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let mut m = Mutation::SetPath {
+            id,
+            path: parse_path(path)?,
+            value,
+        };
+        self.canonicalize_floats(&mut m)?;
+        self.check_value_limit(&m)?;
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Stages removal of the value at `path`, which must exist.
+    pub fn delete_path(&mut self, id: NodeId, path: &str) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::DeletePath {
+            id,
+            path: parse_path(path)?,
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Stages an append to the list at `path`, which is created if
+    /// missing. Only the new element is recorded.
+    pub fn list_push(&mut self, id: NodeId, path: &str, value: Value) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let mut m = Mutation::ListPush {
+            id,
+            path: parse_path(path)?,
+            value,
+        };
+        self.canonicalize_floats(&mut m)?;
+        self.check_value_limit(&m)?;
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    pub fn list_insert(
+        &mut self,
+        id: NodeId,
+        path: &str,
+        index: usize,
+        value: Value,
+    ) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let mut m = Mutation::ListInsert {
+            id,
+            path: parse_path(path)?,
+            index,
+            value,
+        };
+        self.canonicalize_floats(&mut m)?;
+        self.check_value_limit(&m)?;
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    pub fn list_remove(
+        &mut self,
+        id: NodeId,
+        path: &str,
+        index: usize,
+    ) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::ListRemove {
+            id,
+            path: parse_path(path)?,
+            index,
+        };
         Self::apply_mutation(&mut self.head_state, &m)?;
-        self.pending_mutations.push(m);
+        self.stage(m);
         Ok(())
     }
 
@@ -401,16 +1177,72 @@ impl Memory {
         if !node.fields.contains_key(key) {
             return Err(MyosotisError::FieldNotFound(key.to_string()));
         }
+        self.check_lock(id)?;
 
         let m = Mutation::DeleteField {
             id,
             key: key.to_string(),
         };
         Self::apply_mutation(&mut self.head_state, &m)?;
-        self.pending_mutations.push(m);
+        self.stage(m);
         Ok(())
     }
 
+    /// Stages a rename of field `from` to `to` on one node. The value is
+    /// kept; history records a single `RenameField` mutation.
+    pub fn rename_field(&mut self, id: NodeId, from: &str, to: &str) -> Result<(), MyosotisError> {
+        if !self.head_state.contains_key(&id) {
+            return Err(MyosotisError::NodeNotFound(id));
+        }
+        self.check_lock(id)?;
+
+        let m = Mutation::RenameField {
+            id,
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Stages `rename_field` on every live node of type `ty` that has field
+    /// `from`, in node id order. Nothing is staged if any rename would fail.
+    /// Returns the renamed node ids.
+    pub fn rename_field_for_type(
+        &mut self,
+        ty: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<NodeId>, MyosotisError> {
+        let mut ids: Vec<NodeId> = self
+            .head_state
+            .values()
+            .filter(|n| !n.deleted && n.ty == ty && n.fields.contains_key(from))
+            .map(|n| n.id)
+            .collect();
+        ids.sort_unstable();
+
+        let mut state = self.head_state.clone();
+        let mut mutations = Vec::with_capacity(ids.len());
+        for id in &ids {
+            self.check_lock(*id)?;
+            let m = Mutation::RenameField {
+                id: *id,
+                from: from.to_string(),
+                to: to.to_string(),
+            };
+            Self::apply_mutation(&mut state, &m)?;
+            mutations.push(m);
+        }
+
+        self.head_state = state;
+        for m in mutations {
+            self.stage(m);
+        }
+        Ok(ids)
+    }
+
     pub fn delete_node(&mut self, id: NodeId) -> Result<(), MyosotisError> {
         let node = self
             .head_state
@@ -419,20 +1251,272 @@ impl Memory {
         if node.deleted {
             return Err(MyosotisError::DeleteOnDeletedNode(id));
         }
+        self.check_lock(id)?;
 
         let m = Mutation::DeleteNode { id };
         Self::apply_mutation(&mut self.head_state, &m)?;
-        self.pending_mutations.push(m);
+        self.stage(m);
         Ok(())
     }
 
+    /// Stages a change of node `id`'s type to `ty`. Fields are untouched.
+    pub fn set_type(&mut self, id: NodeId, ty: &str) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::SetType {
+            id,
+            ty: ty.to_string(),
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Stages undeletion of node `id`, keeping the fields it had when it
+    /// was deleted if `keep_fields` is set, or restoring it empty otherwise.
+    pub fn restore_node(&mut self, id: NodeId, keep_fields: bool) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::RestoreNode { id, keep_fields };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// State after the last commit, ignoring pending mutations.
+    pub(crate) fn committed_state(&self) -> Result<State, MyosotisError> {
+        let snapshot = self.latest_snapshot_for_head();
+        let start_index = self.commits_start_index_from_snapshot(snapshot.as_ref())?;
+        Self::replay_from_snapshot(snapshot.as_ref(), &self.commits[start_index..])
+    }
+
+    /// Drops all pending mutations and resets `head_state` to the last
+    /// committed state. Returns how many mutations were dropped. Node ids
+    /// handed out by discarded creates are not reused.
+    pub fn discard_pending(&mut self) -> Result<usize, MyosotisError> {
+        let state = self.committed_state()?;
+        let dropped = self.pending_mutations.len();
+        self.head_state = state;
+        self.pending_mutations.clear();
+        self.applied_pending = 0;
+        self.reindex_aliases();
+        self.reindex_labels();
+        self.reset_wal();
+        Ok(dropped)
+    }
+
+    /// Pops the most recent pending mutation and recomputes `head_state`
+    /// from the last committed state plus the remaining pending mutations.
+    pub fn undo_last_pending(&mut self) -> Result<Option<Mutation>, MyosotisError> {
+        let Some(undone) = self.pending_mutations.last().cloned() else {
+            return Ok(None);
+        };
+        self.truncate_pending(self.pending_mutations.len() - 1)?;
+        Ok(Some(undone))
+    }
+
+    /// Keeps only the first `len` pending mutations, recomputing
+    /// `head_state` as `undo_last_pending` does.
+    pub(crate) fn truncate_pending(&mut self, len: usize) -> Result<(), MyosotisError> {
+        let mut state = self.committed_state()?;
+        self.pending_mutations.truncate(len);
+        for mutation in &self.pending_mutations {
+            Self::apply_mutation(&mut state, mutation)?;
+        }
+        self.head_state = state;
+        self.applied_pending = self.pending_mutations.len();
+        self.reindex_aliases();
+        self.reindex_labels();
+        self.reset_wal();
+        Ok(())
+    }
+
+    /// Truncates history back to `commit_id`, 0 meaning genesis: later
+    /// commits, and the checkpoints, anchors and commit times that refer
+    /// to them, are dropped, pending mutations are discarded and
+    /// `head_state` is rebuilt. Returns the dropped commits, oldest first.
+    /// Refused for write-once files, and when the state it returns to
+    /// breaks a unique constraint.
+    pub fn reset_to(&mut self, commit_id: u64) -> Result<Vec<Commit>, MyosotisError> {
+        if self.write_once {
+            return Err(MyosotisError::WriteOnceViolation("reset"));
+        }
+        let keep = match commit_id {
+            0 => 0,
+            id => self
+                .commit_index(id)
+                .map(|i| i + 1)
+                .ok_or(MyosotisError::CommitNotFound(id))?,
+        };
+        let state = match commit_id {
+            0 => self.genesis_state.clone().unwrap_or_default(),
+            id => self.state_at_commit(id)?,
+        };
+        let unique_index = UniqueIndex::build(&self.unique_constraints, &state)?;
+        let dropped = self.commits.split_off(keep);
+        let head = self.commits.last().map_or(0, |c| c.id);
+        self.checkpoints.retain(|cp| cp.commit_id <= head);
+        self.anchors.retain(|a| a.commit_id <= head);
+        self.commit_times.retain(|id, _| *id <= head);
+        self.pending_mutations.clear();
+        self.applied_pending = 0;
+        self.head_state = state;
+        self.unique_index = unique_index;
+        self.reindex_aliases();
+        self.reindex_labels();
+        self.reset_wal();
+        tracing::info!(commit_id, dropped = dropped.len(), "reset commit chain");
+        Ok(dropped)
+    }
+
+    /// Validates `batch` as if it were staged after the current pending
+    /// mutations and returns the commit that would result, without
+    /// changing anything. Created node ids must not be below
+    /// `next_node_id`, and lock rules and pre-commit hooks apply as for a
+    /// real commit.
+    pub fn dry_run_commit(
+        &self,
+        message: Option<String>,
+        batch: &[Mutation],
+    ) -> Result<CommitPreview, MyosotisError> {
+        let mut state = match self.replay_pending()? {
+            Some(state) => state,
+            None => self.head_state.clone(),
+        };
+        for mutation in batch {
+            match mutation {
+                Mutation::CreateNode { id, .. } if *id < self.next_node_id => {
+                    return Err(MyosotisError::InvalidInput(format!(
+                        "node id {} is below next_node_id {}",
+                        id, self.next_node_id
+                    )));
+                }
+                Mutation::CreateNode { .. } => {}
+                other => self.check_lock(other.node_id())?,
+            }
+            Self::apply_mutation(&mut state, mutation)?;
+        }
+
+        let mut mutations = self.pending_mutations.clone();
+        mutations.extend_from_slice(batch);
+        if mutations.is_empty() {
+            return Err(MyosotisError::InvalidInput(
+                "no pending mutations".to_string(),
+            ));
+        }
+        self.check_commit(&mutations, &state)?;
+
+        let parent_hash = match self.commits.last() {
+            Some(last) => Some(last.hash),
+            None => self.genesis_state_hash,
+        };
+        let state_hash = self.hash_state(&state);
+        let commit = Commit {
+            id: self.commits.last().map(|c| c.id + 1).unwrap_or(1),
+            parent: self.commits.last().map(|c| c.id),
+            parent_hash,
+            hash: [0; 32],
+            message,
+            source: None,
+            state_hash: self.record_state_hashes.then_some(state_hash),
+            mutations,
+        };
+        Ok(CommitPreview {
+            id: commit.id,
+            parent_hash,
+            hash: commit.compute_hash_with(self.canonicalization),
+            state_hash,
+        })
+    }
+
+    /// Replays the pending mutations on the committed state the way
+    /// `commit` does and reports every one that would fail, without
+    /// committing. A failed mutation is skipped, so mutations that depend
+    /// on it are reported too. Empty when `commit` would succeed.
+    pub fn check_pending(&self) -> Result<Vec<MutationIssue>, MyosotisError> {
+        let mut state = self.committed_state()?;
+        let mut issues = Vec::new();
+        for (index, mutation) in self.pending_mutations.iter().enumerate() {
+            if let Err(e) = Self::apply_mutation(&mut state, mutation) {
+                issues.push(MutationIssue {
+                    index,
+                    kind: mutation.kind(),
+                    node_id: mutation.node_id(),
+                    message: e.to_string(),
+                });
+            }
+        }
+        Ok(issues)
+    }
+
+    /// State the pending mutations leave, when it is not `head_state`.
+    /// Staging already applied each mutation to `head_state`; only
+    /// mutations pushed onto `pending_mutations` directly need checking,
+    /// against the latest checkpoint and the commits after it.
+    fn replay_pending(&self) -> Result<Option<State>, MyosotisError> {
+        if self.applied_pending == self.pending_mutations.len() {
+            return Ok(None);
+        }
+        let mut state = self.committed_state()?;
+        for mutation in &self.pending_mutations {
+            Self::apply_mutation(&mut state, mutation)?;
+        }
+        Ok(Some(state))
+    }
+
+    /// Checks `commit` and `dry_run_commit` make of `mutations` leaving
+    /// `state`: pre-commit hooks, float policy and unique constraints.
+    /// Returns the unique index changes to apply if the commit goes ahead.
+    fn check_commit(
+        &self,
+        mutations: &[Mutation],
+        state: &State,
+    ) -> Result<UniqueUpdate, MyosotisError> {
+        self.precommit_hooks.check(mutations, state)?;
+        self.check_floats(mutations)?;
+        if self.unique_constraints.is_empty() {
+            return Ok(UniqueUpdate::default());
+        }
+        let touched = mutations.iter().map(Mutation::node_id).collect();
+        self.unique_index
+            .check(&self.unique_constraints, state, &touched)
+    }
+
     pub fn commit(&mut self, message: Option<String>) -> Result<(), MyosotisError> {
         if self.pending_mutations.is_empty() {
             return Err(MyosotisError::InvalidInput(
                 "no pending mutations".to_string(),
             ));
         }
+        self.append_commit(message, None)
+    }
 
+    /// `commit`, recording `source` (a tool name, model call id or other
+    /// component) as the commit's provenance; see `mutations_by_source`.
+    pub fn commit_with_source(
+        &mut self,
+        message: Option<String>,
+        source: &str,
+    ) -> Result<(), MyosotisError> {
+        if self.pending_mutations.is_empty() {
+            return Err(MyosotisError::InvalidInput(
+                "no pending mutations".to_string(),
+            ));
+        }
+        self.append_commit(message, Some(source.to_string()))
+    }
+
+    /// Commits pending mutations, or records a commit with no mutations
+    /// when nothing is staged. Useful as a marker (session boundary,
+    /// heartbeat) in history.
+    pub fn commit_allow_empty(&mut self, message: Option<String>) -> Result<(), MyosotisError> {
+        self.append_commit(message, None)
+    }
+
+    #[cfg_attr(feature = "telemetry", tracing::instrument(skip_all))]
+    fn append_commit(
+        &mut self,
+        message: Option<String>,
+        source: Option<String>,
+    ) -> Result<(), MyosotisError> {
         let commit_id = self.commits.last().map(|c| c.id + 1).unwrap_or(1);
         let parent = self.commits.last().map(|c| c.id);
 
@@ -450,44 +1534,75 @@ impl Memory {
         }
 
         let mutations = self.pending_mutations.clone();
+        let replayed = self.replay_pending()?;
+        let state = replayed.as_ref().unwrap_or(&self.head_state);
+        let unique = self.check_commit(&mutations, state)?;
+        self.unique_index.apply(unique);
 
-        let base_snapshot = self.snapshot_from_genesis();
-        let mut base_state = Self::replay_from_snapshot(base_snapshot.as_ref(), &self.commits)?;
-        for mutation in &mutations {
-            Self::apply_mutation(&mut base_state, mutation)?;
-        }
+        let state_hash = self.record_state_hashes.then(|| self.hash_state(state));
 
         let parent_hash = if let Some(last) = self.commits.last() {
             Some(last.hash)
         } else {
             self.genesis_state_hash
         };
-        let hash = Self::compute_commit_hash(parent_hash, &message, &mutations);
-
-        let commit = Commit {
+        let mut commit = Commit {
             id: commit_id,
             parent,
             parent_hash,
-            hash,
+            hash: [0; 32],
             message,
+            source,
+            state_hash,
             mutations,
         };
+        commit.hash = commit.compute_hash_with(self.canonicalization);
 
         self.commits.push(commit);
+        // Mutations staged unchecked never reached `head_state`.
+        if let Some(state) = replayed {
+            self.head_state = state;
+            self.reindex_aliases();
+            self.reindex_labels();
+        }
+        self.metrics.record_commit(self.pending_mutations.len());
+        if self.retention.is_some() {
+            self.commit_times.insert(commit_id, crate::lock::now_secs());
+        }
+
+        tracing::debug!(
+            commit_id,
+            mutations = self.pending_mutations.len(),
+            "committed"
+        );
 
+        let mut checkpointed = false;
         if self.commits.len().is_multiple_of(CHECKPOINT_INTERVAL)
             && let Some(last) = self.commits.last()
         {
-            let state_hash = Self::compute_state_hash(&self.head_state);
+            tracing::debug!(commit_id = last.id, "writing checkpoint");
+            let state_hash = self.hash_state(&self.head_state);
             self.checkpoints.push(Checkpoint {
                 commit_id: last.id,
                 commit_hash: last.hash,
                 state_hash,
                 state: self.head_state.clone(),
             });
+            checkpointed = true;
         }
 
         self.pending_mutations.clear();
+        self.applied_pending = 0;
+        self.reset_wal();
+
+        // Notify only once the commit is fully applied.
+        if let Some(last) = self.commits.last() {
+            self.observers.commit(last, CommitOrigin::Local);
+        }
+        if checkpointed && let Some(checkpoint) = self.checkpoints.last() {
+            self.observers.checkpoint(checkpoint);
+        }
+        self.check_footprint();
         Ok(())
     }
 
@@ -513,9 +1628,7 @@ impl Memory {
         target_commit_id: u64,
     ) -> Result<HashMap<NodeId, Node>, MyosotisError> {
         let target_index = self
-            .commits
-            .iter()
-            .position(|c| c.id == target_commit_id)
+            .commit_index(target_commit_id)
             .ok_or(MyosotisError::CommitNotFound(target_commit_id))?;
 
         let snapshot = self.snapshot_for_commit(target_commit_id);
@@ -525,7 +1638,54 @@ impl Memory {
             return Err(MyosotisError::InvalidCheckpoint);
         }
 
-        Self::replay_from_snapshot(snapshot.as_ref(), &self.commits[start_index..=target_index])
+        let state = Self::replay_from_snapshot(
+            snapshot.as_ref(),
+            &self.commits[start_index..=target_index],
+        )?;
+        self.check_recorded_state(&self.commits[target_index], &state)?;
+        Ok(state)
+    }
+
+    /// Checks `state` against the state hash `commit` recorded, if any.
+    pub(crate) fn check_recorded_state(
+        &self,
+        commit: &Commit,
+        state: &State,
+    ) -> Result<(), MyosotisError> {
+        Self::check_recorded_state_with(self.canonicalization, commit.id, commit.state_hash, state)
+    }
+
+    /// `check_recorded_state` for readers that hold only the file's
+    /// canonicalization.
+    pub(crate) fn check_recorded_state_with(
+        canonicalization: Canonicalization,
+        commit_id: u64,
+        recorded: Option<[u8; 32]>,
+        state: &State,
+    ) -> Result<(), MyosotisError> {
+        match recorded {
+            Some(hash) if hash != Self::compute_state_hash_with(canonicalization, state) => {
+                Err(MyosotisError::CommitStateHashMismatch(commit_id))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Replays history from genesis and checks the state after every commit
+    /// that recorded a state hash. Returns how many were checked.
+    pub fn verify_state_hashes(&self) -> Result<usize, MyosotisError> {
+        let mut state = self.genesis_state.clone().unwrap_or_default();
+        let mut checked = 0;
+        for commit in &self.commits {
+            for mutation in &commit.mutations {
+                Self::apply_mutation(&mut state, mutation)?;
+            }
+            if commit.state_hash.is_some() {
+                self.check_recorded_state(commit, &state)?;
+                checked += 1;
+            }
+        }
+        Ok(checked)
     }
 
     fn validate_schema(&self) -> Result<(), MyosotisError> {
@@ -535,21 +1695,21 @@ impl Memory {
         Ok(())
     }
 
-    fn validate_snapshot_integrity(&self) -> Result<(), MyosotisError> {
+    pub(crate) fn validate_snapshot_integrity(&self) -> Result<(), MyosotisError> {
         if let Some(genesis_state) = &self.genesis_state {
-            let expected_hash = Self::compute_state_hash(genesis_state);
+            let expected_hash = self.hash_state(genesis_state);
             if self.genesis_state_hash != Some(expected_hash) {
                 return Err(MyosotisError::CorruptGenesisHash);
             }
         } else if self.genesis_state_hash.is_some() {
             return Err(MyosotisError::CorruptGenesisHash);
         }
+        self.validate_seal()?;
 
         for checkpoint in &self.checkpoints {
             let commit = self
-                .commits
-                .iter()
-                .find(|c| c.id == checkpoint.commit_id)
+                .commit_index(checkpoint.commit_id)
+                .map(|i| &self.commits[i])
                 .ok_or(MyosotisError::CheckpointCommitMismatch)?;
             if commit.hash != checkpoint.commit_hash {
                 return Err(MyosotisError::CheckpointCommitMismatch);
@@ -609,15 +1769,13 @@ impl Memory {
         }
 
         for commit in &self.commits {
-            let recomputed =
-                Self::compute_commit_hash(commit.parent_hash, &commit.message, &commit.mutations);
-            if commit.hash != recomputed {
+            if commit.hash != commit.compute_hash_with(self.canonicalization) {
                 return Err(MyosotisError::CorruptCommitHash);
             }
         }
 
         for checkpoint in &self.checkpoints {
-            let recomputed_state_hash = Self::compute_state_hash(&checkpoint.state);
+            let recomputed_state_hash = self.hash_state(&checkpoint.state);
             if recomputed_state_hash != checkpoint.state_hash {
                 return Err(MyosotisError::CorruptCheckpointHash);
             }
@@ -627,13 +1785,16 @@ impl Memory {
     }
 
     fn validate_semantic_replay(&self) -> Result<State, MyosotisError> {
-        let snapshot = self.latest_snapshot_for_head();
-        let start_index = self.commits_start_index_from_snapshot(snapshot.as_ref())?;
-        Self::replay_from_snapshot(snapshot.as_ref(), &self.commits[start_index..])
+        self.committed_state()
     }
 
     fn validate_node_id_bounds(&self, state: &State) -> Result<(), MyosotisError> {
-        let max_id = state.keys().copied().max().unwrap_or(0);
+        let max_id = state
+            .keys()
+            .copied()
+            .filter(|id| is_sequential_id(*id))
+            .max()
+            .unwrap_or(0);
         if self.next_node_id <= max_id {
             return Err(MyosotisError::Invariant(format!(
                 "next_node_id {} <= max created id {}",
@@ -650,6 +1811,7 @@ impl Memory {
         Ok(())
     }
 
+    #[cfg_attr(feature = "telemetry", tracing::instrument(skip(self)))]
     pub fn validate_with_mode(&self, verify_hashes: bool) -> Result<(), MyosotisError> {
         self.validate_schema()?;
         self.validate_snapshot_integrity()?;
@@ -663,6 +1825,32 @@ impl Memory {
     pub fn validate(&self) -> Result<(), MyosotisError> {
         self.validate_with_mode(true)
     }
+
+    /// Cheap sanity check of `head_state`: re-hashes the newest snapshot,
+    /// replays only the commits after it plus pending mutations, and compares
+    /// the result against the head state hash. Commit hashes and the parent
+    /// chain are not verified; use `validate` for that.
+    pub fn verify_head(&self) -> Result<[u8; 32], MyosotisError> {
+        let snapshot = self.latest_snapshot_for_head();
+        if let Some(s) = &snapshot
+            && self.hash_state(&s.state) != s.state_hash
+        {
+            return Err(MyosotisError::HeadSnapshotHashMismatch);
+        }
+
+        let start_index = self.commits_start_index_from_snapshot(snapshot.as_ref())?;
+        let mut state =
+            Self::replay_from_snapshot(snapshot.as_ref(), &self.commits[start_index..])?;
+        for mutation in &self.pending_mutations {
+            Self::apply_mutation(&mut state, mutation)?;
+        }
+
+        let head_hash = self.hash_state(&self.head_state);
+        if self.hash_state(&state) != head_hash {
+            return Err(MyosotisError::HeadStateMismatch);
+        }
+        Ok(head_hash)
+    }
 }
 
 impl Default for Memory {
@@ -0,0 +1,137 @@
+use crate::memory::Memory;
+use serde::Serialize;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters and timings for one `Memory`, from `Memory::metrics`. Counts
+/// start at zero when the `Memory` is created or loaded and are not stored
+/// in the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Metrics {
+    /// Commits made through this `Memory`.
+    pub commits: u64,
+    /// Mutations those commits applied.
+    pub mutations_applied: u64,
+    /// Time `storage::load` took to read, parse and validate the file.
+    pub load_time: Option<Duration>,
+    /// Part of `load_time` spent validating.
+    pub validation_time: Option<Duration>,
+    /// Size of the file when last loaded or saved, in bytes.
+    pub file_size: Option<u64>,
+}
+
+/// Where a `Memory` keeps its `Metrics`. Atomic so that `storage::save`,
+/// which only borrows the `Memory`, can record the file size.
+#[derive(Default)]
+pub(crate) struct MetricsRecorder {
+    commits: AtomicU64,
+    mutations_applied: AtomicU64,
+    /// Durations in nanoseconds, sizes in bytes, `u64::MAX` when unset.
+    load_time: OptionalU64,
+    validation_time: OptionalU64,
+    file_size: OptionalU64,
+}
+
+struct OptionalU64(AtomicU64);
+
+impl Default for OptionalU64 {
+    fn default() -> Self {
+        Self(AtomicU64::new(u64::MAX))
+    }
+}
+
+impl OptionalU64 {
+    fn get(&self) -> Option<u64> {
+        Some(self.0.load(Ordering::Relaxed)).filter(|v| *v != u64::MAX)
+    }
+
+    fn set(&self, value: u64) {
+        self.0.store(value.min(u64::MAX - 1), Ordering::Relaxed);
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+impl MetricsRecorder {
+    pub(crate) fn snapshot(&self) -> Metrics {
+        Metrics {
+            commits: self.commits.load(Ordering::Relaxed),
+            mutations_applied: self.mutations_applied.load(Ordering::Relaxed),
+            load_time: self.load_time.get().map(Duration::from_nanos),
+            validation_time: self.validation_time.get().map(Duration::from_nanos),
+            file_size: self.file_size.get(),
+        }
+    }
+
+    pub(crate) fn record_commit(&self, mutations: usize) {
+        self.commits.fetch_add(1, Ordering::Relaxed);
+        self.mutations_applied
+            .fetch_add(mutations as u64, Ordering::Relaxed);
+        #[cfg(feature = "telemetry")]
+        tracing::event!(
+            target: "myosotis::metrics",
+            tracing::Level::INFO,
+            monotonic_counter.commits = 1u64,
+            monotonic_counter.mutations_applied = mutations as u64,
+        );
+    }
+
+    pub(crate) fn record_validation(&self, validation_time: Duration) {
+        self.validation_time.set(nanos(validation_time));
+        #[cfg(feature = "telemetry")]
+        tracing::event!(
+            target: "myosotis::metrics",
+            tracing::Level::INFO,
+            histogram.validation_seconds = validation_time.as_secs_f64(),
+        );
+    }
+
+    pub(crate) fn record_load(&self, load_time: Duration, size: usize) {
+        self.load_time.set(nanos(load_time));
+        self.record_file_size(size);
+        #[cfg(feature = "telemetry")]
+        tracing::event!(
+            target: "myosotis::metrics",
+            tracing::Level::INFO,
+            histogram.load_seconds = load_time.as_secs_f64(),
+        );
+    }
+
+    pub(crate) fn record_file_size(&self, size: usize) {
+        self.file_size.set(size as u64);
+        #[cfg(feature = "telemetry")]
+        tracing::event!(target: "myosotis::metrics", tracing::Level::INFO, gauge.file_size = size as u64);
+    }
+}
+
+impl Clone for MetricsRecorder {
+    fn clone(&self) -> Self {
+        let copy = |v: &AtomicU64| AtomicU64::new(v.load(Ordering::Relaxed));
+        Self {
+            commits: copy(&self.commits),
+            mutations_applied: copy(&self.mutations_applied),
+            load_time: OptionalU64(copy(&self.load_time.0)),
+            validation_time: OptionalU64(copy(&self.validation_time.0)),
+            file_size: OptionalU64(copy(&self.file_size.0)),
+        }
+    }
+}
+
+impl fmt::Debug for MetricsRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.snapshot().fmt(f)
+    }
+}
+
+impl Memory {
+    /// Counters and timings since this `Memory` was created or loaded.
+    /// With the `telemetry` feature every update is also emitted as a
+    /// tracing event on the `myosotis::metrics` target, and loading,
+    /// validation and commits run in their own spans.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+}
@@ -0,0 +1,147 @@
+use crate::commit::{Commit, Mutation};
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use crate::paged::{self, PageEntry, PageIndex};
+use memmap2::Mmap;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+
+type State = HashMap<NodeId, Node>;
+
+/// A commit read straight out of a mapped page. The message and source
+/// borrow from the map unless they have escapes, and the mutations stay undecoded JSON in
+/// the map until `mutations` is called.
+#[derive(Debug, Deserialize)]
+pub struct CommitFrame<'a> {
+    pub id: u64,
+    pub parent: Option<u64>,
+    pub parent_hash: Option<[u8; 32]>,
+    pub hash: [u8; 32],
+    #[serde(borrow, deserialize_with = "borrowed_message")]
+    pub message: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "borrowed_message")]
+    pub source: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub state_hash: Option<[u8; 32]>,
+    #[serde(borrow)]
+    mutations: &'a RawValue,
+}
+
+/// serde only borrows a `Cow` that is a field itself, not one inside an
+/// `Option`, so the message and source go through a newtype.
+fn borrowed_message<'de, D>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Message<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Message>::deserialize(deserializer)?.map(|m| m.0))
+}
+
+impl CommitFrame<'_> {
+    /// Size of the encoded mutation payload in bytes.
+    pub fn mutations_len(&self) -> usize {
+        self.mutations.get().len()
+    }
+
+    pub fn mutations(&self) -> Result<Vec<Mutation>, MyosotisError> {
+        serde_json::from_str(self.mutations.get())
+            .map_err(|_| MyosotisError::MalformedFileStructure)
+    }
+
+    pub fn to_commit(&self) -> Result<Commit, MyosotisError> {
+        Ok(Commit {
+            id: self.id,
+            parent: self.parent,
+            parent_hash: self.parent_hash,
+            hash: self.hash,
+            message: self.message.as_deref().map(str::to_string),
+            source: self.source.as_deref().map(str::to_string),
+            state_hash: self.state_hash,
+            mutations: self.mutations()?,
+        })
+    }
+}
+
+/// Read-only view of a paged file through a memory map. Opening decodes
+/// only the index; pages are parsed when asked for, borrowing from the map
+/// rather than copying the file into memory.
+pub struct MappedMemory {
+    map: Mmap,
+    index: PageIndex,
+}
+
+/// Maps the paged file at `path` and decodes its index.
+#[tracing::instrument]
+pub fn open(path: &str) -> Result<MappedMemory, MyosotisError> {
+    let file = File::open(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    // SAFETY: the map is only read. Saves replace files by rename rather
+    // than writing in place, so the mapped file does not change under us;
+    // a process truncating it anyway makes reads fault, as with any map.
+    let map =
+        unsafe { Mmap::map(&file) }.map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    let index = paged::index_of(&map)?;
+    tracing::debug!(
+        bytes = map.len(),
+        pages = index.pages.len(),
+        "mapped paged memory"
+    );
+    Ok(MappedMemory { map, index })
+}
+
+impl MappedMemory {
+    /// Page table in commit order.
+    pub fn pages(&self) -> &[PageEntry] {
+        &self.index.pages
+    }
+
+    /// The commits of `page`, one of `pages()`.
+    pub fn frames(&self, page: &PageEntry) -> Result<Vec<CommitFrame<'_>>, MyosotisError> {
+        serde_json::from_slice(paged::slice(&self.map, page.extent)?)
+            .map_err(|_| MyosotisError::MalformedFileStructure)
+    }
+
+    /// Commits with ids in `from..=to`, parsing only the pages that hold
+    /// them.
+    pub fn frames_between(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<CommitFrame<'_>>, MyosotisError> {
+        let mut frames = Vec::new();
+        for page in &self.index.pages {
+            if page.last_commit < from || page.first_commit > to {
+                continue;
+            }
+            frames.extend(
+                self.frames(page)?
+                    .into_iter()
+                    .filter(|f| (from..=to).contains(&f.id)),
+            );
+        }
+        Ok(frames)
+    }
+
+    /// `PagedMemory::state_at_commit` over the map.
+    pub fn state_at_commit(&self, commit_id: u64) -> Result<State, MyosotisError> {
+        let (mut state, from) = self.index.base_state(commit_id, |e| {
+            paged::decode_state(paged::slice(&self.map, e)?)
+        })?;
+        let frames = self.frames_between(from, commit_id)?;
+        for frame in &frames {
+            for mutation in frame.mutations()? {
+                Memory::apply_mutation(&mut state, &mutation)?;
+            }
+        }
+        if let Some(target) = frames.last() {
+            self.index
+                .check_recorded_state(target.id, target.state_hash, &state)?;
+        }
+        Ok(state)
+    }
+}
@@ -0,0 +1,309 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use std::collections::{BTreeMap, HashMap};
+
+pub const ROOT_INO: u64 = 1;
+pub const HISTORY_DIR: &str = ".history";
+pub const TYPE_FILE: &str = ".type";
+
+#[derive(Debug, Clone)]
+pub enum MountEntry {
+    Dir { children: BTreeMap<String, u64> },
+    File { data: Vec<u8> },
+}
+
+/// Read-only directory tree exposing a memory as files.
+///
+/// Layout:
+/// - `/<node_id>/<field>` holds the field value as JSON
+/// - `/<node_id>/.type` holds the node type
+/// - `/.history/<commit_id>/...` repeats the layout at each commit
+///
+/// Field names are percent-encoded where they contain `%` or `/` or start
+/// with `.`, so no field can shadow `.type`. Each `.history/<commit_id>`
+/// directory is filled in from `Memory::state_at_commit` when it is first
+/// looked into, so mounting replays no history. Tombstoned nodes are
+/// omitted. Inode numbers are assigned in build order starting at
+/// `ROOT_INO`.
+#[derive(Debug, Clone)]
+pub struct MountTree {
+    mem: Memory,
+    entries: Vec<MountEntry>,
+    /// History directories not filled in yet, with their commit id.
+    unfilled: HashMap<u64, u64>,
+}
+
+fn field_file_name(key: &str) -> String {
+    let name = key.replace('%', "%25").replace('/', "%2F");
+    match name.strip_prefix('.') {
+        Some(rest) => format!("%2E{}", rest),
+        None => name,
+    }
+}
+
+impl MountTree {
+    pub fn build(mem: &Memory) -> Result<Self, MyosotisError> {
+        let mut tree = Self {
+            mem: mem.clone(),
+            entries: vec![MountEntry::Dir {
+                children: BTreeMap::new(),
+            }],
+            unfilled: HashMap::new(),
+        };
+        tree.add_state(ROOT_INO, mem.head_state())?;
+
+        let history = tree.add_dir(ROOT_INO, HISTORY_DIR);
+        for commit in &mem.commits {
+            let dir = tree.add_dir(history, &commit.id.to_string());
+            tree.unfilled.insert(dir, commit.id);
+        }
+
+        Ok(tree)
+    }
+
+    /// Fills in `ino` if it is a history directory not yet looked into.
+    fn fill(&mut self, ino: u64) -> Result<(), MyosotisError> {
+        if let Some(commit_id) = self.unfilled.remove(&ino) {
+            let state = self.mem.state_at_commit(commit_id)?;
+            self.add_state(ino, &state)?;
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, parent: u64, name: &str, entry: MountEntry) -> u64 {
+        self.entries.push(entry);
+        let ino = self.entries.len() as u64;
+        if let Some(MountEntry::Dir { children }) = self.entries.get_mut(parent as usize - 1) {
+            children.insert(name.to_string(), ino);
+        }
+        ino
+    }
+
+    fn add_dir(&mut self, parent: u64, name: &str) -> u64 {
+        self.push(
+            parent,
+            name,
+            MountEntry::Dir {
+                children: BTreeMap::new(),
+            },
+        )
+    }
+
+    fn add_file(&mut self, parent: u64, name: &str, data: Vec<u8>) -> u64 {
+        self.push(parent, name, MountEntry::File { data })
+    }
+
+    fn add_state(
+        &mut self,
+        parent: u64,
+        state: &HashMap<NodeId, Node>,
+    ) -> Result<(), MyosotisError> {
+        let mut ids: Vec<NodeId> = state.keys().copied().collect();
+        ids.sort_unstable();
+
+        for id in ids {
+            let Some(node) = state.get(&id) else {
+                continue;
+            };
+            if node.deleted {
+                continue;
+            }
+
+            let dir = self.add_dir(parent, &id.to_string());
+            self.add_file(dir, TYPE_FILE, format!("{}\n", node.ty).into_bytes());
+
+            let mut keys: Vec<&String> = node.fields.keys().collect();
+            keys.sort();
+            for key in keys {
+                if let Some(value) = node.fields.get(key) {
+                    let mut data = serde_json::to_string_pretty(&serde_json::to_value(value)?)?;
+                    data.push('\n');
+                    self.add_file(dir, &field_file_name(key), data.into_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The entry at `ino`, filling it in first if needed.
+    pub fn get(&mut self, ino: u64) -> Result<Option<&MountEntry>, MyosotisError> {
+        if ino == 0 {
+            return Ok(None);
+        }
+        self.fill(ino)?;
+        Ok(self.peek(ino))
+    }
+
+    /// The entry at `ino` as it stands; a directory may not be filled in.
+    fn peek(&self, ino: u64) -> Option<&MountEntry> {
+        self.entries.get((ino as usize).checked_sub(1)?)
+    }
+
+    pub fn lookup(&mut self, parent: u64, name: &str) -> Result<Option<u64>, MyosotisError> {
+        Ok(match self.get(parent)? {
+            Some(MountEntry::Dir { children }) => children.get(name).copied(),
+            _ => None,
+        })
+    }
+
+    /// Resolves a `/`-separated path relative to the root.
+    pub fn resolve(&mut self, path: &str) -> Result<Option<u64>, MyosotisError> {
+        let mut ino = ROOT_INO;
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            match self.lookup(ino, segment)? {
+                Some(child) => ino = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(ino))
+    }
+}
+
+#[cfg(feature = "fuse")]
+mod fs {
+    use super::{MountEntry, MountTree};
+    use fuser::{
+        Errno, FileAttr, FileHandle, FileType, Filesystem, Generation, INodeNo, LockOwner,
+        OpenFlags, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, Request,
+    };
+    use std::ffi::OsStr;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+    use std::time::{Duration, SystemTime};
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    pub struct MountFs {
+        tree: Mutex<MountTree>,
+    }
+
+    impl MountFs {
+        pub fn new(tree: MountTree) -> Self {
+            Self {
+                tree: Mutex::new(tree),
+            }
+        }
+
+        fn tree(&self) -> MutexGuard<'_, MountTree> {
+            self.tree.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        /// Attributes need no filling in: directories report size 0.
+        fn attr(tree: &MountTree, req: &Request, ino: u64) -> Option<FileAttr> {
+            let (kind, perm, size, nlink) = match tree.peek(ino)? {
+                MountEntry::Dir { .. } => (FileType::Directory, 0o555, 0, 2),
+                MountEntry::File { data } => (FileType::RegularFile, 0o444, data.len() as u64, 1),
+            };
+            Some(FileAttr {
+                ino: INodeNo(ino),
+                size,
+                blocks: size.div_ceil(512),
+                atime: SystemTime::UNIX_EPOCH,
+                mtime: SystemTime::UNIX_EPOCH,
+                ctime: SystemTime::UNIX_EPOCH,
+                crtime: SystemTime::UNIX_EPOCH,
+                kind,
+                perm,
+                nlink,
+                uid: req.uid(),
+                gid: req.gid(),
+                rdev: 0,
+                blksize: 4096,
+                flags: 0,
+            })
+        }
+    }
+
+    impl Filesystem for MountFs {
+        fn lookup(&self, req: &Request, parent: INodeNo, name: &OsStr, reply: ReplyEntry) {
+            let mut tree = self.tree();
+            let found = match name.to_str().map(|name| tree.lookup(parent.0, name)) {
+                Some(Ok(found)) => found,
+                Some(Err(_)) => return reply.error(Errno::EIO),
+                None => None,
+            };
+            match found.and_then(|ino| Self::attr(&tree, req, ino)) {
+                Some(attr) => reply.entry(&TTL, &attr, Generation(0)),
+                None => reply.error(Errno::ENOENT),
+            }
+        }
+
+        fn getattr(&self, req: &Request, ino: INodeNo, _fh: Option<FileHandle>, reply: ReplyAttr) {
+            match Self::attr(&self.tree(), req, ino.0) {
+                Some(attr) => reply.attr(&TTL, &attr),
+                None => reply.error(Errno::ENOENT),
+            }
+        }
+
+        fn read(
+            &self,
+            _req: &Request,
+            ino: INodeNo,
+            _fh: FileHandle,
+            offset: u64,
+            size: u32,
+            _flags: OpenFlags,
+            _lock_owner: Option<LockOwner>,
+            reply: ReplyData,
+        ) {
+            match self.tree().peek(ino.0) {
+                Some(MountEntry::File { data }) => {
+                    let start = (offset as usize).min(data.len());
+                    let end = start.saturating_add(size as usize).min(data.len());
+                    reply.data(&data[start..end]);
+                }
+                Some(MountEntry::Dir { .. }) => reply.error(Errno::EISDIR),
+                None => reply.error(Errno::ENOENT),
+            }
+        }
+
+        fn readdir(
+            &self,
+            _req: &Request,
+            ino: INodeNo,
+            _fh: FileHandle,
+            offset: u64,
+            mut reply: ReplyDirectory,
+        ) {
+            let mut tree = self.tree();
+            let children = match tree.get(ino.0) {
+                Ok(Some(MountEntry::Dir { children })) => children.clone(),
+                Ok(Some(MountEntry::File { .. })) => return reply.error(Errno::ENOTDIR),
+                Ok(None) => return reply.error(Errno::ENOENT),
+                Err(_) => return reply.error(Errno::EIO),
+            };
+
+            let mut entries = vec![
+                (ino.0, FileType::Directory, ".".to_string()),
+                (ino.0, FileType::Directory, "..".to_string()),
+            ];
+            for (name, child) in children {
+                let kind = match tree.peek(child) {
+                    Some(MountEntry::Dir { .. }) => FileType::Directory,
+                    _ => FileType::RegularFile,
+                };
+                entries.push((child, kind, name));
+            }
+
+            for (i, (child, kind, name)) in entries.into_iter().enumerate().skip(offset as usize) {
+                if reply.add(INodeNo(child), (i + 1) as u64, kind, name) {
+                    break;
+                }
+            }
+            reply.ok();
+        }
+    }
+}
+
+/// Mounts a read-only view of `mem` at `mountpoint`, blocking until unmounted.
+#[cfg(feature = "fuse")]
+pub fn mount(mem: &Memory, mountpoint: &str) -> Result<(), MyosotisError> {
+    let tree = MountTree::build(mem)?;
+    let mut config = fuser::Config::default();
+    config.mount_options = vec![
+        fuser::MountOption::RO,
+        fuser::MountOption::FSName("myosotis".to_string()),
+    ];
+    fuser::mount(fs::MountFs::new(tree), mountpoint, &config)?;
+    Ok(())
+}
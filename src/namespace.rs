@@ -0,0 +1,127 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+
+/// A named partition of the nodes in one `Memory`. All namespaces share
+/// the commit chain; the handle only scopes creates, lookups and writes.
+/// Nodes outside the namespace are reported as `NodeNotFound`.
+pub struct Namespace<'a> {
+    mem: &'a mut Memory,
+    name: String,
+}
+
+impl Memory {
+    /// Handle for the namespace `name`, which need not exist yet.
+    pub fn namespace(&mut self, name: &str) -> Namespace<'_> {
+        Namespace {
+            mem: self,
+            name: name.to_string(),
+        }
+    }
+
+    /// Stages moving node `id` into `namespace`, or with `None` into the
+    /// default namespace.
+    pub fn set_namespace(
+        &mut self,
+        id: NodeId,
+        namespace: Option<&str>,
+    ) -> Result<(), MyosotisError> {
+        self.check_lock(id)?;
+        let m = Mutation::SetNamespace {
+            id,
+            namespace: namespace.map(str::to_string),
+        };
+        Self::apply_mutation(&mut self.head_state, &m)?;
+        self.stage(m);
+        Ok(())
+    }
+
+    /// Names of the namespaces holding live, unarchived nodes, sorted.
+    pub fn namespaces(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .head_state
+            .values()
+            .filter(|node| !node.deleted && !node.archived)
+            .filter_map(|node| node.namespace.as_deref())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Live nodes in `namespace` (`None` for the default one), by id.
+    /// Archived nodes are left out.
+    pub fn nodes_in(&self, namespace: Option<&str>) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self
+            .head_state
+            .values()
+            .filter(|node| {
+                !node.deleted && !node.archived && node.namespace.as_deref() == namespace
+            })
+            .collect();
+        nodes.sort_unstable_by_key(|node| node.id);
+        nodes
+    }
+}
+
+impl Namespace<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Stages a new node of type `ty` in this namespace.
+    pub fn create(&mut self, ty: &str) -> Result<NodeId, MyosotisError> {
+        let id = self.mem.create(ty);
+        self.mem.set_namespace(id, Some(&self.name))?;
+        Ok(id)
+    }
+
+    /// The live node `id`, if it belongs to this namespace.
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.mem
+            .head_state
+            .get(&id)
+            .filter(|node| !node.deleted && node.namespace.as_deref() == Some(self.name.as_str()))
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Live, unarchived nodes in this namespace, by id.
+    pub fn nodes(&self) -> Vec<&Node> {
+        self.mem.nodes_in(Some(&self.name))
+    }
+
+    /// Live, unarchived nodes of type `ty` in this namespace, by id.
+    pub fn nodes_of_type(&self, ty: &str) -> Vec<&Node> {
+        self.nodes()
+            .into_iter()
+            .filter(|node| node.ty == ty)
+            .collect()
+    }
+
+    pub fn set(&mut self, id: NodeId, key: &str, value: Value) -> Result<(), MyosotisError> {
+        self.check_member(id)?;
+        self.mem.set(id, key, value)
+    }
+
+    pub fn delete_field(&mut self, id: NodeId, key: &str) -> Result<(), MyosotisError> {
+        self.check_member(id)?;
+        self.mem.delete_field(id, key)
+    }
+
+    pub fn delete_node(&mut self, id: NodeId) -> Result<(), MyosotisError> {
+        self.check_member(id)?;
+        self.mem.delete_node(id)
+    }
+
+    fn check_member(&self, id: NodeId) -> Result<(), MyosotisError> {
+        if self.contains(id) {
+            Ok(())
+        } else {
+            Err(MyosotisError::NodeNotFound(id))
+        }
+    }
+}
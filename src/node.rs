@@ -1,7 +1,109 @@
+use crate::error::MyosotisError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-pub type NodeId = u64;
+pub type NodeId = u128;
+
+/// How a file allocates new node ids. Fixed per file and recorded in the
+/// header; `Uuid` lets independently created files share one id space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdMode {
+    #[default]
+    Sequential,
+    Uuid,
+}
+
+impl IdMode {
+    pub fn is_sequential(&self) -> bool {
+        matches!(self, IdMode::Sequential)
+    }
+}
+
+/// Serde form of node ids in JSON: a number when the id fits in `u64`, a
+/// decimal string past it, since `serde_json::Value` holds no wider
+/// numbers. Both forms are read back.
+pub(crate) mod wide_id {
+    use super::NodeId;
+    use serde::de::{self, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(id: &NodeId, serializer: S) -> Result<S::Ok, S::Error> {
+        match u64::try_from(*id) {
+            Ok(id) => serializer.serialize_u64(id),
+            Err(_) => serializer.collect_str(id),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NodeId, D::Error> {
+        deserializer.deserialize_any(IdVisitor)
+    }
+
+    struct IdVisitor;
+
+    impl Visitor<'_> for IdVisitor {
+        type Value = NodeId;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a node id as a number or decimal string")
+        }
+
+        fn visit_u64<E: de::Error>(self, id: u64) -> Result<NodeId, E> {
+            Ok(id as NodeId)
+        }
+
+        fn visit_u128<E: de::Error>(self, id: u128) -> Result<NodeId, E> {
+            Ok(id)
+        }
+
+        fn visit_str<E: de::Error>(self, id: &str) -> Result<NodeId, E> {
+            match id.bytes().all(|b| b.is_ascii_digit()) {
+                true => id.parse().map_err(E::custom),
+                false => Err(E::invalid_value(de::Unexpected::Str(id), &self)),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Wide(#[serde(with = "super::wide_id")] NodeId);
+
+    /// `wide_id` for a list of ids.
+    pub mod list {
+        use super::{NodeId, Wide};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(ids: &[NodeId], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(ids.iter().map(|id| Wide(*id)))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<NodeId>, D::Error> {
+            let ids = Vec::<Wide>::deserialize(deserializer)?;
+            Ok(ids.into_iter().map(|Wide(id)| id).collect())
+        }
+    }
+
+    /// `wide_id` for an optional id.
+    pub mod option {
+        use super::{NodeId, Wide};
+        use serde::{Serialize, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            id: &Option<NodeId>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            id.map(Wide).serialize(serializer)
+        }
+    }
+}
+
+/// Whether `id` is in the range of the sequential counter. Random UUIDs
+/// have version bits above the low 64, so they never fall in it.
+pub fn is_sequential_id(id: NodeId) -> bool {
+    id < u64::MAX as NodeId
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Value {
@@ -9,15 +111,345 @@ pub enum Value {
     Float(f64),
     Bool(bool),
     Str(String),
-    Ref(NodeId),
+    Ref(#[serde(with = "wide_id")] NodeId),
     List(Vec<Value>),
     Map(HashMap<String, Value>),
+    /// Reference by alias (see `Node::alias`). Unlike `Ref` it is not
+    /// checked on write and is resolved when read, with `Memory::resolve`.
+    AliasRef(String),
+    /// Reference to a node in another file of a `Workspace`, named by the
+    /// alias the file was opened under. Checked when the workspace saves.
+    ExternalRef {
+        file_alias: String,
+        #[serde(with = "wide_id")]
+        node_id: NodeId,
+    },
+    /// Embedding vector, searched with `Memory::similar`.
+    Vector(Vec<f32>),
+}
+
+impl Value {
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_ref_id(&self) -> Option<NodeId> {
+        match self {
+            Value::Ref(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&HashMap<String, Value>> {
+        match self {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn as_vector(&self) -> Option<&[f32]> {
+        match self {
+            Value::Vector(v) => Some(v),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Node {
+    #[serde(with = "wide_id")]
     pub id: NodeId,
     pub ty: String,
     pub fields: HashMap<String, Value>,
     pub deleted: bool,
+    /// Unique external name such as `user:alice`, set by `SetAlias`. A
+    /// deleted node keeps its alias.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Namespace the node belongs to, set by `SetNamespace`; `None` is the
+    /// default namespace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Set by `SetArchived`. Archived nodes stay live and referenceable
+    /// but are left out of listings and searches.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// Set by `AddLabel` and `RemoveLabel`, apart from the fields. A
+    /// deleted node keeps its labels.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub labels: BTreeSet<String>,
+}
+
+/// One step into a field value: a map key or a list index. The first
+/// segment of a path is always the field name.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PathSeg {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses `field.key.0.key`. All-digit segments after the first are list
+/// indices; everything else is a map key.
+pub fn parse_path(path: &str) -> Result<Vec<PathSeg>, MyosotisError> {
+    let mut segs = Vec::new();
+    for (i, part) in path.split('.').enumerate() {
+        if part.is_empty() {
+            return Err(MyosotisError::InvalidPath(path.to_string()));
+        }
+        match part.parse::<usize>() {
+            Ok(index) if i > 0 && part.bytes().all(|b| b.is_ascii_digit()) => {
+                segs.push(PathSeg::Index(index))
+            }
+            _ => segs.push(PathSeg::Key(part.to_string())),
+        }
+    }
+    Ok(segs)
+}
+
+pub fn format_path(path: &[PathSeg]) -> String {
+    path.iter()
+        .map(|seg| match seg {
+            PathSeg::Key(key) => key.clone(),
+            PathSeg::Index(index) => index.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+impl Node {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields.get(key)
+    }
+
+    /// `None` if the field is missing or not a string; likewise for the
+    /// other typed getters.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        self.get(key)?.as_int()
+    }
+
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        self.get(key)?.as_float()
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.as_bool()
+    }
+
+    pub fn get_ref(&self, key: &str) -> Option<NodeId> {
+        self.get(key)?.as_ref_id()
+    }
+
+    pub fn get_list(&self, key: &str) -> Option<&[Value]> {
+        self.get(key)?.as_list()
+    }
+
+    pub fn get_map(&self, key: &str) -> Option<&HashMap<String, Value>> {
+        self.get(key)?.as_map()
+    }
+
+    pub fn get_vector(&self, key: &str) -> Option<&[f32]> {
+        self.get(key)?.as_vector()
+    }
+
+    /// Sets the value at `path`. Missing map keys along the way, including
+    /// the field itself, are created as empty maps; an index may address an
+    /// existing element or append at the end of a list.
+    pub fn set_path(&mut self, path: &[PathSeg], value: Value) -> Result<(), MyosotisError> {
+        let invalid = || MyosotisError::InvalidPath(format_path(path));
+        let (PathSeg::Key(field), rest) = path.split_first().ok_or_else(invalid)? else {
+            return Err(invalid());
+        };
+        let Some((last, inner)) = rest.split_last() else {
+            self.fields.insert(field.clone(), value);
+            return Ok(());
+        };
+        let mut target = self
+            .fields
+            .entry(field.clone())
+            .or_insert_with(|| Value::Map(HashMap::new()));
+        for seg in inner {
+            target = match (target, seg) {
+                (Value::Map(map), PathSeg::Key(key)) => map
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Map(HashMap::new())),
+                (Value::List(items), PathSeg::Index(index)) => {
+                    items.get_mut(*index).ok_or_else(invalid)?
+                }
+                _ => return Err(invalid()),
+            };
+        }
+        match (target, last) {
+            (Value::Map(map), PathSeg::Key(key)) => {
+                map.insert(key.clone(), value);
+            }
+            (Value::List(items), PathSeg::Index(index)) if *index < items.len() => {
+                items[*index] = value;
+            }
+            (Value::List(items), PathSeg::Index(index)) if *index == items.len() => {
+                items.push(value);
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+    /// Removes the value at `path`, which must exist. Later list elements
+    /// shift down. A one-segment path deletes the field.
+    pub fn delete_path(&mut self, path: &[PathSeg]) -> Result<(), MyosotisError> {
+        let invalid = || MyosotisError::InvalidPath(format_path(path));
+        let (PathSeg::Key(field), rest) = path.split_first().ok_or_else(invalid)? else {
+            return Err(invalid());
+        };
+        let Some((last, inner)) = rest.split_last() else {
+            return self
+                .fields
+                .remove(field)
+                .map(|_| ())
+                .ok_or_else(|| MyosotisError::FieldNotFound(field.clone()));
+        };
+        let mut target = self
+            .fields
+            .get_mut(field)
+            .ok_or_else(|| MyosotisError::FieldNotFound(field.clone()))?;
+        for seg in inner {
+            target = match (target, seg) {
+                (Value::Map(map), PathSeg::Key(key)) => map.get_mut(key),
+                (Value::List(items), PathSeg::Index(index)) => items.get_mut(*index),
+                _ => None,
+            }
+            .ok_or_else(invalid)?;
+        }
+        match (target, last) {
+            (Value::Map(map), PathSeg::Key(key)) => map.remove(key).map(|_| ()).ok_or_else(invalid),
+            (Value::List(items), PathSeg::Index(index)) if *index < items.len() => {
+                items.remove(*index);
+                Ok(())
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The list at `path`. With `create`, a missing last segment becomes an
+    /// empty list and missing map keys before it empty maps.
+    fn list_mut(
+        &mut self,
+        path: &[PathSeg],
+        create: bool,
+    ) -> Result<&mut Vec<Value>, MyosotisError> {
+        let invalid = || MyosotisError::InvalidPath(format_path(path));
+        let (PathSeg::Key(field), rest) = path.split_first().ok_or_else(invalid)? else {
+            return Err(invalid());
+        };
+        let empty = |last: bool| {
+            if last {
+                Value::List(Vec::new())
+            } else {
+                Value::Map(HashMap::new())
+            }
+        };
+        let mut target = if create {
+            self.fields
+                .entry(field.clone())
+                .or_insert_with(|| empty(rest.is_empty()))
+        } else {
+            self.fields
+                .get_mut(field)
+                .ok_or_else(|| MyosotisError::FieldNotFound(field.clone()))?
+        };
+        for (i, seg) in rest.iter().enumerate() {
+            target = match (target, seg) {
+                (Value::Map(map), PathSeg::Key(key)) => {
+                    if create {
+                        map.entry(key.clone())
+                            .or_insert_with(|| empty(i + 1 == rest.len()))
+                    } else {
+                        map.get_mut(key).ok_or_else(invalid)?
+                    }
+                }
+                (Value::List(items), PathSeg::Index(index)) => {
+                    items.get_mut(*index).ok_or_else(invalid)?
+                }
+                _ => return Err(invalid()),
+            };
+        }
+        match target {
+            Value::List(items) => Ok(items),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Appends to the list at `path`, creating it if missing.
+    pub fn list_push(&mut self, path: &[PathSeg], value: Value) -> Result<(), MyosotisError> {
+        self.list_mut(path, true)?.push(value);
+        Ok(())
+    }
+
+    /// Inserts at `index` (at most the list length), creating the list if
+    /// missing.
+    pub fn list_insert(
+        &mut self,
+        path: &[PathSeg],
+        index: usize,
+        value: Value,
+    ) -> Result<(), MyosotisError> {
+        let items = self.list_mut(path, true)?;
+        if index > items.len() {
+            return Err(MyosotisError::InvalidPath(format!(
+                "{}.{}",
+                format_path(path),
+                index
+            )));
+        }
+        items.insert(index, value);
+        Ok(())
+    }
+
+    /// Removes the element at `index`; later elements shift down.
+    pub fn list_remove(&mut self, path: &[PathSeg], index: usize) -> Result<(), MyosotisError> {
+        let items = self.list_mut(path, false)?;
+        if index >= items.len() {
+            return Err(MyosotisError::InvalidPath(format!(
+                "{}.{}",
+                format_path(path),
+                index
+            )));
+        }
+        items.remove(index);
+        Ok(())
+    }
 }
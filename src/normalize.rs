@@ -0,0 +1,81 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{NodeId, PathSeg};
+use std::collections::HashMap;
+
+/// Whether `mutation` reads or moves field `key` of its node other than by
+/// overwriting or deleting it outright.
+fn touches(mutation: &Mutation, key: &str) -> bool {
+    match mutation {
+        Mutation::DeleteNode { .. } | Mutation::RestoreNode { .. } => true,
+        Mutation::RenameField { from, to, .. } => from == key || to == key,
+        _ => matches!(
+            mutation.path().and_then(<[PathSeg]>::first),
+            Some(PathSeg::Key(k)) if k == key
+        ),
+    }
+}
+
+impl Memory {
+    /// Collapses redundant pending mutations before a commit: a field set
+    /// that a later set of the same field overwrites is dropped, and a set
+    /// followed by a delete of the same field leaves only the delete, or
+    /// nothing if the field did not exist before the set. A set stays when
+    /// a mutation in between could observe it (a rename or path mutation of
+    /// that field, or deleting or restoring the node).
+    ///
+    /// Head state is unchanged. Mutations staged with `stage_unchecked`
+    /// and everything after them are left as they are. Returns how many
+    /// mutations were dropped.
+    pub fn normalize_pending(&mut self) -> Result<usize, MyosotisError> {
+        let checked = self.applied_pending;
+        let mut state = self.committed_state()?;
+        let mut kept: Vec<Option<Mutation>> = Vec::with_capacity(checked);
+        // Latest kept set of each field, and whether the field existed
+        // before the first set of the run.
+        let mut open: HashMap<(NodeId, String), (usize, bool)> = HashMap::new();
+
+        for mutation in &self.pending_mutations[..checked] {
+            match mutation {
+                Mutation::SetField { id, key, .. } => {
+                    let existed = match open.remove(&(*id, key.clone())) {
+                        Some((at, existed)) => {
+                            kept[at] = None;
+                            existed
+                        }
+                        None => state.get(id).is_some_and(|n| n.fields.contains_key(key)),
+                    };
+                    open.insert((*id, key.clone()), (kept.len(), existed));
+                    kept.push(Some(mutation.clone()));
+                }
+                Mutation::DeleteField { id, key } => match open.remove(&(*id, key.clone())) {
+                    Some((at, existed)) => {
+                        kept[at] = None;
+                        if existed {
+                            kept.push(Some(mutation.clone()));
+                        }
+                    }
+                    None => kept.push(Some(mutation.clone())),
+                },
+                other => {
+                    let id = other.node_id();
+                    open.retain(|(node, key), _| *node != id || !touches(other, key));
+                    kept.push(Some(mutation.clone()));
+                }
+            }
+            Self::apply_mutation(&mut state, mutation)?;
+        }
+
+        let mut normalized: Vec<Mutation> = kept.into_iter().flatten().collect();
+        let dropped = checked - normalized.len();
+        self.applied_pending = normalized.len();
+        normalized.extend(self.pending_mutations.drain(checked..));
+        self.pending_mutations = normalized;
+        if dropped > 0 {
+            self.reset_wal();
+        }
+        tracing::debug!(dropped, "normalized pending mutations");
+        Ok(dropped)
+    }
+}
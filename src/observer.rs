@@ -0,0 +1,126 @@
+use crate::commit::{Commit, Mutation};
+use crate::memory::{Checkpoint, Memory};
+use crate::stats::MemoryFootprint;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Receives change notifications from a `Memory`.
+///
+/// Hooks run after the operation has fully succeeded and only get shared
+/// references, so they cannot alter or interrupt engine state. Use
+/// interior mutability to record what they see.
+pub trait Observer: Send + Sync {
+    /// A mutation was staged.
+    fn on_mutation(&self, _mutation: &Mutation) {}
+
+    /// A commit was appended to the log.
+    fn on_commit(&self, _commit: &Commit) {}
+
+    /// A checkpoint was written after a commit.
+    fn on_checkpoint(&self, _checkpoint: &Checkpoint) {}
+
+    /// A commit left the estimated footprint above the limit set with
+    /// `Memory::set_footprint_limit`.
+    fn on_footprint_exceeded(&self, _footprint: &MemoryFootprint, _limit: usize) {}
+}
+
+/// How a commit reached the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitOrigin {
+    /// Made by `Memory::commit`.
+    Local,
+    /// Taken from another memory by `Memory::fetch`.
+    Fetched,
+}
+
+/// A commit appended to the log, as sent to `Memory::subscribe` receivers.
+#[derive(Debug, Clone)]
+pub struct CommitEvent {
+    pub commit: Commit,
+    pub origin: CommitOrigin,
+}
+
+/// Registered observers and subscribers. Not serialized, and not carried
+/// over when a `Memory` is cloned, so scratch copies never notify.
+#[derive(Default)]
+pub struct Observers {
+    observers: Vec<Arc<dyn Observer>>,
+    subscribers: Mutex<Vec<Sender<CommitEvent>>>,
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.observers.len())
+    }
+}
+
+impl Observers {
+    pub(crate) fn mutation(&self, mutation: &Mutation) {
+        self.observers.iter().for_each(|o| o.on_mutation(mutation));
+    }
+
+    /// Notifies observers, then sends the commit to subscribers, dropping
+    /// those whose receiver is gone.
+    pub(crate) fn commit(&self, commit: &Commit, origin: CommitOrigin) {
+        self.observers.iter().for_each(|o| o.on_commit(commit));
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|sender| {
+            sender
+                .send(CommitEvent {
+                    commit: commit.clone(),
+                    origin,
+                })
+                .is_ok()
+        });
+    }
+
+    pub(crate) fn checkpoint(&self, checkpoint: &Checkpoint) {
+        self.observers
+            .iter()
+            .for_each(|o| o.on_checkpoint(checkpoint));
+    }
+
+    pub(crate) fn footprint_exceeded(&self, footprint: &MemoryFootprint, limit: usize) {
+        self.observers
+            .iter()
+            .for_each(|o| o.on_footprint_exceeded(footprint, limit));
+    }
+}
+
+impl Memory {
+    pub fn add_observer(&mut self, observer: Arc<dyn Observer>) {
+        self.observers.observers.push(observer);
+    }
+
+    /// Receives an event for every commit appended from now on, in log
+    /// order, with its mutations. Dropping the receiver unsubscribes.
+    pub fn subscribe(&mut self) -> Receiver<CommitEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.observers
+            .subscribers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sender);
+        receiver
+    }
+
+    /// Removes observers and subscribers.
+    pub fn clear_observers(&mut self) {
+        self.observers.observers.clear();
+        self.observers
+            .subscribers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
@@ -0,0 +1,432 @@
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::{Checkpoint, Memory};
+use crate::node::{Node, NodeId};
+use crate::storage::{self, LoadMode, StorageFormatV1};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+type State = HashMap<NodeId, Node>;
+
+pub const PAGED_MAGIC: &[u8; 8] = b"MYOPAGED";
+const INDEX_MAGIC: &[u8; 8] = b"MYOINDEX";
+
+/// Commits per page for `save`.
+pub const DEFAULT_PAGE_COMMITS: usize = 64;
+
+/// Where a page, checkpoint or genesis state lies in a paged file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct Extent {
+    pub(crate) offset: u64,
+    pub(crate) len: u64,
+}
+
+/// A run of consecutive commits stored together as one JSON array.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageEntry {
+    pub first_commit: u64,
+    pub last_commit: u64,
+    pub(crate) extent: Extent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckpointEntry {
+    commit_id: u64,
+    commit_hash: [u8; 32],
+    state_hash: [u8; 32],
+    extent: Extent,
+}
+
+/// Index segment at the end of a paged file. `header` is the file format
+/// of `storage::save` with its commits, checkpoints and genesis state
+/// left out, since those are stored in extents.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PageIndex {
+    page_commits: usize,
+    pub(crate) pages: Vec<PageEntry>,
+    checkpoints: Vec<CheckpointEntry>,
+    genesis: Option<Extent>,
+    header: StorageFormatV1,
+}
+
+/// Appends `bytes` to `out`, returning where they went.
+fn put(out: &mut Vec<u8>, bytes: Vec<u8>) -> Extent {
+    let extent = Extent {
+        offset: out.len() as u64,
+        len: bytes.len() as u64,
+    };
+    out.extend(bytes);
+    extent
+}
+
+/// `save_with` using `DEFAULT_PAGE_COMMITS`.
+pub fn save(path: &str, memory: &Memory) -> Result<(), MyosotisError> {
+    save_with(path, memory, DEFAULT_PAGE_COMMITS)
+}
+
+/// Writes `memory` as a paged file: `PAGED_MAGIC`, the commits in pages
+/// of `page_commits`, every checkpoint state in full, the genesis state,
+/// then the JSON index, its offset (u64 little-endian) and `INDEX_MAGIC`.
+/// Each part is JSON. Values are kept inline whatever the blob threshold.
+/// Overwriting a write-once paged file refuses the same changes
+/// `storage::save` does.
+#[tracing::instrument(skip(memory))]
+pub fn save_with(path: &str, memory: &Memory, page_commits: usize) -> Result<(), MyosotisError> {
+    if memory.is_ephemeral() {
+        return Err(MyosotisError::Ephemeral);
+    }
+    if let Ok(existing) = open(path) {
+        existing.check_write_once(memory)?;
+    }
+    let out = to_bytes(memory, page_commits)?;
+    storage::write_atomic(path, &out, memory.durability)?;
+    tracing::debug!(
+        commits = memory.commits.len(),
+        bytes = out.len(),
+        "saved paged memory"
+    );
+    Ok(())
+}
+
+/// The bytes `save_with` writes.
+pub fn to_bytes(memory: &Memory, page_commits: usize) -> Result<Vec<u8>, MyosotisError> {
+    if page_commits == 0 {
+        return Err(MyosotisError::InvalidInput(
+            "pages must hold at least one commit".to_string(),
+        ));
+    }
+
+    let mut out = PAGED_MAGIC.to_vec();
+
+    let mut pages = Vec::new();
+    for chunk in memory.commits.chunks(page_commits) {
+        let extent = put(&mut out, serde_json::to_vec(chunk)?);
+        pages.push(PageEntry {
+            first_commit: chunk[0].id,
+            last_commit: chunk[chunk.len() - 1].id,
+            extent,
+        });
+    }
+    let mut checkpoints = Vec::new();
+    for cp in &memory.checkpoints {
+        let extent = put(&mut out, serde_json::to_vec(&cp.state)?);
+        checkpoints.push(CheckpointEntry {
+            commit_id: cp.commit_id,
+            commit_hash: cp.commit_hash,
+            state_hash: cp.state_hash,
+            extent,
+        });
+    }
+    let genesis = match &memory.genesis_state {
+        Some(state) => Some(put(&mut out, serde_json::to_vec(state)?)),
+        None => None,
+    };
+
+    let mut header = storage::from_memory(memory);
+    header.commits.clear();
+    header.checkpoints.clear();
+    header.genesis_state = None;
+    let index = PageIndex {
+        page_commits,
+        pages,
+        checkpoints,
+        genesis,
+        header,
+    };
+    let index_offset = out.len() as u64;
+    out.extend(serde_json::to_vec(&index)?);
+    out.extend_from_slice(&index_offset.to_le_bytes());
+    out.extend_from_slice(INDEX_MAGIC);
+    Ok(out)
+}
+
+/// Read-only handle on a paged file. Opening reads only the index; each
+/// query then reads just the pages and checkpoint it needs, so history
+/// far from the query is never decoded.
+pub struct PagedMemory {
+    path: String,
+    index: PageIndex,
+    pages_read: Cell<usize>,
+}
+
+fn read_extent(path: &str, extent: Extent) -> Result<Vec<u8>, MyosotisError> {
+    let read_failed = |e| MyosotisError::ReadFailed(path.to_string(), e);
+    let mut file = File::open(path).map_err(read_failed)?;
+    file.seek(SeekFrom::Start(extent.offset))
+        .map_err(read_failed)?;
+    let mut bytes = vec![0u8; extent.len as usize];
+    file.read_exact(&mut bytes).map_err(read_failed)?;
+    Ok(bytes)
+}
+
+/// Where the index of a paged file of `len` bytes lies, from the last 16
+/// bytes of the file.
+pub(crate) fn index_extent(trailer: &[u8], len: u64) -> Result<Extent, MyosotisError> {
+    if trailer.len() != 16 || &trailer[8..] != INDEX_MAGIC {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    let mut offset = [0u8; 8];
+    offset.copy_from_slice(&trailer[..8]);
+    let offset = u64::from_le_bytes(offset);
+    let end = len.saturating_sub(16);
+    if offset < PAGED_MAGIC.len() as u64 || offset > end {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    Ok(Extent {
+        offset,
+        len: end - offset,
+    })
+}
+
+pub(crate) fn decode_index(bytes: &[u8]) -> Result<PageIndex, MyosotisError> {
+    serde_json::from_slice(bytes).map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+pub(crate) fn decode_state(bytes: &[u8]) -> Result<State, MyosotisError> {
+    serde_json::from_slice(bytes).map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+pub(crate) fn decode_page(bytes: &[u8]) -> Result<Vec<Commit>, MyosotisError> {
+    serde_json::from_slice(bytes).map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+/// Index of the paged file held in `bytes`, after checking its magic.
+pub(crate) fn index_of(bytes: &[u8]) -> Result<PageIndex, MyosotisError> {
+    if bytes.len() < PAGED_MAGIC.len() + 16 || !bytes.starts_with(PAGED_MAGIC) {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    let extent = index_extent(&bytes[bytes.len() - 16..], bytes.len() as u64)?;
+    decode_index(slice(bytes, extent)?)
+}
+
+/// The part of `bytes` that `extent` names.
+pub(crate) fn slice(bytes: &[u8], extent: Extent) -> Result<&[u8], MyosotisError> {
+    let start =
+        usize::try_from(extent.offset).map_err(|_| MyosotisError::MalformedFileStructure)?;
+    let len = usize::try_from(extent.len).map_err(|_| MyosotisError::MalformedFileStructure)?;
+    bytes
+        .get(start..start.saturating_add(len))
+        .ok_or(MyosotisError::MalformedFileStructure)
+}
+
+/// Reads a whole paged file held in `bytes`, as written by `to_bytes`,
+/// into a fully validated `Memory`.
+pub fn from_bytes(bytes: &[u8]) -> Result<Memory, MyosotisError> {
+    index_of(bytes)?.to_memory(
+        |page| decode_page(slice(bytes, page.extent)?),
+        |extent| decode_state(slice(bytes, extent)?),
+    )
+}
+
+impl PageIndex {
+    fn contains(&self, commit_id: u64) -> bool {
+        self.pages
+            .iter()
+            .any(|page| (page.first_commit..=page.last_commit).contains(&commit_id))
+    }
+
+    /// Builds a fully validated `Memory` from every page, read with
+    /// `read_page`, and every checkpoint and genesis state, read with
+    /// `read_state`.
+    pub(crate) fn to_memory(
+        &self,
+        read_page: impl Fn(&PageEntry) -> Result<Vec<Commit>, MyosotisError>,
+        read_state: impl Fn(Extent) -> Result<State, MyosotisError>,
+    ) -> Result<Memory, MyosotisError> {
+        let mut commits = Vec::new();
+        for page in &self.pages {
+            commits.extend(read_page(page)?);
+        }
+        let mut checkpoints = Vec::new();
+        for cp in &self.checkpoints {
+            checkpoints.push(Checkpoint {
+                commit_id: cp.commit_id,
+                commit_hash: cp.commit_hash,
+                state_hash: cp.state_hash,
+                state: read_state(cp.extent)?,
+            });
+        }
+        let genesis_state = self.genesis.map(&read_state).transpose()?;
+
+        let mut mem = storage::to_memory(self.header.clone(), true)?;
+        mem.genesis_state = genesis_state;
+        mem.commits = commits;
+        mem.checkpoints = checkpoints;
+        storage::validate_and_build_head(mem, LoadMode::Strict)
+    }
+
+    /// Checks `state` against the state hash commit `commit_id` recorded.
+    pub(crate) fn check_recorded_state(
+        &self,
+        commit_id: u64,
+        recorded: Option<[u8; 32]>,
+        state: &State,
+    ) -> Result<(), MyosotisError> {
+        Memory::check_recorded_state_with(self.header.canonicalization, commit_id, recorded, state)
+    }
+
+    /// State to replay commit `commit_id` onto, and the first commit id to
+    /// replay: the nearest checkpoint at or before it, or genesis, read with
+    /// `read` and checked against its hash.
+    pub(crate) fn base_state(
+        &self,
+        commit_id: u64,
+        read: impl Fn(Extent) -> Result<State, MyosotisError>,
+    ) -> Result<(State, u64), MyosotisError> {
+        if !self.contains(commit_id) {
+            return Err(MyosotisError::CommitNotFound(commit_id));
+        }
+        let canonicalization = self.header.canonicalization;
+        let checkpoint = self
+            .checkpoints
+            .iter()
+            .filter(|cp| cp.commit_id <= commit_id)
+            .max_by_key(|cp| cp.commit_id);
+        match checkpoint {
+            Some(cp) => {
+                let state = read(cp.extent)?;
+                if Memory::compute_state_hash_with(canonicalization, &state) != cp.state_hash {
+                    return Err(MyosotisError::CorruptCheckpointHash);
+                }
+                Ok((state, cp.commit_id + 1))
+            }
+            None => match self.genesis {
+                Some(extent) => {
+                    let state = read(extent)?;
+                    if Some(Memory::compute_state_hash_with(canonicalization, &state))
+                        != self.header.genesis_state_hash
+                    {
+                        return Err(MyosotisError::CorruptGenesisHash);
+                    }
+                    Ok((state, 0))
+                }
+                None => Ok((State::new(), 0)),
+            },
+        }
+    }
+}
+
+/// Opens the paged file at `path`, reading its magic and index only.
+#[tracing::instrument]
+pub fn open(path: &str) -> Result<PagedMemory, MyosotisError> {
+    let read_failed = |e| MyosotisError::ReadFailed(path.to_string(), e);
+    let mut file = File::open(path).map_err(read_failed)?;
+    let mut magic = [0u8; 8];
+    file.read_exact(&mut magic).map_err(read_failed)?;
+    if &magic != PAGED_MAGIC {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+
+    let len = file.seek(SeekFrom::End(-16)).map_err(read_failed)? + 16;
+    let mut trailer = [0u8; 16];
+    file.read_exact(&mut trailer).map_err(read_failed)?;
+    let extent = index_extent(&trailer, len)?;
+    let index = decode_index(&read_extent(path, extent)?)?;
+    tracing::debug!(pages = index.pages.len(), "opened paged memory");
+    Ok(PagedMemory {
+        path: path.to_string(),
+        index,
+        pages_read: Cell::new(0),
+    })
+}
+
+impl PagedMemory {
+    fn read_page(&self, page: &PageEntry) -> Result<Vec<Commit>, MyosotisError> {
+        self.pages_read.set(self.pages_read.get() + 1);
+        decode_page(&read_extent(&self.path, page.extent)?)
+    }
+
+    fn read_state(&self, extent: Extent) -> Result<State, MyosotisError> {
+        decode_state(&read_extent(&self.path, extent)?)
+    }
+
+    /// Refuses to replace this file with `memory` if the file is
+    /// write-once and `memory` drops or rewrites any of its history.
+    fn check_write_once(&self, memory: &Memory) -> Result<(), MyosotisError> {
+        let header = &self.index.header;
+        if !header.write_once {
+            return Ok(());
+        }
+        let stored = self.commits_between(0, u64::MAX)?;
+        storage::check_write_once_over(
+            header.write_once,
+            header.genesis_state_hash,
+            header.canonicalization,
+            stored.iter().map(|c| (c.id, c.hash)),
+            memory,
+        )
+    }
+
+    /// Page table in commit order.
+    pub fn pages(&self) -> &[PageEntry] {
+        &self.index.pages
+    }
+
+    /// Commits per page the file was written with.
+    pub fn page_commits(&self) -> usize {
+        self.index.page_commits
+    }
+
+    /// Pages read from disk since the file was opened.
+    pub fn pages_read(&self) -> usize {
+        self.pages_read.get()
+    }
+
+    pub fn head_commit_id(&self) -> Option<u64> {
+        self.index.pages.last().map(|page| page.last_commit)
+    }
+
+    /// Commits with ids in `from..=to`, reading only the pages that hold
+    /// them.
+    pub fn commits_between(&self, from: u64, to: u64) -> Result<Vec<Commit>, MyosotisError> {
+        let mut commits = Vec::new();
+        for page in &self.index.pages {
+            if page.last_commit < from || page.first_commit > to {
+                continue;
+            }
+            commits.extend(
+                self.read_page(page)?
+                    .into_iter()
+                    .filter(|c| (from..=to).contains(&c.id)),
+            );
+        }
+        Ok(commits)
+    }
+
+    /// Commit `id`, from the one page that holds it.
+    pub fn commit(&self, id: u64) -> Result<Commit, MyosotisError> {
+        self.commits_between(id, id)?
+            .pop()
+            .ok_or(MyosotisError::CommitNotFound(id))
+    }
+
+    /// State after commit `commit_id`: the nearest checkpoint at or before
+    /// it, or genesis, replayed through the pages up to it. The checkpoint
+    /// or genesis state is checked against its hash, and the result against
+    /// the state hash commit `commit_id` recorded, if any.
+    pub fn state_at_commit(&self, commit_id: u64) -> Result<State, MyosotisError> {
+        let (mut state, from) = self.index.base_state(commit_id, |e| self.read_state(e))?;
+        let commits = self.commits_between(from, commit_id)?;
+        for commit in &commits {
+            for mutation in &commit.mutations {
+                Memory::apply_mutation(&mut state, mutation)?;
+            }
+        }
+        if let Some(target) = commits.last() {
+            self.index
+                .check_recorded_state(target.id, target.state_hash, &state)?;
+        }
+        Ok(state)
+    }
+
+    /// Reads every page into a fully validated `Memory`.
+    pub fn to_memory(&self) -> Result<Memory, MyosotisError> {
+        self.index.to_memory(
+            |page| self.read_page(page),
+            |extent| self.read_state(extent),
+        )
+    }
+}
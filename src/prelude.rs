@@ -0,0 +1,11 @@
+//! The types and functions most programs need: `use myosotis::prelude::*;`.
+//! Everything here is kept stable across minor releases; modules outside
+//! it may still change.
+
+pub use crate::commit::{Commit, Mutation};
+pub use crate::error::MyosotisError;
+pub use crate::memory::Memory;
+pub use crate::node::{Node, NodeId, Value};
+pub use crate::storage::{
+    self, LoadLimits, LoadMode, exists, load, load_with_limits, load_with_mode, save,
+};
@@ -0,0 +1,21 @@
+use crate::commit::{Commit, Mutation};
+use crate::memory::Memory;
+
+impl Memory {
+    /// Commits made with `commit_with_source(.., source)`, oldest first.
+    pub fn commits_by_source(&self, source: &str) -> Vec<&Commit> {
+        self.commits
+            .iter()
+            .filter(|c| c.source.as_deref() == Some(source))
+            .collect()
+    }
+
+    /// `(commit_id, mutation)` of every committed mutation whose commit
+    /// names `source` as its provenance, oldest first.
+    pub fn mutations_by_source(&self, source: &str) -> Vec<(u64, &Mutation)> {
+        self.commits_by_source(source)
+            .into_iter()
+            .flat_map(|c| c.mutations.iter().map(move |m| (c.id, m)))
+            .collect()
+    }
+}
@@ -0,0 +1,270 @@
+use crate::backend::StorageBackend;
+use crate::canonical::Canonicalization;
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use crate::paged::{self, Extent, PAGED_MAGIC, PageIndex};
+use crate::storage;
+use object_store::path::Path;
+use object_store::{
+    Error as StoreError, GetOptions, ObjectStore, ObjectStoreExt, PutMode, PutPayload,
+    UpdateVersion,
+};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::runtime::Runtime;
+
+type State = HashMap<NodeId, Node>;
+
+/// How `ObjectStoreBackend::save` writes the object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// The JSON file of `storage::save`.
+    #[default]
+    File,
+    /// The paged file of `paged::save_with`, with this many commits per
+    /// page, so queries fetch only the pages they need.
+    Paged(usize),
+}
+
+/// The object as this backend last loaded or saved it.
+struct Seen {
+    version: UpdateVersion,
+    write_once: bool,
+    genesis_state_hash: Option<[u8; 32]>,
+    canonicalization: Canonicalization,
+    commits: Vec<(u64, [u8; 32])>,
+}
+
+impl Seen {
+    fn new(version: UpdateVersion, memory: &Memory) -> Self {
+        Self {
+            version,
+            write_once: memory.write_once,
+            genesis_state_hash: memory.genesis_state_hash,
+            canonicalization: memory.canonicalization,
+            commits: memory.commits.iter().map(|c| (c.id, c.hash)).collect(),
+        }
+    }
+}
+
+/// Memory kept as one object in S3, GCS, Azure or any other
+/// `object_store::ObjectStore`, for processes without a local disk.
+///
+/// Saves are optimistic: each one only succeeds if the object still has
+/// the ETag (or version) this backend last loaded or saved, and a backend
+/// that has done neither only creates the object. Losing a race fails with
+/// `SaveConflict`; load again, redo the change and save. Values are kept
+/// inline whatever the blob threshold.
+///
+/// Calls block on a runtime owned by the backend, so it must not be used
+/// from inside another async runtime.
+pub struct ObjectStoreBackend {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    layout: Layout,
+    runtime: Runtime,
+    seen: Mutex<Option<Seen>>,
+}
+
+/// A paged object's index, and the ETag every ranged read must match so
+/// that all of them see the same object.
+struct PagedObject {
+    index: PageIndex,
+    e_tag: Option<String>,
+}
+
+fn range(extent: Extent) -> Range<u64> {
+    extent.offset..extent.offset.saturating_add(extent.len)
+}
+
+impl ObjectStoreBackend {
+    /// Backend for the object at `location` in `store`, written with
+    /// `Layout::File`.
+    pub fn new(store: Arc<dyn ObjectStore>, location: &str) -> Result<Self, MyosotisError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            store,
+            location: Path::from(location),
+            layout: Layout::default(),
+            runtime,
+            seen: Mutex::new(None),
+        })
+    }
+
+    /// Sets how later saves write the object. Either layout is read back.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Reads the whole object, in either layout, with its version.
+    fn fetch(&self) -> Result<(Memory, UpdateVersion), MyosotisError> {
+        let (bytes, version) = self.runtime.block_on(async {
+            let got = self.store.get(&self.location).await?;
+            let version = UpdateVersion {
+                e_tag: got.meta.e_tag.clone(),
+                version: got.meta.version.clone(),
+            };
+            Ok::<_, StoreError>((got.bytes().await?, version))
+        })?;
+        let memory = if bytes.starts_with(PAGED_MAGIC) {
+            paged::from_bytes(&bytes)?
+        } else {
+            let data =
+                std::str::from_utf8(&bytes).map_err(|_| MyosotisError::MalformedFileStructure)?;
+            storage::from_str(data)?
+        };
+        Ok((memory, version))
+    }
+
+    fn read(&self, e_tag: &Option<String>, range: Range<u64>) -> Result<Vec<u8>, MyosotisError> {
+        let options = GetOptions::new()
+            .with_range(Some(range))
+            .with_if_match(e_tag.clone());
+        let bytes = self.runtime.block_on(async {
+            self.store
+                .get_opts(&self.location, options)
+                .await?
+                .bytes()
+                .await
+        })?;
+        Ok(bytes.to_vec())
+    }
+
+    /// The index of the object if it is paged, from its first and last
+    /// bytes, or `None` for a JSON file.
+    fn paged(&self) -> Result<Option<PagedObject>, MyosotisError> {
+        let meta = self.runtime.block_on(self.store.head(&self.location))?;
+        if meta.size < PAGED_MAGIC.len() as u64 + 16 {
+            return Ok(None);
+        }
+        let magic = self.read(&meta.e_tag, 0..PAGED_MAGIC.len() as u64)?;
+        if magic != PAGED_MAGIC {
+            return Ok(None);
+        }
+        let trailer = self.read(&meta.e_tag, meta.size - 16..meta.size)?;
+        let extent = paged::index_extent(&trailer, meta.size)?;
+        let index = paged::decode_index(&self.read(&meta.e_tag, range(extent))?)?;
+        Ok(Some(PagedObject {
+            index,
+            e_tag: meta.e_tag,
+        }))
+    }
+
+    fn paged_commits(
+        &self,
+        object: &PagedObject,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Commit>, MyosotisError> {
+        let mut commits = Vec::new();
+        for page in &object.index.pages {
+            if page.last_commit < from || page.first_commit > to {
+                continue;
+            }
+            let bytes = self.read(&object.e_tag, range(page.extent))?;
+            commits.extend(
+                paged::decode_page(&bytes)?
+                    .into_iter()
+                    .filter(|c| (from..=to).contains(&c.id)),
+            );
+        }
+        Ok(commits)
+    }
+
+    /// Commits with ids in `from..=to`. A paged object is read only as far
+    /// as its index and the pages holding them; a JSON file is read whole.
+    pub fn commits_between(&self, from: u64, to: u64) -> Result<Vec<Commit>, MyosotisError> {
+        match self.paged()? {
+            Some(object) => self.paged_commits(&object, from, to),
+            None => Ok(self
+                .fetch()?
+                .0
+                .commits
+                .into_iter()
+                .filter(|c| (from..=to).contains(&c.id))
+                .collect()),
+        }
+    }
+
+    /// State after commit `commit_id`. A paged object is read only as far
+    /// as its index, the nearest checkpoint or genesis, and the pages up to
+    /// the commit; a JSON file is read whole.
+    pub fn state_at_commit(&self, commit_id: u64) -> Result<State, MyosotisError> {
+        let Some(object) = self.paged()? else {
+            return self.fetch()?.0.state_at_commit(commit_id);
+        };
+        let (mut state, from) = object.index.base_state(commit_id, |extent| {
+            paged::decode_state(&self.read(&object.e_tag, range(extent))?)
+        })?;
+        for commit in self.paged_commits(&object, from, commit_id)? {
+            for mutation in &commit.mutations {
+                Memory::apply_mutation(&mut state, mutation)?;
+            }
+        }
+        Ok(state)
+    }
+}
+
+impl StorageBackend for ObjectStoreBackend {
+    /// Reads the object and remembers its version for the next save.
+    fn load(&self) -> Result<Memory, MyosotisError> {
+        let (memory, version) = self.fetch()?;
+        *self.seen.lock().unwrap_or_else(PoisonError::into_inner) =
+            Some(Seen::new(version, &memory));
+        tracing::debug!(location = %self.location, commits = memory.commits.len(), "loaded object");
+        Ok(memory)
+    }
+
+    /// Writes the object if nobody else has since this backend last loaded
+    /// or saved it, or creates it if this backend has done neither. A
+    /// write-once memory refuses the same changes `storage::save` does.
+    fn save(&mut self, memory: &Memory) -> Result<(), MyosotisError> {
+        if memory.is_ephemeral() {
+            return Err(MyosotisError::Ephemeral);
+        }
+        let seen = self.seen.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let mode = match seen {
+            Some(seen) => {
+                storage::check_write_once_over(
+                    seen.write_once,
+                    seen.genesis_state_hash,
+                    seen.canonicalization,
+                    seen.commits.iter().copied(),
+                    memory,
+                )?;
+                PutMode::Update(seen.version.clone())
+            }
+            None => PutMode::Create,
+        };
+        let bytes = match self.layout {
+            Layout::File => storage::to_string(memory)?.into_bytes(),
+            Layout::Paged(page_commits) => paged::to_bytes(memory, page_commits)?,
+        };
+        let len = bytes.len();
+
+        let put = self.runtime.block_on(self.store.put_opts(
+            &self.location,
+            PutPayload::from(bytes),
+            mode.into(),
+        ));
+        let result = match put {
+            Ok(result) => result,
+            Err(StoreError::Precondition { .. } | StoreError::AlreadyExists { .. }) => {
+                return Err(MyosotisError::SaveConflict(self.location.to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        *seen = Some(Seen::new(result.into(), memory));
+        tracing::debug!(location = %self.location, bytes = len, "saved object");
+        Ok(())
+    }
+}
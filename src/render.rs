@@ -0,0 +1,103 @@
+use crate::node::{NodeId, Value};
+
+/// Default limit for `myo show`: characters per string, items per list or map.
+pub const DEFAULT_MAX_LEN: usize = 200;
+
+/// A node id as plain JSON: a number, or a decimal string past `u64`.
+pub fn id_json(id: NodeId) -> serde_json::Value {
+    match u64::try_from(id) {
+        Ok(id) => id.into(),
+        Err(_) => id.to_string().into(),
+    }
+}
+
+/// Converts a value to plain JSON for display. Refs become `{"ref": id}`,
+/// alias refs `{"alias_ref": alias}`, external refs
+/// `{"external_ref": {"file": alias, "id": id}}` and vectors
+/// `{"vector": [..]}`, kept on one line by `pretty`.
+/// With `max_len`, longer strings are cut to that many characters and longer
+/// lists and maps to that many entries, each with a marker saying how much
+/// was left out.
+pub fn to_json(value: &Value, max_len: Option<usize>) -> serde_json::Value {
+    match value {
+        Value::Int(v) => serde_json::Value::from(*v),
+        Value::Float(v) => serde_json::Value::from(*v),
+        Value::Bool(v) => serde_json::Value::from(*v),
+        Value::Str(s) => serde_json::Value::String(truncate_str(s, max_len)),
+        Value::Ref(id) => serde_json::json!({ "ref": id_json(*id) }),
+        Value::AliasRef(alias) => serde_json::json!({ "alias_ref": alias }),
+        Value::ExternalRef {
+            file_alias,
+            node_id,
+        } => serde_json::json!({ "external_ref": { "file": file_alias, "id": id_json(*node_id) } }),
+        Value::Vector(v) => {
+            let limit = max_len.unwrap_or(usize::MAX);
+            let mut out: Vec<serde_json::Value> = v
+                .iter()
+                .take(limit)
+                .map(|c| serde_json::Value::from(*c))
+                .collect();
+            if v.len() > limit {
+                out.push(serde_json::Value::String(format!(
+                    "… {} more components",
+                    v.len() - limit
+                )));
+            }
+            serde_json::json!({ "vector": out })
+        }
+        Value::List(items) => {
+            let limit = max_len.unwrap_or(usize::MAX);
+            let mut out: Vec<serde_json::Value> = items
+                .iter()
+                .take(limit)
+                .map(|v| to_json(v, max_len))
+                .collect();
+            if items.len() > limit {
+                out.push(serde_json::Value::String(format!(
+                    "… {} more items",
+                    items.len() - limit
+                )));
+            }
+            serde_json::Value::Array(out)
+        }
+        Value::Map(map) => {
+            let limit = max_len.unwrap_or(usize::MAX);
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut out = serde_json::Map::new();
+            for key in keys.iter().take(limit) {
+                out.insert((*key).clone(), to_json(&map[*key], max_len));
+            }
+            if keys.len() > limit {
+                out.insert(
+                    "…".to_string(),
+                    serde_json::Value::String(format!("{} more entries", keys.len() - limit)),
+                );
+            }
+            serde_json::Value::Object(out)
+        }
+    }
+}
+
+fn truncate_str(s: &str, max_len: Option<usize>) -> String {
+    match max_len {
+        Some(limit) => match s.char_indices().nth(limit) {
+            Some((cut, _)) => format!("{}… ({} more chars)", &s[..cut], s[cut..].chars().count()),
+            None => s.to_string(),
+        },
+        None => s.to_string(),
+    }
+}
+
+/// Renders a value for the terminal: scalars on one line, lists and maps as
+/// indented JSON. Continuation lines are prefixed with `indent`.
+pub fn pretty(value: &Value, max_len: Option<usize>, indent: &str) -> String {
+    let json = to_json(value, max_len);
+    let text = match value {
+        Value::List(_) | Value::Map(_) => {
+            serde_json::to_string_pretty(&json).unwrap_or_else(|_| json.to_string())
+        }
+        _ => json.to_string(),
+    };
+    text.replace('\n', &format!("\n{}", indent))
+}
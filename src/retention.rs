@@ -0,0 +1,73 @@
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use serde::{Deserialize, Serialize};
+
+/// How much history `maintenance::apply_retention` keeps. Stored in the
+/// file outside the hash chain. Pruning folds the oldest commits into the
+/// genesis snapshot, so head state is never affected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionPolicy {
+    /// Keep at most this many commits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_commits: Option<usize>,
+    /// Prune commits made more than this many seconds ago. Commits with no
+    /// recorded time (made before a policy was set, or unbundled) never
+    /// count as old.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+    /// Commits tagged with any of these are kept, and with them everything
+    /// after. A commit is tagged `t` when its message is `t` or starts with
+    /// `t:`, so `session` keeps session markers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep_tags: Vec<String>,
+}
+
+impl RetentionPolicy {
+    pub fn keeps(&self, commit: &Commit) -> bool {
+        let Some(message) = commit.message.as_deref() else {
+            return false;
+        };
+        self.keep_tags.iter().any(|tag| {
+            message
+                .strip_prefix(tag.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+        })
+    }
+}
+
+impl Memory {
+    /// Commit the retention policy would fold into genesis at `now`
+    /// (seconds since the Unix epoch), or `None` if nothing is due.
+    pub fn retention_target(&self, now: u64) -> Option<u64> {
+        let policy = self.retention.as_ref()?;
+        let mut prune = 0;
+        if let Some(max_commits) = policy.max_commits {
+            prune = self.commits.len().saturating_sub(max_commits);
+        }
+        if let Some(max_age) = policy.max_age {
+            let cutoff = now.saturating_sub(max_age);
+            if let Some(index) = self.commits.iter().rposition(|c| {
+                self.commit_times
+                    .get(&c.id)
+                    .is_some_and(|&time| time < cutoff)
+            }) {
+                prune = prune.max(index + 1);
+            }
+        }
+        if let Some(kept) = self.commits[..prune].iter().position(|c| policy.keeps(c)) {
+            prune = kept;
+        }
+        prune.checked_sub(1).map(|index| self.commits[index].id)
+    }
+
+    /// Compacts according to the retention policy. Returns the commit
+    /// folded into genesis, or `None` if nothing was due.
+    pub fn apply_retention(&mut self, now: u64) -> Result<Option<u64>, MyosotisError> {
+        match self.retention_target(now) {
+            Some(target) => self.compact_in_memory(target).map(|_| Some(target)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{NodeId, Value};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A node in a script: a numeric id, a decimal id string (for ids past
+/// `u64`), or a name bound by an earlier `create` step.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum NodeRef {
+    Id(u64),
+    Name(String),
+}
+
+/// One step of a mutation script, tagged by `op`.
+///
+/// Values use the same JSON form as the file (`{"Str": "explore"}`), except
+/// that `{"Ref": "name"}` may name a node created earlier in the script.
+/// Node ids past `u64` are written as decimal strings.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    Create {
+        #[serde(rename = "type")]
+        ty: String,
+        /// Name later steps refer to the new node by.
+        #[serde(default, rename = "as")]
+        name: Option<String>,
+    },
+    Set {
+        node: NodeRef,
+        key: String,
+        value: serde_json::Value,
+    },
+    DeleteField {
+        node: NodeRef,
+        key: String,
+    },
+    Delete {
+        node: NodeRef,
+    },
+}
+
+/// Parses a script: a JSON array of steps.
+pub fn parse(data: &str) -> Result<Vec<Step>, MyosotisError> {
+    serde_json::from_str(data)
+        .map_err(|e| MyosotisError::InvalidInput(format!("invalid mutation script: {}", e)))
+}
+
+fn resolve(names: &BTreeMap<String, NodeId>, node: &NodeRef) -> Result<NodeId, MyosotisError> {
+    match node {
+        NodeRef::Id(id) => Ok(*id as NodeId),
+        NodeRef::Name(name) => match names.get(name) {
+            Some(id) => Ok(*id),
+            None => name
+                .parse()
+                .map_err(|_| MyosotisError::InvalidInput(format!("unknown node name '{}'", name))),
+        },
+    }
+}
+
+/// Decodes a script value, resolving `Ref`s given as strings.
+fn script_value(
+    names: &BTreeMap<String, NodeId>,
+    value: &serde_json::Value,
+) -> Result<Value, MyosotisError> {
+    let tagged = value.as_object().filter(|obj| obj.len() == 1);
+    Ok(match tagged.and_then(|obj| obj.iter().next()) {
+        Some((tag, serde_json::Value::String(name))) if tag == "Ref" => {
+            Value::Ref(resolve(names, &NodeRef::Name(name.clone()))?)
+        }
+        Some((tag, serde_json::Value::Array(items))) if tag == "List" => Value::List(
+            items
+                .iter()
+                .map(|v| script_value(names, v))
+                .collect::<Result<_, _>>()?,
+        ),
+        Some((tag, serde_json::Value::Object(obj))) if tag == "Map" => Value::Map(
+            obj.iter()
+                .map(|(k, v)| Ok((k.clone(), script_value(names, v)?)))
+                .collect::<Result<_, MyosotisError>>()?,
+        ),
+        _ => serde_json::from_value(value.clone())?,
+    })
+}
+
+impl Memory {
+    fn apply_step(
+        &mut self,
+        step: &Step,
+        names: &mut BTreeMap<String, NodeId>,
+    ) -> Result<(), MyosotisError> {
+        match step {
+            Step::Create { ty, name } => {
+                if let Some(name) = name
+                    && names.contains_key(name)
+                {
+                    return Err(MyosotisError::InvalidInput(format!(
+                        "node name '{}' is bound twice",
+                        name
+                    )));
+                }
+                let id = self.create(ty);
+                if let Some(name) = name {
+                    names.insert(name.clone(), id);
+                }
+            }
+            Step::Set { node, key, value } => {
+                let value = script_value(names, value)?;
+                self.set(resolve(names, node)?, key, value)?;
+            }
+            Step::DeleteField { node, key } => self.delete_field(resolve(names, node)?, key)?,
+            Step::Delete { node } => self.delete_node(resolve(names, node)?)?,
+        }
+        Ok(())
+    }
+
+    /// Stages every step of a script, in order. If one fails, the steps
+    /// staged before it are undone and its error is returned, so a script
+    /// applies fully or not at all. Returns the ids bound to names by
+    /// `create` steps. Nothing is committed.
+    pub fn apply_script(
+        &mut self,
+        steps: &[Step],
+    ) -> Result<BTreeMap<String, NodeId>, MyosotisError> {
+        let pending_before = self.pending_mutations.len();
+        let mut names = BTreeMap::new();
+        for (i, step) in steps.iter().enumerate() {
+            if let Err(err) = self.apply_step(step, &mut names) {
+                while self.pending_mutations.len() > pending_before {
+                    self.undo_last_pending()?;
+                }
+                tracing::debug!(step = i, error = %err, "mutation script rolled back");
+                return Err(err);
+            }
+        }
+        Ok(names)
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::verify::{hmac_sha256, to_hex};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+
+const SEAL_DOMAIN: &[u8] = b"myosotis-seal-v1";
+
+/// Pins the genesis state left by compaction, so that editing it later is
+/// caught on load even in `LoadMode::Unsafe`.
+///
+/// `value` covers `state_hash`. `algorithm` is `hmac-sha256` when signed
+/// with a key, else `sha256`, which only catches edits that leave the seal
+/// alone. Loads check the state hash and unkeyed values; keyed values need
+/// the key and are checked by `Memory::verify_seal`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Seal {
+    pub state_hash: [u8; 32],
+    pub algorithm: String,
+    pub value: String,
+}
+
+impl Seal {
+    pub fn new(state_hash: [u8; 32], key: Option<&[u8]>) -> Self {
+        let mut message = SEAL_DOMAIN.to_vec();
+        message.extend_from_slice(&state_hash);
+        match key {
+            Some(key) => Self {
+                state_hash,
+                algorithm: "hmac-sha256".to_string(),
+                value: to_hex(&hmac_sha256(key, &message)),
+            },
+            None => Self {
+                state_hash,
+                algorithm: "sha256".to_string(),
+                value: to_hex(&Sha256::digest(&message)),
+            },
+        }
+    }
+
+    pub fn is_keyed(&self) -> bool {
+        self.algorithm != "sha256"
+    }
+
+    /// Checks `value` with the same `key` passed to `new`.
+    pub fn valid(&self, key: Option<&[u8]>) -> bool {
+        *self == Self::new(self.state_hash, key)
+    }
+}
+
+/// Key that compaction and redaction re-sign keyed seals with. Never
+/// printed.
+#[derive(Clone, Default)]
+pub struct SealKey(Option<Vec<u8>>);
+
+impl fmt::Debug for SealKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "SealKey(..)"),
+            None => write!(f, "SealKey(None)"),
+        }
+    }
+}
+
+impl Memory {
+    fn genesis_hash(&self) -> [u8; 32] {
+        match &self.genesis_state {
+            Some(state) => self.hash_state(state),
+            None => self.hash_state(&HashMap::new()),
+        }
+    }
+
+    /// Seals the current genesis state, signed with HMAC-SHA256 under
+    /// `key` when given, replacing any earlier seal. Fails when nothing has
+    /// been compacted yet.
+    pub fn seal(&mut self, key: Option<&[u8]>) -> Result<&Seal, MyosotisError> {
+        if self.genesis_state.is_none() {
+            return Err(MyosotisError::InvalidInput(
+                "nothing to seal: history has not been compacted".to_string(),
+            ));
+        }
+        Ok(self.seal.insert(Seal::new(self.genesis_hash(), key)))
+    }
+
+    /// Removes the seal, returning it.
+    pub fn unseal(&mut self) -> Option<Seal> {
+        self.seal.take()
+    }
+
+    /// Sets the key used to re-sign a keyed seal when compaction or
+    /// redaction rewrites genesis. Not stored in the file. Without it those
+    /// operations are refused on a file with a keyed seal.
+    pub fn set_seal_key(&mut self, key: Option<Vec<u8>>) {
+        self.seal_key = SealKey(key);
+    }
+
+    /// Checks the seal against the genesis state and, for keyed seals,
+    /// against `key`. Fails with `InvalidInput` when there is no seal.
+    pub fn verify_seal(&self, key: Option<&[u8]>) -> Result<(), MyosotisError> {
+        let seal = self
+            .seal
+            .as_ref()
+            .ok_or_else(|| MyosotisError::InvalidInput("file has no seal".to_string()))?;
+        if seal.state_hash != self.genesis_hash() || !seal.valid(key) {
+            return Err(MyosotisError::SealMismatch);
+        }
+        Ok(())
+    }
+
+    /// Load-time check: everything `verify_seal` can check without a key.
+    pub(crate) fn validate_seal(&self) -> Result<(), MyosotisError> {
+        match &self.seal {
+            Some(seal) if seal.state_hash != self.genesis_hash() => {
+                Err(MyosotisError::SealMismatch)
+            }
+            Some(seal) if !seal.is_keyed() && !seal.valid(None) => Err(MyosotisError::SealMismatch),
+            _ => Ok(()),
+        }
+    }
+
+    /// The seal to store for a new genesis with `state_hash`. Existing
+    /// seals are renewed, keyed ones only when the key is set; `create`
+    /// adds a seal where there was none.
+    pub(crate) fn renewed_seal(
+        &self,
+        state_hash: [u8; 32],
+        create: bool,
+    ) -> Result<Option<Seal>, MyosotisError> {
+        let key = self.seal_key.0.as_deref();
+        match &self.seal {
+            None if !create => Ok(None),
+            Some(seal) if seal.is_keyed() && key.is_none() => Err(MyosotisError::InvalidInput(
+                "genesis is sealed with a key; set it with set_seal_key or unseal first"
+                    .to_string(),
+            )),
+            _ => Ok(Some(Seal::new(state_hash, key))),
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//! gRPC access to a `SharedMemory`, behind the `server` feature.
+//!
+//! The service is `myosotis.Memory` with seven unary methods: `GetNode`,
+//! `Mutate`, `Commit`, `DryRun`, `History`, `StateAt` and `Compact`. Node ids travel as decimal
+//! strings because UUID ids do not fit 64 bits. Field values and
+//! mutations travel as JSON in the same encoding the file format uses.
+//!
+//! `Mutate` stages a batch of operations; if any of them fails, the ones
+//! already staged from that batch are undone and the pending set is left
+//! as it was. `Commit` commits everything pending, whoever staged it.
+//! `DryRun` is `Memory::dry_run_commit`: it checks a batch of mutations
+//! as if staged after everything pending and returns the commit that
+//! would result, staging nothing.
+//!
+//! `Compact` folds history into genesis while the service keeps serving.
+//! A copy of the memory is compacted and written to `FILE.tmp` without
+//! holding the lock; the write lock is only taken to swap the compacted
+//! history in and rename the file over the old one. Commits made in the
+//! meantime are rebased onto the compacted chain and the file is saved
+//! again instead of renamed. Pending mutations are kept.
+
+use crate::commit::{Commit, Mutation};
+use crate::error::MyosotisError;
+use crate::maintenance::DefaultCompaction;
+use crate::memory::{CommitPreview, Memory};
+use crate::node::{Node, NodeId, Value};
+use crate::shared::SharedMemory;
+use crate::storage;
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    use std::collections::BTreeMap;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetNodeRequest {
+        #[prost(string, tag = "1")]
+        pub id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct NodeReply {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub ty: String,
+        #[prost(bool, tag = "3")]
+        pub deleted: bool,
+        /// Field values as JSON.
+        #[prost(btree_map = "string, string", tag = "4")]
+        pub fields: BTreeMap<String, String>,
+        #[prost(string, optional, tag = "5")]
+        pub alias: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub namespace: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CreateNode {
+        #[prost(string, tag = "1")]
+        pub ty: String,
+        #[prost(string, optional, tag = "2")]
+        pub namespace: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SetField {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub key: String,
+        /// The value as JSON.
+        #[prost(string, tag = "3")]
+        pub value: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DeleteField {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub key: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DeleteNode {
+        #[prost(string, tag = "1")]
+        pub id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Operation {
+        #[prost(oneof = "operation::Kind", tags = "1, 2, 3, 4")]
+        pub kind: Option<operation::Kind>,
+    }
+
+    pub mod operation {
+        #[derive(Clone, PartialEq, prost::Oneof)]
+        pub enum Kind {
+            #[prost(message, tag = "1")]
+            CreateNode(super::CreateNode),
+            #[prost(message, tag = "2")]
+            SetField(super::SetField),
+            #[prost(message, tag = "3")]
+            DeleteField(super::DeleteField),
+            #[prost(message, tag = "4")]
+            DeleteNode(super::DeleteNode),
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MutateRequest {
+        #[prost(message, repeated, tag = "1")]
+        pub operations: Vec<Operation>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct MutateResponse {
+        /// Ids of the nodes created by the batch, in order.
+        #[prost(string, repeated, tag = "1")]
+        pub created_ids: Vec<String>,
+        /// Pending mutations after the batch, from all clients.
+        #[prost(uint64, tag = "2")]
+        pub pending: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CommitRequest {
+        #[prost(string, optional, tag = "1")]
+        pub message: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CommitInfo {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(uint64, optional, tag = "2")]
+        pub parent: Option<u64>,
+        /// Commit hash as lowercase hex.
+        #[prost(string, tag = "3")]
+        pub hash: String,
+        #[prost(string, optional, tag = "4")]
+        pub message: Option<String>,
+        /// Mutations as JSON, one per entry.
+        #[prost(string, repeated, tag = "5")]
+        pub mutations: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CommitResponse {
+        #[prost(message, optional, tag = "1")]
+        pub commit: Option<CommitInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DryRunRequest {
+        #[prost(string, optional, tag = "1")]
+        pub message: Option<String>,
+        /// Mutations as JSON, one per entry, with explicit node ids.
+        #[prost(string, repeated, tag = "2")]
+        pub mutations: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DryRunResponse {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        /// Hashes as lowercase hex.
+        #[prost(string, optional, tag = "2")]
+        pub parent_hash: Option<String>,
+        #[prost(string, tag = "3")]
+        pub hash: String,
+        #[prost(string, tag = "4")]
+        pub state_hash: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HistoryRequest {
+        /// Only return commits with a greater id.
+        #[prost(uint64, optional, tag = "1")]
+        pub after: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HistoryResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub commits: Vec<CommitInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StateAtRequest {
+        #[prost(uint64, tag = "1")]
+        pub commit_id: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StateAtResponse {
+        /// All nodes, tombstones included, ordered by id.
+        #[prost(message, repeated, tag = "1")]
+        pub nodes: Vec<NodeReply>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactRequest {
+        /// Commit to fold into genesis; by default the latest checkpoint,
+        /// else the last commit.
+        #[prost(uint64, optional, tag = "1")]
+        pub at: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactResponse {
+        #[prost(uint64, tag = "1")]
+        pub target_commit_id: u64,
+        #[prost(uint64, tag = "2")]
+        pub remaining_commits: u64,
+        /// Commits made while compacting, rebased onto the result.
+        #[prost(uint64, tag = "3")]
+        pub rebased_commits: u64,
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/myosotis.Memory.rs"));
+}
+
+use proto::memory_server::{Memory as MemoryRpc, MemoryServer};
+use proto::operation::Kind;
+
+fn status(err: MyosotisError) -> Status {
+    let message = err.to_string();
+    match err {
+        MyosotisError::NodeNotFound(_)
+        | MyosotisError::CommitNotFound(_)
+        | MyosotisError::FieldNotFound(_)
+        | MyosotisError::DeleteNonexistentNode(_) => Status::not_found(message),
+        MyosotisError::InvalidInput(_)
+        | MyosotisError::InvalidCompactionTarget
+        | MyosotisError::InvalidPath(_)
+        | MyosotisError::FloatPolicyViolation(..)
+        | MyosotisError::Serde(_) => Status::invalid_argument(message),
+        MyosotisError::NodeDeleted(_)
+        | MyosotisError::DeleteOnDeletedNode(_)
+        | MyosotisError::NodeLocked(..)
+        | MyosotisError::AliasTaken(..)
+        | MyosotisError::PolicyViolation(_)
+        | MyosotisError::UniqueViolation(..)
+        | MyosotisError::WriteOnceViolation(_) => Status::failed_precondition(message),
+        _ => Status::internal(message),
+    }
+}
+
+fn parse_id(id: &str) -> Result<NodeId, MyosotisError> {
+    id.parse()
+        .map_err(|_| MyosotisError::InvalidInput(format!("invalid node id: {:?}", id)))
+}
+
+fn hex(hash: &[u8; 32]) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn node_reply(node: &Node) -> Result<proto::NodeReply, MyosotisError> {
+    let mut fields = std::collections::BTreeMap::new();
+    for (key, value) in &node.fields {
+        fields.insert(key.clone(), serde_json::to_string(value)?);
+    }
+    Ok(proto::NodeReply {
+        id: node.id.to_string(),
+        ty: node.ty.clone(),
+        deleted: node.deleted,
+        fields,
+        alias: node.alias.clone(),
+        namespace: node.namespace.clone(),
+    })
+}
+
+fn commit_info(commit: &Commit) -> Result<proto::CommitInfo, MyosotisError> {
+    let mutations = commit
+        .mutations
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<_, _>>()?;
+    Ok(proto::CommitInfo {
+        id: commit.id,
+        parent: commit.parent,
+        hash: hex(&commit.hash),
+        message: commit.message.clone(),
+        mutations,
+    })
+}
+
+fn preview_reply(preview: CommitPreview) -> proto::DryRunResponse {
+    proto::DryRunResponse {
+        id: preview.id,
+        parent_hash: preview.parent_hash.as_ref().map(hex),
+        hash: hex(&preview.hash),
+        state_hash: hex(&preview.state_hash),
+    }
+}
+
+fn apply(mem: &mut Memory, kind: Kind, created: &mut Vec<String>) -> Result<(), MyosotisError> {
+    match kind {
+        Kind::CreateNode(op) => {
+            let id = match &op.namespace {
+                Some(name) => mem.namespace(name).create(&op.ty)?,
+                None => mem.create(&op.ty),
+            };
+            created.push(id.to_string());
+        }
+        Kind::SetField(op) => {
+            let value: Value = serde_json::from_str(&op.value)?;
+            mem.set(parse_id(&op.id)?, &op.key, value)?;
+        }
+        Kind::DeleteField(op) => mem.delete_field(parse_id(&op.id)?, &op.key)?,
+        Kind::DeleteNode(op) => mem.delete_node(parse_id(&op.id)?)?,
+    }
+    Ok(())
+}
+
+/// Writes the compacted `copy` of the memory file `path` to `path.tmp`
+/// and reads it back. Returns the temporary path.
+fn write_replacement(path: &str, copy: &Memory) -> Result<String, MyosotisError> {
+    let tmp_path = format!("{}.tmp", path);
+    let written = storage::save_replacement(&tmp_path, path, copy).and_then(|()| {
+        let reloaded = storage::load_replacement(&tmp_path, path)?;
+        if reloaded.committed_state()? == copy.committed_state()? {
+            Ok(())
+        } else {
+            Err(MyosotisError::CompactionIntegrityMismatch)
+        }
+    });
+    match written {
+        Ok(()) => Ok(tmp_path),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+/// The `myosotis.Memory` service over a `SharedMemory`.
+#[derive(Debug, Clone)]
+pub struct MemoryService {
+    shared: SharedMemory,
+    path: Option<String>,
+    /// Held for a whole `Compact`, so two never overlap.
+    compacting: Arc<Mutex<()>>,
+}
+
+impl MemoryService {
+    pub fn new(shared: SharedMemory) -> Self {
+        Self {
+            shared,
+            path: None,
+            compacting: Arc::default(),
+        }
+    }
+
+    /// Saves the memory to `path` after every successful commit. A commit
+    /// whose save fails is reported as an error but stays in memory.
+    pub fn persist_to(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn into_server(self) -> MemoryServer<Self> {
+        MemoryServer::new(self)
+    }
+
+    fn mutate_batch(
+        &self,
+        operations: Vec<proto::Operation>,
+    ) -> Result<proto::MutateResponse, MyosotisError> {
+        self.shared.write(|mem| {
+            let pending_before = mem.pending_mutations.len();
+            let mut created = Vec::new();
+            for operation in operations {
+                let result = match operation.kind {
+                    Some(kind) => apply(mem, kind, &mut created),
+                    None => Err(MyosotisError::InvalidInput("empty operation".to_string())),
+                };
+                if let Err(err) = result {
+                    while mem.pending_mutations.len() > pending_before {
+                        mem.undo_last_pending()?;
+                    }
+                    return Err(err);
+                }
+            }
+            Ok(proto::MutateResponse {
+                created_ids: created,
+                pending: mem.pending_mutations.len() as u64,
+            })
+        })
+    }
+
+    fn commit_pending(
+        &self,
+        message: Option<String>,
+    ) -> Result<proto::CommitResponse, MyosotisError> {
+        self.shared.write(|mem| {
+            mem.commit(message)?;
+            if let Some(path) = &self.path {
+                storage::save(path, mem)?;
+            }
+            let commit = mem.commits.last().map(commit_info).transpose()?;
+            Ok(proto::CommitResponse { commit })
+        })
+    }
+
+    fn compact_history(&self, at: Option<u64>) -> Result<proto::CompactResponse, MyosotisError> {
+        let _compacting = self
+            .compacting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut copy = self.shared.snapshot();
+        let base = copy
+            .commits
+            .last()
+            .map(|c| c.hash)
+            .ok_or(MyosotisError::InvalidCompactionTarget)?;
+        let target_commit_id = copy.compact_with(&mut DefaultCompaction { at })?;
+        let replacement = match &self.path {
+            Some(path) => Some(write_replacement(path, &copy)?),
+            None => None,
+        };
+
+        let swapped = self.shared.write(|mem| {
+            let rebased = mem.adopt_compacted(copy, base)?;
+            if let (Some(path), Some(tmp_path)) = (&self.path, &replacement) {
+                if rebased == 0 {
+                    fs::rename(tmp_path, path)
+                        .map_err(|e| MyosotisError::ReplaceFailed(path.clone(), e))?;
+                } else {
+                    storage::save(path, mem)?;
+                }
+            }
+            Ok(proto::CompactResponse {
+                target_commit_id,
+                remaining_commits: mem.commits.len() as u64,
+                rebased_commits: rebased as u64,
+            })
+        });
+        // Already gone if it was renamed over the file.
+        if let Some(tmp_path) = &replacement {
+            let _ = fs::remove_file(tmp_path);
+        }
+        swapped
+    }
+}
+
+#[tonic::async_trait]
+impl MemoryRpc for MemoryService {
+    async fn get_node(
+        &self,
+        request: Request<proto::GetNodeRequest>,
+    ) -> Result<Response<proto::NodeReply>, Status> {
+        let id = parse_id(&request.into_inner().id).map_err(status)?;
+        self.shared
+            .read(|mem| {
+                let node = mem
+                    .head_state
+                    .get(&id)
+                    .ok_or(MyosotisError::NodeNotFound(id))?;
+                node_reply(node)
+            })
+            .map(Response::new)
+            .map_err(status)
+    }
+
+    async fn mutate(
+        &self,
+        request: Request<proto::MutateRequest>,
+    ) -> Result<Response<proto::MutateResponse>, Status> {
+        self.mutate_batch(request.into_inner().operations)
+            .map(Response::new)
+            .map_err(status)
+    }
+
+    async fn commit(
+        &self,
+        request: Request<proto::CommitRequest>,
+    ) -> Result<Response<proto::CommitResponse>, Status> {
+        self.commit_pending(request.into_inner().message)
+            .map(Response::new)
+            .map_err(status)
+    }
+
+    async fn dry_run(
+        &self,
+        request: Request<proto::DryRunRequest>,
+    ) -> Result<Response<proto::DryRunResponse>, Status> {
+        let request = request.into_inner();
+        let batch = request
+            .mutations
+            .iter()
+            .map(|m| serde_json::from_str::<Mutation>(m))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| status(e.into()))?;
+        self.shared
+            .read(|mem| mem.dry_run_commit(request.message, &batch))
+            .map(|preview| Response::new(preview_reply(preview)))
+            .map_err(status)
+    }
+
+    async fn history(
+        &self,
+        request: Request<proto::HistoryRequest>,
+    ) -> Result<Response<proto::HistoryResponse>, Status> {
+        let after = request.into_inner().after;
+        self.shared
+            .read(|mem| {
+                let commits = mem
+                    .commits
+                    .iter()
+                    .filter(|c| after.is_none_or(|after| c.id > after))
+                    .map(commit_info)
+                    .collect::<Result<_, _>>()?;
+                Ok(proto::HistoryResponse { commits })
+            })
+            .map(Response::new)
+            .map_err(status)
+    }
+
+    async fn state_at(
+        &self,
+        request: Request<proto::StateAtRequest>,
+    ) -> Result<Response<proto::StateAtResponse>, Status> {
+        let commit_id = request.into_inner().commit_id;
+        self.shared
+            .read(|mem| {
+                let state: HashMap<NodeId, Node> = mem.state_at_commit(commit_id)?;
+                let mut nodes: Vec<&Node> = state.values().collect();
+                nodes.sort_by_key(|n| n.id);
+                let nodes = nodes
+                    .into_iter()
+                    .map(node_reply)
+                    .collect::<Result<_, _>>()?;
+                Ok(proto::StateAtResponse { nodes })
+            })
+            .map(Response::new)
+            .map_err(status)
+    }
+
+    async fn compact(
+        &self,
+        request: Request<proto::CompactRequest>,
+    ) -> Result<Response<proto::CompactResponse>, Status> {
+        let at = request.into_inner().at;
+        let service = self.clone();
+        tokio::task::spawn_blocking(move || service.compact_history(at))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map(Response::new)
+            .map_err(status)
+    }
+}
+
+/// Serves `service` on `addr` until the process is stopped.
+pub async fn serve(service: MemoryService, addr: SocketAddr) -> Result<(), MyosotisError> {
+    tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve(addr)
+        .await
+        .map_err(|e| MyosotisError::ServerFailed(e.to_string()))
+}
@@ -0,0 +1,146 @@
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId};
+use serde::Serialize;
+use std::collections::HashMap;
+
+const BEGIN_PREFIX: &str = "session:begin ";
+const END_PREFIX: &str = "session:end ";
+
+/// Session boundary recorded as an empty commit whose message is
+/// `session:begin <id>` or `session:end <id>`. Non-empty commits are never
+/// markers, whatever their message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionMarker {
+    Begin(u64),
+    End(u64),
+}
+
+impl SessionMarker {
+    pub fn of(commit: &Commit) -> Option<Self> {
+        if !commit.mutations.is_empty() {
+            return None;
+        }
+        let message = commit.message.as_deref()?;
+        if let Some(id) = message.strip_prefix(BEGIN_PREFIX) {
+            id.parse().ok().map(SessionMarker::Begin)
+        } else if let Some(id) = message.strip_prefix(END_PREFIX) {
+            id.parse().ok().map(SessionMarker::End)
+        } else {
+            None
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            SessionMarker::Begin(id) => format!("{}{}", BEGIN_PREFIX, id),
+            SessionMarker::End(id) => format!("{}{}", END_PREFIX, id),
+        }
+    }
+}
+
+/// A session as found in the commit log. Sessions whose begin marker was
+/// compacted into genesis are not listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionInfo {
+    pub id: u64,
+    pub begin_commit: u64,
+    /// `None` while the session is open.
+    pub end_commit: Option<u64>,
+}
+
+impl Memory {
+    /// Sessions in commit order.
+    pub fn sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = Vec::new();
+        for commit in &self.commits {
+            match SessionMarker::of(commit) {
+                Some(SessionMarker::Begin(id)) => sessions.push(SessionInfo {
+                    id,
+                    begin_commit: commit.id,
+                    end_commit: None,
+                }),
+                Some(SessionMarker::End(id)) => {
+                    if let Some(session) = sessions
+                        .iter_mut()
+                        .rev()
+                        .find(|s| s.id == id && s.end_commit.is_none())
+                    {
+                        session.end_commit = Some(commit.id);
+                    }
+                }
+                None => {}
+            }
+        }
+        sessions
+    }
+
+    pub fn current_session(&self) -> Option<u64> {
+        self.sessions()
+            .last()
+            .filter(|s| s.end_commit.is_none())
+            .map(|s| s.id)
+    }
+
+    fn session(&self, id: u64) -> Result<SessionInfo, MyosotisError> {
+        self.sessions()
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or(MyosotisError::SessionNotFound(id))
+    }
+
+    fn commit_marker(&mut self, marker: SessionMarker) -> Result<(), MyosotisError> {
+        if !self.pending_mutations.is_empty() {
+            return Err(MyosotisError::InvalidInput(
+                "commit pending mutations before a session boundary".to_string(),
+            ));
+        }
+        self.commit_allow_empty(Some(marker.message()))
+    }
+
+    /// Commits a begin marker and returns the new session id. Sessions do
+    /// not nest.
+    pub fn begin_session(&mut self) -> Result<u64, MyosotisError> {
+        if let Some(open) = self.current_session() {
+            return Err(MyosotisError::InvalidInput(format!(
+                "session {} is still open",
+                open
+            )));
+        }
+        let id = self.sessions().iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        self.commit_marker(SessionMarker::Begin(id))?;
+        Ok(id)
+    }
+
+    /// Commits the end marker for the open session and returns its id.
+    pub fn end_session(&mut self) -> Result<u64, MyosotisError> {
+        let id = self
+            .current_session()
+            .ok_or_else(|| MyosotisError::InvalidInput("no open session".to_string()))?;
+        self.commit_marker(SessionMarker::End(id))?;
+        Ok(id)
+    }
+
+    /// Commits between the session's markers, excluding the markers. For
+    /// an open session, every commit after its begin marker.
+    pub fn commits_in_session(&self, id: u64) -> Result<&[Commit], MyosotisError> {
+        let session = self.session(id)?;
+        let position = |commit_id: u64| self.commit_index(commit_id);
+        let start = position(session.begin_commit).ok_or(MyosotisError::SessionNotFound(id))? + 1;
+        let end = match session.end_commit {
+            Some(commit_id) => position(commit_id).ok_or(MyosotisError::SessionNotFound(id))?,
+            None => self.commits.len(),
+        };
+        Ok(&self.commits[start..end])
+    }
+
+    /// State as of the session's end marker.
+    pub fn state_at_session_end(&self, id: u64) -> Result<HashMap<NodeId, Node>, MyosotisError> {
+        let end_commit = self
+            .session(id)?
+            .end_commit
+            .ok_or_else(|| MyosotisError::InvalidInput(format!("session {} is still open", id)))?;
+        self.state_at_commit(end_commit)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::memory::Memory;
+use crate::snapshot::Snapshot;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A memory that can be handed to several threads or tasks.
+///
+/// Clones share the same underlying `Memory`. Reads run concurrently;
+/// writes are exclusive, so a batch of mutations applied inside one
+/// `write` call is never interleaved with another writer. A panic inside
+/// a closure does not make the memory unusable for the other handles.
+#[derive(Debug, Clone)]
+pub struct SharedMemory {
+    inner: Arc<RwLock<Memory>>,
+}
+
+impl SharedMemory {
+    pub fn new(mem: Memory) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(mem)),
+        }
+    }
+
+    pub fn read<R>(&self, f: impl FnOnce(&Memory) -> R) -> R {
+        let guard = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        f(&guard)
+    }
+
+    pub fn write<R>(&self, f: impl FnOnce(&mut Memory) -> R) -> R {
+        let mut guard = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        f(&mut guard)
+    }
+
+    /// Returns a copy of the memory as it is right now.
+    pub fn snapshot(&self) -> Memory {
+        self.read(Memory::clone)
+    }
+
+    /// Head state as it is right now, for readers that should not hold the
+    /// lock. Much cheaper than `snapshot`; see `Memory::snapshot`.
+    pub fn view(&self) -> Snapshot {
+        self.read(Memory::snapshot)
+    }
+}
+
+impl From<Memory> for SharedMemory {
+    fn from(mem: Memory) -> Self {
+        Self::new(mem)
+    }
+}
@@ -0,0 +1,187 @@
+use crate::diff::Change;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{NodeId, Value};
+use crate::render;
+use std::fmt::Write as _;
+
+pub const COMMANDS: &[&str] = &[
+    "create", "set", "show", "commit", "diff", "undo", "help", "exit", "quit",
+];
+
+const HELP: &str = "\
+create <type>               stage a new node
+set <id> <key> <value...>   stage a string field
+show <id> [commit]          print a node at head or at a commit
+commit [message...]         commit staged mutations
+diff <from> [to]            changes since commit <from> (to head if no <to>)
+undo                        drop the last staged mutation
+exit | quit                 leave the shell";
+
+pub enum Outcome {
+    Output(String),
+    Exit,
+}
+
+/// Line-oriented session over one in-memory `Memory`. Staged mutations
+/// stay in memory between commands; persisting is left to the caller.
+pub struct Session {
+    pub mem: Memory,
+}
+
+/// Splits off the first whitespace-delimited word.
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    let (word, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+    (word, rest.trim_start())
+}
+
+fn parse_id<T: std::str::FromStr>(arg: Option<&str>, what: &str) -> Result<T, MyosotisError> {
+    arg.ok_or_else(|| MyosotisError::InvalidInput(format!("missing {}", what)))?
+        .parse()
+        .map_err(|_| MyosotisError::InvalidInput(format!("invalid {}", what)))
+}
+
+fn describe(change: &Change) -> String {
+    match change {
+        Change::NodeCreated { id, ty } => format!("+ node {} ({})", id, ty),
+        Change::NodeDeleted { id } => format!("- node {}", id),
+        Change::FieldSet {
+            id,
+            key,
+            old: Some(old),
+            new,
+        } => format!("~ {}.{}: {:?} -> {:?}", id, key, old, new),
+        Change::FieldSet {
+            id,
+            key,
+            old: None,
+            new,
+        } => format!("+ {}.{} = {:?}", id, key, new),
+        Change::FieldRemoved { id, key, old } => format!("- {}.{} (was {:?})", id, key, old),
+    }
+}
+
+impl Session {
+    pub fn new(mem: Memory) -> Self {
+        Self { mem }
+    }
+
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, MyosotisError> {
+        let (command, rest) = split_word(line.trim());
+        let mut args = rest.split_whitespace();
+
+        let out = match command {
+            "" => String::new(),
+            "help" => HELP.to_string(),
+            "exit" | "quit" => return Ok(Outcome::Exit),
+            "create" => {
+                let ty = args
+                    .next()
+                    .ok_or_else(|| MyosotisError::InvalidInput("missing type".to_string()))?;
+                let id = self.mem.create(ty);
+                format!("staged node {} ({})", id, ty)
+            }
+            "set" => {
+                let (id, tail) = split_word(rest);
+                let id = parse_id(Some(id).filter(|s| !s.is_empty()), "node id")?;
+                let (key, value) = split_word(tail);
+                if key.is_empty() {
+                    return Err(MyosotisError::InvalidInput("missing key".to_string()));
+                }
+                self.mem.set(id, key, Value::Str(value.to_string()))?;
+                format!("staged {}.{} = {:?}", id, key, value)
+            }
+            "show" => {
+                let id = parse_id(args.next(), "node id")?;
+                let state = match args.next() {
+                    Some(at) => self.mem.state_at_commit(parse_id(Some(at), "commit id")?)?,
+                    None => self.mem.head_state.clone(),
+                };
+                let node = state.get(&id).ok_or(MyosotisError::NodeNotFound(id))?;
+                if node.deleted {
+                    return Err(MyosotisError::NodeDeleted(id));
+                }
+                let mut out = format!("node {} ({})", id, node.ty);
+                let mut keys: Vec<&String> = node.fields.keys().collect();
+                keys.sort();
+                for key in keys {
+                    if let Some(value) = node.fields.get(key) {
+                        let value = render::pretty(value, Some(render::DEFAULT_MAX_LEN), "  ");
+                        let _ = write!(out, "\n  {}: {}", key, value);
+                    }
+                }
+                out
+            }
+            "commit" => {
+                let message = (!rest.is_empty()).then(|| rest.to_string());
+                self.mem.commit(message)?;
+                format!(
+                    "committed {}",
+                    self.mem.commits.last().map(|c| c.id).unwrap_or(0)
+                )
+            }
+            "diff" => {
+                let from = parse_id(args.next(), "commit id")?;
+                let to = args
+                    .next()
+                    .map(|a| parse_id(Some(a), "commit id"))
+                    .transpose()?;
+                let changes = self.mem.diff(from, to)?;
+                changes.iter().map(describe).collect::<Vec<_>>().join("\n")
+            }
+            "undo" => self.undo()?,
+            other => {
+                return Err(MyosotisError::InvalidInput(format!(
+                    "unknown command '{}', try 'help'",
+                    other
+                )));
+            }
+        };
+        Ok(Outcome::Output(out))
+    }
+
+    fn undo(&mut self) -> Result<String, MyosotisError> {
+        let mutation = self
+            .mem
+            .undo_last_pending()?
+            .ok_or_else(|| MyosotisError::InvalidInput("nothing to undo".to_string()))?;
+        Ok(format!(
+            "dropped staged {} on node {}",
+            mutation.kind(),
+            mutation.node_id()
+        ))
+    }
+
+    /// Completion candidates for the word starting with `prefix`: command
+    /// names in first position, otherwise unarchived node ids and field
+    /// keys.
+    pub fn completions(&self, first_word: bool, prefix: &str) -> Vec<String> {
+        let mut candidates: Vec<String> = if first_word {
+            COMMANDS.iter().map(|c| c.to_string()).collect()
+        } else {
+            let mut ids: Vec<NodeId> = self
+                .mem
+                .head_state
+                .values()
+                .filter(|n| !n.deleted && !n.archived)
+                .map(|n| n.id)
+                .collect();
+            ids.sort_unstable();
+            let mut keys: Vec<String> = self
+                .mem
+                .head_state
+                .values()
+                .flat_map(|n| n.fields.keys().cloned())
+                .collect();
+            keys.sort();
+            keys.dedup();
+            ids.into_iter()
+                .map(|id| id.to_string())
+                .chain(keys)
+                .collect()
+        };
+        candidates.retain(|c| c.starts_with(prefix));
+        candidates
+    }
+}
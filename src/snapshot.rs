@@ -0,0 +1,205 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+type Hash = [u8; 32];
+
+/// Immutable view of head state from `Memory::snapshot`. It stays as
+/// taken while the memory changes, and clones share it.
+///
+/// Snapshots share nodes: one taken after changes holds new copies of the
+/// nodes touched since the previous one and the same `Arc`s for the rest.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    nodes: Arc<HashMap<NodeId, Arc<Node>>>,
+    head_commit: Option<u64>,
+    pending: usize,
+}
+
+impl Snapshot {
+    /// A node, tombstones included.
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(&id).map(|node| &**node)
+    }
+
+    /// Shared handle to a node, which outlives the snapshot.
+    pub fn node(&self, id: NodeId) -> Option<Arc<Node>> {
+        self.nodes.get(&id).cloned()
+    }
+
+    /// `Memory::get_field` against the snapshot.
+    pub fn get_field(&self, id: NodeId, key: &str) -> Result<&Value, MyosotisError> {
+        let node = self.get(id).ok_or(MyosotisError::NodeNotFound(id))?;
+        if node.deleted {
+            return Err(MyosotisError::NodeDeleted(id));
+        }
+        node.get(key)
+            .ok_or_else(|| MyosotisError::FieldNotFound(key.to_string()))
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.nodes.values().map(|node| &**node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Last commit the snapshot includes.
+    pub fn head_commit(&self) -> Option<u64> {
+        self.head_commit
+    }
+
+    /// Pending mutations that were staged on top of `head_commit`.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Copies the snapshot into a plain state map.
+    pub fn to_state(&self) -> HashMap<NodeId, Node> {
+        self.nodes
+            .iter()
+            .map(|(id, node)| (*id, Node::clone(node)))
+            .collect()
+    }
+}
+
+/// What a snapshot was taken at. Head state follows from genesis, the
+/// commits and the staged mutations, so an equal key means an equal state.
+#[derive(Clone, PartialEq, Eq)]
+struct SnapshotKey {
+    genesis: Option<Hash>,
+    head: Option<(u64, Hash)>,
+    /// Digest of the pending mutations and of how many head state holds.
+    pending: Hash,
+}
+
+#[derive(Clone)]
+struct Cached {
+    key: SnapshotKey,
+    pending_nodes: HashSet<NodeId>,
+    snapshot: Snapshot,
+}
+
+/// The last snapshot a `Memory` handed out, which the next one starts
+/// from. Not serialized.
+#[derive(Default)]
+pub struct SnapshotCache(Mutex<Option<Cached>>);
+
+impl Clone for SnapshotCache {
+    fn clone(&self) -> Self {
+        let cached = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        Self(Mutex::new(cached.clone()))
+    }
+}
+
+impl fmt::Debug for SnapshotCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SnapshotCache")
+    }
+}
+
+impl Memory {
+    /// Immutable view of head state, pending mutations included, for
+    /// readers and long-running queries that must not see later changes.
+    /// Returns the previous snapshot while head state is unchanged; after
+    /// changes only the nodes touched since are copied.
+    pub fn snapshot(&self) -> Snapshot {
+        let key = self.snapshot_key();
+        let pending_nodes: HashSet<NodeId> = self
+            .pending_mutations
+            .iter()
+            .map(Mutation::node_id)
+            .collect();
+        let mut cache = self
+            .snapshot_cache
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let touched = match cache.as_ref() {
+            Some(cached) if cached.key == key => return cached.snapshot.clone(),
+            Some(cached) => self.touched_since(cached, &pending_nodes),
+            None => None,
+        };
+        let nodes = match (touched, cache.as_ref()) {
+            (Some(touched), Some(cached)) => {
+                let mut nodes = HashMap::clone(&cached.snapshot.nodes);
+                for id in touched {
+                    match self.head_state.get(&id) {
+                        Some(node) => nodes.insert(id, Arc::new(node.clone())),
+                        None => nodes.remove(&id),
+                    };
+                }
+                nodes
+            }
+            _ => self
+                .head_state
+                .iter()
+                .map(|(id, node)| (*id, Arc::new(node.clone())))
+                .collect(),
+        };
+
+        let snapshot = Snapshot {
+            nodes: Arc::new(nodes),
+            head_commit: key.head.map(|(id, _)| id),
+            pending: self.pending_mutations.len(),
+        };
+        *cache = Some(Cached {
+            key,
+            pending_nodes,
+            snapshot: snapshot.clone(),
+        });
+        snapshot
+    }
+
+    fn snapshot_key(&self) -> SnapshotKey {
+        let pending = Sha256::new()
+            .chain_update((self.applied_pending as u64).to_le_bytes())
+            .chain_update(serde_json::to_vec(&self.pending_mutations).unwrap_or_default())
+            .finalize()
+            .into();
+        SnapshotKey {
+            genesis: self.genesis_state_hash,
+            head: self.commits.last().map(|c| (c.id, c.hash)),
+            pending,
+        }
+    }
+
+    /// Nodes head state may have changed in since `cached` was taken, or
+    /// `None` when history was rewritten rather than extended.
+    fn touched_since(
+        &self,
+        cached: &Cached,
+        pending_nodes: &HashSet<NodeId>,
+    ) -> Option<HashSet<NodeId>> {
+        if cached.key.genesis != self.genesis_state_hash {
+            return None;
+        }
+        let start = match cached.key.head {
+            None => 0,
+            Some((id, hash)) => {
+                let index = self.commit_index(id)?;
+                (self.commits[index].hash == hash).then_some(index + 1)?
+            }
+        };
+        let mut touched: HashSet<NodeId> =
+            cached.pending_nodes.union(pending_nodes).copied().collect();
+        touched.extend(
+            self.commits[start..]
+                .iter()
+                .flat_map(|c| &c.mutations)
+                .map(Mutation::node_id),
+        );
+        Some(touched)
+    }
+}
@@ -0,0 +1,403 @@
+use crate::backend::StorageBackend;
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::{Checkpoint, Memory};
+use crate::node::{Node, NodeId};
+use crate::storage::{self, Durability, LoadMode, StorageFormatV1};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use std::collections::HashMap;
+
+type State = HashMap<NodeId, Node>;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS header (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    format TEXT NOT NULL,
+    genesis TEXT
+);
+CREATE TABLE IF NOT EXISTS commits (
+    id INTEGER PRIMARY KEY,
+    parent INTEGER,
+    parent_hash BLOB,
+    hash BLOB NOT NULL,
+    message TEXT,
+    source TEXT,
+    state_hash BLOB,
+    mutations TEXT NOT NULL,
+    time INTEGER
+);
+CREATE TABLE IF NOT EXISTS checkpoints (
+    commit_id INTEGER PRIMARY KEY,
+    commit_hash BLOB NOT NULL,
+    state_hash BLOB NOT NULL,
+    state TEXT NOT NULL
+);
+";
+
+/// Memory stored in a SQLite database instead of a JSON file: a header
+/// row with the settings of the file format and the genesis state, a row
+/// per commit and a row per checkpoint, each checkpoint state in full.
+///
+/// The database is opened in WAL mode, so other connections keep reading
+/// the last saved memory while a save is in progress. Commits, ranges of
+/// commits and past states can be queried without loading the rest.
+pub struct SqliteBackend {
+    conn: Connection,
+    path: String,
+}
+
+fn to_hash(bytes: Vec<u8>) -> Result<[u8; 32], MyosotisError> {
+    bytes
+        .try_into()
+        .map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+fn decode<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, MyosotisError> {
+    serde_json::from_str(json).map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+/// `(id, hash)` of every stored commit, or `(commit_id, state_hash)` of
+/// every stored checkpoint, in order.
+fn stored_hashes(tx: &Transaction, sql: &str) -> Result<Vec<(u64, [u8; 32])>, MyosotisError> {
+    let mut stmt = tx.prepare(sql)?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, u64>(0)?, row.get::<_, Vec<u8>>(1)?))
+    })?;
+    let mut hashes = Vec::new();
+    for row in rows {
+        let (id, hash) = row?;
+        hashes.push((id, to_hash(hash)?));
+    }
+    Ok(hashes)
+}
+
+/// How many of `stored` are the first entries of `new`, or `None` when
+/// `new` does not start with all of them.
+fn kept_prefix(
+    stored: &[(u64, [u8; 32])],
+    new: impl Iterator<Item = (u64, [u8; 32])>,
+) -> Option<usize> {
+    let mut new = new;
+    for old in stored {
+        if new.next() != Some(*old) {
+            return None;
+        }
+    }
+    Some(stored.len())
+}
+
+impl SqliteBackend {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: &str) -> Result<Self, MyosotisError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn,
+            path: path.to_string(),
+        })
+    }
+
+    fn header(&self) -> Result<StorageFormatV1, MyosotisError> {
+        let format: Option<String> = self
+            .conn
+            .query_row("SELECT format FROM header WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let format = format.ok_or_else(|| {
+            MyosotisError::InvalidInput(format!("no memory saved in {}", self.path))
+        })?;
+        decode(&format)
+    }
+
+    fn genesis(&self) -> Result<Option<State>, MyosotisError> {
+        let genesis: Option<Option<String>> = self
+            .conn
+            .query_row("SELECT genesis FROM header WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        genesis.flatten().map(|g| decode(&g)).transpose()
+    }
+
+    /// Commits with ids in `from..=to`, with the time each was made.
+    fn commit_rows(&self, from: u64, to: u64) -> Result<Vec<(Commit, Option<u64>)>, MyosotisError> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, parent, parent_hash, hash, message, source, state_hash, mutations, time
+             FROM commits
+             WHERE id BETWEEN ?1 AND ?2 ORDER BY id",
+        )?;
+        let to = to.min(i64::MAX as u64);
+        let rows = stmt.query_map(params![from.min(to), to], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, Option<u64>>(1)?,
+                row.get::<_, Option<Vec<u8>>>(2)?,
+                row.get::<_, Vec<u8>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<Vec<u8>>>(6)?,
+                row.get::<_, String>(7)?,
+                row.get::<_, Option<u64>>(8)?,
+            ))
+        })?;
+        let mut commits = Vec::new();
+        for row in rows {
+            let (id, parent, parent_hash, hash, message, source, state_hash, mutations, time) =
+                row?;
+            let commit = Commit {
+                id,
+                parent,
+                parent_hash: parent_hash.map(to_hash).transpose()?,
+                hash: to_hash(hash)?,
+                message,
+                source,
+                state_hash: state_hash.map(to_hash).transpose()?,
+                mutations: decode(&mutations)?,
+            };
+            commits.push((commit, time));
+        }
+        Ok(commits)
+    }
+
+    pub fn head_commit_id(&self) -> Result<Option<u64>, MyosotisError> {
+        Ok(self
+            .conn
+            .query_row("SELECT MAX(id) FROM commits", [], |row| row.get(0))?)
+    }
+
+    /// Commits with ids in `from..=to`, read without loading the others.
+    pub fn commits_between(&self, from: u64, to: u64) -> Result<Vec<Commit>, MyosotisError> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .commit_rows(from, to)?
+            .into_iter()
+            .map(|(commit, _)| commit)
+            .collect())
+    }
+
+    pub fn commit(&self, id: u64) -> Result<Commit, MyosotisError> {
+        self.commits_between(id, id)?
+            .pop()
+            .ok_or(MyosotisError::CommitNotFound(id))
+    }
+
+    /// State after commit `commit_id`: the nearest checkpoint at or before
+    /// it, or genesis, replayed through the commits up to it. The
+    /// checkpoint or genesis state is checked against its hash, and the
+    /// result against the state hash commit `commit_id` recorded, if any.
+    pub fn state_at_commit(&self, commit_id: u64) -> Result<State, MyosotisError> {
+        let target = self.commit(commit_id)?;
+        let header = self.header()?;
+        let checkpoint: Option<(u64, Vec<u8>, String)> = self
+            .conn
+            .query_row(
+                "SELECT commit_id, state_hash, state FROM checkpoints
+                 WHERE commit_id <= ?1 ORDER BY commit_id DESC LIMIT 1",
+                params![commit_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        let (mut state, from) = match checkpoint {
+            Some((id, state_hash, state)) => {
+                let state: State = decode(&state)?;
+                let hash = Memory::compute_state_hash_with(header.canonicalization, &state);
+                if hash != to_hash(state_hash)? {
+                    return Err(MyosotisError::CorruptCheckpointHash);
+                }
+                (state, id + 1)
+            }
+            None => match self.genesis()? {
+                Some(state) => {
+                    let hash = Memory::compute_state_hash_with(header.canonicalization, &state);
+                    if Some(hash) != header.genesis_state_hash {
+                        return Err(MyosotisError::CorruptGenesisHash);
+                    }
+                    (state, 0)
+                }
+                None => (State::new(), 0),
+            },
+        };
+        for commit in self.commits_between(from, commit_id)? {
+            for mutation in &commit.mutations {
+                Memory::apply_mutation(&mut state, mutation)?;
+            }
+        }
+        Memory::check_recorded_state_with(
+            header.canonicalization,
+            commit_id,
+            target.state_hash,
+            &state,
+        )?;
+        Ok(state)
+    }
+}
+
+impl StorageBackend for SqliteBackend {
+    /// Reads every row into a fully validated `Memory`.
+    fn load(&self) -> Result<Memory, MyosotisError> {
+        let mut header = self.header()?;
+        for (commit, time) in self.commit_rows(0, u64::MAX)? {
+            if let Some(time) = time {
+                header.commit_times.insert(commit.id, time);
+            }
+            header.commits.push(commit);
+        }
+        header.genesis_state = self.genesis()?;
+
+        let mut checkpoints = Vec::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT commit_id, commit_hash, state_hash, state FROM checkpoints
+             ORDER BY commit_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, Vec<u8>>(1)?,
+                row.get::<_, Vec<u8>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (commit_id, commit_hash, state_hash, state) = row?;
+            checkpoints.push(Checkpoint {
+                commit_id,
+                commit_hash: to_hash(commit_hash)?,
+                state_hash: to_hash(state_hash)?,
+                state: decode(&state)?,
+            });
+        }
+
+        let mut mem = storage::to_memory(header, true)?;
+        mem.checkpoints = checkpoints;
+        let mem = storage::validate_and_build_head(mem, LoadMode::Strict)?;
+        tracing::debug!(path = %self.path, commits = mem.commits.len(), "loaded sqlite memory");
+        Ok(mem)
+    }
+
+    /// Writes `memory` in one transaction. When the stored commits are a
+    /// prefix of `memory`'s chain only the new commits are inserted, and
+    /// likewise for checkpoints; otherwise the rows are replaced. Values
+    /// are kept inline whatever the blob threshold. A write-once memory
+    /// refuses the same changes `storage::save` does.
+    fn save(&mut self, memory: &Memory) -> Result<(), MyosotisError> {
+        if memory.is_ephemeral() {
+            return Err(MyosotisError::Ephemeral);
+        }
+        let synchronous = match memory.durability {
+            Durability::Atomic => "NORMAL",
+            Durability::Sync => "FULL",
+            Durability::Full => "EXTRA",
+        };
+        self.conn.pragma_update(None, "synchronous", synchronous)?;
+
+        let tx = self.conn.transaction()?;
+        let stored: Option<String> = tx
+            .query_row("SELECT format FROM header WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let stored = stored
+            .map(|format| decode::<StorageFormatV1>(&format))
+            .transpose()?;
+
+        let commits = stored_hashes(&tx, "SELECT id, hash FROM commits ORDER BY id")?;
+        let kept = kept_prefix(&commits, memory.commits.iter().map(|c| (c.id, c.hash)));
+        if let Some(stored) = &stored {
+            storage::check_write_once_over(
+                stored.write_once,
+                stored.genesis_state_hash,
+                stored.canonicalization,
+                commits.iter().copied(),
+                memory,
+            )?;
+        }
+        let kept = match kept {
+            Some(n) => n,
+            None => {
+                tx.execute("DELETE FROM commits", [])?;
+                0
+            }
+        };
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO commits
+                 (id, parent, parent_hash, hash, message, source, state_hash, mutations, time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for commit in &memory.commits[kept..] {
+                insert.execute(params![
+                    commit.id,
+                    commit.parent,
+                    commit.parent_hash.map(Vec::from),
+                    commit.hash.to_vec(),
+                    commit.message,
+                    commit.source,
+                    commit.state_hash.map(Vec::from),
+                    serde_json::to_string(&commit.mutations)?,
+                    memory.commit_times.get(&commit.id),
+                ])?;
+            }
+        }
+
+        let checkpoints = stored_hashes(
+            &tx,
+            "SELECT commit_id, state_hash FROM checkpoints ORDER BY commit_id",
+        )?;
+        let kept_checkpoints = kept_prefix(
+            &checkpoints,
+            memory
+                .checkpoints
+                .iter()
+                .map(|cp| (cp.commit_id, cp.state_hash)),
+        );
+        let kept_checkpoints = match kept_checkpoints {
+            Some(n) => n,
+            None => {
+                tx.execute("DELETE FROM checkpoints", [])?;
+                0
+            }
+        };
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO checkpoints (commit_id, commit_hash, state_hash, state)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for cp in &memory.checkpoints[kept_checkpoints..] {
+                insert.execute(params![
+                    cp.commit_id,
+                    cp.commit_hash.to_vec(),
+                    cp.state_hash.to_vec(),
+                    serde_json::to_string(&cp.state)?,
+                ])?;
+            }
+        }
+
+        let mut header = storage::from_memory(memory);
+        let genesis = header
+            .genesis_state
+            .take()
+            .map(|state| serde_json::to_string(&state))
+            .transpose()?;
+        header.commits.clear();
+        header.checkpoints.clear();
+        header.commit_times.clear();
+        tx.execute(
+            "INSERT OR REPLACE INTO header (id, format, genesis) VALUES (0, ?1, ?2)",
+            params![serde_json::to_string(&header)?, genesis],
+        )?;
+        tx.commit()?;
+
+        tracing::debug!(
+            path = %self.path,
+            commits = memory.commits.len() - kept,
+            checkpoints = memory.checkpoints.len() - kept_checkpoints,
+            "saved sqlite memory"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+use crate::commit::{Commit, Mutation};
+use crate::error::MyosotisError;
+use crate::memory::{Checkpoint, Memory};
+use crate::node::{Node, PathSeg, Value};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+
+/// Sizes of canonical hash inputs, in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HashInputStats {
+    pub count: usize,
+    pub total_bytes: usize,
+    pub max_bytes: usize,
+    /// Commit id of the largest input (for checkpoints, the commit the
+    /// checkpoint was taken at).
+    pub max_commit_id: Option<u64>,
+}
+
+impl HashInputStats {
+    fn from_sizes(sizes: &[(u64, usize)]) -> Self {
+        let mut stats = Self {
+            count: sizes.len(),
+            ..Self::default()
+        };
+        for &(commit_id, bytes) in sizes {
+            stats.total_bytes += bytes;
+            if stats.max_commit_id.is_none() || bytes > stats.max_bytes {
+                stats.max_bytes = bytes;
+                stats.max_commit_id = Some(commit_id);
+            }
+        }
+        stats
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryStats {
+    /// Live nodes per type in head state.
+    pub nodes_by_type: BTreeMap<String, usize>,
+    pub live_nodes: usize,
+    /// Tombstoned nodes still present in head state.
+    pub deleted_nodes: usize,
+    pub commits: usize,
+    pub checkpoints: usize,
+    /// Committed mutations per kind (see `Mutation::kind`).
+    pub mutations_by_kind: BTreeMap<String, usize>,
+    pub pending_mutations: usize,
+    /// Size of the in-memory structure serialized as compact JSON; the
+    /// pretty-printed file on disk is larger.
+    pub approx_size_bytes: usize,
+    pub commit_hash_inputs: HashInputStats,
+    pub checkpoint_hash_inputs: HashInputStats,
+}
+
+/// Estimated heap bytes held by a `Memory`, by part. Allocator overhead
+/// and hash table control bytes beyond one per slot are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryFootprint {
+    pub head_state: usize,
+    pub genesis_state: usize,
+    pub commits: usize,
+    pub checkpoints: usize,
+    pub pending_mutations: usize,
+    /// Anchors, locks, commit times and the like.
+    pub other: usize,
+}
+
+impl MemoryFootprint {
+    pub fn total(&self) -> usize {
+        self.head_state
+            + self.genesis_state
+            + self.commits
+            + self.checkpoints
+            + self.pending_mutations
+            + self.other
+    }
+}
+
+/// Heap bytes owned by a value, excluding the value itself.
+trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for HashMap<K, V> {
+    fn heap_size(&self) -> usize {
+        let slots = self.capacity() * (size_of::<K>() + size_of::<V>() + 1);
+        slots
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl HeapSize for u64 {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for u128 {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for Value {
+    fn heap_size(&self) -> usize {
+        match self {
+            Value::Str(s) | Value::AliasRef(s) => s.heap_size(),
+            Value::ExternalRef { file_alias, .. } => file_alias.heap_size(),
+            Value::Vector(v) => v.capacity() * size_of::<f32>(),
+            Value::List(items) => items.heap_size(),
+            Value::Map(map) => map.heap_size(),
+            Value::Int(_) | Value::Float(_) | Value::Bool(_) | Value::Ref(_) => 0,
+        }
+    }
+}
+
+impl HeapSize for PathSeg {
+    fn heap_size(&self) -> usize {
+        match self {
+            PathSeg::Key(key) => key.heap_size(),
+            PathSeg::Index(_) => 0,
+        }
+    }
+}
+
+impl HeapSize for Node {
+    fn heap_size(&self) -> usize {
+        self.ty.heap_size()
+            + self.fields.heap_size()
+            + self.alias.as_ref().map_or(0, String::heap_size)
+            + self.namespace.as_ref().map_or(0, String::heap_size)
+            + self.labels.iter().map(String::heap_size).sum::<usize>()
+    }
+}
+
+impl HeapSize for Mutation {
+    fn heap_size(&self) -> usize {
+        match self {
+            Mutation::CreateNode { ty, .. } | Mutation::SetType { ty, .. } => ty.heap_size(),
+            Mutation::SetField { key, value, .. } => key.heap_size() + value.heap_size(),
+            Mutation::DeleteField { key, .. } => key.heap_size(),
+            Mutation::AddLabel { label, .. } | Mutation::RemoveLabel { label, .. } => {
+                label.heap_size()
+            }
+            Mutation::DeleteNode { .. }
+            | Mutation::RestoreNode { .. }
+            | Mutation::SetArchived { .. } => 0,
+            Mutation::SetAlias { alias, .. } => alias.as_ref().map_or(0, String::heap_size),
+            Mutation::SetNamespace { namespace, .. } => {
+                namespace.as_ref().map_or(0, String::heap_size)
+            }
+            Mutation::RenameField { from, to, .. } => from.heap_size() + to.heap_size(),
+            Mutation::SetPath { path, value, .. } => path.heap_size() + value.heap_size(),
+            Mutation::DeletePath { path, .. } | Mutation::ListRemove { path, .. } => {
+                path.heap_size()
+            }
+            Mutation::ListPush { path, value, .. } | Mutation::ListInsert { path, value, .. } => {
+                path.heap_size() + value.heap_size()
+            }
+        }
+    }
+}
+
+impl HeapSize for Commit {
+    fn heap_size(&self) -> usize {
+        self.message.as_ref().map_or(0, HeapSize::heap_size)
+            + self.source.as_ref().map_or(0, HeapSize::heap_size)
+            + self.mutations.heap_size()
+    }
+}
+
+impl HeapSize for Checkpoint {
+    fn heap_size(&self) -> usize {
+        self.state.heap_size()
+    }
+}
+
+impl Memory {
+    /// Estimates heap usage. Cost is linear in everything held, so call it
+    /// occasionally rather than per mutation.
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        let other = self.anchors.capacity() * size_of::<crate::anchor::AnchorReceipt>()
+            + self
+                .anchors
+                .iter()
+                .map(|a| a.authority.capacity() + a.receipt.capacity())
+                .sum::<usize>()
+            + self.locks.capacity() * size_of::<crate::lock::NodeLock>()
+            + self.locks.iter().map(|l| l.owner.capacity()).sum::<usize>()
+            // BTreeMap nodes: key and value plus roughly a pointer per entry.
+            + self.commit_times.len() * (2 * size_of::<u64>() + size_of::<usize>());
+        MemoryFootprint {
+            head_state: self.head_state.heap_size(),
+            genesis_state: self.genesis_state.as_ref().map_or(0, HeapSize::heap_size),
+            commits: self.commits.heap_size(),
+            checkpoints: self.checkpoints.heap_size(),
+            pending_mutations: self.pending_mutations.heap_size(),
+            other,
+        }
+    }
+
+    /// Warns observers and the log after each commit that leaves the
+    /// estimated footprint above `limit` bytes. `None` turns the check off.
+    pub fn set_footprint_limit(&mut self, limit: Option<usize>) {
+        self.footprint_limit = limit;
+    }
+
+    pub(crate) fn check_footprint(&self) {
+        let Some(limit) = self.footprint_limit else {
+            return;
+        };
+        let footprint = self.memory_footprint();
+        if footprint.total() > limit {
+            tracing::warn!(
+                total = footprint.total(),
+                limit,
+                "memory footprint above limit"
+            );
+            self.observers.footprint_exceeded(&footprint, limit);
+        }
+    }
+
+    /// `(commit_id, bytes)` of each commit's canonical hash input.
+    pub fn commit_hash_input_sizes(&self) -> Vec<(u64, usize)> {
+        self.commits
+            .iter()
+            .map(|c| (c.id, c.canonical_bytes_with(self.canonicalization).len()))
+            .collect()
+    }
+
+    /// `(commit_id, bytes)` of each checkpoint's canonical state hash input.
+    pub fn checkpoint_hash_input_sizes(&self) -> Vec<(u64, usize)> {
+        self.checkpoints
+            .iter()
+            .map(|cp| {
+                let bytes = Memory::state_hash_input(self.canonicalization, &cp.state);
+                (cp.commit_id, bytes.len())
+            })
+            .collect()
+    }
+
+    pub fn stats(&self) -> Result<MemoryStats, MyosotisError> {
+        let mut stats = MemoryStats {
+            commits: self.commits.len(),
+            checkpoints: self.checkpoints.len(),
+            pending_mutations: self.pending_mutations.len(),
+            approx_size_bytes: serde_json::to_vec(self)?.len(),
+            commit_hash_inputs: HashInputStats::from_sizes(&self.commit_hash_input_sizes()),
+            checkpoint_hash_inputs: HashInputStats::from_sizes(&self.checkpoint_hash_input_sizes()),
+            ..MemoryStats::default()
+        };
+
+        for node in self.head_state.values() {
+            if node.deleted {
+                stats.deleted_nodes += 1;
+            } else {
+                stats.live_nodes += 1;
+                *stats.nodes_by_type.entry(node.ty.clone()).or_default() += 1;
+            }
+        }
+        for mutation in self.commits.iter().flat_map(|c| &c.mutations) {
+            *stats
+                .mutations_by_kind
+                .entry(mutation.kind().to_string())
+                .or_default() += 1;
+        }
+        Ok(stats)
+    }
+}
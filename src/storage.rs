@@ -1,13 +1,26 @@
+use crate::blob::{BlobRef, BlobSite, Offloader, Restorer, placeholder};
+use crate::canonical::Canonicalization;
 use crate::error::MyosotisError;
+use crate::float::FloatPolicy;
 use crate::memory::Memory;
-use anyhow::{Context, Result};
+use crate::node::IdMode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io::{Read, Seek, Write};
 use std::path::Path;
+use std::time::Instant;
 
 pub const FILE_MAGIC: &str = "MYOSOTIS";
-pub const FORMAT_VERSION: u32 = 1;
+pub const FORMAT_VERSION: u32 = 2;
+/// First format version that stores later checkpoints as deltas.
+const DELTA_CHECKPOINTS_VERSION: u32 = 2;
+/// First format version that may hold UUID node ids.
+const UUID_IDS_VERSION: u32 = 2;
+pub const EMBED_MAGIC: &[u8; 8] = b"MYOEMBED";
+
+type State = HashMap<crate::node::NodeId, crate::node::Node>;
 
 #[derive(Debug, Clone, Copy)]
 pub enum LoadMode {
@@ -15,16 +28,288 @@ pub enum LoadMode {
     Unsafe,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Bounds `load_with_limits` checks before decoding a file any further,
+/// for services that load files they do not trust. `None` is unbounded,
+/// and the default bounds nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadLimits {
+    /// Size of the file, checked before it is read.
+    pub max_file_bytes: Option<u64>,
+    pub max_commits: Option<usize>,
+    /// Nodes in genesis plus nodes created by commits, and nodes in any
+    /// one checkpoint.
+    pub max_nodes: Option<usize>,
+    /// Encoded size of any one field value.
+    pub max_value_bytes: Option<usize>,
+}
+
+fn check_limit(
+    limit: &'static str,
+    actual: usize,
+    max: Option<usize>,
+) -> Result<(), MyosotisError> {
+    match max {
+        Some(max) if actual > max => Err(MyosotisError::LoadLimitExceeded(
+            limit,
+            actual as u64,
+            max as u64,
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Counts the bytes written to it.
+struct ByteCount(usize);
+
+impl Write for ByteCount {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn check_state_values(state: &State, limits: &LoadLimits) -> Result<(), MyosotisError> {
+    for node in state.values() {
+        for value in node.fields.values() {
+            check_value(value, limits)?;
+        }
+    }
+    Ok(())
+}
+
+/// Encoded size of `value`, as value limits and blob thresholds count it.
+pub(crate) fn encoded_value_len(value: &crate::node::Value) -> Result<usize, MyosotisError> {
+    let mut count = ByteCount(0);
+    serde_json::to_writer(&mut count, value)?;
+    Ok(count.0)
+}
+
+fn check_value(value: &crate::node::Value, limits: &LoadLimits) -> Result<(), MyosotisError> {
+    if limits.max_value_bytes.is_some() {
+        check_limit(
+            "value bytes",
+            encoded_value_len(value)?,
+            limits.max_value_bytes,
+        )?;
+    }
+    Ok(())
+}
+
+fn check_limits(sf: &StorageFormatV1, limits: &LoadLimits) -> Result<(), MyosotisError> {
+    use crate::commit::Mutation;
+
+    check_limit("commits", sf.commits.len(), limits.max_commits)?;
+
+    let genesis = sf.genesis_state.as_ref();
+    let created = sf
+        .commits
+        .iter()
+        .flat_map(|c| &c.mutations)
+        .filter(|m| matches!(m, Mutation::CreateNode { .. }))
+        .count();
+    check_limit(
+        "nodes",
+        genesis.map_or(0, HashMap::len).saturating_add(created),
+        limits.max_nodes,
+    )?;
+    let mut checkpoint_nodes = 0usize;
+    for cp in &sf.checkpoints {
+        checkpoint_nodes = match (&cp.state, &cp.changed) {
+            (Some(state), _) => state.len(),
+            (None, Some(changed)) => checkpoint_nodes.saturating_add(changed.len()),
+            (None, None) => checkpoint_nodes,
+        };
+        check_limit("checkpoint nodes", checkpoint_nodes, limits.max_nodes)?;
+    }
+
+    if limits.max_value_bytes.is_none() {
+        return Ok(());
+    }
+    if let Some(genesis) = genesis {
+        check_state_values(genesis, limits)?;
+    }
+    for cp in &sf.checkpoints {
+        for state in [&cp.state, &cp.changed].into_iter().flatten() {
+            check_state_values(state, limits)?;
+        }
+    }
+    for value in sf
+        .commits
+        .iter()
+        .flat_map(|c| &c.mutations)
+        .filter_map(Mutation::value)
+    {
+        check_value(value, limits)?;
+    }
+    Ok(())
+}
+
+fn offload_state(
+    state: &mut State,
+    offloader: &mut Offloader,
+    site: impl Fn(crate::node::NodeId, &str) -> BlobSite,
+) -> Result<(), MyosotisError> {
+    let mut nodes: Vec<_> = state.iter_mut().collect();
+    nodes.sort_by_key(|(id, _)| **id);
+    for (id, node) in nodes {
+        let mut fields: Vec<_> = node.fields.iter_mut().collect();
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in fields {
+            offloader.offload(value, site(*id, key))?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the field values of `sf` over `threshold` encoded bytes into the
+/// blob store of `path`, recording where they were in `sf.blobs`.
+fn offload_values(
+    sf: &mut StorageFormatV1,
+    path: &str,
+    threshold: usize,
+    durability: Durability,
+) -> Result<(), MyosotisError> {
+    let mut offloader = Offloader::new(path, threshold, durability);
+    if let Some(genesis) = &mut sf.genesis_state {
+        offload_state(genesis, &mut offloader, |node_id, key| BlobSite::Genesis {
+            node_id,
+            key: key.to_string(),
+        })?;
+    }
+    for commit in &mut sf.commits {
+        for (index, mutation) in commit.mutations.iter_mut().enumerate() {
+            if let Some(value) = mutation.value_mut() {
+                let site = BlobSite::Commit {
+                    commit_id: commit.id,
+                    mutation: index,
+                };
+                offloader.offload(value, site)?;
+            }
+        }
+    }
+    for cp in &mut sf.checkpoints {
+        let commit_id = cp.commit_id;
+        for state in [&mut cp.state, &mut cp.changed].into_iter().flatten() {
+            offload_state(state, &mut offloader, |node_id, key| BlobSite::Checkpoint {
+                commit_id,
+                node_id,
+                key: key.to_string(),
+            })?;
+        }
+    }
+    sf.blobs = offloader.blobs;
+    Ok(())
+}
+
+fn blob_slot<'a>(
+    sf: &'a mut StorageFormatV1,
+    site: &BlobSite,
+) -> Option<&'a mut crate::node::Value> {
+    match site {
+        BlobSite::Genesis { node_id, key } => sf
+            .genesis_state
+            .as_mut()?
+            .get_mut(node_id)?
+            .fields
+            .get_mut(key),
+        BlobSite::Commit {
+            commit_id,
+            mutation,
+        } => sf
+            .commits
+            .iter_mut()
+            .find(|c| c.id == *commit_id)?
+            .mutations
+            .get_mut(*mutation)?
+            .value_mut(),
+        BlobSite::Checkpoint {
+            commit_id,
+            node_id,
+            key,
+        } => {
+            let cp = sf
+                .checkpoints
+                .iter_mut()
+                .find(|cp| cp.commit_id == *commit_id)?;
+            cp.state
+                .as_mut()
+                .or(cp.changed.as_mut())?
+                .get_mut(node_id)?
+                .fields
+                .get_mut(key)
+        }
+    }
+}
+
+/// Puts the values `offload_values` moved out back into `sf`, from the
+/// blob store of the memory file `blobs_of`. Every blob is checked against
+/// its hash.
+fn restore_values(
+    sf: &mut StorageFormatV1,
+    blobs_of: Option<&str>,
+    limits: &LoadLimits,
+) -> Result<(), MyosotisError> {
+    if sf.blobs.is_empty() {
+        return Ok(());
+    }
+    // Embedded frames are written without a blob store.
+    let blobs_of = blobs_of.ok_or(MyosotisError::MalformedFileStructure)?;
+    let mut restorer = Restorer::new(blobs_of, limits.max_value_bytes);
+    for blob in std::mem::take(&mut sf.blobs) {
+        let value = restorer.value(&blob.hash)?;
+        let slot = blob_slot(sf, &blob.site)
+            .filter(|slot| **slot == placeholder(&blob.hash))
+            .ok_or(MyosotisError::MalformedFileStructure)?;
+        *slot = value;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-struct StorageFormatV1 {
+pub(crate) struct StorageFormatV1 {
     magic: String,
     format_version: u32,
-    genesis_state: Option<HashMap<crate::node::NodeId, crate::node::Node>>,
-    genesis_state_hash: Option<[u8; 32]>,
-    commits: Vec<crate::commit::Commit>,
-    checkpoints: Vec<crate::memory::Checkpoint>,
+    #[serde(default, skip_serializing_if = "Canonicalization::is_binary")]
+    pub(crate) canonicalization: Canonicalization,
+    #[serde(default, skip_serializing_if = "FloatPolicy::is_bit_exact")]
+    float_policy: FloatPolicy,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) write_once: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    record_state_hashes: bool,
+    #[serde(default, skip_serializing_if = "IdMode::is_sequential")]
+    id_mode: IdMode,
+    pub(crate) genesis_state: Option<HashMap<crate::node::NodeId, crate::node::Node>>,
+    pub(crate) genesis_state_hash: Option<[u8; 32]>,
+    pub(crate) commits: Vec<crate::commit::Commit>,
+    pub(crate) checkpoints: Vec<StoredCheckpoint>,
     next_node_id: crate::node::NodeId,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    anchors: Vec<crate::anchor::AnchorReceipt>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    locks: Vec<crate::lock::NodeLock>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    expiries: Vec<crate::expiry::FieldExpiry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retention: Option<crate::retention::RetentionPolicy>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    unique_constraints: Vec<crate::unique::UniqueConstraint>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) commit_times: BTreeMap<u64, u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seal: Option<crate::seal::Seal>,
+    /// Written by `add_checksum`, never through serde.
+    #[serde(default, skip_serializing)]
+    checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blob_threshold: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blobs: Vec<BlobRef>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -33,130 +318,892 @@ struct LegacyStorageFormatV05 {
     genesis_state: Option<HashMap<crate::node::NodeId, crate::node::Node>>,
     genesis_state_hash: Option<[u8; 32]>,
     commits: Vec<crate::commit::Commit>,
-    checkpoints: Vec<crate::memory::Checkpoint>,
+    checkpoints: Vec<StoredCheckpoint>,
     next_node_id: crate::node::NodeId,
 }
 
-fn to_memory(sf: StorageFormatV1) -> Memory {
+/// On-disk checkpoint. The first is stored in full; each later one stores
+/// only `changed`, the nodes that differ from the previous checkpoint, and
+/// the ids it no longer has in `removed`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct StoredCheckpoint {
+    commit_id: u64,
+    commit_hash: [u8; 32],
+    state_hash: [u8; 32],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state: Option<State>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    changed: Option<State>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "crate::node::wide_id::list"
+    )]
+    removed: Vec<crate::node::NodeId>,
+}
+
+impl StoredCheckpoint {
+    fn is_delta(&self) -> bool {
+        self.changed.is_some() || !self.removed.is_empty()
+    }
+}
+
+/// Version 1 files stored every checkpoint in full and only sequential
+/// ids; a delta or UUID id mode in one is malformed rather than something
+/// to guess at.
+fn check_version_layout(
+    format_version: u32,
+    id_mode: IdMode,
+    mut deltas: impl Iterator<Item = bool>,
+) -> Result<(), MyosotisError> {
+    if format_version < DELTA_CHECKPOINTS_VERSION && deltas.any(|delta| delta) {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    if format_version < UUID_IDS_VERSION && !id_mode.is_sequential() {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    Ok(())
+}
+
+fn store_checkpoints(checkpoints: &[crate::memory::Checkpoint]) -> Vec<StoredCheckpoint> {
+    let mut prev: Option<&State> = None;
+    checkpoints
+        .iter()
+        .map(|cp| {
+            let mut stored = StoredCheckpoint {
+                commit_id: cp.commit_id,
+                commit_hash: cp.commit_hash,
+                state_hash: cp.state_hash,
+                state: None,
+                changed: None,
+                removed: Vec::new(),
+            };
+            match prev {
+                None => stored.state = Some(cp.state.clone()),
+                Some(base) => {
+                    stored.changed = Some(
+                        cp.state
+                            .iter()
+                            .filter(|(id, node)| base.get(id) != Some(node))
+                            .map(|(id, node)| (*id, node.clone()))
+                            .collect(),
+                    );
+                    stored.removed = base
+                        .keys()
+                        .filter(|id| !cp.state.contains_key(id))
+                        .copied()
+                        .collect();
+                    stored.removed.sort_unstable();
+                }
+            }
+            prev = Some(&cp.state);
+            stored
+        })
+        .collect()
+}
+
+/// Rebuilds full checkpoint states from `store_checkpoints` output. With
+/// `verify`, every state rebuilt from a delta must match its state hash.
+fn restore_checkpoints(
+    stored: Vec<StoredCheckpoint>,
+    canonicalization: Canonicalization,
+    verify: bool,
+) -> Result<Vec<crate::memory::Checkpoint>, MyosotisError> {
+    let mut checkpoints: Vec<crate::memory::Checkpoint> = Vec::with_capacity(stored.len());
+    for cp in stored {
+        let state = match (cp.state, cp.changed) {
+            (Some(state), None) if cp.removed.is_empty() => state,
+            (None, Some(changed)) => {
+                let mut state = checkpoints
+                    .last()
+                    .map(|base| base.state.clone())
+                    .ok_or(MyosotisError::MalformedFileStructure)?;
+                for id in &cp.removed {
+                    state.remove(id);
+                }
+                state.extend(changed);
+                if verify
+                    && Memory::compute_state_hash_with(canonicalization, &state) != cp.state_hash
+                {
+                    return Err(MyosotisError::CorruptCheckpointHash);
+                }
+                state
+            }
+            _ => return Err(MyosotisError::MalformedFileStructure),
+        };
+        checkpoints.push(crate::memory::Checkpoint {
+            commit_id: cp.commit_id,
+            commit_hash: cp.commit_hash,
+            state_hash: cp.state_hash,
+            state,
+        });
+    }
+    Ok(checkpoints)
+}
+
+pub(crate) fn to_memory(sf: StorageFormatV1, verify: bool) -> Result<Memory, MyosotisError> {
+    check_version_layout(
+        sf.format_version,
+        sf.id_mode,
+        sf.checkpoints.iter().map(StoredCheckpoint::is_delta),
+    )?;
     let mut mem = Memory::new();
     mem.genesis_state = sf.genesis_state;
     mem.genesis_state_hash = sf.genesis_state_hash;
     mem.commits = sf.commits;
-    mem.checkpoints = sf.checkpoints;
+    mem.checkpoints = restore_checkpoints(sf.checkpoints, sf.canonicalization, verify)?;
+    mem.checkpoints.sort_by_key(|cp| cp.commit_id);
     mem.next_node_id = sf.next_node_id;
-    mem
+    mem.canonicalization = sf.canonicalization;
+    mem.float_policy = sf.float_policy;
+    mem.write_once = sf.write_once;
+    mem.record_state_hashes = sf.record_state_hashes;
+    mem.id_mode = sf.id_mode;
+    mem.anchors = sf.anchors;
+    mem.locks = sf.locks;
+    mem.expiries = sf.expiries;
+    mem.retention = sf.retention;
+    mem.unique_constraints = sf.unique_constraints;
+    mem.seal = sf.seal;
+    mem.file_checksum = sf.checksum.is_some();
+    mem.blob_threshold = sf.blob_threshold;
+    mem.commit_times = sf.commit_times;
+    Ok(mem)
 }
 
-fn from_memory(memory: &Memory) -> StorageFormatV1 {
+pub(crate) fn from_memory(memory: &Memory) -> StorageFormatV1 {
     StorageFormatV1 {
         magic: FILE_MAGIC.to_string(),
         format_version: FORMAT_VERSION,
+        canonicalization: memory.canonicalization,
+        float_policy: memory.float_policy,
+        write_once: memory.write_once,
+        record_state_hashes: memory.record_state_hashes,
+        id_mode: memory.id_mode,
         genesis_state: memory.genesis_state.clone(),
         genesis_state_hash: memory.genesis_state_hash,
         commits: memory.commits.clone(),
-        checkpoints: memory.checkpoints.clone(),
+        checkpoints: store_checkpoints(&memory.checkpoints),
         next_node_id: memory.next_node_id,
+        anchors: memory.anchors.clone(),
+        locks: memory.locks.clone(),
+        expiries: memory.expiries.clone(),
+        retention: memory.retention.clone(),
+        unique_constraints: memory.unique_constraints.clone(),
+        commit_times: memory.commit_times.clone(),
+        seal: memory.seal.clone(),
+        checksum: None,
+        blob_threshold: memory.blob_threshold,
+        blobs: Vec::new(),
     }
 }
 
-fn validate_and_build_head(mut mem: Memory, mode: LoadMode) -> Result<Memory> {
+pub(crate) fn validate_and_build_head(
+    mut mem: Memory,
+    mode: LoadMode,
+) -> Result<Memory, MyosotisError> {
     let verify_hashes = matches!(mode, LoadMode::Strict);
-    mem.validate_with_mode(verify_hashes)
-        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let started = Instant::now();
+    mem.validate_with_mode(verify_hashes)?;
+    mem.metrics.record_validation(started.elapsed());
 
-    let state = if let Some(cp) = mem.checkpoints.iter().max_by_key(|c| c.commit_id) {
+    let state = if let Some(cp) = mem.checkpoints.last() {
         let start_index = mem
-            .commits
-            .iter()
-            .position(|c| c.id == cp.commit_id)
-            .ok_or_else(|| anyhow::anyhow!(MyosotisError::InvalidCheckpoint))?
+            .commit_index(cp.commit_id)
+            .ok_or(MyosotisError::InvalidCheckpoint)?
             + 1;
-        Memory::replay_from(cp.state.clone(), &mem.commits[start_index..])
-            .map_err(|e| anyhow::anyhow!(e.to_string()))?
+        Memory::replay_from(cp.state.clone(), &mem.commits[start_index..])?
     } else {
-        Memory::replay_from(mem.genesis_state.clone().unwrap_or_default(), &mem.commits)
-            .map_err(|e| anyhow::anyhow!(e.to_string()))?
+        Memory::replay_from(mem.genesis_state.clone().unwrap_or_default(), &mem.commits)?
     };
 
     mem.head_state = state;
     mem.pending_mutations = Vec::new();
+    mem.reindex_aliases();
+    mem.reindex_labels();
+    mem.reindex_unique()?;
+    mem.check_float_history()?;
+    tracing::debug!(
+        commits = mem.commits.len(),
+        checkpoints = mem.checkpoints.len(),
+        nodes = mem.head_state.len(),
+        "loaded memory"
+    );
     Ok(mem)
 }
 
-pub fn save(path: &str, memory: &Memory) -> Result<()> {
-    let sf = from_memory(memory);
-    let data = serde_json::to_string_pretty(&sf)?;
-    fs::write(path, data).with_context(|| format!("Failed to write to file: {}", path))?;
+/// Validates the `magic`/`format_version` header fields. Returns the
+/// version of a versioned file and `None` for a legacy v0.5.0 file without
+/// header.
+fn check_header(
+    magic: Option<&serde_json::Value>,
+    format_version: Option<&serde_json::Value>,
+) -> Result<Option<u32>, MyosotisError> {
+    let Some(format_version) = format_version else {
+        // Legacy v0.5.0 path: no magic + no format_version
+        if magic.is_some() {
+            return Err(MyosotisError::MissingFormatVersion);
+        }
+        return Ok(None);
+    };
+
+    let version = format_version
+        .as_u64()
+        .ok_or(MyosotisError::MissingFormatVersion)? as u32;
+
+    if version == 0 {
+        return Err(MyosotisError::MissingFormatVersion);
+    }
+    if version > FORMAT_VERSION {
+        return Err(MyosotisError::UnsupportedFormatVersion(version));
+    }
+
+    let magic = magic
+        .and_then(|v| v.as_str())
+        .ok_or(MyosotisError::InvalidFileMagic)?;
+    if magic != FILE_MAGIC {
+        return Err(MyosotisError::InvalidFileMagic);
+    }
+    Ok(Some(version))
+}
+
+/// Refuses to replace a write-once file with one that clears the flag or
+/// does not extend its commit chain. Existing files that cannot be read as
+/// a commit stream are not protected.
+fn check_write_once(path: &str, memory: &Memory) -> Result<(), MyosotisError> {
+    let Ok(existing) = stream_commits(path) else {
+        return Ok(());
+    };
+    // Commits `save_incremental` appended to the segment log are stored
+    // history too, and `write_full` drops the log.
+    let appended = match fs::read(path) {
+        Ok(data) => read_segments(path, Sha256::digest(&data).into())?
+            .map(|(records, _, _)| records)
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    check_write_once_over(
+        existing.write_once,
+        existing.genesis_state_hash,
+        existing.canonicalization,
+        existing.map(|c| (c.id, c.hash)).chain(
+            appended
+                .iter()
+                .flat_map(|record| &record.commits)
+                .map(|c| (c.id, c.hash)),
+        ),
+        memory,
+    )
+}
+
+/// `check_write_once` against a stored memory known by its settings and
+/// the `(id, hash)` of each stored commit, for stores other than files.
+pub(crate) fn check_write_once_over(
+    write_once: bool,
+    genesis_state_hash: Option<[u8; 32]>,
+    canonicalization: Canonicalization,
+    existing: impl IntoIterator<Item = (u64, [u8; 32])>,
+    memory: &Memory,
+) -> Result<(), MyosotisError> {
+    if !write_once {
+        return Ok(());
+    }
+    if !memory.write_once {
+        return Err(MyosotisError::WriteOnceViolation(
+            "clearing write-once mode",
+        ));
+    }
+    if genesis_state_hash != memory.genesis_state_hash
+        || canonicalization != memory.canonicalization
+    {
+        return Err(MyosotisError::WriteOnceViolation("replacing genesis"));
+    }
+    // A matching hash only proves the commit is unchanged if it is also
+    // the genuine hash of the new commit's contents.
+    let mut kept = memory.commits.iter();
+    for (id, hash) in existing {
+        let unchanged = kept.next().is_some_and(|new| {
+            new.id == id
+                && new.hash == hash
+                && new.compute_hash_with(memory.canonicalization) == new.hash
+        });
+        if !unchanged {
+            return Err(MyosotisError::WriteOnceViolation(
+                "rewriting or truncating history",
+            ));
+        }
+    }
     Ok(())
 }
 
-pub fn load_with_mode(path: &str, mode: LoadMode) -> Result<Memory> {
+/// How much `save` does to make a write survive a crash. Every level
+/// writes `path.tmp` and renames it over `path`, so readers and crashes
+/// never see a half-written file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// Rename only. After a power loss the file may still hold the old
+    /// contents, or on some file systems be empty.
+    Atomic,
+    /// Flush the temporary file to disk before the rename.
+    #[default]
+    Sync,
+    /// Also flush the directory after the rename, so the rename itself is
+    /// on disk when `save` returns. Only differs from `Sync` on Unix.
+    Full,
+}
+
+impl Memory {
+    /// Sets the durability `save` uses for this memory. Not stored in the
+    /// file; loaded memories start at `Durability::Sync`.
+    pub fn set_durability(&mut self, durability: Durability) {
+        self.durability = durability;
+    }
+
+    /// Makes `save` start the file with a checksum of the whole file,
+    /// which loads check before parsing. Memories loaded from a file with a
+    /// checksum keep writing one.
+    pub fn set_file_checksum(&mut self, enabled: bool) {
+        self.file_checksum = enabled;
+    }
+
+    /// Whether `save` writes a whole-file checksum.
+    pub fn file_checksum(&self) -> bool {
+        self.file_checksum
+    }
+}
+
+const CHECKSUM_PREFIX: &str = "{\n  \"checksum\": \"sha256:";
+
+/// Adds a `checksum` member to `data`, pretty-printed JSON as `save`
+/// writes it, as its first line. It holds the SHA-256 of `data` without
+/// that line, so it can be checked before the JSON is parsed.
+fn add_checksum(data: &str) -> String {
+    let hex = crate::verify::to_hex(&Sha256::digest(data.as_bytes()));
+    format!("{}{}\",\n{}", CHECKSUM_PREFIX, hex, &data[2..])
+}
+
+/// Checks the first-line checksum `add_checksum` writes. Returns whether
+/// `data` had one.
+fn verify_checksum(data: &str) -> Result<bool, MyosotisError> {
+    let Some(rest) = data.strip_prefix(CHECKSUM_PREFIX) else {
+        return Ok(false);
+    };
+    let (hex, body) = rest
+        .split_once("\",\n")
+        .ok_or(MyosotisError::FileChecksumMismatch)?;
+    let digest = Sha256::new()
+        .chain_update(b"{\n")
+        .chain_update(body.as_bytes())
+        .finalize();
+    if crate::verify::to_hex(&digest) != hex {
+        return Err(MyosotisError::FileChecksumMismatch);
+    }
+    Ok(true)
+}
+
+/// Checks the whole-file checksum of `path`, if it has one, for `fsck`.
+pub(crate) fn verify_file_checksum(path: &str) -> Result<bool, MyosotisError> {
     let data =
-        fs::read_to_string(path).with_context(|| format!("Failed to read file: {}", path))?;
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    verify_checksum(&data)
+}
 
-    let root: serde_json::Value =
-        serde_json::from_str(&data).map_err(|_| anyhow::anyhow!(MyosotisError::MalformedFileStructure))?;
+/// Writes `data` to `path` through a temporary file, as described on
+/// `Durability`. The temporary file is removed if writing it fails.
+pub(crate) fn write_atomic(
+    path: &str,
+    data: &[u8],
+    durability: Durability,
+) -> Result<(), MyosotisError> {
+    let tmp_path = format!("{}.tmp", path);
+    let written = fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(data)?;
+        if durability != Durability::Atomic {
+            file.sync_all()?;
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(MyosotisError::WriteFailed(tmp_path, e));
+    }
+    fs::rename(&tmp_path, path).map_err(|e| MyosotisError::ReplaceFailed(path.to_string(), e))?;
+    if durability == Durability::Full {
+        sync_parent_dir(path)?;
+    }
+    Ok(())
+}
 
-    let obj = root
-        .as_object()
-        .ok_or_else(|| anyhow::anyhow!(MyosotisError::MalformedFileStructure))?;
+#[cfg(unix)]
+fn sync_parent_dir(path: &str) -> Result<(), MyosotisError> {
+    let dir = match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| MyosotisError::ReplaceFailed(path.to_string(), e))
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &str) -> Result<(), MyosotisError> {
+    Ok(())
+}
 
-    let has_magic = obj.contains_key("magic");
-    let has_version = obj.contains_key("format_version");
+#[tracing::instrument(skip(memory))]
+pub fn save(path: &str, memory: &Memory) -> Result<(), MyosotisError> {
+    write_full(path, memory).map(|_| ())
+}
+
+/// `save` to `tmp_path`, a file about to be renamed over `path`. Values
+/// are offloaded into the blob store of `path`, which the file keeps
+/// after the rename.
+pub(crate) fn save_replacement(
+    tmp_path: &str,
+    path: &str,
+    memory: &Memory,
+) -> Result<(), MyosotisError> {
+    write_full_with(tmp_path, path, memory).map(|_| ())
+}
+
+/// `load` of a file written by `save_replacement`, before the rename.
+pub(crate) fn load_replacement(tmp_path: &str, path: &str) -> Result<Memory, MyosotisError> {
+    let data = fs::read_to_string(tmp_path)
+        .map_err(|e| MyosotisError::ReadFailed(tmp_path.to_string(), e))?;
+    memory_from_str(
+        &data,
+        None,
+        Some(path),
+        LoadMode::Strict,
+        &LoadLimits::default(),
+    )
+}
 
-    if has_magic && !has_version {
-        return Err(anyhow::anyhow!(MyosotisError::MissingFormatVersion));
+/// `save`, returning the bytes written. Any segment log of `path` is
+/// removed once the file holds everything it did.
+fn write_full(path: &str, memory: &Memory) -> Result<String, MyosotisError> {
+    write_full_with(path, path, memory)
+}
+
+/// `write_full` with the blob store of the memory file `blobs_of`.
+fn write_full_with(path: &str, blobs_of: &str, memory: &Memory) -> Result<String, MyosotisError> {
+    if memory.is_ephemeral() {
+        return Err(MyosotisError::Ephemeral);
+    }
+    check_write_once(path, memory)?;
+    let mut sf = from_memory(memory);
+    if let Some(threshold) = memory.blob_threshold {
+        offload_values(&mut sf, blobs_of, threshold, memory.durability)?;
+    }
+    let mut data = serde_json::to_string_pretty(&sf)?;
+    if memory.file_checksum {
+        data = add_checksum(&data);
+    }
+    write_atomic(path, data.as_bytes(), memory.durability)?;
+    let segments = segment_path(path);
+    match fs::remove_file(&segments) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(MyosotisError::WriteFailed(segments, e));
+        }
+        _ => {}
     }
+    memory.metrics.record_file_size(data.len());
+    tracing::debug!(
+        commits = memory.commits.len(),
+        bytes = data.len(),
+        "saved memory"
+    );
+    Ok(data)
+}
 
-    if has_version {
-        let version = obj
-            .get("format_version")
-            .and_then(|v| v.as_u64())
-            .ok_or_else(|| anyhow::anyhow!(MyosotisError::MissingFormatVersion))? as u32;
+/// Where `save_incremental` appends to the memory file `path`.
+pub fn segment_path(path: &str) -> String {
+    format!("{}.seg", path)
+}
 
-        if version == 0 {
-            return Err(anyhow::anyhow!(MyosotisError::MissingFormatVersion));
+/// One line of a segment log: the commits and checkpoints added since the
+/// line before, or a footer closing the lines before it.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SegmentLine {
+    Record(SegmentRecord),
+    Footer(SegmentFooter),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SegmentRecord {
+    commits: Vec<crate::commit::Commit>,
+    /// Stored like the file's checkpoints, each relative to the one before.
+    checkpoints: Vec<StoredCheckpoint>,
+    commit_times: BTreeMap<u64, u64>,
+    next_node_id: crate::node::NodeId,
+}
+
+/// What the file and its segment log hold up to this line. `base` is the
+/// SHA-256 of the file the log extends, so a log left behind by a full
+/// rewrite is ignored; `meta` covers the settings a record cannot change.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SegmentFooter {
+    base: [u8; 32],
+    commits: usize,
+    head: Option<[u8; 32]>,
+    checkpoints: usize,
+    checkpoint_head: Option<[u8; 32]>,
+    next_node_id: crate::node::NodeId,
+    meta: [u8; 32],
+}
+
+/// Settings kept in the file alone; any change to them needs a full save.
+#[derive(Serialize)]
+struct SegmentMeta<'a> {
+    canonicalization: Canonicalization,
+    float_policy: FloatPolicy,
+    write_once: bool,
+    record_state_hashes: bool,
+    id_mode: IdMode,
+    genesis_state_hash: Option<[u8; 32]>,
+    anchors: &'a [crate::anchor::AnchorReceipt],
+    locks: &'a [crate::lock::NodeLock],
+    expiries: &'a [crate::expiry::FieldExpiry],
+    retention: &'a Option<crate::retention::RetentionPolicy>,
+    unique_constraints: &'a [crate::unique::UniqueConstraint],
+    seal: &'a Option<crate::seal::Seal>,
+}
+
+fn segment_meta(memory: &Memory) -> Result<[u8; 32], MyosotisError> {
+    let meta = serde_json::to_vec(&SegmentMeta {
+        canonicalization: memory.canonicalization,
+        float_policy: memory.float_policy,
+        write_once: memory.write_once,
+        record_state_hashes: memory.record_state_hashes,
+        id_mode: memory.id_mode,
+        genesis_state_hash: memory.genesis_state_hash,
+        anchors: &memory.anchors,
+        locks: &memory.locks,
+        expiries: &memory.expiries,
+        retention: &memory.retention,
+        unique_constraints: &memory.unique_constraints,
+        seal: &memory.seal,
+    })?;
+    Ok(Sha256::digest(&meta).into())
+}
+
+fn segment_footer(memory: &Memory, base: [u8; 32]) -> Result<SegmentFooter, MyosotisError> {
+    Ok(SegmentFooter {
+        base,
+        commits: memory.commits.len(),
+        head: memory.commits.last().map(|c| c.hash),
+        checkpoints: memory.checkpoints.len(),
+        checkpoint_head: memory.checkpoints.last().map(|cp| cp.commit_hash),
+        next_node_id: memory.next_node_id,
+        meta: segment_meta(memory)?,
+    })
+}
+
+/// Records of a segment log up to its last footer for the file with
+/// SHA-256 `base`, that footer, and the byte length of the log up to it.
+/// Lines after that footer are a torn append and are left out. `None`
+/// when there is no log or it extends a different file.
+fn read_segments(
+    path: &str,
+    base: [u8; 32],
+) -> Result<Option<(Vec<SegmentRecord>, SegmentFooter, u64)>, MyosotisError> {
+    let seg_path = segment_path(path);
+    let data = match fs::read_to_string(&seg_path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(MyosotisError::ReadFailed(seg_path, e)),
+    };
+
+    let mut closed = None;
+    let mut records = Vec::new();
+    let mut offset = 0;
+    for line in data.split_inclusive('\n') {
+        offset += line.len();
+        if !line.ends_with('\n') {
+            break;
         }
-        if version > FORMAT_VERSION {
-            return Err(anyhow::anyhow!(MyosotisError::UnsupportedFormatVersion(version)));
+        match serde_json::from_str(line) {
+            Ok(SegmentLine::Record(record)) => records.push(record),
+            Ok(SegmentLine::Footer(footer)) if footer.base == base => {
+                let kept = std::mem::take(&mut records);
+                let mut all = closed.map(|(r, _, _)| r).unwrap_or_else(Vec::new);
+                all.extend(kept);
+                closed = Some((all, footer, offset as u64));
+            }
+            Ok(SegmentLine::Footer(_)) => return Ok(None),
+            Err(_) => break,
         }
+    }
+    Ok(closed)
+}
+
+/// Adds the commits and checkpoints of the segment log of `path` to `sf`,
+/// read from `data`. Every footer must agree with what it closes.
+fn apply_segments(sf: &mut StorageFormatV1, data: &str, path: &str) -> Result<(), MyosotisError> {
+    let base: [u8; 32] = Sha256::digest(data.as_bytes()).into();
+    let Some((records, footer, _)) = read_segments(path, base)? else {
+        return Ok(());
+    };
+    for record in records {
+        sf.commits.extend(record.commits);
+        sf.checkpoints.extend(record.checkpoints);
+        sf.commit_times.extend(record.commit_times);
+        sf.next_node_id = sf.next_node_id.max(record.next_node_id);
+    }
+    if sf.commits.len() != footer.commits
+        || sf.commits.last().map(|c| c.hash) != footer.head
+        || sf.checkpoints.len() != footer.checkpoints
+    {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    tracing::debug!(commits = footer.commits, "applied segment log");
+    Ok(())
+}
 
-        let magic = obj
-            .get("magic")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!(MyosotisError::InvalidFileMagic))?;
-        if magic != FILE_MAGIC {
-            return Err(anyhow::anyhow!(MyosotisError::InvalidFileMagic));
+/// `save` that, when `path` already holds a prefix of `memory`'s chain,
+/// only appends the new commits and checkpoints to `segment_path(path)`
+/// with a small footer, instead of rewriting the file. `load` reads the
+/// log back in; the next `save` folds it into the file and removes it.
+///
+/// The file is read and hashed, never written. A full save is done when
+/// the file does not exist yet, when its chain was rewritten, when
+/// settings such as locks, anchors or the seal changed, and for memories
+/// with a blob threshold or file checksum. Copying the file means copying
+/// its segment log too; `backup` does.
+#[tracing::instrument(skip(memory))]
+pub fn save_incremental(path: &str, memory: &Memory) -> Result<(), MyosotisError> {
+    if memory.is_ephemeral() {
+        return Err(MyosotisError::Ephemeral);
+    }
+    let base = match fs::read(path) {
+        Ok(data) if memory.blob_threshold.is_none() && !memory.file_checksum => {
+            Sha256::digest(&data).into()
+        }
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(MyosotisError::ReadFailed(path.to_string(), e));
         }
+        _ => return save_fresh_segments(path, memory),
+    };
+    let Some((_, footer, end)) = read_segments(path, base)? else {
+        return save_fresh_segments(path, memory);
+    };
+
+    let commit_at = |n: usize| n.checked_sub(1).map(|i| memory.commits[i].hash);
+    let checkpoint_at = |n: usize| n.checked_sub(1).map(|i| memory.checkpoints[i].commit_hash);
+    let extends = memory.commits.len() >= footer.commits
+        && commit_at(footer.commits) == footer.head
+        && memory.checkpoints.len() >= footer.checkpoints
+        && checkpoint_at(footer.checkpoints) == footer.checkpoint_head
+        && footer.meta == segment_meta(memory)?;
+    if !extends {
+        return save_fresh_segments(path, memory);
+    }
+    if memory.commits.len() == footer.commits
+        && memory.checkpoints.len() == footer.checkpoints
+        && memory.next_node_id == footer.next_node_id
+    {
+        return Ok(());
+    }
+
+    let head_id = match footer.commits {
+        0 => 0,
+        n => memory.commits[n - 1].id,
+    };
+    let from = footer.checkpoints.saturating_sub(1);
+    let mut checkpoints = store_checkpoints(&memory.checkpoints[from..]);
+    if footer.checkpoints > 0 {
+        checkpoints.remove(0);
+    }
+    let record = SegmentLine::Record(SegmentRecord {
+        commits: memory.commits[footer.commits..].to_vec(),
+        checkpoints,
+        commit_times: memory
+            .commit_times
+            .range(head_id + 1..)
+            .map(|(id, time)| (*id, *time))
+            .collect(),
+        next_node_id: memory.next_node_id,
+    });
+    let mut lines = serde_json::to_string(&record)?;
+    lines.push('\n');
+    lines.push_str(&serde_json::to_string(&SegmentLine::Footer(
+        segment_footer(memory, base)?,
+    ))?);
+    lines.push('\n');
+
+    let seg_path = segment_path(path);
+    let appended = fs::OpenOptions::new()
+        .write(true)
+        .open(&seg_path)
+        .and_then(|mut file| {
+            // Drop a torn append before writing after the last footer.
+            file.set_len(end)?;
+            file.seek(std::io::SeekFrom::End(0))?;
+            file.write_all(lines.as_bytes())?;
+            if memory.durability != Durability::Atomic {
+                file.sync_all()?;
+            }
+            Ok(())
+        });
+    appended.map_err(|e| MyosotisError::WriteFailed(seg_path, e))?;
+    tracing::debug!(
+        commits = memory.commits.len() - footer.commits,
+        bytes = lines.len(),
+        "appended segment"
+    );
+    Ok(())
+}
 
-        let sf: StorageFormatV1 = serde_json::from_value(root)
-            .map_err(|_| anyhow::anyhow!(MyosotisError::MalformedFileStructure))?;
-        let mem = to_memory(sf);
-        return validate_and_build_head(mem, mode);
+/// Full save of `path` followed by a segment log holding only a footer.
+fn save_fresh_segments(path: &str, memory: &Memory) -> Result<(), MyosotisError> {
+    let data = write_full(path, memory)?;
+    if memory.blob_threshold.is_some() || memory.file_checksum {
+        return Ok(());
     }
+    let footer = segment_footer(memory, Sha256::digest(data.as_bytes()).into())?;
+    let mut line = serde_json::to_string(&SegmentLine::Footer(footer))?;
+    line.push('\n');
+    write_atomic(&segment_path(path), line.as_bytes(), memory.durability)
+}
 
-    // Legacy v0.5.0 path: no magic + no format_version
-    if has_magic {
-        return Err(anyhow::anyhow!(MyosotisError::InvalidFileMagic));
+/// The file `save` would write for `memory`, with every value inline, for
+/// storing it somewhere other than a local file.
+pub fn to_string(memory: &Memory) -> Result<String, MyosotisError> {
+    let mut data = serde_json::to_string_pretty(&from_memory(memory))?;
+    if memory.file_checksum {
+        data = add_checksum(&data);
     }
+    Ok(data)
+}
+
+/// `load` of a file held in `data`, as written by `to_string`.
+pub fn from_str(data: &str) -> Result<Memory, MyosotisError> {
+    memory_from_str(data, None, None, LoadMode::Strict, &LoadLimits::default())
+}
+
+/// Size in bytes `save` would write for `memory`.
+pub(crate) fn encoded_len(memory: &Memory) -> Result<usize, MyosotisError> {
+    Ok(serde_json::to_string_pretty(&from_memory(memory))?.len())
+}
+
+pub fn load_with_mode(path: &str, mode: LoadMode) -> Result<Memory, MyosotisError> {
+    load_with_limits(path, mode, &LoadLimits::default())
+}
+
+/// `load_with_mode` that refuses, with `LoadLimitExceeded`, files beyond
+/// `limits`. The checks run before validation and replay.
+#[tracing::instrument]
+pub fn load_with_limits(
+    path: &str,
+    mode: LoadMode,
+    limits: &LoadLimits,
+) -> Result<Memory, MyosotisError> {
+    let started = Instant::now();
+    if let Some(max) = limits.max_file_bytes {
+        let size = fs::metadata(path)
+            .map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?
+            .len();
+        if size > max {
+            return Err(MyosotisError::LoadLimitExceeded("file bytes", size, max));
+        }
+    }
+    let data =
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    let mem = memory_from_str(&data, Some(path), Some(path), mode, limits)?;
+    mem.metrics.record_load(started.elapsed(), data.len());
+    Ok(mem)
+}
+
+fn memory_from_str(
+    data: &str,
+    path: Option<&str>,
+    blobs_of: Option<&str>,
+    mode: LoadMode,
+    limits: &LoadLimits,
+) -> Result<Memory, MyosotisError> {
+    let summed = verify_checksum(data)?;
+    let verify = matches!(mode, LoadMode::Strict);
+    let mem = parse_memory(data, path, blobs_of, verify, limits)?;
+    // A checksum that is not on the first line was moved by an edit.
+    if mem.file_checksum && !summed {
+        return Err(MyosotisError::FileChecksumMismatch);
+    }
+    validate_and_build_head(mem, mode)
+}
+
+/// Reads a file into a `Memory` without validating it or building
+/// `head_state`, for diagnostics that must see broken files.
+pub(crate) fn load_unvalidated(path: &str) -> Result<Memory, MyosotisError> {
+    let data =
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    parse_memory(&data, Some(path), Some(path), false, &LoadLimits::default())
+}
+
+/// Reads `data`, the memory file at `path` if it has one, whose blobs are
+/// those of the file `blobs_of`.
+fn parse_memory(
+    data: &str,
+    path: Option<&str>,
+    blobs_of: Option<&str>,
+    verify: bool,
+    limits: &LoadLimits,
+) -> Result<Memory, MyosotisError> {
+    let root: serde_json::Value =
+        serde_json::from_str(data).map_err(|_| MyosotisError::MalformedFileStructure)?;
+
+    let obj = root
+        .as_object()
+        .ok_or(MyosotisError::MalformedFileStructure)?;
+
+    if check_header(obj.get("magic"), obj.get("format_version"))?.is_some() {
+        let mut sf: StorageFormatV1 =
+            serde_json::from_value(root).map_err(|_| MyosotisError::MalformedFileStructure)?;
+        restore_values(&mut sf, blobs_of, limits)?;
+        if let Some(path) = path {
+            apply_segments(&mut sf, data, path)?;
+        }
+        check_limits(&sf, limits)?;
+        return to_memory(sf, verify);
+    }
+
+    tracing::info!("loading legacy v0.5.0 file; header will be added on next write");
 
     let legacy: LegacyStorageFormatV05 =
-        serde_json::from_str(&data).map_err(|_| anyhow::anyhow!(MyosotisError::MalformedFileStructure))?;
+        serde_json::from_str(data).map_err(|_| MyosotisError::MalformedFileStructure)?;
     let sf = StorageFormatV1 {
         magic: FILE_MAGIC.to_string(),
         format_version: FORMAT_VERSION,
+        canonicalization: Canonicalization::Binary,
+        float_policy: FloatPolicy::BitExact,
+        write_once: false,
+        record_state_hashes: false,
+        id_mode: IdMode::Sequential,
         genesis_state: legacy.genesis_state,
         genesis_state_hash: legacy.genesis_state_hash,
         commits: legacy.commits,
         checkpoints: legacy.checkpoints,
         next_node_id: legacy.next_node_id,
+        anchors: Vec::new(),
+        locks: Vec::new(),
+        expiries: Vec::new(),
+        retention: None,
+        unique_constraints: Vec::new(),
+        commit_times: BTreeMap::new(),
+        seal: None,
+        checksum: None,
+        blob_threshold: None,
+        blobs: Vec::new(),
     };
 
-    let mem = to_memory(sf);
-    validate_and_build_head(mem, mode)
+    check_limits(&sf, limits)?;
+    to_memory(sf, verify)
 }
 
-pub fn load(path: &str) -> Result<Memory> {
+pub fn load(path: &str) -> Result<Memory, MyosotisError> {
     load_with_mode(path, LoadMode::Strict)
 }
 
@@ -164,6 +1211,576 @@ pub fn exists(path: &str) -> bool {
     Path::new(path).exists()
 }
 
-pub fn compact(path: &str, at: Option<u64>) -> Result<()> {
+pub fn compact(path: &str, at: Option<u64>) -> Result<(), MyosotisError> {
     crate::maintenance::compact(path, at)
 }
+
+/// Writes `memory` as a self-delimiting frame for embedding in another
+/// file: `EMBED_MAGIC`, payload length (u64 little-endian), the JSON
+/// payload as written by `save`, then the SHA-256 of the payload.
+pub fn embed<W: Write>(writer: &mut W, memory: &Memory) -> Result<(), MyosotisError> {
+    let payload = serde_json::to_vec(&from_memory(memory))?;
+    writer.write_all(EMBED_MAGIC)?;
+    writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+    writer.write_all(&payload)?;
+    writer.write_all(&Sha256::digest(&payload))?;
+    Ok(())
+}
+
+/// Reads one frame written by `embed`, leaving `reader` positioned just
+/// after it. The checksum is verified before the memory is validated.
+pub fn extract<R: Read>(reader: &mut R) -> Result<Memory, MyosotisError> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != EMBED_MAGIC {
+        return Err(MyosotisError::InvalidFileMagic);
+    }
+
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len)?;
+    let len = usize::try_from(u64::from_le_bytes(len))
+        .map_err(|_| MyosotisError::MalformedFileStructure)?;
+
+    let mut payload = Vec::new();
+    reader.take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() != len {
+        return Err(MyosotisError::MalformedFileStructure);
+    }
+    let mut checksum = [0u8; 32];
+    reader.read_exact(&mut checksum)?;
+    if Sha256::digest(&payload).as_slice() != checksum {
+        return Err(MyosotisError::EmbedChecksumMismatch);
+    }
+
+    let data = std::str::from_utf8(&payload).map_err(|_| MyosotisError::MalformedFileStructure)?;
+    memory_from_str(data, None, None, LoadMode::Strict, &LoadLimits::default())
+}
+
+/// A commit whose mutations are kept as undecoded JSON until requested.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LazyCommit {
+    pub id: u64,
+    pub parent: Option<u64>,
+    pub parent_hash: Option<[u8; 32]>,
+    pub hash: [u8; 32],
+    pub message: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub state_hash: Option<[u8; 32]>,
+    mutations: Box<serde_json::value::RawValue>,
+}
+
+impl LazyCommit {
+    /// Size of the encoded mutation payload in bytes.
+    pub fn mutations_len(&self) -> usize {
+        self.mutations.get().len()
+    }
+
+    pub fn mutations(&self) -> Result<Vec<crate::commit::Mutation>, MyosotisError> {
+        serde_json::from_str(self.mutations.get())
+            .map_err(|_| MyosotisError::MalformedFileStructure)
+    }
+
+    pub fn into_commit(self) -> Result<crate::commit::Commit, MyosotisError> {
+        let mutations = self.mutations()?;
+        Ok(crate::commit::Commit {
+            id: self.id,
+            parent: self.parent,
+            parent_hash: self.parent_hash,
+            hash: self.hash,
+            message: self.message,
+            source: self.source,
+            state_hash: self.state_hash,
+            mutations,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct CommitStreamFormat {
+    magic: Option<serde_json::Value>,
+    format_version: Option<serde_json::Value>,
+    #[serde(default)]
+    canonicalization: Canonicalization,
+    #[serde(default)]
+    write_once: bool,
+    genesis_state_hash: Option<[u8; 32]>,
+    commits: Vec<LazyCommit>,
+}
+
+/// Commit headers in log order. Only the fields needed to walk the chain
+/// are decoded; genesis state, checkpoints and mutation bodies are skipped.
+pub struct CommitStream {
+    pub canonicalization: Canonicalization,
+    pub write_once: bool,
+    pub genesis_state_hash: Option<[u8; 32]>,
+    commits: std::vec::IntoIter<LazyCommit>,
+}
+
+impl Iterator for CommitStream {
+    type Item = LazyCommit;
+
+    fn next(&mut self) -> Option<LazyCommit> {
+        self.commits.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.commits.size_hint()
+    }
+}
+
+/// Opens `path` for header-only commit iteration. The file header is
+/// checked, but no hash or replay validation is performed. Values kept in
+/// the blob store show as their `sha256:` placeholder in mutation bodies.
+/// Commits in the segment log are not included.
+pub fn stream_commits(path: &str) -> Result<CommitStream, MyosotisError> {
+    let data =
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    verify_checksum(&data)?;
+    let sf: CommitStreamFormat =
+        serde_json::from_str(&data).map_err(|_| MyosotisError::MalformedFileStructure)?;
+    check_header(sf.magic.as_ref(), sf.format_version.as_ref())?;
+
+    Ok(CommitStream {
+        canonicalization: sf.canonicalization,
+        write_once: sf.write_once,
+        genesis_state_hash: sf.genesis_state_hash,
+        commits: sf.commits.into_iter(),
+    })
+}
+
+/// Checks commit id sequencing and parent-hash links using headers only.
+/// Commit hashes themselves are not recomputed.
+pub fn verify_commit_links(stream: CommitStream) -> Result<usize, MyosotisError> {
+    let mut prev: Option<(u64, [u8; 32])> = None;
+    let mut count = 0;
+    let genesis_hash = stream.genesis_state_hash;
+
+    for commit in stream {
+        match prev {
+            None => {
+                if commit.parent.is_some() || commit.parent_hash != genesis_hash {
+                    return Err(MyosotisError::CorruptParentHash);
+                }
+            }
+            Some((prev_id, prev_hash)) => {
+                if commit.id != prev_id + 1 || commit.parent != Some(prev_id) {
+                    return Err(MyosotisError::CorruptCommitChain(format!(
+                        "commit {} does not follow {}",
+                        commit.id, prev_id
+                    )));
+                }
+                if commit.parent_hash != Some(prev_hash) {
+                    return Err(MyosotisError::CorruptParentHash);
+                }
+            }
+        }
+        prev = Some((commit.id, commit.hash));
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[derive(Clone, Deserialize)]
+struct LazyCheckpoint {
+    commit_id: u64,
+    commit_hash: [u8; 32],
+    state_hash: [u8; 32],
+    #[serde(default)]
+    state: Option<Box<serde_json::value::RawValue>>,
+    #[serde(default)]
+    changed: Option<Box<serde_json::value::RawValue>>,
+    #[serde(default, with = "crate::node::wide_id::list")]
+    removed: Vec<crate::node::NodeId>,
+}
+
+impl LazyCheckpoint {
+    fn is_delta(&self) -> bool {
+        self.changed.is_some() || !self.removed.is_empty()
+    }
+
+    fn decode(&self) -> Result<StoredCheckpoint, MyosotisError> {
+        Ok(StoredCheckpoint {
+            commit_id: self.commit_id,
+            commit_hash: self.commit_hash,
+            state_hash: self.state_hash,
+            state: self.state.as_deref().map(decode_state).transpose()?,
+            changed: self.changed.as_deref().map(decode_state).transpose()?,
+            removed: self.removed.clone(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct LazyFormat {
+    magic: Option<serde_json::Value>,
+    format_version: Option<serde_json::Value>,
+    #[serde(default)]
+    canonicalization: Canonicalization,
+    #[serde(default)]
+    float_policy: FloatPolicy,
+    #[serde(default)]
+    write_once: bool,
+    #[serde(default)]
+    record_state_hashes: bool,
+    #[serde(default)]
+    id_mode: IdMode,
+    genesis_state: Option<Box<serde_json::value::RawValue>>,
+    genesis_state_hash: Option<[u8; 32]>,
+    commits: Vec<LazyCommit>,
+    checkpoints: Vec<LazyCheckpoint>,
+    next_node_id: crate::node::NodeId,
+    #[serde(default)]
+    anchors: Vec<crate::anchor::AnchorReceipt>,
+    #[serde(default)]
+    locks: Vec<crate::lock::NodeLock>,
+    #[serde(default)]
+    expiries: Vec<crate::expiry::FieldExpiry>,
+    #[serde(default)]
+    retention: Option<crate::retention::RetentionPolicy>,
+    #[serde(default)]
+    unique_constraints: Vec<crate::unique::UniqueConstraint>,
+    #[serde(default)]
+    commit_times: BTreeMap<u64, u64>,
+    #[serde(default)]
+    seal: Option<crate::seal::Seal>,
+    #[serde(default)]
+    blobs: Vec<BlobRef>,
+}
+
+fn decode_state(raw: &serde_json::value::RawValue) -> Result<State, MyosotisError> {
+    serde_json::from_str(raw.get()).map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+/// Read-mostly handle that builds head state from the latest checkpoint
+/// and the commits after it.
+///
+/// The file is read once, but genesis state, older checkpoint states and
+/// the mutations of commits before the latest checkpoint stay undecoded
+/// until `state_at_commit`, `validate` or `to_memory` needs them. A delta
+/// checkpoint is rebuilt from the last full one before it. Only the latest
+/// snapshot's state hash is checked on open.
+pub struct LazyMemory {
+    pub canonicalization: Canonicalization,
+    pub float_policy: FloatPolicy,
+    pub write_once: bool,
+    pub record_state_hashes: bool,
+    pub id_mode: IdMode,
+    pub next_node_id: crate::node::NodeId,
+    genesis_state: Option<Box<serde_json::value::RawValue>>,
+    genesis_state_hash: Option<[u8; 32]>,
+    commits: Vec<LazyCommit>,
+    checkpoints: Vec<LazyCheckpoint>,
+    anchors: Vec<crate::anchor::AnchorReceipt>,
+    locks: Vec<crate::lock::NodeLock>,
+    expiries: Vec<crate::expiry::FieldExpiry>,
+    retention: Option<crate::retention::RetentionPolicy>,
+    unique_constraints: Vec<crate::unique::UniqueConstraint>,
+    commit_times: BTreeMap<u64, u64>,
+    seal: Option<crate::seal::Seal>,
+    file_checksum: bool,
+    head_state: State,
+}
+
+impl LazyMemory {
+    pub fn head_state(&self) -> &State {
+        &self.head_state
+    }
+
+    /// Commit headers in log order; mutation bodies decode on demand.
+    pub fn commits(&self) -> &[LazyCommit] {
+        &self.commits
+    }
+
+    fn genesis(&self) -> Result<State, MyosotisError> {
+        match &self.genesis_state {
+            Some(raw) => decode_state(raw),
+            None => Ok(State::new()),
+        }
+    }
+
+    /// Decodes the checkpoint at `index`, applying deltas from the nearest
+    /// full checkpoint before it.
+    fn checkpoint_state(&self, index: usize) -> Result<State, MyosotisError> {
+        let base = self.checkpoints[..=index]
+            .iter()
+            .rposition(|cp| cp.state.is_some())
+            .ok_or(MyosotisError::MalformedFileStructure)?;
+        let stored = self.checkpoints[base..=index]
+            .iter()
+            .map(LazyCheckpoint::decode)
+            .collect::<Result<Vec<_>, MyosotisError>>()?;
+        let mut restored = restore_checkpoints(stored, self.canonicalization, false)?;
+        Ok(restored.pop().map(|cp| cp.state).unwrap_or_default())
+    }
+
+    fn commit_index(&self, commit_id: u64) -> Option<usize> {
+        self.commits.binary_search_by_key(&commit_id, |c| c.id).ok()
+    }
+
+    /// Replays the commits at indices `start..end` onto `state`.
+    fn replay_range(
+        &self,
+        mut state: State,
+        start: usize,
+        end: usize,
+    ) -> Result<State, MyosotisError> {
+        for commit in &self.commits[start..end] {
+            for mutation in commit.mutations()? {
+                Memory::apply_mutation(&mut state, &mutation)?;
+            }
+        }
+        Ok(state)
+    }
+
+    /// Decodes the nearest checkpoint at or before `commit_id` (or genesis)
+    /// and the commits up to it.
+    pub fn state_at_commit(&self, commit_id: u64) -> Result<State, MyosotisError> {
+        let target = self
+            .commit_index(commit_id)
+            .ok_or(MyosotisError::CommitNotFound(commit_id))?;
+
+        let checkpoint = self
+            .checkpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.commit_id <= commit_id)
+            .max_by_key(|(_, c)| c.commit_id);
+        let (state, start) = match checkpoint {
+            Some((cp_index, cp)) => {
+                let index = self
+                    .commit_index(cp.commit_id)
+                    .ok_or(MyosotisError::InvalidCheckpoint)?;
+                (self.checkpoint_state(cp_index)?, index + 1)
+            }
+            None => (self.genesis()?, 0),
+        };
+        self.replay_range(state, start, target + 1)
+    }
+
+    /// Decodes everything into a fully validated `Memory`.
+    pub fn to_memory(&self) -> Result<Memory, MyosotisError> {
+        let mut mem = Memory::new();
+        mem.canonicalization = self.canonicalization;
+        mem.float_policy = self.float_policy;
+        mem.write_once = self.write_once;
+        mem.record_state_hashes = self.record_state_hashes;
+        mem.id_mode = self.id_mode;
+        mem.genesis_state = self
+            .genesis_state
+            .as_deref()
+            .map(decode_state)
+            .transpose()?;
+        mem.genesis_state_hash = self.genesis_state_hash;
+        mem.commits = self
+            .commits
+            .iter()
+            .cloned()
+            .map(LazyCommit::into_commit)
+            .collect::<Result<_, MyosotisError>>()?;
+        let stored = self
+            .checkpoints
+            .iter()
+            .map(LazyCheckpoint::decode)
+            .collect::<Result<_, MyosotisError>>()?;
+        mem.checkpoints = restore_checkpoints(stored, self.canonicalization, true)?;
+        mem.checkpoints.sort_by_key(|cp| cp.commit_id);
+        mem.next_node_id = self.next_node_id;
+        mem.anchors = self.anchors.clone();
+        mem.locks = self.locks.clone();
+        mem.expiries = self.expiries.clone();
+        mem.retention = self.retention.clone();
+        mem.unique_constraints = self.unique_constraints.clone();
+        mem.commit_times = self.commit_times.clone();
+        mem.seal = self.seal.clone();
+        mem.file_checksum = self.file_checksum;
+        validate_and_build_head(mem, LoadMode::Strict)
+    }
+
+    /// Full validation, equivalent to a strict `load`.
+    pub fn validate(&self) -> Result<(), MyosotisError> {
+        self.to_memory().map(|_| ())
+    }
+}
+
+/// Opens `path` reading only what is needed for head state. See
+/// `LazyMemory`. Files that keep values in a blob store are refused.
+#[tracing::instrument]
+pub fn open_lazy(path: &str) -> Result<LazyMemory, MyosotisError> {
+    let data =
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    let file_checksum = verify_checksum(&data)?;
+    let lf: LazyFormat =
+        serde_json::from_str(&data).map_err(|_| MyosotisError::MalformedFileStructure)?;
+    let format_version = check_header(lf.magic.as_ref(), lf.format_version.as_ref())?;
+    if let Some(format_version) = format_version {
+        check_version_layout(
+            format_version,
+            lf.id_mode,
+            lf.checkpoints.iter().map(LazyCheckpoint::is_delta),
+        )?;
+    }
+    if !lf.blobs.is_empty() {
+        return Err(MyosotisError::InvalidInput(format!(
+            "{} keeps values in a blob store; load it with storage::load",
+            path
+        )));
+    }
+
+    let mut lazy = LazyMemory {
+        canonicalization: lf.canonicalization,
+        float_policy: lf.float_policy,
+        write_once: lf.write_once,
+        record_state_hashes: lf.record_state_hashes,
+        id_mode: lf.id_mode,
+        next_node_id: lf.next_node_id,
+        genesis_state: lf.genesis_state,
+        genesis_state_hash: lf.genesis_state_hash,
+        commits: lf.commits,
+        checkpoints: lf.checkpoints,
+        anchors: lf.anchors,
+        locks: lf.locks,
+        expiries: lf.expiries,
+        retention: lf.retention,
+        unique_constraints: lf.unique_constraints,
+        commit_times: lf.commit_times,
+        seal: lf.seal,
+        file_checksum,
+        head_state: State::new(),
+    };
+
+    let latest = lazy
+        .checkpoints
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, c)| c.commit_id);
+    let (state, start) = match latest {
+        Some((cp_index, cp)) => {
+            let index = lazy
+                .commits
+                .iter()
+                .position(|c| c.id == cp.commit_id && c.hash == cp.commit_hash)
+                .ok_or(MyosotisError::CheckpointCommitMismatch)?;
+            let state = lazy.checkpoint_state(cp_index)?;
+            if Memory::compute_state_hash_with(lazy.canonicalization, &state) != cp.state_hash {
+                return Err(MyosotisError::CorruptCheckpointHash);
+            }
+            (state, index + 1)
+        }
+        None => {
+            let state = lazy.genesis()?;
+            if lazy.genesis_state.is_some()
+                && Some(Memory::compute_state_hash_with(
+                    lazy.canonicalization,
+                    &state,
+                )) != lazy.genesis_state_hash
+            {
+                return Err(MyosotisError::CorruptGenesisHash);
+            }
+            (state, 0)
+        }
+    };
+    lazy.head_state = lazy.replay_range(state, start, lazy.commits.len())?;
+
+    tracing::debug!(
+        commits = lazy.commits.len(),
+        replayed = lazy.commits.len() - start,
+        "opened lazily"
+    );
+    Ok(lazy)
+}
+
+/// How often `watch` re-checks the file when no notification arrives, in
+/// case the platform drops events.
+const WATCH_RECHECK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// A change seen by `watch`.
+pub struct WatchUpdate<'a> {
+    /// The file as just reloaded.
+    pub memory: &'a Memory,
+    /// Commits appended since the previous update, or every commit when
+    /// `rewritten` is set.
+    pub new_commits: &'a [crate::commit::Commit],
+    /// The previously seen head commit is gone, e.g. after compaction.
+    pub rewritten: bool,
+}
+
+/// Loads `path`, then blocks, reloading it whenever it changes and calling
+/// `on_update` with the commits that appeared. Changes that add no commits
+/// are skipped. Returning `false` from `on_update` stops watching.
+///
+/// The parent directory is watched, so files replaced by rename are
+/// followed. A reload that fails, e.g. because a save is still in
+/// progress, is retried on the next change.
+pub fn watch<F>(path: &str, mut on_update: F) -> Result<(), MyosotisError>
+where
+    F: FnMut(&WatchUpdate) -> bool,
+{
+    use notify::Watcher;
+
+    let target = Path::new(path);
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut last = load(path)?.commits.last().map(|c| (c.id, c.hash));
+    let mut last_modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| MyosotisError::WatchFailed(e.to_string()))?;
+    watcher
+        .watch(dir, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| MyosotisError::WatchFailed(e.to_string()))?;
+
+    loop {
+        let changed = match rx.recv_timeout(WATCH_RECHECK) {
+            Ok(Ok(event)) => event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == target.file_name()),
+            Ok(Err(e)) => return Err(MyosotisError::WatchFailed(e.to_string())),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+                modified != last_modified
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(MyosotisError::WatchFailed("watcher stopped".to_string()));
+            }
+        };
+        if !changed {
+            continue;
+        }
+        last_modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        let memory = match load(path) {
+            Ok(memory) => memory,
+            Err(e) => {
+                tracing::debug!(error = %e, "reload failed; waiting for next change");
+                continue;
+            }
+        };
+        let head = memory.commits.last().map(|c| (c.id, c.hash));
+        if head == last {
+            continue;
+        }
+        let kept = match last {
+            None => Some(0),
+            Some((id, hash)) => memory
+                .commits
+                .iter()
+                .position(|c| c.id == id && c.hash == hash)
+                .map(|i| i + 1),
+        };
+        let update = WatchUpdate {
+            memory: &memory,
+            new_commits: &memory.commits[kept.unwrap_or(0)..],
+            rewritten: kept.is_none(),
+        };
+        last = head;
+        if !on_update(&update) {
+            return Ok(());
+        }
+    }
+}
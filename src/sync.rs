@@ -0,0 +1,255 @@
+use crate::canonical::Canonicalization;
+use crate::commit::Commit;
+use crate::error::MyosotisError;
+use crate::memory::{CHECKPOINT_INTERVAL, Checkpoint, Memory};
+use crate::node::is_sequential_id;
+use crate::observer::CommitOrigin;
+use crate::storage::Durability;
+use crate::unique::UniqueIndex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+impl Memory {
+    /// Appends the commits `other` has beyond this memory's last commit.
+    /// Both must share genesis, canonicalization and every commit this
+    /// memory already has. Fetched commits are re-hashed and replayed
+    /// before anything is changed, and checkpoints are written as if they
+    /// had been committed here. Returns the number of commits fetched.
+    pub fn fetch(&mut self, other: &Memory) -> Result<usize, MyosotisError> {
+        if self.canonicalization != other.canonicalization {
+            return Err(MyosotisError::HistoriesDiverged(
+                "canonicalization differs".to_string(),
+            ));
+        }
+        if self.genesis_state_hash != other.genesis_state_hash {
+            return Err(MyosotisError::HistoriesDiverged(
+                "genesis differs".to_string(),
+            ));
+        }
+        let last_id = self.commits.last().map(|c| c.id).unwrap_or(0);
+        let fetched = self.append_verified(&other.commits)?;
+        self.next_node_id = self.next_node_id.max(other.next_node_id);
+        self.commit_times
+            .extend(other.commit_times.range(last_id + 1..));
+        Ok(fetched)
+    }
+
+    /// Takes a contiguous run of commits. Those this memory already has
+    /// must match by hash; the rest are verified, replayed and appended
+    /// (all or nothing). The caller checks where the run is anchored.
+    fn append_verified(&mut self, incoming: &[Commit]) -> Result<usize, MyosotisError> {
+        if !self.pending_mutations.is_empty() {
+            return Err(MyosotisError::InvalidInput(
+                "commit or discard pending mutations before appending commits".to_string(),
+            ));
+        }
+        let last_id = self.commits.last().map(|c| c.id).unwrap_or(0);
+        let (known, new) = incoming.split_at(incoming.partition_point(|c| c.id <= last_id));
+        for theirs in known {
+            let ours = self.commits.binary_search_by_key(&theirs.id, |c| c.id);
+            if !ours.is_ok_and(|i| self.commits[i].hash == theirs.hash) {
+                return Err(MyosotisError::HistoriesDiverged(format!(
+                    "commit {} differs",
+                    theirs.id
+                )));
+            }
+        }
+        if new.is_empty() {
+            return Ok(0);
+        }
+
+        let mut commits = self.commits.clone();
+        let mut checkpoints = Vec::new();
+        let mut state = self.head_state.clone();
+        for commit in new {
+            let last = commits.last();
+            let parent_hash = last.map(|c| c.hash).or(self.genesis_state_hash);
+            if commit.parent != last.map(|c| c.id)
+                || commit.id != last.map(|c| c.id + 1).unwrap_or(1)
+                || commit.parent_hash != parent_hash
+            {
+                return Err(MyosotisError::CorruptParentHash);
+            }
+            if commit.compute_hash_with(self.canonicalization) != commit.hash {
+                return Err(MyosotisError::CorruptCommitHash);
+            }
+            self.check_floats(&commit.mutations)?;
+            for mutation in &commit.mutations {
+                Self::apply_mutation(&mut state, mutation)?;
+            }
+            self.check_recorded_state(commit, &state)?;
+            commits.push(commit.clone());
+
+            if commits.len().is_multiple_of(CHECKPOINT_INTERVAL) {
+                checkpoints.push(Checkpoint {
+                    commit_id: commit.id,
+                    commit_hash: commit.hash,
+                    state_hash: self.hash_state(&state),
+                    state: state.clone(),
+                });
+            }
+        }
+
+        let unique_index = UniqueIndex::build(&self.unique_constraints, &state)?;
+
+        let appended = new.len();
+        let first_new = self.commits.len();
+        self.commits = commits;
+        self.head_state = state;
+        self.unique_index = unique_index;
+        self.reindex_aliases();
+        self.reindex_labels();
+        if let Some(max_id) = self
+            .head_state
+            .keys()
+            .copied()
+            .filter(|id| is_sequential_id(*id))
+            .max()
+        {
+            self.next_node_id = self.next_node_id.max(max_id + 1);
+        }
+        for commit in &self.commits[first_new..] {
+            self.observers.commit(commit, CommitOrigin::Fetched);
+        }
+        for checkpoint in checkpoints {
+            self.observers.checkpoint(&checkpoint);
+            self.checkpoints.push(checkpoint);
+        }
+        self.check_footprint();
+        tracing::info!(appended, "appended verified commits");
+        Ok(appended)
+    }
+
+    /// Appends this memory's extra commits to `other`. See `fetch`.
+    pub fn push(&self, other: &mut Memory) -> Result<usize, MyosotisError> {
+        other.fetch(self)
+    }
+
+    /// Packs the commits after `since` together with the hash they build
+    /// on. `since` 0 bundles everything after genesis.
+    pub fn bundle(&self, since: u64) -> Result<Bundle, MyosotisError> {
+        let (start, base_hash) = if since == 0 {
+            (0, self.genesis_state_hash)
+        } else {
+            let index = self
+                .commits
+                .binary_search_by_key(&since, |c| c.id)
+                .map_err(|_| {
+                    MyosotisError::InvalidInput(format!("commit {} is not in this history", since))
+                })?;
+            (index + 1, Some(self.commits[index].hash))
+        };
+        Ok(Bundle {
+            magic: BUNDLE_MAGIC.to_string(),
+            format_version: BUNDLE_FORMAT_VERSION,
+            canonicalization: self.canonicalization,
+            base_commit: since,
+            base_hash,
+            commits: self.commits[start..].to_vec(),
+        })
+    }
+
+    /// Appends the bundle's commits this memory does not have yet. The
+    /// bundle's base commit must be present here with the same hash.
+    pub fn unbundle(&mut self, bundle: &Bundle) -> Result<usize, MyosotisError> {
+        if bundle.magic != BUNDLE_MAGIC {
+            return Err(MyosotisError::InvalidFileMagic);
+        }
+        if bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(MyosotisError::UnsupportedFormatVersion(
+                bundle.format_version,
+            ));
+        }
+        if bundle.canonicalization != self.canonicalization {
+            return Err(MyosotisError::HistoriesDiverged(
+                "canonicalization differs".to_string(),
+            ));
+        }
+        let base_hash = if bundle.base_commit == 0 {
+            self.genesis_state_hash
+        } else {
+            self.commits
+                .binary_search_by_key(&bundle.base_commit, |c| c.id)
+                .ok()
+                .map(|i| self.commits[i].hash)
+        };
+        if base_hash != bundle.base_hash {
+            return Err(MyosotisError::HistoriesDiverged(format!(
+                "base commit {} is missing or differs",
+                bundle.base_commit
+            )));
+        }
+        if bundle
+            .commits
+            .first()
+            .is_some_and(|c| c.parent_hash != bundle.base_hash)
+        {
+            return Err(MyosotisError::CorruptParentHash);
+        }
+        self.append_verified(&bundle.commits)
+    }
+}
+
+const BUNDLE_MAGIC: &str = "MYOBUNDLE";
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// A slice of history for exchange between files. `base_hash` is the hash
+/// of `base_commit`, or the genesis state hash when `base_commit` is 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bundle {
+    magic: String,
+    format_version: u32,
+    #[serde(default, skip_serializing_if = "Canonicalization::is_binary")]
+    pub canonicalization: Canonicalization,
+    pub base_commit: u64,
+    pub base_hash: Option<[u8; 32]>,
+    pub commits: Vec<Commit>,
+}
+
+/// Fetches commits from the file at `remote` into the file at `local`,
+/// saving `local` only if something was fetched.
+#[tracing::instrument]
+pub fn pull(local: &str, remote: &str) -> Result<usize, MyosotisError> {
+    let mut mem = crate::storage::load(local)?;
+    let theirs = crate::storage::load(remote)?;
+    let fetched = mem.fetch(&theirs)?;
+    if fetched > 0 {
+        crate::storage::save(local, &mem)?;
+    }
+    Ok(fetched)
+}
+
+/// Sends commits from the file at `local` to the file at `remote`.
+pub fn push(local: &str, remote: &str) -> Result<usize, MyosotisError> {
+    pull(remote, local)
+}
+
+/// Writes the commits of `path` after `since` to a bundle file at `out`.
+/// Returns the number of commits written.
+#[tracing::instrument]
+pub fn write_bundle(path: &str, out: &str, since: u64) -> Result<usize, MyosotisError> {
+    let bundle = crate::storage::load(path)?.bundle(since)?;
+    let data = serde_json::to_string_pretty(&bundle)?;
+    crate::storage::write_atomic(out, data.as_bytes(), Durability::default())?;
+    Ok(bundle.commits.len())
+}
+
+pub fn read_bundle(path: &str) -> Result<Bundle, MyosotisError> {
+    let data =
+        fs::read_to_string(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    serde_json::from_str(&data).map_err(|_| MyosotisError::MalformedFileStructure)
+}
+
+/// Applies the bundle file at `bundle` to the memory file at `path`,
+/// saving only if commits were added.
+#[tracing::instrument]
+pub fn apply_bundle(path: &str, bundle: &str) -> Result<usize, MyosotisError> {
+    let bundle = read_bundle(bundle)?;
+    let mut mem = crate::storage::load(path)?;
+    let added = mem.unbundle(&bundle)?;
+    if added > 0 {
+        crate::storage::save(path, &mem)?;
+    }
+    Ok(added)
+}
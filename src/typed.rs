@@ -0,0 +1,214 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use std::collections::{BTreeSet, HashMap};
+
+#[cfg(feature = "derive")]
+pub use myosotis_derive::MyoNode;
+
+/// A struct stored as one node, one field per struct field. Usually
+/// derived with `#[derive(MyoNode)]`; `#[myo(type = "...")]` overrides the
+/// node type and `#[myo(rename = "...")]` a field name.
+pub trait MyoNode: Sized {
+    /// Node type, the struct name unless overridden.
+    const TYPE: &'static str;
+
+    /// Field values in declaration order. Fields that are `None` are left
+    /// out rather than stored.
+    fn to_fields(&self) -> Vec<(&'static str, Value)>;
+
+    /// Reads a node of type `TYPE`. Missing fields are an error unless the
+    /// struct field is an `Option`.
+    fn from_node(node: &Node) -> Result<Self, MyosotisError>;
+
+    fn to_node(&self, id: NodeId) -> Node {
+        Node {
+            id,
+            ty: Self::TYPE.to_string(),
+            fields: self
+                .to_fields()
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+            deleted: false,
+            alias: None,
+            namespace: None,
+            archived: false,
+            labels: BTreeSet::new(),
+        }
+    }
+}
+
+/// Conversion between a struct field and a node `Value`.
+pub trait FieldValue: Sized {
+    /// `None` leaves the field unset.
+    fn to_value(&self) -> Option<Value>;
+
+    /// `value` is `None` when the node has no such field. Returns `None`
+    /// if the field is required but missing, or has the wrong shape.
+    fn from_value(value: Option<&Value>) -> Option<Self>;
+}
+
+impl FieldValue for Value {
+    fn to_value(&self) -> Option<Value> {
+        Some(self.clone())
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        value.cloned()
+    }
+}
+
+impl FieldValue for String {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Str(self.clone()))
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        value?.as_str().map(str::to_string)
+    }
+}
+
+impl FieldValue for i64 {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Int(*self))
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        value?.as_int()
+    }
+}
+
+impl FieldValue for f64 {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Float(*self))
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value? {
+            Value::Float(f) => Some(*f),
+            Value::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FieldValue for bool {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Bool(*self))
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        value?.as_bool()
+    }
+}
+
+/// A reference to another node, stored as `Value::Ref`. Plain integers
+/// are stored as `Value::Int`, so ids need this wrapper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeRef(pub NodeId);
+
+impl FieldValue for NodeRef {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Ref(self.0))
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        value?.as_ref_id().map(NodeRef)
+    }
+}
+
+impl<T: FieldValue> FieldValue for Option<T> {
+    fn to_value(&self) -> Option<Value> {
+        self.as_ref().and_then(T::to_value)
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value {
+            None => Some(None),
+            Some(value) => T::from_value(Some(value)).map(Some),
+        }
+    }
+}
+
+impl<T: FieldValue> FieldValue for Vec<T> {
+    fn to_value(&self) -> Option<Value> {
+        self.iter()
+            .map(T::to_value)
+            .collect::<Option<Vec<_>>>()
+            .map(Value::List)
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value? {
+            Value::List(items) => items.iter().map(|v| T::from_value(Some(v))).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FieldValue> FieldValue for HashMap<String, T> {
+    fn to_value(&self) -> Option<Value> {
+        self.iter()
+            .map(|(k, v)| v.to_value().map(|v| (k.clone(), v)))
+            .collect::<Option<HashMap<_, _>>>()
+            .map(Value::Map)
+    }
+
+    fn from_value(value: Option<&Value>) -> Option<Self> {
+        match value? {
+            Value::Map(entries) => entries
+                .iter()
+                .map(|(k, v)| T::from_value(Some(v)).map(|v| (k.clone(), v)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Used by derived `from_node`: fails unless `node` is a live node of
+/// type `ty`.
+pub fn check_type(node: &Node, ty: &str) -> Result<(), MyosotisError> {
+    if node.deleted {
+        return Err(MyosotisError::NodeDeleted(node.id));
+    }
+    if node.ty != ty {
+        return Err(MyosotisError::InvalidInput(format!(
+            "node {} is a {}, not a {}",
+            node.id, node.ty, ty
+        )));
+    }
+    Ok(())
+}
+
+/// Used by derived `from_node`: reads and converts one field.
+pub fn field<T: FieldValue>(node: &Node, key: &str) -> Result<T, MyosotisError> {
+    let value = node.fields.get(key);
+    T::from_value(value).ok_or_else(|| match value {
+        None => MyosotisError::FieldNotFound(key.to_string()),
+        Some(_) => MyosotisError::InvalidInput(format!(
+            "field {} of node {} has the wrong type",
+            key, node.id
+        )),
+    })
+}
+
+impl Memory {
+    /// Stages a new node of type `T::TYPE` with one `set` per field.
+    pub fn insert_typed<T: MyoNode>(&mut self, value: &T) -> Result<NodeId, MyosotisError> {
+        let id = self.create(T::TYPE);
+        for (key, value) in value.to_fields() {
+            self.set(id, key, value)?;
+        }
+        Ok(id)
+    }
+
+    /// Reads node `id` from head state as a `T`.
+    pub fn get_typed<T: MyoNode>(&self, id: NodeId) -> Result<T, MyosotisError> {
+        let node = self
+            .head_state
+            .get(&id)
+            .ok_or(MyosotisError::NodeNotFound(id))?;
+        T::from_node(node)
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+type State = HashMap<NodeId, Node>;
+/// Each touched node and the constrained value it now holds, if any.
+type Changes = Vec<(NodeId, Option<Vec<u8>>)>;
+
+/// A field no two live nodes of a type may share a value of, added with
+/// `Memory::add_unique_constraint`. Like locks, constraints are kept in
+/// the file outside the hash chain; every commit and load checks them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UniqueConstraint {
+    pub ty: String,
+    pub key: String,
+}
+
+impl UniqueConstraint {
+    /// Canonical bytes of the constrained value of `node`, if it is a live
+    /// node of the type that has the field.
+    fn value_of(&self, node: &Node) -> Option<Vec<u8>> {
+        if node.deleted || node.ty != self.ty {
+            return None;
+        }
+        node.fields.get(&self.key).map(Memory::value_bytes)
+    }
+
+    fn violation(&self, a: NodeId, b: NodeId) -> MyosotisError {
+        MyosotisError::UniqueViolation(format!("{}.{}", self.ty, self.key), a.min(b), a.max(b))
+    }
+}
+
+/// Node holding each value of one constraint in committed state, and the
+/// value each such node holds.
+#[derive(Debug, Clone, Default)]
+struct ValueIndex {
+    by_value: HashMap<Vec<u8>, NodeId>,
+    by_node: HashMap<NodeId, Vec<u8>>,
+}
+
+impl ValueIndex {
+    fn build(constraint: &UniqueConstraint, state: &State) -> Result<Self, MyosotisError> {
+        let mut ids: Vec<NodeId> = state.keys().copied().collect();
+        ids.sort_unstable();
+        let mut index = Self::default();
+        for id in ids {
+            let Some(value) = constraint.value_of(&state[&id]) else {
+                continue;
+            };
+            if let Some(other) = index.by_value.insert(value.clone(), id) {
+                return Err(constraint.violation(other, id));
+            }
+            index.by_node.insert(id, value);
+        }
+        Ok(index)
+    }
+
+    /// Values `touched` nodes hold in `state`, checked against each other
+    /// and against the untouched nodes in the index.
+    fn changes(
+        &self,
+        constraint: &UniqueConstraint,
+        state: &State,
+        touched: &BTreeSet<NodeId>,
+    ) -> Result<Changes, MyosotisError> {
+        let mut seen: HashMap<&[u8], NodeId> = HashMap::new();
+        let mut changes = Vec::with_capacity(touched.len());
+        for &id in touched {
+            let value = state.get(&id).and_then(|node| constraint.value_of(node));
+            changes.push((id, value));
+        }
+        for (id, value) in &changes {
+            let Some(value) = value else { continue };
+            if let Some(other) = seen.insert(value, *id) {
+                return Err(constraint.violation(other, *id));
+            }
+            if let Some(&other) = self.by_value.get(value)
+                && !touched.contains(&other)
+            {
+                return Err(constraint.violation(other, *id));
+            }
+        }
+        Ok(changes)
+    }
+
+    fn apply(&mut self, changes: Changes) {
+        for (id, _) in &changes {
+            if let Some(old) = self.by_node.remove(id) {
+                self.by_value.remove(&old);
+            }
+        }
+        for (id, value) in changes {
+            if let Some(value) = value {
+                self.by_value.insert(value.clone(), id);
+                self.by_node.insert(id, value);
+            }
+        }
+    }
+}
+
+/// Index changes a commit makes, per constraint, from
+/// `UniqueIndex::check`.
+#[derive(Debug, Default)]
+pub(crate) struct UniqueUpdate(Vec<Changes>);
+
+/// One `ValueIndex` per entry of `Memory::unique_constraints`, in order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UniqueIndex(Vec<ValueIndex>);
+
+impl UniqueIndex {
+    /// Index of `state`, or the first pair of nodes breaking a constraint.
+    pub(crate) fn build(
+        constraints: &[UniqueConstraint],
+        state: &State,
+    ) -> Result<Self, MyosotisError> {
+        constraints
+            .iter()
+            .map(|c| ValueIndex::build(c, state))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Changes moving the index to `state`, in which only `touched` nodes
+    /// changed, or the first pair of nodes breaking a constraint there.
+    pub(crate) fn check(
+        &self,
+        constraints: &[UniqueConstraint],
+        state: &State,
+        touched: &BTreeSet<NodeId>,
+    ) -> Result<UniqueUpdate, MyosotisError> {
+        constraints
+            .iter()
+            .zip(&self.0)
+            .map(|(c, index)| index.changes(c, state, touched))
+            .collect::<Result<_, _>>()
+            .map(UniqueUpdate)
+    }
+
+    /// Applies changes `check` returned.
+    pub(crate) fn apply(&mut self, update: UniqueUpdate) {
+        for (index, changes) in self.0.iter_mut().zip(update.0) {
+            index.apply(changes);
+        }
+    }
+}
+
+impl Memory {
+    /// Requires live nodes of type `ty` to hold distinct values of field
+    /// `key`; a commit that would break it fails with `UniqueViolation`.
+    /// Fails the same way, adding nothing, if committed state already
+    /// breaks it. Adding a constraint twice is a no-op.
+    pub fn add_unique_constraint(&mut self, ty: &str, key: &str) -> Result<(), MyosotisError> {
+        let constraint = UniqueConstraint {
+            ty: ty.to_string(),
+            key: key.to_string(),
+        };
+        if self.unique_constraints.contains(&constraint) {
+            return Ok(());
+        }
+        let index = ValueIndex::build(&constraint, &self.committed_state()?)?;
+        self.unique_constraints.push(constraint);
+        self.unique_index.0.push(index);
+        Ok(())
+    }
+
+    /// Drops the constraint on `ty` and `key`. Returns whether there was one.
+    pub fn remove_unique_constraint(&mut self, ty: &str, key: &str) -> bool {
+        let Some(at) = self
+            .unique_constraints
+            .iter()
+            .position(|c| c.ty == ty && c.key == key)
+        else {
+            return false;
+        };
+        self.unique_constraints.remove(at);
+        self.unique_index.0.remove(at);
+        true
+    }
+
+    pub fn unique_constraints(&self) -> &[UniqueConstraint] {
+        &self.unique_constraints
+    }
+
+    /// The live node of type `ty` whose field `key` is `value` as of the
+    /// last commit, looked up in the index of a unique constraint on them.
+    /// `None` without such a constraint.
+    pub fn find_unique(&self, ty: &str, key: &str, value: &Value) -> Option<NodeId> {
+        let at = self
+            .unique_constraints
+            .iter()
+            .position(|c| c.ty == ty && c.key == key)?;
+        self.unique_index.0[at]
+            .by_value
+            .get(&Self::value_bytes(value))
+            .copied()
+    }
+
+    /// Rebuilds the unique index after committed state was replaced
+    /// wholesale with nothing pending, failing if that state breaks a
+    /// constraint.
+    pub(crate) fn reindex_unique(&mut self) -> Result<(), MyosotisError> {
+        self.unique_index = UniqueIndex::build(&self.unique_constraints, &self.head_state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::NodeId;
+use serde::Serialize;
+
+/// A node found by `Memory::similar`, with its cosine similarity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Match {
+    pub id: NodeId,
+    pub score: f32,
+}
+
+/// Cosine similarity of `a` and `b`, accumulated in f64. `None` when the
+/// lengths differ, either vector has zero norm, or the result is not finite.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(*x), f64::from(*y));
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    let score = (dot / (norm_a.sqrt() * norm_b.sqrt())) as f32;
+    score.is_finite().then_some(score)
+}
+
+impl Memory {
+    /// The `k` live, unarchived nodes whose `field_key` vector is most
+    /// similar to `query`, best first, ties broken by id. Nodes without a
+    /// vector of the query's dimension under `field_key` are skipped. This
+    /// is a brute-force scan of head state.
+    pub fn similar(
+        &self,
+        field_key: &str,
+        query: &[f32],
+        k: usize,
+    ) -> Result<Vec<Match>, MyosotisError> {
+        if query.iter().all(|c| *c == 0.0) {
+            return Err(MyosotisError::InvalidInput(
+                "query vector must be non-empty and non-zero".to_string(),
+            ));
+        }
+        let mut matches: Vec<Match> = self
+            .head_state
+            .values()
+            .filter(|node| !node.deleted && !node.archived)
+            .filter_map(|node| {
+                let vector = node.fields.get(field_key)?.as_vector()?;
+                let score = cosine_similarity(query, vector)?;
+                Some(Match { id: node.id, score })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        matches.truncate(k);
+        Ok(matches)
+    }
+}
@@ -0,0 +1,173 @@
+use crate::error::MyosotisError;
+use crate::maintenance::{self, FSCK_CHECKS, FSCK_DEEP_CHECKS, FsckIssue, Severity};
+use crate::node::NodeId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HMAC_BLOCK: usize = 64;
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// HMAC-SHA256 (RFC 2104).
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; HMAC_BLOCK];
+    if key.len() > HMAC_BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Failure {
+    pub check: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<NodeId>,
+}
+
+impl From<&FsckIssue> for Failure {
+    fn from(issue: &FsckIssue) -> Self {
+        Self {
+            check: issue.check.to_string(),
+            message: issue.message.clone(),
+            commit: issue.commit,
+            node: issue.node,
+        }
+    }
+}
+
+/// `algorithm` is `hmac-sha256` when signed with a key, else `sha256`,
+/// which only detects accidental changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSignature {
+    pub algorithm: String,
+    pub value: String,
+}
+
+/// Result of verifying a memory file, for archiving alongside it. The
+/// signature covers the compact JSON encoding of every other field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub tool: String,
+    pub tool_version: String,
+    pub file: String,
+    /// SHA-256 of the file bytes, hex encoded.
+    pub file_sha256: String,
+    pub head_commit: Option<u64>,
+    pub head_hash: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub generated_at: u64,
+    pub deep: bool,
+    pub checks: Vec<CheckResult>,
+    /// Error-severity fsck issues. Warnings do not fail verification.
+    pub failures: Vec<Failure>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ReportSignature>,
+}
+
+impl VerificationReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn signed_bytes(&self) -> Result<Vec<u8>, MyosotisError> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        Ok(serde_json::to_vec(&unsigned)?)
+    }
+
+    fn compute_signature(&self, key: Option<&[u8]>) -> Result<ReportSignature, MyosotisError> {
+        let bytes = self.signed_bytes()?;
+        Ok(match key {
+            Some(key) => ReportSignature {
+                algorithm: "hmac-sha256".to_string(),
+                value: to_hex(&hmac_sha256(key, &bytes)),
+            },
+            None => ReportSignature {
+                algorithm: "sha256".to_string(),
+                value: to_hex(&Sha256::digest(&bytes)),
+            },
+        })
+    }
+
+    /// Signs with HMAC-SHA256 under `key`, or adds a plain SHA-256 digest.
+    pub fn sign(&mut self, key: Option<&[u8]>) -> Result<(), MyosotisError> {
+        self.signature = Some(self.compute_signature(key)?);
+        Ok(())
+    }
+
+    /// Checks the signature with the same `key` passed to `sign`.
+    pub fn signature_valid(&self, key: Option<&[u8]>) -> bool {
+        self.signature.as_ref().is_some_and(|sig| {
+            self.compute_signature(key)
+                .is_ok_and(|expected| expected == *sig)
+        })
+    }
+}
+
+/// Runs `fsck` on `path` and collects the result into an unsigned report.
+#[tracing::instrument]
+pub fn verify(path: &str, deep: bool) -> Result<VerificationReport, MyosotisError> {
+    let bytes = fs::read(path).map_err(|e| MyosotisError::ReadFailed(path.to_string(), e))?;
+    let fsck = maintenance::fsck(path, deep)?;
+
+    let errors: Vec<&FsckIssue> = fsck
+        .issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .collect();
+    let mut names: Vec<&str> = FSCK_CHECKS.to_vec();
+    if deep {
+        names.extend_from_slice(FSCK_DEEP_CHECKS);
+    }
+    let checks = names
+        .into_iter()
+        .map(|name| CheckResult {
+            name: name.to_string(),
+            passed: !errors.iter().any(|i| i.check == name),
+        })
+        .collect();
+
+    let head = crate::storage::stream_commits(path)
+        .ok()
+        .and_then(|stream| stream.last());
+
+    Ok(VerificationReport {
+        tool: "myo".to_string(),
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        file: path.to_string(),
+        file_sha256: to_hex(&Sha256::digest(&bytes)),
+        head_commit: head.as_ref().map(|c| c.id),
+        head_hash: head.as_ref().map(|c| to_hex(&c.hash)),
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        deep,
+        checks,
+        failures: errors.into_iter().map(Failure::from).collect(),
+        signature: None,
+    })
+}
@@ -0,0 +1,287 @@
+use crate::commit::Mutation;
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::is_sequential_id;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+
+/// Where the write-ahead log of the memory file `path` lives.
+pub fn wal_path(path: &str) -> String {
+    format!("{}.wal", path)
+}
+
+/// Where the staging area of the memory file `path` lives: a log like the
+/// write-ahead log, kept between CLI calls until `myo commit`.
+pub fn staged_path(path: &str) -> String {
+    format!("{}.staged", path)
+}
+
+/// What `load` does with mutations left in a write-ahead log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalRecovery {
+    /// Stage them again on top of the loaded file.
+    Restage,
+    /// Drop them.
+    Discard,
+}
+
+/// First line of a log: the commit the staged mutations apply on top of.
+#[derive(Debug, Serialize, Deserialize)]
+struct WalHeader {
+    head: Option<[u8; 32]>,
+}
+
+/// The log a `Memory` appends staged mutations to. Not serialized, and not
+/// carried over when a `Memory` is cloned, so scratch copies never write
+/// to it.
+#[derive(Default)]
+pub struct WalHandle(Option<WalFile>);
+
+struct WalFile {
+    path: String,
+    file: File,
+}
+
+impl Clone for WalHandle {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for WalHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(wal) => write!(f, "WalHandle({})", wal.path),
+            None => write!(f, "WalHandle(None)"),
+        }
+    }
+}
+
+fn line<T: Serialize>(value: &T) -> Result<String, MyosotisError> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    Ok(line)
+}
+
+impl WalHandle {
+    /// Appends one staged mutation and syncs it to disk. A failed write
+    /// closes the log rather than leave a gap in it; `Memory::wal_path`
+    /// then returns `None`.
+    pub(crate) fn append(&mut self, mutation: &Mutation) {
+        let Some(wal) = &mut self.0 else {
+            return;
+        };
+        let written = line(mutation).and_then(|line| {
+            wal.file
+                .write_all(line.as_bytes())
+                .and_then(|_| wal.file.sync_data())
+                .map_err(|e| MyosotisError::WriteFailed(wal.path.clone(), e))
+        });
+        if let Err(e) = written {
+            tracing::error!(error = %e, "closing write-ahead log");
+            self.0 = None;
+        }
+    }
+
+    /// Replaces the log with `pending` on top of `head`. Failures close the
+    /// log, as for `append`.
+    pub(crate) fn reset(&mut self, head: Option<[u8; 32]>, pending: &[Mutation]) {
+        let Some(wal) = &self.0 else {
+            return;
+        };
+        match write_log(&wal.path, head, pending) {
+            Ok(file) => {
+                if let Some(wal) = &mut self.0 {
+                    wal.file = file;
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "closing write-ahead log");
+                self.0 = None;
+            }
+        }
+    }
+}
+
+/// Writes a complete log next to `path` and renames it into place, so a
+/// crash leaves either the old log or the new one. Returns the new log
+/// opened for appending.
+fn write_log(
+    path: &str,
+    head: Option<[u8; 32]>,
+    pending: &[Mutation],
+) -> Result<File, MyosotisError> {
+    let mut data = line(&WalHeader { head })?;
+    for mutation in pending {
+        data.push_str(&line(mutation)?);
+    }
+    let tmp = format!("{}.tmp", path);
+    let mut file = File::create(&tmp).map_err(|e| MyosotisError::WriteFailed(tmp.clone(), e))?;
+    file.write_all(data.as_bytes())
+        .and_then(|_| file.sync_data())
+        .map_err(|e| MyosotisError::WriteFailed(tmp.clone(), e))?;
+    fs::rename(&tmp, path).map_err(|e| MyosotisError::ReplaceFailed(path.to_string(), e))?;
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .map_err(|e| MyosotisError::WriteFailed(path.to_string(), e))
+}
+
+/// Reads a log. A last line without its newline is a write cut short by a
+/// crash and is ignored; anything else that does not parse is an error.
+fn read_log(path: &str) -> Result<Option<(WalHeader, Vec<Mutation>)>, MyosotisError> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(MyosotisError::ReadFailed(path.to_string(), e)),
+    };
+    let complete = match data.rfind('\n') {
+        Some(end) => &data[..end],
+        None => return Ok(None),
+    };
+    let mut lines = complete.lines();
+    let header: WalHeader = match lines.next() {
+        Some(line) => {
+            serde_json::from_str(line).map_err(|_| MyosotisError::MalformedFileStructure)?
+        }
+        None => return Ok(None),
+    };
+    let mutations = lines
+        .map(|line| serde_json::from_str(line).map_err(|_| MyosotisError::MalformedFileStructure))
+        .collect::<Result<_, _>>()?;
+    Ok(Some((header, mutations)))
+}
+
+/// Mutations left in the write-ahead log of the memory file `path`; empty
+/// when there is no log.
+pub fn pending(path: &str) -> Result<Vec<Mutation>, MyosotisError> {
+    Ok(read_log(&wal_path(path))?
+        .map(|(_, mutations)| mutations)
+        .unwrap_or_default())
+}
+
+/// Loads `path` and logs staged mutations to `FILE.wal` from then on.
+///
+/// Mutations left in the log by a process that stopped before committing
+/// them are staged again with `Restage` or dropped with `Discard`.
+/// Restaging fails with `StaleWal` when the file's head is no longer the
+/// commit they were staged on, and with the usual errors when one of them
+/// no longer applies; the log is left untouched in both cases.
+pub fn load(path: &str, recovery: WalRecovery) -> Result<Memory, MyosotisError> {
+    load_with_log(path, &wal_path(path), recovery)
+}
+
+/// Loads `path` with the mutations in its staging area staged again, and
+/// logs further staged mutations there. Fails like `load` with `Restage`.
+/// The area is not emptied by committing; save the commit first, then call
+/// `clear_staged`.
+pub fn load_staged(path: &str) -> Result<Memory, MyosotisError> {
+    load_with_log(path, &staged_path(path), WalRecovery::Restage)
+}
+
+/// Mutations in the staging area of `path`; empty when there is none.
+pub fn staged(path: &str) -> Result<Vec<Mutation>, MyosotisError> {
+    Ok(read_log(&staged_path(path))?
+        .map(|(_, mutations)| mutations)
+        .unwrap_or_default())
+}
+
+/// Removes the staging area of `path`, returning how many mutations it
+/// held.
+pub fn clear_staged(path: &str) -> Result<usize, MyosotisError> {
+    let staged_path = staged_path(path);
+    let count = staged(path)?.len();
+    match fs::remove_file(&staged_path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(MyosotisError::WriteFailed(staged_path, e))
+        }
+        _ => Ok(count),
+    }
+}
+
+fn load_with_log(path: &str, wal: &str, recovery: WalRecovery) -> Result<Memory, MyosotisError> {
+    let mut mem = storage::load(path)?;
+    let wal = wal.to_string();
+    if recovery == WalRecovery::Restage
+        && let Some((header, mutations)) = read_log(&wal)?
+        && !mutations.is_empty()
+    {
+        if header.head != mem.commits.last().map(|c| c.hash) {
+            return Err(MyosotisError::StaleWal(wal));
+        }
+        mem.restage(mutations)?;
+    }
+    mem.enable_wal(&wal)?;
+    Ok(mem)
+}
+
+impl Memory {
+    /// Starts appending staged mutations to the log at `path`, replacing
+    /// whatever it held with the mutations pending now. Each commit, undo
+    /// and discard rewrites the log to match what is still pending. Fails
+    /// with `Ephemeral` on an ephemeral memory.
+    pub fn enable_wal(&mut self, path: &str) -> Result<(), MyosotisError> {
+        if self.is_ephemeral() {
+            return Err(MyosotisError::Ephemeral);
+        }
+        let head = self.commits.last().map(|c| c.hash);
+        let file = write_log(path, head, &self.pending_mutations)?;
+        self.wal = WalHandle(Some(WalFile {
+            path: path.to_string(),
+            file,
+        }));
+        Ok(())
+    }
+
+    /// Stops logging and removes the log file.
+    pub fn disable_wal(&mut self) -> Result<(), MyosotisError> {
+        if let Some(wal) = self.wal.0.take() {
+            match fs::remove_file(&wal.path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    return Err(MyosotisError::WriteFailed(wal.path, e));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the write-ahead log, while one is open.
+    pub fn wal_path(&self) -> Option<&str> {
+        self.wal.0.as_ref().map(|wal| wal.path.as_str())
+    }
+
+    pub(crate) fn reset_wal(&mut self) {
+        let head = self.commits.last().map(|c| c.hash);
+        self.wal.reset(head, &self.pending_mutations);
+    }
+
+    /// Stages mutations recovered from a log, checking each one the way
+    /// `dry_run_commit` does. Sequential ids of recovered creates move
+    /// `next_node_id` past them.
+    fn restage(&mut self, mutations: Vec<Mutation>) -> Result<(), MyosotisError> {
+        for mutation in mutations {
+            match &mutation {
+                Mutation::CreateNode { id, .. } if is_sequential_id(*id) => {
+                    if *id < self.next_node_id {
+                        return Err(MyosotisError::InvalidInput(format!(
+                            "node id {} is below next_node_id {}",
+                            id, self.next_node_id
+                        )));
+                    }
+                    self.next_node_id = id + 1;
+                }
+                Mutation::CreateNode { .. } => {}
+                other => self.check_lock(other.node_id())?,
+            }
+            Self::apply_mutation(&mut self.head_state, &mutation)?;
+            self.stage(mutation);
+        }
+        self.reindex_aliases();
+        self.reindex_labels();
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use crate::error::MyosotisError;
+use crate::memory::Memory;
+use crate::node::{Node, NodeId, Value};
+use crate::storage;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+struct Member {
+    path: String,
+    memory: Memory,
+}
+
+/// Several files opened together under short aliases, so that values in
+/// one can point at nodes in another with `Value::ExternalRef`. Each file
+/// keeps its own commit chain; only `save` looks across them. Refs store
+/// the alias, so a file must be opened under the same alias every time.
+#[derive(Default)]
+pub struct Workspace {
+    files: BTreeMap<String, Member>,
+}
+
+/// An `ExternalRef` in field `key` of node `node_id` in file `file` whose
+/// target is missing or deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DanglingRef {
+    pub file: String,
+    pub node_id: NodeId,
+    pub key: String,
+    pub target_file: String,
+    pub target_id: NodeId,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads `path` and adds it under `alias`.
+    pub fn open(&mut self, alias: &str, path: &str) -> Result<(), MyosotisError> {
+        let memory = storage::load(path)?;
+        self.add(alias, path, memory)
+    }
+
+    /// Adds an in-memory file that `save` will write to `path`.
+    pub fn add(&mut self, alias: &str, path: &str, memory: Memory) -> Result<(), MyosotisError> {
+        if self.files.contains_key(alias) {
+            return Err(MyosotisError::InvalidInput(format!(
+                "workspace alias '{}' is already open",
+                alias
+            )));
+        }
+        self.files.insert(
+            alias.to_string(),
+            Member {
+                path: path.to_string(),
+                memory,
+            },
+        );
+        Ok(())
+    }
+
+    /// File aliases, sorted.
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    pub fn memory(&self, alias: &str) -> Option<&Memory> {
+        self.files.get(alias).map(|member| &member.memory)
+    }
+
+    pub fn memory_mut(&mut self, alias: &str) -> Option<&mut Memory> {
+        self.files.get_mut(alias).map(|member| &mut member.memory)
+    }
+
+    /// The live node an `ExternalRef` points at; `None` for other values
+    /// and for refs that do not resolve.
+    pub fn resolve(&self, value: &Value) -> Option<&Node> {
+        let Value::ExternalRef {
+            file_alias,
+            node_id,
+        } = value
+        else {
+            return None;
+        };
+        self.target(file_alias, *node_id)
+    }
+
+    fn target(&self, file_alias: &str, node_id: NodeId) -> Option<&Node> {
+        self.memory(file_alias)?
+            .head_state
+            .get(&node_id)
+            .filter(|node| !node.deleted)
+    }
+
+    /// External refs in live nodes of every file, nested ones included,
+    /// whose target does not resolve. Ordered by file, node and key.
+    pub fn dangling_refs(&self) -> Vec<DanglingRef> {
+        let mut dangling = Vec::new();
+        for (alias, member) in &self.files {
+            let mut nodes: Vec<&Node> = member
+                .memory
+                .head_state
+                .values()
+                .filter(|node| !node.deleted)
+                .collect();
+            nodes.sort_unstable_by_key(|node| node.id);
+            for node in nodes {
+                let mut keys: Vec<&String> = node.fields.keys().collect();
+                keys.sort();
+                for key in keys {
+                    let mut targets = Vec::new();
+                    collect_external(&node.fields[key], &mut targets);
+                    for (target_file, target_id) in targets {
+                        if self.target(target_file, target_id).is_none() {
+                            dangling.push(DanglingRef {
+                                file: alias.clone(),
+                                node_id: node.id,
+                                key: key.clone(),
+                                target_file: target_file.to_string(),
+                                target_id,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        dangling
+    }
+
+    /// Saves every file, after checking that all external refs resolve.
+    /// Nothing is written if one does not.
+    pub fn save(&self) -> Result<(), MyosotisError> {
+        if let Some(first) = self.dangling_refs().into_iter().next() {
+            return Err(MyosotisError::DanglingExternalRef(
+                first.target_file,
+                first.target_id,
+            ));
+        }
+        for member in self.files.values() {
+            storage::save(&member.path, &member.memory)?;
+        }
+        Ok(())
+    }
+}
+
+fn collect_external<'a>(value: &'a Value, out: &mut Vec<(&'a str, NodeId)>) {
+    match value {
+        Value::ExternalRef {
+            file_alias,
+            node_id,
+        } => out.push((file_alias, *node_id)),
+        Value::List(items) => {
+            for item in items {
+                collect_external(item, out);
+            }
+        }
+        Value::Map(map) => {
+            for item in map.values() {
+                collect_external(item, out);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,113 @@
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn alias_resolves_to_node() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let alice = mem.create_with_alias("Person", "alice")?;
+    let bob = mem.create("Person");
+    mem.set(bob, "friend", Value::AliasRef("alice".to_string()))?;
+    mem.commit(Some("people".to_string()))?;
+
+    assert_eq!(mem.resolve("alice"), Some(alice));
+    assert_eq!(mem.resolve("bob"), None);
+    let friend = mem.get_field(bob, "friend")?;
+    assert_eq!(mem.resolve_ref(friend), Some(alice));
+    assert_eq!(mem.resolve_ref(&Value::Ref(bob)), Some(bob));
+    Ok(())
+}
+
+#[test]
+fn alias_must_be_unique() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let alice = mem.create_with_alias("Person", "alice")?;
+    let other = mem.create("Person");
+
+    let err = mem.set_alias(other, Some("alice")).unwrap_err();
+    assert!(matches!(err, MyosotisError::AliasTaken(ref a, id) if a == "alice" && id == alice));
+    assert!(matches!(
+        mem.create_with_alias("Person", "alice"),
+        Err(MyosotisError::AliasTaken(..))
+    ));
+    assert!(matches!(
+        mem.set_alias(other, Some("")),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+
+    mem.set_alias(alice, None)?;
+    mem.set_alias(other, Some("alice"))?;
+    assert_eq!(mem.resolve("alice"), Some(other));
+    Ok(())
+}
+
+#[test]
+fn deleted_node_keeps_alias() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let alice = mem.create_with_alias("Person", "alice")?;
+    mem.commit(Some("create".to_string()))?;
+    mem.delete_node(alice)?;
+    mem.commit(Some("delete".to_string()))?;
+
+    assert_eq!(mem.resolve("alice"), Some(alice));
+    let other = mem.create("Person");
+    assert!(matches!(
+        mem.set_alias(other, Some("alice")),
+        Err(MyosotisError::AliasTaken(..))
+    ));
+    Ok(())
+}
+
+#[test]
+fn discard_forgets_staged_alias() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Person");
+    mem.commit(Some("create".to_string()))?;
+
+    mem.set_alias(id, Some("alice"))?;
+    assert_eq!(mem.resolve("alice"), Some(id));
+    mem.discard_pending()?;
+    assert_eq!(mem.resolve("alice"), None);
+    Ok(())
+}
+
+#[test]
+fn alias_survives_save_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_alias_roundtrip.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create_with_alias("Person", "alice")?;
+    mem.commit(Some("create".to_string()))?;
+    storage::save(path, &mem)?;
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.resolve("alice"), Some(id));
+    assert_eq!(
+        Memory::compute_state_hash(loaded.head_state()),
+        Memory::compute_state_hash(mem.head_state())
+    );
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn alias_changes_state_hash() -> Result<(), Box<dyn std::error::Error>> {
+    let mut plain = Memory::new();
+    plain.create("Person");
+    let mut aliased = Memory::new();
+    let id = aliased.create("Person");
+    let before = Memory::compute_state_hash(aliased.head_state());
+    assert_eq!(before, Memory::compute_state_hash(plain.head_state()));
+
+    aliased.set_alias(id, Some("alice"))?;
+    assert_ne!(before, Memory::compute_state_hash(aliased.head_state()));
+    aliased.set_alias(id, None)?;
+    assert_eq!(before, Memory::compute_state_hash(aliased.head_state()));
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use myosotis::analytics::{KeyCardinality, ReportOptions};
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError};
+
+#[test]
+fn report_breaks_down_types_time_nodes_and_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let agent = mem.create("Agent");
+    let task = mem.create("Task");
+    mem.set(agent, "goal", Value::Str("Explore".to_string()))?;
+    mem.set(task, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(None)?;
+    for note in ["a", "bb", "a long note about the map"] {
+        mem.set(agent, "note", Value::Str(note.to_string()))?;
+        mem.commit(None)?;
+    }
+    mem.delete_node(task)?;
+    mem.commit(None)?;
+    mem.commit_times.insert(1, 100);
+    mem.commit_times.insert(2, 150);
+    mem.commit_times.insert(3, 250);
+
+    let options = ReportOptions {
+        period_secs: 100,
+        top: 2,
+    };
+    let report = mem.report(&options)?;
+
+    let agents = &report.by_type["Agent"];
+    assert_eq!(
+        (agents.live_nodes, agents.deleted_nodes, agents.mutations),
+        (1, 0, 5)
+    );
+    let tasks = &report.by_type["Task"];
+    assert_eq!(
+        (tasks.live_nodes, tasks.deleted_nodes, tasks.mutations),
+        (0, 1, 3)
+    );
+
+    let periods: Vec<_> = report
+        .commit_frequency
+        .periods
+        .iter()
+        .map(|p| (p.start, p.commits, p.mutations))
+        .collect();
+    assert_eq!(periods, vec![(100, 2, 5), (200, 1, 1)]);
+    assert_eq!(report.commit_frequency.untimed, 2);
+
+    assert_eq!(report.most_mutated.len(), 2);
+    assert_eq!(report.most_mutated[0].node_id, agent);
+    assert_eq!(report.most_mutated[0].ty, "Agent");
+    assert_eq!(report.largest_values[0].key, "note");
+    assert_eq!(
+        report.field_keys["goal"],
+        KeyCardinality {
+            nodes: 2,
+            distinct_values: 1
+        }
+    );
+
+    let zero = ReportOptions {
+        period_secs: 0,
+        ..ReportOptions::default()
+    };
+    assert!(matches!(
+        mem.report(&zero),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,250 @@
+#[cfg(feature = "tsa")]
+use myosotis::anchor::Rfc3161Anchorer;
+use myosotis::anchor::{self, CallbackAnchorer};
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+#[cfg(feature = "tsa")]
+use std::io::{Read, Write};
+#[cfg(feature = "tsa")]
+use std::net::TcpListener;
+#[cfg(feature = "tsa")]
+use std::thread;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn memory_with_commits(n: u64) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    for i in 0..n {
+        mem.set(id, "n", Value::Int(i as i64))?;
+        mem.commit(Some(format!("c{}", i + 1)))?;
+    }
+    Ok(mem)
+}
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match content.len() {
+        len if len < 0x80 => out.push(len as u8),
+        len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// A `TimeStampResp` with `status` and a token stamping `hash`; the token
+/// carries no certificates or signer infos.
+fn timestamp_response(status: u8, hash: &[u8; 32]) -> Vec<u8> {
+    let sha256 = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+    let algorithm = der(0x30, &[der(0x06, &sha256), der(0x05, &[])].concat());
+    let imprint = der(0x30, &[algorithm, der(0x04, hash)].concat());
+    let tst_info = [
+        der(0x02, &[0x01]),
+        der(0x06, &[0x2a, 0x03]),
+        imprint,
+        der(0x02, &[0x07]),
+        der(0x18, b"20261015120000Z"),
+    ]
+    .concat();
+    let tst_info_oid = [
+        0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x01, 0x04,
+    ];
+    let encap = [
+        der(0x06, &tst_info_oid),
+        der(0xa0, &der(0x04, &der(0x30, &tst_info))),
+    ]
+    .concat();
+    let signed_data = [
+        der(0x02, &[0x03]),
+        der(0x31, &[]),
+        der(0x30, &encap),
+        der(0x31, &[]),
+    ]
+    .concat();
+    let signed_data_oid = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+    let token = [
+        der(0x06, &signed_data_oid),
+        der(0xa0, &der(0x30, &signed_data)),
+    ]
+    .concat();
+    let status = der(0x30, &der(0x02, &[status]));
+    der(0x30, &[status, der(0x30, &token)].concat())
+}
+
+/// Serves one HTTP request with `body`, in chunks if `chunked`, returning
+/// the request bytes.
+#[cfg(feature = "tsa")]
+fn mock_tsa(body: Vec<u8>, chunked: bool) -> (String, thread::JoinHandle<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock TSA");
+    let url = format!("http://{}/tsr", listener.local_addr().expect("addr"));
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).expect("read");
+            request.extend_from_slice(&buf[..n]);
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&request[..pos]).to_lowercase();
+                let len: usize = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length: "))
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(0);
+                if request.len() >= pos + 4 + len {
+                    break;
+                }
+            }
+        }
+        let head =
+            "HTTP/1.1 200 OK\r\nContent-Type: application/timestamp-reply\r\nConnection: close\r\n";
+        stream.write_all(head.as_bytes()).expect("write");
+        if chunked {
+            stream
+                .write_all(b"Transfer-Encoding: chunked\r\n\r\n")
+                .expect("write");
+            let (first, second) = body.split_at(body.len() / 2);
+            for chunk in [first, second, &[]] {
+                write!(stream, "{:x}\r\n", chunk.len()).expect("write");
+                stream.write_all(chunk).expect("write");
+                stream.write_all(b"\r\n").expect("write");
+            }
+        } else {
+            write!(stream, "Content-Length: {}\r\n\r\n", body.len()).expect("write");
+            stream.write_all(&body).expect("write");
+        }
+        request
+    });
+    (url, handle)
+}
+
+#[test]
+fn callback_anchor_receipt_persists() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_anchor_persist.myo";
+    cleanup(path);
+
+    let mut mem = memory_with_commits(2)?;
+    let anchorer = CallbackAnchorer::new("ledger", |hash: &[u8; 32]| Ok(hash[..4].to_vec()));
+    let receipt = mem.anchor_head(&anchorer)?.clone();
+    assert_eq!(receipt.commit_id, 2);
+    assert_eq!(receipt.receipt, mem.commits[1].hash[..4].to_vec());
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.anchors, vec![receipt.clone()]);
+    assert!(loaded.anchor_matches_chain(&receipt));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn anchor_verifies_until_history_is_rewritten() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = memory_with_commits(3)?;
+    let anchor = mem.anchor()?;
+    assert_eq!(anchor.head_commit_id, 3);
+    assert_eq!(anchor.head_hash, mem.commits[2].hash);
+    mem.verify_anchor(&anchor)?;
+
+    // Later commits leave the anchored prefix intact.
+    let id = mem.create("Agent");
+    mem.set(id, "n", Value::Int(9))?;
+    mem.commit(Some("c4".to_string()))?;
+    mem.verify_anchor(&anchor)?;
+
+    let mut forged = anchor.clone();
+    forged.state_hash[0] ^= 1;
+    assert!(matches!(
+        mem.verify_anchor(&forged),
+        Err(MyosotisError::AnchorMismatch(3))
+    ));
+
+    // Compaction rewrites the commit hashes.
+    mem.compact_in_memory(2)?;
+    assert!(matches!(
+        mem.verify_anchor(&anchor),
+        Err(MyosotisError::AnchorMismatch(3))
+    ));
+
+    assert!(Memory::new().anchor().is_err());
+    Ok(())
+}
+
+#[test]
+fn anchor_if_due_respects_interval() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = memory_with_commits(3)?;
+    let anchorer = CallbackAnchorer::new("ledger", |_: &[u8; 32]| Ok(vec![1]));
+
+    assert!(mem.anchor_if_due(&anchorer, 5)?.is_none());
+    assert!(mem.anchor_if_due(&anchorer, 3)?.is_some());
+    assert!(mem.anchor_if_due(&anchorer, 3)?.is_none());
+    assert_eq!(mem.anchors.len(), 1);
+
+    let failing = CallbackAnchorer::new("down", |_: &[u8; 32]| {
+        Err(MyosotisError::AnchorFailed("unreachable".to_string()))
+    });
+    assert!(matches!(
+        mem.anchor_head(&failing),
+        Err(MyosotisError::AnchorFailed(_))
+    ));
+    assert_eq!(mem.anchors.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn rfc3161_request_encoding() {
+    let request = anchor::timestamp_request(&[0xab; 32]);
+    assert_eq!(request.len(), 2 + 3 + 2 + 15 + 34 + 3);
+    assert_eq!(&request[..5], &[0x30, 0x39, 0x02, 0x01, 0x01]);
+    assert_eq!(&request[request.len() - 3..], &[0x01, 0x01, 0xff]);
+}
+
+#[test]
+fn rfc3161_response_must_stamp_the_commit_hash() {
+    let hash = [0xab; 32];
+    let granted = timestamp_response(0, &hash);
+    assert_eq!(anchor::timestamp_response_status(&granted), Some(0));
+    assert_eq!(anchor::timestamp_response_imprint(&granted), Some(hash));
+    assert!(anchor::check_timestamp_response(&granted, &hash).is_ok());
+
+    let refused = [
+        timestamp_response(0, &[0xcd; 32]),
+        timestamp_response(2, &hash),
+        vec![0x30, 0x05, 0x30, 0x03, 0x02, 0x01, 0x00],
+        granted[..granted.len() - 1].to_vec(),
+    ];
+    for response in refused {
+        assert!(matches!(
+            anchor::check_timestamp_response(&response, &hash),
+            Err(MyosotisError::AnchorFailed(_))
+        ));
+    }
+}
+
+#[cfg(feature = "tsa")]
+#[test]
+fn rfc3161_anchor_against_mock_tsa() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = memory_with_commits(1)?;
+    let hash = mem.commits[0].hash;
+    let granted = timestamp_response(0, &hash);
+    for chunked in [false, true] {
+        let (url, server) = mock_tsa(granted.clone(), chunked);
+        let receipt = mem.anchor_head(&Rfc3161Anchorer::new(&url))?.clone();
+        let request = server.join().map_err(|_| "mock TSA panicked")?;
+        assert!(request.ends_with(&anchor::timestamp_request(&hash)));
+        assert_eq!(receipt.receipt, granted);
+        assert_eq!(receipt.authority, url);
+    }
+
+    let (url, server) = mock_tsa(timestamp_response(0, &[0xcd; 32]), false);
+    assert!(matches!(
+        mem.anchor_head(&Rfc3161Anchorer::new(&url)),
+        Err(MyosotisError::AnchorFailed(_))
+    ));
+    server.join().map_err(|_| "mock TSA panicked")?;
+    assert_eq!(mem.anchors.len(), 2);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn archived_nodes_are_hidden_but_usable() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let hot = mem.create("Note");
+    let cold = mem.create("Note");
+    mem.set(cold, "embedding", Value::Vector(vec![1.0, 0.0]))?;
+    mem.set(hot, "link", Value::Ref(cold))?;
+    mem.commit(Some("create".to_string()))?;
+
+    mem.archive(cold)?;
+    mem.commit(Some("archive".to_string()))?;
+
+    assert!(mem.is_archived(cold));
+    let ids = |nodes: Vec<&myosotis::node::Node>| nodes.iter().map(|n| n.id).collect::<Vec<_>>();
+    assert_eq!(ids(mem.nodes_in(None)), vec![hot]);
+    assert_eq!(ids(mem.archived_nodes()), vec![cold]);
+    assert!(mem.similar("embedding", &[1.0, 0.0], 5)?.is_empty());
+
+    // Still live: readable, writable and a valid ref target.
+    let node = mem.head_state().get(&cold).ok_or("missing node")?;
+    assert!(!node.deleted);
+    mem.set(cold, "text", Value::Str("kept".to_string()))?;
+    mem.commit(Some("write".to_string()))?;
+
+    mem.unarchive(cold)?;
+    mem.commit(Some("unarchive".to_string()))?;
+    assert!(!mem.is_archived(cold));
+    assert_eq!(ids(mem.nodes_in(None)), vec![hot, cold]);
+    Ok(())
+}
+
+#[test]
+fn archive_replays_and_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_archive_round_trip.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Note");
+    mem.commit(Some("create".to_string()))?;
+    mem.archive(id)?;
+    mem.commit(Some("archive".to_string()))?;
+
+    let replayed = Memory::replay(&mem.commits)?;
+    assert!(replayed.get(&id).ok_or("missing node")?.archived);
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert!(loaded.is_archived(id));
+    assert_eq!(loaded.head_state(), mem.head_state());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn deleted_nodes_cannot_be_archived() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Note");
+    mem.delete_node(id)?;
+
+    assert!(matches!(
+        mem.archive(id),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+    assert!(matches!(
+        mem.archive(99),
+        Err(MyosotisError::NodeNotFound(99))
+    ));
+    assert!(mem.archived_nodes().is_empty());
+    Ok(())
+}
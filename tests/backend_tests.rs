@@ -0,0 +1,198 @@
+use myosotis::Memory;
+use myosotis::backend::{FileBackend, StorageBackend};
+use myosotis::node::Value;
+use std::fs;
+
+fn add_commits(mem: &mut Memory, n: usize) -> Result<(), Box<dyn std::error::Error>> {
+    for _ in 0..n {
+        let id = mem.create("Note");
+        mem.set(id, "n", Value::Int(mem.commits.len() as i64))?;
+        mem.commit(None)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn file_backend_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_backend_file.myo";
+    let _ = fs::remove_file(path);
+
+    let mut mem = Memory::new();
+    add_commits(&mut mem, 3)?;
+    let mut backend = FileBackend::new(path);
+    backend.save(&mem)?;
+    let loaded = backend.load()?;
+    assert_eq!(loaded.head_state(), mem.head_state());
+    assert_eq!(loaded.commits.len(), 3);
+
+    let _ = fs::remove_file(path);
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::add_commits;
+    use myosotis::backend::StorageBackend;
+    use myosotis::memory::CHECKPOINT_INTERVAL;
+    use myosotis::sqlite::SqliteBackend;
+    use myosotis::{Memory, MyosotisError};
+    use std::fs;
+
+    fn cleanup(path: &str) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", path, suffix));
+        }
+    }
+
+    #[test]
+    fn sqlite_backend_round_trips_and_appends() -> Result<(), Box<dyn std::error::Error>> {
+        let path = "test_backend_round_trip.db";
+        cleanup(path);
+
+        let mut backend = SqliteBackend::open(path)?;
+        assert!(matches!(
+            backend.load(),
+            Err(MyosotisError::InvalidInput(_))
+        ));
+
+        let mut mem = Memory::new();
+        add_commits(&mut mem, 5)?;
+        backend.save(&mem)?;
+        add_commits(&mut mem, CHECKPOINT_INTERVAL)?;
+        backend.save(&mem)?;
+        assert!(!mem.checkpoints.is_empty());
+
+        // A second connection reads what the first one saved.
+        let reader = SqliteBackend::open(path)?;
+        let loaded = reader.load()?;
+        assert_eq!(loaded.head_state(), mem.head_state());
+        assert_eq!(loaded.commits.len(), mem.commits.len());
+        assert_eq!(loaded.checkpoints.len(), mem.checkpoints.len());
+        assert_eq!(loaded.commit_times, mem.commit_times);
+        assert_eq!(loaded.next_node_id, mem.next_node_id);
+
+        assert_eq!(reader.head_commit_id()?, mem.commits.last().map(|c| c.id));
+        assert_eq!(reader.commit(3)?.hash, mem.commits[2].hash);
+        let ids: Vec<_> = reader.commits_between(4, 6)?.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![4, 5, 6]);
+        for id in [2, CHECKPOINT_INTERVAL as u64 + 3] {
+            assert_eq!(reader.state_at_commit(id)?, mem.state_at_commit(id)?);
+        }
+        assert!(matches!(
+            reader.state_at_commit(9999),
+            Err(MyosotisError::CommitNotFound(9999))
+        ));
+
+        cleanup(path);
+        Ok(())
+    }
+
+    #[test]
+    fn sqlite_backend_replaces_rewritten_history() -> Result<(), Box<dyn std::error::Error>> {
+        let path = "test_backend_rewrite.db";
+        cleanup(path);
+
+        let mut backend = SqliteBackend::open(path)?;
+        let mut mem = Memory::new();
+        add_commits(&mut mem, 6)?;
+        backend.save(&mem)?;
+
+        mem.compact_in_memory(4)?;
+        backend.save(&mem)?;
+        let loaded = backend.load()?;
+        assert_eq!(loaded.commits.len(), mem.commits.len());
+        assert_eq!(loaded.genesis_state, mem.genesis_state);
+        assert_eq!(loaded.head_state(), mem.head_state());
+
+        cleanup(path);
+        Ok(())
+    }
+
+    #[test]
+    fn sqlite_reads_check_recorded_state_hashes() -> Result<(), Box<dyn std::error::Error>> {
+        let path = "test_backend_state_hash.db";
+        cleanup(path);
+
+        let mut mem = Memory::new();
+        mem.record_state_hashes = true;
+        add_commits(&mut mem, 4)?;
+        mem.commits[2].state_hash = mem.commits[1].state_hash;
+        let mut backend = SqliteBackend::open(path)?;
+        backend.save(&mem)?;
+
+        assert!(backend.state_at_commit(4).is_ok());
+        assert!(matches!(
+            backend.state_at_commit(3),
+            Err(MyosotisError::CommitStateHashMismatch(3))
+        ));
+
+        cleanup(path);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "object-store")]
+mod object_store_backend {
+    use super::add_commits;
+    use myosotis::backend::StorageBackend;
+    use myosotis::remote::{Layout, ObjectStoreBackend};
+    use myosotis::{Memory, MyosotisError};
+    use object_store::memory::InMemory;
+    use std::sync::Arc;
+
+    #[test]
+    fn object_store_saves_are_optimistic() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(InMemory::new());
+        let mut first = ObjectStoreBackend::new(store.clone(), "agents/memory.myo")?;
+        let mut second = ObjectStoreBackend::new(store, "agents/memory.myo")?;
+
+        let mut mem = Memory::new();
+        add_commits(&mut mem, 2)?;
+        first.save(&mem)?;
+        // `second` has not read the object, so it may not replace it.
+        assert!(matches!(
+            second.save(&mem),
+            Err(MyosotisError::SaveConflict(_))
+        ));
+
+        let mut theirs = second.load()?;
+        assert_eq!(theirs.head_state(), mem.head_state());
+        add_commits(&mut theirs, 1)?;
+        second.save(&theirs)?;
+
+        add_commits(&mut mem, 1)?;
+        assert!(matches!(
+            first.save(&mem),
+            Err(MyosotisError::SaveConflict(_))
+        ));
+        let mut mem = first.load()?;
+        assert_eq!(mem.commits.len(), 3);
+        add_commits(&mut mem, 1)?;
+        first.save(&mem)?;
+        Ok(())
+    }
+
+    #[test]
+    fn paged_objects_answer_queries_from_pages() -> Result<(), Box<dyn std::error::Error>> {
+        let mut backend = ObjectStoreBackend::new(Arc::new(InMemory::new()), "paged.myo")?;
+        backend.set_layout(Layout::Paged(8));
+
+        let mut mem = Memory::new();
+        add_commits(&mut mem, 30)?;
+        backend.save(&mem)?;
+
+        let ids: Vec<_> = backend
+            .commits_between(9, 11)?
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![9, 10, 11]);
+        assert_eq!(backend.state_at_commit(20)?, mem.state_at_commit(20)?);
+        assert_eq!(backend.load()?.head_state(), mem.head_state());
+
+        backend.set_layout(Layout::File);
+        backend.save(&mem)?;
+        assert_eq!(backend.state_at_commit(20)?, mem.state_at_commit(20)?);
+        Ok(())
+    }
+}
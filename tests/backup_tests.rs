@@ -0,0 +1,108 @@
+use myosotis::backup::{self, RotationPolicy};
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn setup(path: &str, dir: &str) -> Result<Memory, Box<dyn std::error::Error>> {
+    cleanup(path, dir);
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    for step in 0..3 {
+        mem.set(id, "step", Value::Int(step))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+    Ok(mem)
+}
+
+fn cleanup(path: &str, dir: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn backup_and_restore_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_backup_round_trip.myo";
+    let dir = "test_backup_round_trip_dir";
+    let mem = setup(path, dir)?;
+
+    let made = backup::backup(path, Some(dir))?;
+    assert!(made.path.starts_with(dir));
+    assert_eq!(fs::read(&made.path)?, fs::read(path)?);
+    assert_eq!(backup::list(path, Some(dir))?, vec![made.clone()]);
+
+    storage::compact(path, None)?;
+    assert!(storage::load(path)?.commits.len() < mem.commits.len());
+
+    let restored = backup::restore(&made.path, path)?;
+    assert_eq!(restored.commits.len(), mem.commits.len());
+    assert_eq!(storage::load(path)?.head_state(), mem.head_state());
+
+    cleanup(path, dir);
+    Ok(())
+}
+
+#[test]
+fn rotation_keeps_the_newest_backups() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_backup_rotation.myo";
+    let dir = "test_backup_rotation_dir";
+    setup(path, dir)?;
+
+    let made: Vec<_> = (0..4)
+        .map(|_| backup::backup(path, Some(dir)))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(backup::list(path, Some(dir))?, made);
+
+    let policy = RotationPolicy {
+        keep_last: None,
+        max_age: Some(3600),
+    };
+    assert!(backup::rotate(path, Some(dir), &policy)?.is_empty());
+
+    let policy = RotationPolicy {
+        keep_last: Some(2),
+        max_age: None,
+    };
+    let removed = backup::rotate(path, Some(dir), &policy)?;
+    assert_eq!(removed, made[..2]);
+    assert_eq!(backup::list(path, Some(dir))?, made[2..]);
+
+    // The newest backup survives even a policy that keeps nothing.
+    let policy = RotationPolicy {
+        keep_last: Some(0),
+        max_age: Some(0),
+    };
+    backup::rotate(path, Some(dir), &policy)?;
+    assert_eq!(backup::list(path, Some(dir))?, made[3..]);
+
+    cleanup(path, dir);
+    Ok(())
+}
+
+#[test]
+fn broken_files_are_not_backed_up_or_restored() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_backup_broken.myo";
+    let dir = "test_backup_broken_dir";
+    let mut mem = setup(path, dir)?;
+    let good = backup::backup(path, Some(dir))?;
+
+    fs::write(path, "{ not a memory")?;
+    assert!(backup::backup(path, Some(dir)).is_err());
+    assert_eq!(backup::list(path, Some(dir))?.len(), 1);
+
+    fs::write(&good.path, "{ not a memory")?;
+    assert!(backup::restore(&good.path, path).is_err());
+
+    // Restoring an older copy over a write-once file is refused.
+    mem.write_once = true;
+    storage::save(path, &mem)?;
+    let old = backup::backup(path, Some(dir))?;
+    mem.set(1, "step", Value::Int(9))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    let err = backup::restore(&old.path, path).unwrap_err();
+    assert!(matches!(err, MyosotisError::WriteOnceViolation(_)));
+
+    cleanup(path, dir);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use myosotis::Memory;
+use myosotis::memory::CHECKPOINT_INTERVAL;
+use myosotis::node::{Node, NodeId, Value};
+use std::collections::HashMap;
+
+fn goal_is(id: NodeId, goal: &str) -> impl Fn(&HashMap<NodeId, Node>) -> bool {
+    let goal = Value::Str(goal.to_string());
+    move |state| state.get(&id).and_then(|n| n.fields.get("goal")) == Some(&goal)
+}
+
+#[test]
+fn bisect_finds_first_flip_across_checkpoints() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    for i in 1..(2 * CHECKPOINT_INTERVAL as i64) {
+        mem.set(id, "step", Value::Int(i))?;
+        if i == 73 {
+            mem.set(id, "goal", Value::Str("rest".to_string()))?;
+        }
+        mem.commit(None)?;
+    }
+    assert_eq!(mem.checkpoints.len(), 2);
+
+    assert_eq!(mem.bisect(goal_is(id, "rest"))?, Some(74));
+    assert_eq!(mem.bisect(goal_is(id, "explore"))?, None);
+    assert_eq!(mem.bisect(|state| state.get(&id).is_some())?, Some(1));
+    assert_eq!(Memory::new().bisect(|_| true)?, None);
+    Ok(())
+}
+
+#[test]
+fn bisect_starts_from_compacted_genesis() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    mem.commit(None)?;
+    mem.compact_in_memory(1)?;
+
+    assert_eq!(mem.bisect(goal_is(id, "rest"))?, None);
+    // Set and reset again after genesis: the flips cancel out.
+    assert_eq!(mem.bisect(goal_is(id, "explore"))?, None);
+    mem.set(id, "goal", Value::Str("sleep".to_string()))?;
+    mem.commit(None)?;
+    assert_eq!(mem.bisect(goal_is(id, "rest"))?, Some(4));
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use myosotis::blob::{self, ValueLimits};
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, backup, maintenance, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+    let _ = fs::remove_dir_all(blob::blob_dir(path));
+}
+
+fn big(fill: char) -> Value {
+    Value::Str(fill.to_string().repeat(200))
+}
+
+fn blob_files(path: &str) -> usize {
+    fs::read_dir(blob::blob_dir(path)).map_or(0, |entries| entries.count())
+}
+
+#[test]
+fn large_values_live_in_the_blob_store() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_blob_store.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    mem.set_blob_threshold(Some(64));
+    let id = mem.create("Doc");
+    mem.set(id, "body", big('a'))?;
+    mem.set(id, "title", Value::Str("short".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "body", big('b'))?;
+    mem.commit(None)?;
+    mem.rebuild_checkpoints(1)?;
+    storage::save(path, &mem)?;
+
+    let data = fs::read_to_string(path)?;
+    assert!(!data.contains(&"a".repeat(200)));
+    assert!(data.contains("short"));
+    assert_eq!(blob_files(path), 2);
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.blob_threshold(), Some(64));
+    assert_eq!(loaded.get_field(id, "body")?, &big('b'));
+    assert_eq!(loaded.head_state(), mem.head_state());
+    assert!(storage::open_lazy(path).is_err());
+
+    // Compaction moves the value into genesis; it stays offloaded, and the
+    // blob of the folded commit is left for pruning.
+    maintenance::compact(path, None)?;
+    assert_eq!(storage::load(path)?.get_field(id, "body")?, &big('b'));
+    assert_eq!(blob::prune_blobs(path)?, 1);
+    assert_eq!(blob_files(path), 1);
+
+    let made = backup::backup(path, Some("test_blob_backups"))?;
+    assert_eq!(storage::load(&made.path)?.get_field(id, "body")?, &big('b'));
+    let _ = fs::remove_dir_all("test_blob_backups");
+
+    // A tampered blob fails the load.
+    let hash = blob::referenced(path)?.into_iter().next().unwrap();
+    fs::write(format!("{}/{}", blob::blob_dir(path), hash), "\"x\"")?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::CorruptBlob(h)) if h == hash
+    ));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn redaction_removes_offloaded_values() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_blob_redact.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    mem.set_blob_threshold(Some(64));
+    let id = mem.create("Doc");
+    mem.set(id, "secret", big('s'))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    let secret = blob::referenced(path)?;
+    assert_eq!(secret.len(), 1);
+
+    maintenance::redact(path, id, "secret")?;
+    let kept = blob::referenced(path)?;
+    assert!(secret.is_disjoint(&kept));
+    assert_eq!(blob_files(path), kept.len());
+    assert_eq!(blob_files(&format!("{}.tmp", path)), 0);
+    storage::load(path)?;
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn files_named_like_temporaries_keep_their_own_blobs() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_blob_tmp_name.myo";
+    let tmp_named = format!("{}.tmp", path);
+    cleanup(path);
+    cleanup(&tmp_named);
+
+    let mut mem = Memory::new();
+    mem.set_blob_threshold(Some(64));
+    let id = mem.create("Doc");
+    mem.set(id, "body", big('t'))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    let mut other = Memory::new();
+    other.set_blob_threshold(Some(64));
+    let id = other.create("Doc");
+    other.set(id, "body", big('u'))?;
+    other.commit(None)?;
+    storage::save(&tmp_named, &other)?;
+    assert_ne!(blob::blob_dir(path), blob::blob_dir(&tmp_named));
+
+    assert_eq!(blob::prune_blobs(&tmp_named)?, 0);
+    assert_eq!(blob::prune_blobs(path)?, 0);
+    assert_eq!(blob_files(path), 1);
+    assert_eq!(blob_files(&tmp_named), 1);
+    storage::load(path)?;
+    storage::load(&tmp_named)?;
+
+    cleanup(path);
+    cleanup(&tmp_named);
+    Ok(())
+}
+
+#[test]
+fn value_limits_cap_writes_per_field() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Doc");
+    mem.set_value_limits(ValueLimits {
+        max_bytes: Some(16),
+        fields: [("body".to_string(), 256)].into(),
+    });
+
+    mem.set(id, "body", big('a'))?;
+    assert!(matches!(
+        mem.set(id, "title", big('a')),
+        Err(MyosotisError::ValueTooLarge(key, _, 16)) if key == "title"
+    ));
+    assert!(matches!(
+        mem.list_push(id, "tags", big('a')),
+        Err(MyosotisError::ValueTooLarge(..))
+    ));
+    mem.list_push(id, "tags", Value::Str("ok".to_string()))?;
+    assert!(mem.get_field(id, "title").is_err());
+    assert_eq!(mem.pending_mutations().len(), 3);
+    Ok(())
+}
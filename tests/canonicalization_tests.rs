@@ -0,0 +1,424 @@
+use myosotis::canonical::{self, Canonicalization};
+use myosotis::commit::{Commit, Mutation};
+use myosotis::node::{NodeId, PathSeg, Value};
+use myosotis::{Memory, storage};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+
+const BINARY_STATE_HASH: &str = "9bb77487fea8fe242c18efc35f9aad5b32b065ea329cdd5910587d1be4af6966";
+const JCS_STATE_HASH: &str = "f72973e164963642af7bd4c38c062ef0ac61ceb06a5aa2f209e7430ab18e507e";
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn hex(hash: [u8; 32]) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn golden_mutations() -> Vec<Mutation> {
+    let mut map = HashMap::new();
+    map.insert("z".to_string(), Value::Bool(true));
+    map.insert("a".to_string(), Value::Float(0.5));
+    vec![
+        Mutation::CreateNode {
+            id: 1,
+            ty: "Agent".to_string(),
+        },
+        Mutation::SetField {
+            id: 1,
+            key: "goal".to_string(),
+            value: Value::Str("Explore \"ideas\"\n".to_string()),
+        },
+        Mutation::SetField {
+            id: 1,
+            key: "meta".to_string(),
+            value: Value::List(vec![Value::Int(-7), Value::Map(map), Value::Ref(1)]),
+        },
+        Mutation::DeleteField {
+            id: 1,
+            key: "goal".to_string(),
+        },
+        Mutation::DeleteNode { id: 1 },
+    ]
+}
+
+fn golden_state() -> Result<HashMap<NodeId, myosotis::node::Node>, Box<dyn std::error::Error>> {
+    let commit = Commit {
+        id: 1,
+        parent: None,
+        parent_hash: None,
+        hash: [0u8; 32],
+        message: None,
+        source: None,
+        state_hash: None,
+        mutations: golden_mutations()[..3].to_vec(),
+    };
+    Ok(Memory::replay(&[commit])?)
+}
+
+#[test]
+fn commit_hash_golden_vectors() {
+    let message = Some("golden".to_string());
+    let mutations = golden_mutations();
+
+    let binary =
+        Memory::compute_commit_hash_with(Canonicalization::Binary, None, &message, &mutations);
+    assert_eq!(
+        hex(binary),
+        "3827748568451d148d22de4fc8bec5ab7f35975502274d88091667b06bbb785a"
+    );
+    assert_eq!(
+        binary,
+        Memory::compute_commit_hash(None, &message, &mutations)
+    );
+
+    let bytes = canonical::jcs_commit_bytes(None, &message, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":"golden","mutations":["#,
+            r#"{"id":"1","op":"create_node","ty":"Agent"},"#,
+            r#"{"id":"1","key":"goal","op":"set_field","value":{"str":"Explore \"ideas\"\n"}},"#,
+            r#"{"id":"1","key":"meta","op":"set_field","value":{"list":[{"int":"-7"},"#,
+            r#"{"map":{"a":{"float":0.5},"z":{"bool":true}}},{"ref":"1"}]}},"#,
+            r#"{"id":"1","key":"goal","op":"delete_field"},"#,
+            r#"{"id":"1","op":"delete_node"}],"parent_hash":null}"#
+        ))
+    );
+    let jcs = Memory::compute_commit_hash_with(Canonicalization::Jcs, None, &message, &mutations);
+    assert_eq!(
+        hex(jcs),
+        "5df7d7fcabe25a3cc6e92a247da5dd0a0bfefef22c673c4714ef94246839f30f"
+    );
+}
+
+#[test]
+fn state_hash_golden_vectors() -> Result<(), Box<dyn std::error::Error>> {
+    let state = golden_state()?;
+
+    let bytes = canonical::jcs_state_bytes(&state);
+    assert_eq!(
+        String::from_utf8(bytes)?,
+        concat!(
+            r#"{"nodes":[{"deleted":false,"fields":{"goal":{"str":"Explore \"ideas\"\n"},"#,
+            r#""meta":{"list":[{"int":"-7"},{"map":{"a":{"float":0.5},"z":{"bool":true}}},"#,
+            r#"{"ref":"1"}]}},"id":"1","ty":"Agent"}]}"#
+        )
+    );
+    assert_eq!(
+        hex(Memory::compute_state_hash_with(
+            Canonicalization::Binary,
+            &state
+        )),
+        BINARY_STATE_HASH
+    );
+    assert_eq!(
+        hex(Memory::compute_state_hash_with(
+            Canonicalization::Jcs,
+            &state
+        )),
+        JCS_STATE_HASH
+    );
+    Ok(())
+}
+
+#[test]
+fn jcs_number_formatting() {
+    let cases = [
+        (0.0, "0"),
+        (-0.0, "0"),
+        (1.0, "1"),
+        (-1.5, "-1.5"),
+        (0.000001, "0.000001"),
+        (1e-7, "1e-7"),
+        (1e21, "1e+21"),
+        (123456789012345680000.0, "123456789012345680000"),
+        (5e-324, "5e-324"),
+        (1.7976931348623157e308, "1.7976931348623157e+308"),
+        (333333333.3333333, "333333333.3333333"),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(canonical::format_jcs_number(value), expected);
+    }
+}
+
+#[test]
+fn jcs_mode_persists_and_validates() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_jcs_mode.myo";
+    cleanup(path);
+
+    let mut mem = Memory::with_canonicalization(Canonicalization::Jcs);
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("c1".to_string()))?;
+    storage::save(path, &mem)?;
+
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    assert_eq!(json["canonicalization"], serde_json::json!("jcs"));
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.canonicalization, Canonicalization::Jcs);
+    assert_eq!(
+        loaded.commits[0].hash,
+        Memory::compute_commit_hash_with(
+            Canonicalization::Jcs,
+            None,
+            &Some("c1".to_string()),
+            &loaded.commits[0].mutations
+        )
+    );
+
+    // Dropping the header field makes the hashes unverifiable as binary.
+    let mut stripped = json.clone();
+    if let Some(obj) = stripped.as_object_mut() {
+        obj.remove("canonicalization");
+    }
+    fs::write(path, serde_json::to_string_pretty(&stripped)?)?;
+    assert!(storage::load(path).is_err());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn binary_mode_omits_header_field() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_binary_mode_header.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    mem.create("Agent");
+    mem.commit(Some("c1".to_string()))?;
+    storage::save(path, &mem)?;
+
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    assert!(json.get("canonicalization").is_none());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn jcs_rename_field_keys_are_sorted() {
+    let mutations = vec![Mutation::RenameField {
+        id: 3,
+        from: "goal".to_string(),
+        to: "aim".to_string(),
+    }];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"from":"goal","id":"3","op":"rename_field","to":"aim"}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_path_mutations() {
+    let path = vec![
+        PathSeg::Key("profile".to_string()),
+        PathSeg::Key("tags".to_string()),
+        PathSeg::Index(2),
+    ];
+    let mutations = vec![
+        Mutation::SetPath {
+            id: 3,
+            path: path.clone(),
+            value: Value::Bool(true),
+        },
+        Mutation::DeletePath { id: 3, path },
+    ];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"id":"3","op":"set_path","path":[{"key":"profile"},{"key":"tags"},{"index":"2"}],"value":{"bool":true}},"#,
+            r#"{"id":"3","op":"delete_path","path":[{"key":"profile"},{"key":"tags"},{"index":"2"}]}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_list_mutations() {
+    let path = vec![PathSeg::Key("log".to_string())];
+    let mutations = vec![
+        Mutation::ListPush {
+            id: 1,
+            path: path.clone(),
+            value: Value::Int(5),
+        },
+        Mutation::ListInsert {
+            id: 1,
+            path: path.clone(),
+            index: 0,
+            value: Value::Str("a".to_string()),
+        },
+        Mutation::ListRemove {
+            id: 1,
+            path,
+            index: 2,
+        },
+    ];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"id":"1","op":"list_push","path":[{"key":"log"}],"value":{"int":"5"}},"#,
+            r#"{"id":"1","index":"0","op":"list_insert","path":[{"key":"log"}],"value":{"str":"a"}},"#,
+            r#"{"id":"1","index":"2","op":"list_remove","path":[{"key":"log"}]}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_restore_node() {
+    let mutations = vec![
+        Mutation::RestoreNode {
+            id: 4,
+            keep_fields: true,
+        },
+        Mutation::RestoreNode {
+            id: 5,
+            keep_fields: false,
+        },
+    ];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"id":"4","keep_fields":true,"op":"restore_node"},"#,
+            r#"{"id":"5","keep_fields":false,"op":"restore_node"}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_set_type() {
+    let mutations = vec![Mutation::SetType {
+        id: 2,
+        ty: "Person".to_string(),
+    }];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"id":"2","op":"set_type","ty":"Person"}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_set_alias_and_alias_ref() {
+    let mutations = vec![
+        Mutation::SetAlias {
+            id: 3,
+            alias: Some("alice".to_string()),
+        },
+        Mutation::SetField {
+            id: 4,
+            key: "friend".to_string(),
+            value: Value::AliasRef("alice".to_string()),
+        },
+        Mutation::SetAlias { id: 3, alias: None },
+    ];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"alias":"alice","id":"3","op":"set_alias"},"#,
+            r#"{"id":"4","key":"friend","op":"set_field","value":{"alias_ref":"alice"}},"#,
+            r#"{"alias":null,"id":"3","op":"set_alias"}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_set_namespace() {
+    let mutations = vec![
+        Mutation::SetNamespace {
+            id: 6,
+            namespace: Some("episodic".to_string()),
+        },
+        Mutation::SetNamespace {
+            id: 6,
+            namespace: None,
+        },
+    ];
+    let bytes = canonical::jcs_commit_bytes(None, &None, &mutations);
+    assert_eq!(
+        String::from_utf8(bytes).ok().as_deref(),
+        Some(concat!(
+            r#"{"message":null,"mutations":["#,
+            r#"{"id":"6","namespace":"episodic","op":"set_namespace"},"#,
+            r#"{"id":"6","namespace":null,"op":"set_namespace"}],"parent_hash":null}"#
+        ))
+    );
+}
+
+#[test]
+fn jcs_external_ref() {
+    let mut value = Vec::new();
+    canonical::write_jcs_value(
+        &mut value,
+        &Value::ExternalRef {
+            file_alias: "world".to_string(),
+            node_id: 7,
+        },
+    );
+    assert_eq!(
+        String::from_utf8(value).ok().as_deref(),
+        Some(r#"{"external_ref":{"file_alias":"world","node_id":"7"}}"#)
+    );
+}
+
+#[test]
+fn jcs_vector() {
+    let mut value = Vec::new();
+    canonical::write_jcs_value(&mut value, &Value::Vector(vec![0.5, -2.0, 0.1, f32::NAN]));
+    assert_eq!(
+        String::from_utf8(value).ok().as_deref(),
+        Some(r#"{"vector":[0.5,-2,0.10000000149011612,"NaN"]}"#)
+    );
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&Sha256::digest(bytes));
+    out
+}
+
+#[test]
+fn commit_canonical_bytes_reproduce_hashes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut binary = Memory::new();
+    let id = binary.create("Agent");
+    binary.set(id, "goal", Value::Str("Explore".to_string()))?;
+    binary.commit(Some("c1".to_string()))?;
+    binary.delete_field(id, "goal")?;
+    binary.commit(None)?;
+    for commit in &binary.commits {
+        assert_eq!(sha256(&commit.canonical_bytes()), commit.hash);
+    }
+
+    let mut jcs = Memory::with_canonicalization(Canonicalization::Jcs);
+    let id = jcs.create("Agent");
+    jcs.set(id, "goal", Value::Str("Explore".to_string()))?;
+    jcs.commit(Some("c1".to_string()))?;
+    let commit = &jcs.commits[0];
+    let json = myosotis::export::commit_json(commit);
+    assert_eq!(sha256(json.as_bytes()), commit.hash);
+    assert_eq!(
+        json.as_bytes(),
+        commit.canonical_bytes_with(Canonicalization::Jcs)
+    );
+    let parsed: serde_json::Value = serde_json::from_str(&json)?;
+    assert_eq!(parsed["message"], serde_json::json!("c1"));
+    Ok(())
+}
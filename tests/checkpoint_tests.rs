@@ -1,6 +1,6 @@
 use myosotis::memory::CHECKPOINT_INTERVAL;
-use myosotis::node::Value;
-use myosotis::{Memory, storage};
+use myosotis::node::{NodeId, Value};
+use myosotis::{Memory, MyosotisError, maintenance, storage};
 use std::fs;
 
 fn cleanup(path: &str) {
@@ -68,13 +68,13 @@ fn checkpoint_integrity_test() -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|v| v.as_object_mut())
         .ok_or("missing checkpoint state")?;
 
-    if let Some((_k, node_val)) = state.iter_mut().next() {
-        if let Some(node_obj) = node_val.as_object_mut() {
-            node_obj.insert(
-                "ty".to_string(),
-                serde_json::Value::String("Tampered".to_string()),
-            );
-        }
+    if let Some((_k, node_val)) = state.iter_mut().next()
+        && let Some(node_obj) = node_val.as_object_mut()
+    {
+        node_obj.insert(
+            "ty".to_string(),
+            serde_json::Value::String("Tampered".to_string()),
+        );
     }
 
     fs::write(path, serde_json::to_string_pretty(&json)?)?;
@@ -132,9 +132,161 @@ fn cross_restart_determinism_with_checkpoint_test() -> Result<(), Box<dyn std::e
     storage::save(path, &loaded)?;
     let reloaded = storage::load(path)?;
 
-    assert_eq!(loaded.head_state, reloaded.head_state);
+    assert_eq!(loaded.head_state(), reloaded.head_state());
     assert_eq!(loaded.commits.len(), reloaded.commits.len());
 
     cleanup(path);
     Ok(())
 }
+
+#[test]
+fn later_checkpoints_are_saved_as_deltas() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_checkpoint_deltas.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let ids: Vec<NodeId> = (0..20).map(|_| mem.create("Agent")).collect();
+    mem.commit(None)?;
+    for i in 1..(3 * CHECKPOINT_INTERVAL) {
+        mem.set(ids[0], "n", Value::Int(i as i64))?;
+        mem.commit(None)?;
+    }
+    assert_eq!(mem.checkpoints.len(), 3);
+    storage::save(path, &mem)?;
+
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let checkpoints = json["checkpoints"]
+        .as_array()
+        .ok_or("missing checkpoints")?;
+    assert_eq!(
+        checkpoints[0]["state"].as_object().map(|s| s.len()),
+        Some(20)
+    );
+    assert!(checkpoints[0].get("changed").is_none());
+    for cp in &checkpoints[1..] {
+        assert!(cp.get("state").is_none());
+        assert_eq!(cp["changed"].as_object().map(|s| s.len()), Some(1));
+    }
+
+    let loaded = storage::load(path)?;
+    for (a, b) in loaded.checkpoints.iter().zip(&mem.checkpoints) {
+        assert_eq!(a.state, b.state);
+        assert_eq!(a.state_hash, b.state_hash);
+    }
+
+    let mut tampered = json.clone();
+    let changed = tampered["checkpoints"][1]["changed"]
+        .as_object_mut()
+        .ok_or("missing delta")?;
+    let node = changed.values_mut().next().ok_or("empty delta")?;
+    node["ty"] = serde_json::json!("Tampered");
+    fs::write(path, serde_json::to_string_pretty(&tampered)?)?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::CorruptCheckpointHash)
+    ));
+
+    let mut orphan = json.clone();
+    orphan["checkpoints"]
+        .as_array_mut()
+        .ok_or("missing")?
+        .remove(0);
+    fs::write(path, serde_json::to_string_pretty(&orphan)?)?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::MalformedFileStructure)
+    ));
+
+    // Version 1 stored every checkpoint in full.
+    let mut v1 = json;
+    v1["format_version"] = serde_json::json!(1);
+    fs::write(path, serde_json::to_string_pretty(&v1)?)?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::MalformedFileStructure)
+    ));
+    assert!(matches!(
+        storage::open_lazy(path),
+        Err(MyosotisError::MalformedFileStructure)
+    ));
+    v1["checkpoints"]
+        .as_array_mut()
+        .ok_or("missing")?
+        .truncate(1);
+    fs::write(path, serde_json::to_string_pretty(&v1)?)?;
+    assert_eq!(storage::load(path)?.head_state(), mem.head_state());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn state_at_commit_lookup_test() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    for i in 0..(3 * CHECKPOINT_INTERVAL + 7) {
+        mem.set(id, "n", Value::Int(i as i64))?;
+        mem.commit(None)?;
+    }
+    mem.compact_in_memory(10)?;
+    assert_eq!(mem.commits[0].id, 11);
+    assert!(
+        mem.checkpoints
+            .windows(2)
+            .all(|w| w[0].commit_id < w[1].commit_id)
+    );
+
+    let genesis = mem.genesis_state.clone().unwrap_or_default();
+    for (i, commit) in mem.commits.iter().enumerate() {
+        let expected = Memory::replay_from(genesis.clone(), &mem.commits[..=i])?;
+        assert_eq!(mem.state_at_commit(commit.id)?, expected);
+    }
+    for missing in [0, 10, mem.commits.len() as u64 + 11] {
+        assert!(matches!(
+            mem.state_at_commit(missing),
+            Err(MyosotisError::CommitNotFound(id)) if id == missing
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn rebuild_and_prune_checkpoints_test() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_checkpoint_rebuild_prune.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    for i in 0..12 {
+        let id = mem.create("Agent");
+        mem.set(id, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    assert!(mem.checkpoints.is_empty());
+    assert!(matches!(
+        mem.rebuild_checkpoints(0),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+
+    assert_eq!(mem.rebuild_checkpoints(4)?, 3);
+    let ids: Vec<u64> = mem.checkpoints.iter().map(|cp| cp.commit_id).collect();
+    assert_eq!(ids, vec![4, 8, 12]);
+    assert_eq!(mem.checkpoints[1].state, mem.state_at_commit(8)?);
+    mem.validate()?;
+    storage::save(path, &mem)?;
+
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    json["checkpoints"][0]["commit_hash"] = serde_json::json!(vec![0u8; 32]);
+    fs::write(path, serde_json::to_string_pretty(&json)?)?;
+    assert!(storage::load(path).is_err());
+
+    assert_eq!(maintenance::prune_checkpoints(path, 1)?, 2);
+    let loaded = storage::load(path)?;
+    let ids: Vec<u64> = loaded.checkpoints.iter().map(|cp| cp.commit_id).collect();
+    assert_eq!(ids, vec![12]);
+
+    assert_eq!(mem.prune_checkpoints(0)?, 3);
+    assert!(mem.checkpoints.is_empty());
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use myosotis::maintenance::{self, Severity};
+use myosotis::node::Value;
+use myosotis::storage;
+use myosotis::{Memory, MyosotisError};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+fn saved(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(None)?;
+    mem.set_file_checksum(true);
+    storage::save(path, &mem)?;
+    Ok(())
+}
+
+#[test]
+fn checksum_is_written_and_sticks() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_checksum_sticky.myo";
+    cleanup(path);
+    saved(path)?;
+
+    let data = fs::read_to_string(path)?;
+    assert!(data.starts_with("{\n  \"checksum\": \"sha256:"));
+
+    let mut mem = storage::load(path)?;
+    assert!(mem.file_checksum());
+    let id = mem.create("Task");
+    mem.set(id, "title", Value::Str("Map".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    assert!(fs::read_to_string(path)?.starts_with("{\n  \"checksum\""));
+
+    mem.set_file_checksum(false);
+    storage::save(path, &mem)?;
+    assert!(!fs::read_to_string(path)?.contains("\"checksum\""));
+    assert!(!storage::load(path)?.file_checksum());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn corrupt_or_truncated_file_fails_checksum() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_checksum_corrupt.myo";
+    cleanup(path);
+    saved(path)?;
+    let data = fs::read_to_string(path)?;
+
+    fs::write(path, data.replace("Explore", "Exploit"))?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::FileChecksumMismatch)
+    ));
+    assert!(matches!(
+        storage::open_lazy(path),
+        Err(MyosotisError::FileChecksumMismatch)
+    ));
+    assert!(matches!(
+        storage::stream_commits(path),
+        Err(MyosotisError::FileChecksumMismatch)
+    ));
+    let report = maintenance::fsck(path, false)?;
+    assert!(
+        report
+            .issues
+            .iter()
+            .any(|i| i.check == "checksum" && i.severity == Severity::Error)
+    );
+
+    fs::write(path, &data[..data.len() / 2])?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::FileChecksumMismatch)
+    ));
+
+    fs::write(path, &data)?;
+    assert!(storage::load(path)?.file_checksum());
+    assert!(storage::open_lazy(path)?.to_memory()?.file_checksum());
+
+    cleanup(path);
+    Ok(())
+}
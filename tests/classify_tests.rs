@@ -0,0 +1,57 @@
+use myosotis::Memory;
+use myosotis::classify::Classifier;
+use myosotis::node::Value;
+
+fn build() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(Some("obs: agent spawned".to_string()))?;
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("plan: set goal".to_string()))?;
+    mem.delete_node(id)?;
+    mem.commit(None)?;
+    Ok(mem)
+}
+
+#[test]
+fn regex_rules_label_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let mem = build()?;
+    let classifier = Classifier::from_rules_json(
+        r#"{"rules": [
+            {"class": "observation", "message": "^obs:"},
+            {"class": "cleanup", "mutation": "delete_node"},
+            {"class": "goal", "message": "goal", "mutation": "set_field"}
+        ]}"#,
+    )?;
+
+    assert_eq!(classifier.classify(&mem.commits[0]), vec!["observation"]);
+    assert_eq!(classifier.classify(&mem.commits[1]), vec!["goal"]);
+    assert_eq!(classifier.classify(&mem.commits[2]), vec!["cleanup"]);
+
+    let observations = mem.commits_with_class(&classifier, "observation");
+    assert_eq!(observations.len(), 1);
+    assert_eq!(observations[0].id, mem.commits[0].id);
+    Ok(())
+}
+
+#[test]
+fn registered_functions_combine_with_rules() -> Result<(), Box<dyn std::error::Error>> {
+    let mem = build()?;
+    let mut classifier = Classifier::new();
+    classifier.register("large", |c| c.mutations.len() > 1);
+    classifier.register("unlabelled", |c| c.message.is_none());
+    classifier.register("large", |c| c.message.is_none());
+
+    assert!(classifier.classify(&mem.commits[0]).is_empty());
+    assert_eq!(
+        classifier.classify(&mem.commits[2]),
+        vec!["unlabelled", "large"]
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    assert!(Classifier::from_rules_json(r#"{"rules": [{"class": "x", "message": "("}]}"#).is_err());
+    assert!(Classifier::from_rules_json(r#"{"rules": [{"class": "x", "kind": "y"}]}"#).is_err());
+}
@@ -0,0 +1,86 @@
+use myosotis::node::Value;
+use myosotis::{Memory, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+fn build(path: &str) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("c1".to_string()))?;
+    for i in 0..5 {
+        mem.set(id, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+    Ok(mem)
+}
+
+#[test]
+fn stream_yields_headers_and_lazy_mutations() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_stream_headers.myo";
+    cleanup(path);
+    let mem = build(path)?;
+
+    let stream = storage::stream_commits(path)?;
+    assert_eq!(stream.size_hint(), (6, Some(6)));
+    for (lazy, commit) in stream.zip(&mem.commits) {
+        assert_eq!(lazy.id, commit.id);
+        assert_eq!(lazy.hash, commit.hash);
+        assert_eq!(lazy.message, commit.message);
+        assert!(lazy.mutations_len() > 0);
+        let decoded = lazy.into_commit()?;
+        assert_eq!(
+            Memory::compute_commit_hash(decoded.parent_hash, &decoded.message, &decoded.mutations),
+            commit.hash
+        );
+    }
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn stream_link_verification() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_stream_links.myo";
+    cleanup(path);
+    build(path)?;
+
+    assert_eq!(
+        storage::verify_commit_links(storage::stream_commits(path)?)?,
+        6
+    );
+
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    json["commits"][3]["parent_hash"] = serde_json::json!(vec![7u8; 32]);
+    fs::write(path, serde_json::to_string_pretty(&json)?)?;
+    assert!(storage::verify_commit_links(storage::stream_commits(path)?).is_err());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn stream_checks_header_and_survives_compaction() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_stream_header.myo";
+    cleanup(path);
+    build(path)?;
+
+    storage::compact(path, Some(3))?;
+    assert_eq!(
+        storage::verify_commit_links(storage::stream_commits(path)?)?,
+        3
+    );
+
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    json["format_version"] = serde_json::json!(99);
+    fs::write(path, serde_json::to_string_pretty(&json)?)?;
+    assert!(storage::stream_commits(path).is_err());
+
+    cleanup(path);
+    Ok(())
+}
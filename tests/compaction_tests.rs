@@ -1,6 +1,8 @@
+use myosotis::commit::Commit;
+use myosotis::maintenance;
 use myosotis::memory::CHECKPOINT_INTERVAL;
 use myosotis::node::Value;
-use myosotis::{storage, Memory};
+use myosotis::{Memory, MyosotisError, storage};
 use std::fs;
 
 fn cleanup(path: &str) {
@@ -31,12 +33,12 @@ fn compaction_equivalence() -> Result<(), Box<dyn std::error::Error>> {
     cleanup(path);
 
     let mem = build_state_with_history()?;
-    let before_hash = Memory::compute_state_hash(&mem.head_state);
+    let before_hash = Memory::compute_state_hash(mem.head_state());
     storage::save(path, &mem)?;
 
     storage::compact(path, None)?;
     let after = storage::load(path)?;
-    let after_hash = Memory::compute_state_hash(&after.head_state);
+    let after_hash = Memory::compute_state_hash(after.head_state());
 
     assert_eq!(before_hash, after_hash);
     cleanup(path);
@@ -122,7 +124,7 @@ fn tombstone_preservation_after_compaction() -> Result<(), Box<dyn std::error::E
     storage::compact(path, Some(1))?;
     let compacted = storage::load(path)?;
 
-    let node = compacted.head_state.get(&id).ok_or("missing node")?;
+    let node = compacted.head_state().get(&id).ok_or("missing node")?;
     assert!(node.deleted);
 
     cleanup(path);
@@ -142,7 +144,186 @@ fn cross_restart_stability_after_compaction() -> Result<(), Box<dyn std::error::
     storage::save(path, &loaded)?;
     let reloaded = storage::load(path)?;
 
-    assert_eq!(loaded.head_state, reloaded.head_state);
+    assert_eq!(loaded.head_state(), reloaded.head_state());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn advise_recommends_latest_checkpoint() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_compaction_advise.myo";
+    cleanup(path);
+
+    let mut mem = build_state_with_history()?;
+    for _ in 0..20 {
+        mem.set(1, "name", Value::Str("churn".repeat(20)))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+
+    let advice = maintenance::advise(path)?;
+    assert_eq!(advice.file_bytes, fs::metadata(path)?.len() as usize);
+    assert_eq!(advice.commits, 90);
+    assert_eq!(advice.tombstones, 7);
+    assert_eq!(advice.checkpoints, 1);
+    assert_eq!(advice.redundant_checkpoints, 0);
+    assert_eq!(advice.hotspots[0].node_id, 1);
+    assert_eq!(advice.hotspots[0].mutations, 22);
+
+    let rec = advice.recommendation.ok_or("expected a recommendation")?;
+    assert_eq!(rec.target_commit_id, CHECKPOINT_INTERVAL as u64);
+
+    maintenance::compact(path, Some(rec.target_commit_id))?;
+    assert_eq!(
+        fs::metadata(path)?.len() as usize,
+        rec.estimated_bytes_after
+    );
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn advise_skips_small_files() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_compaction_advise_small.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "name", Value::Str("root".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+
+    let advice = maintenance::advise(path)?;
+    assert!(advice.recommendation.is_none());
+    assert_eq!(advice.mutations, 2);
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn compaction_in_memory_matches_file_compaction() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_compaction_in_memory.myo";
+    cleanup(path);
+
+    let mut mem = build_state_with_history()?;
+    storage::save(path, &mem)?;
+    maintenance::compact(path, Some(20))?;
+    let from_file = storage::load(path)?;
+
+    mem.set(1, "name", Value::Str("pending".to_string()))?;
+    let head = mem.head_state().clone();
+    mem.compact_in_memory(20)?;
+    assert_eq!(mem.head_state(), &head);
+    assert_eq!(mem.pending_mutations().len(), 1);
+    assert_eq!(mem.genesis_state_hash, from_file.genesis_state_hash);
+    assert_eq!(
+        mem.commits.iter().map(|c| c.hash).collect::<Vec<_>>(),
+        from_file.commits.iter().map(|c| c.hash).collect::<Vec<_>>()
+    );
+
+    mem.commit(None)?;
+    mem.validate()?;
+    assert!(mem.compact_in_memory(5).is_err());
+
+    cleanup(path);
+    Ok(())
+}
+
+struct Archiving {
+    archived: Vec<u64>,
+    reject: bool,
+}
+
+impl maintenance::CompactionStrategy for Archiving {
+    fn select_target(&self, _mem: &Memory) -> Result<u64, MyosotisError> {
+        Ok(10)
+    }
+
+    fn archive(&mut self, folded: &[Commit]) -> Result<(), MyosotisError> {
+        self.archived.extend(folded.iter().map(|c| c.id));
+        Ok(())
+    }
+
+    fn verify(&self, before: &Memory, after: &Memory) -> Result<(), MyosotisError> {
+        if self.reject || before.head_state() != after.head_state() {
+            return Err(MyosotisError::CompactionIntegrityMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn compaction_strategy_hooks() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_compaction_strategy.myo";
+    cleanup(path);
+
+    let mem = build_state_with_history()?;
+    storage::save(path, &mem)?;
+
+    let mut rejecting = Archiving {
+        archived: Vec::new(),
+        reject: true,
+    };
+    assert!(maintenance::compact_with(path, &mut rejecting).is_err());
+    assert_eq!(storage::load(path)?.commits.len(), mem.commits.len());
+    assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+    let mut in_memory = mem.clone();
+    assert!(in_memory.compact_with(&mut rejecting).is_err());
+    assert_eq!(in_memory.commits.len(), mem.commits.len());
+
+    let mut strategy = Archiving {
+        archived: Vec::new(),
+        reject: false,
+    };
+    assert_eq!(maintenance::compact_with(path, &mut strategy)?, 10);
+    assert_eq!(strategy.archived, (1..=10).collect::<Vec<_>>());
+    let compacted = storage::load(path)?;
+    assert_eq!(compacted.commits.first().map(|c| c.id), Some(11));
+    assert_eq!(compacted.head_state(), mem.head_state());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn adopting_a_compacted_copy_rebases_new_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_compaction_adopt.myo";
+    cleanup(path);
+
+    let mut mem = build_state_with_history()?;
+    let base = mem.commits.last().ok_or("no commits")?.hash;
+    let mut copy = mem.clone();
+    copy.compact_in_memory(40)?;
+
+    mem.set(1, "name", Value::Str("moved on".to_string()))?;
+    mem.commit(Some("c71".to_string()))?;
+    mem.set(2, "n", Value::Int(-2))?;
+    let head = mem.head_state().clone();
+
+    assert_eq!(mem.adopt_compacted(copy.clone(), base)?, 1);
+    assert_eq!(mem.head_state(), &head);
+    assert_eq!(mem.pending_mutations().len(), 1);
+    assert_eq!(mem.genesis_state_hash, copy.genesis_state_hash);
+    assert_eq!(
+        mem.commits.iter().map(|c| c.id).collect::<Vec<_>>(),
+        (41..=71).collect::<Vec<_>>()
+    );
+    mem.commit(None)?;
+    mem.validate()?;
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.head_state(), mem.head_state());
+
+    // The chain `base` named is gone now.
+    let err = mem
+        .adopt_compacted(copy, base)
+        .expect_err("base was rewritten");
+    assert!(matches!(err, MyosotisError::CompactionIntegrityMismatch));
 
     cleanup(path);
     Ok(())
@@ -73,7 +73,7 @@ fn delete_after_checkpoint() -> Result<(), Box<dyn std::error::Error>> {
     storage::save(path, &mem)?;
     let loaded = storage::load(path)?;
 
-    let node = loaded.head_state.get(&first).ok_or("missing node")?;
+    let node = loaded.head_state().get(&first).ok_or("missing node")?;
     assert!(node.deleted);
 
     cleanup(path);
@@ -111,19 +111,67 @@ fn hash_stability_after_deletion() -> Result<(), Box<dyn std::error::Error>> {
     mem.set(id, "goal", Value::Str("Explore".to_string()))?;
     mem.commit(Some("c1".to_string()))?;
 
-    let before_hash = Memory::compute_state_hash(&mem.head_state);
+    let before_hash = Memory::compute_state_hash(mem.head_state());
 
     mem.delete_node(id)?;
     mem.commit(Some("c2".to_string()))?;
 
-    let after_hash = Memory::compute_state_hash(&mem.head_state);
+    let after_hash = Memory::compute_state_hash(mem.head_state());
     assert_ne!(before_hash, after_hash);
 
     storage::save(path, &mem)?;
     let loaded = storage::load(path)?;
-    let loaded_hash = Memory::compute_state_hash(&loaded.head_state);
+    let loaded_hash = Memory::compute_state_hash(loaded.head_state());
     assert_eq!(after_hash, loaded_hash);
 
     cleanup(path);
     Ok(())
 }
+
+#[test]
+fn restore_node_replays_and_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_restore_node.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let kept = mem.create("Agent");
+    let cleared = mem.create("Agent");
+    for id in [kept, cleared] {
+        mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    }
+    mem.commit(Some("create".to_string()))?;
+
+    assert!(matches!(
+        mem.restore_node(kept, true),
+        Err(MyosotisError::RestoreLiveNode(_))
+    ));
+    assert!(matches!(
+        mem.restore_node(99, true),
+        Err(MyosotisError::NodeNotFound(99))
+    ));
+
+    mem.delete_node(kept)?;
+    mem.delete_node(cleared)?;
+    mem.commit(Some("delete".to_string()))?;
+    mem.restore_node(kept, true)?;
+    mem.restore_node(cleared, false)?;
+    mem.commit(Some("restore".to_string()))?;
+
+    let replayed = Memory::replay(&mem.commits)?;
+    assert_eq!(&replayed, mem.head_state());
+    let node = replayed.get(&kept).ok_or("missing node")?;
+    assert!(!node.deleted);
+    assert_eq!(node.get_str("goal"), Some("Explore"));
+    let node = replayed.get(&cleared).ok_or("missing node")?;
+    assert!(!node.deleted);
+    assert!(node.fields.is_empty());
+
+    mem.set(kept, "goal", Value::Str("Return".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.head_state(), mem.head_state());
+
+    cleanup(path);
+    Ok(())
+}
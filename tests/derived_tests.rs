@@ -0,0 +1,83 @@
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn display(mem: &mut Memory) {
+    mem.define_derived("Agent", "display", |node| {
+        let name = match node.get("name") {
+            Some(Value::Str(name)) => name.as_str(),
+            _ => "?",
+        };
+        Value::Str(format!("Agent {} ({})", node.id, name))
+    });
+}
+
+#[test]
+fn derived_fields_are_computed_on_read() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    display(&mut mem);
+    let a = mem.create("Agent");
+    mem.set(a, "name", Value::Str("scout".to_string()))?;
+    let task = mem.create("Task");
+
+    let expected = Value::Str(format!("Agent {} (scout)", a));
+    assert_eq!(mem.get_derived(a, "display")?, expected);
+    assert_eq!(mem.read_field(a, "display")?, expected);
+    assert_eq!(mem.derived_values(a)?.len(), 1);
+    assert!(mem.derived_values(task)?.is_empty());
+
+    // Reads track the node as it changes.
+    mem.set(a, "name", Value::Str("planner".to_string()))?;
+    assert_eq!(
+        mem.read_field(a, "display")?,
+        Value::Str(format!("Agent {} (planner)", a))
+    );
+
+    // A stored field of the same name wins.
+    mem.set(a, "display", Value::Str("Scout".to_string()))?;
+    assert_eq!(
+        mem.read_field(a, "display")?,
+        Value::Str("Scout".to_string())
+    );
+
+    assert!(matches!(
+        mem.get_derived(task, "display"),
+        Err(MyosotisError::FieldNotFound(_))
+    ));
+    assert!(mem.remove_derived("Agent", "display"));
+    assert!(!mem.remove_derived("Agent", "display"));
+    assert!(matches!(
+        mem.get_derived(a, "display"),
+        Err(MyosotisError::FieldNotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn derived_fields_are_not_stored_or_hashed() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_derived_not_stored.myo";
+    cleanup(path);
+
+    let mut plain = Memory::new();
+    let mut derived = Memory::new();
+    display(&mut derived);
+    for mem in [&mut plain, &mut derived] {
+        let a = mem.create("Agent");
+        mem.set(a, "name", Value::Str("scout".to_string()))?;
+        mem.commit(Some("init".to_string()))?;
+    }
+    assert_eq!(plain.commits[0].hash, derived.commits[0].hash);
+    assert_eq!(plain.head_state(), derived.head_state());
+
+    storage::save(path, &derived)?;
+    let loaded = storage::load(path)?;
+    let id = *loaded.head_state().keys().next().unwrap();
+    assert!(loaded.get_field(id, "display").is_err());
+    assert!(loaded.derived_values(id)?.is_empty());
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::io::{Cursor, Read};
+
+fn build() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("c1".to_string()))?;
+    Ok(mem)
+}
+
+#[test]
+fn embed_round_trips_inside_host_data() -> Result<(), Box<dyn std::error::Error>> {
+    let mem = build()?;
+
+    let mut host = b"SAVEGAME:level=3;".to_vec();
+    storage::embed(&mut host, &mem)?;
+    host.extend_from_slice(b";trailer");
+
+    let mut reader = Cursor::new(host);
+    reader.set_position("SAVEGAME:level=3;".len() as u64);
+    let extracted = storage::extract(&mut reader)?;
+    assert_eq!(extracted.head_state(), mem.head_state());
+    assert_eq!(extracted.commits.len(), 1);
+
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest)?;
+    assert_eq!(rest, ";trailer");
+    Ok(())
+}
+
+#[test]
+fn extract_rejects_corrupt_frames() -> Result<(), Box<dyn std::error::Error>> {
+    let mem = build()?;
+    let mut frame = Vec::new();
+    storage::embed(&mut frame, &mem)?;
+
+    let mut flipped = frame.clone();
+    flipped[20] ^= 0x01;
+    let err = storage::extract(&mut Cursor::new(flipped))
+        .err()
+        .ok_or("corrupt payload accepted")?;
+    assert!(matches!(err, MyosotisError::EmbedChecksumMismatch));
+
+    let mut bad_magic = frame.clone();
+    bad_magic[0] = b'X';
+    assert!(storage::extract(&mut Cursor::new(bad_magic)).is_err());
+
+    frame.truncate(frame.len() - 1);
+    assert!(storage::extract(&mut Cursor::new(frame)).is_err());
+    Ok(())
+}
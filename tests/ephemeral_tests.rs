@@ -0,0 +1,82 @@
+use myosotis::ephemeral::Persistence;
+use myosotis::node::Value;
+use myosotis::storage;
+use myosotis::{Memory, MyosotisError};
+use std::fs;
+use std::path::Path;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+    let _ = fs::remove_file(format!("{}.wal", path));
+}
+
+#[test]
+fn ephemeral_memory_never_writes() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_ephemeral_never.myo";
+    cleanup(path);
+
+    let mut mem = Memory::open_ephemeral();
+    assert!(mem.is_ephemeral());
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(None)?;
+
+    assert!(matches!(
+        storage::save(path, &mem),
+        Err(MyosotisError::Ephemeral)
+    ));
+    assert!(matches!(
+        mem.enable_wal(&format!("{}.wal", path)),
+        Err(MyosotisError::Ephemeral)
+    ));
+    assert!(matches!(mem.persist(), Err(MyosotisError::Ephemeral)));
+    assert!(!Path::new(path).exists());
+    assert!(!Path::new(&format!("{}.wal", path)).exists());
+
+    // Clones stay ephemeral.
+    assert!(mem.clone().is_ephemeral());
+    Ok(())
+}
+
+#[test]
+fn attach_persist_and_detach() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_ephemeral_attach.myo";
+    cleanup(path);
+
+    let mut mem = Memory::open_ephemeral();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(None)?;
+
+    mem.attach(path)?;
+    assert_eq!(mem.attached_path(), Some(path));
+    assert_eq!(storage::load(path)?.commits.len(), 1);
+
+    // Commits stay in memory until the next persist.
+    mem.set(id, "goal", Value::Str("Rest".to_string()))?;
+    mem.commit(None)?;
+    assert_eq!(storage::load(path)?.commits.len(), 1);
+    mem.persist()?;
+    assert_eq!(storage::load(path)?.commits.len(), 2);
+
+    mem.enable_wal(&format!("{}.wal", path))?;
+    assert_eq!(mem.detach()?, Some(path.to_string()));
+    assert_eq!(mem.persistence(), &Persistence::Ephemeral);
+    assert!(mem.wal_path().is_none());
+    assert!(!Path::new(&format!("{}.wal", path)).exists());
+
+    mem.set(id, "goal", Value::Str("Sleep".to_string()))?;
+    mem.commit(None)?;
+    assert!(matches!(mem.persist(), Err(MyosotisError::Ephemeral)));
+    assert_eq!(storage::load(path)?.commits.len(), 2);
+
+    // A loaded memory is unbound; detaching it makes it ephemeral.
+    let mut loaded = storage::load(path)?;
+    assert_eq!(loaded.persistence(), &Persistence::Unbound);
+    assert_eq!(loaded.detach()?, None);
+    assert!(loaded.is_ephemeral());
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use myosotis::commit::Mutation;
+use myosotis::node::Value;
+use myosotis::{Memory, maintenance, storage};
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[test]
+fn expired_fields_are_deleted_in_one_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_expiry.myo";
+    let _ = fs::remove_file(path);
+
+    let mut mem = Memory::new();
+    let a = mem.create("Scratch");
+    let b = mem.create("Scratch");
+    mem.set_with_ttl(
+        a,
+        "note",
+        Value::Str("soon".to_string()),
+        Duration::from_secs(60),
+    )?;
+    mem.set_with_ttl(
+        b,
+        "note",
+        Value::Str("later".to_string()),
+        Duration::from_secs(3600),
+    )?;
+    mem.set_with_ttl(b, "gone", Value::Int(1), Duration::from_secs(60))?;
+    mem.set(a, "keep", Value::Int(1))?;
+    mem.commit(None)?;
+    mem.delete_field(b, "gone")?;
+    mem.commit(None)?;
+    assert!(mem.expiry(a, "note").is_some());
+    storage::save(path, &mem)?;
+
+    // Nothing is due yet.
+    assert!(maintenance::expire(path, now())?.is_empty());
+    assert_eq!(storage::load(path)?.commits.len(), 2);
+
+    let expired = maintenance::expire(path, now() + 120)?;
+    assert_eq!(expired.len(), 1);
+    assert_eq!((expired[0].node_id, expired[0].key.as_str()), (a, "note"));
+
+    let mem = storage::load(path)?;
+    let sweep = mem.commits.last().unwrap();
+    assert_eq!(
+        sweep.message.as_deref(),
+        Some("expire 1 fields past their TTL")
+    );
+    assert!(matches!(
+        sweep.mutations.as_slice(),
+        [Mutation::DeleteField { id, key }] if *id == a && key == "note"
+    ));
+    assert!(mem.get_field(a, "note").is_err());
+    assert!(mem.get_field(a, "keep").is_ok());
+    // The expiry of the field deleted by hand is dropped; the other stays.
+    assert_eq!(mem.expiries.len(), 1);
+    assert!(mem.expiry(b, "note").is_some());
+
+    let _ = fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn expire_needs_a_clean_stage() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Scratch");
+    mem.set_with_ttl(id, "note", Value::Int(1), Duration::ZERO)?;
+    assert!(mem.expire(now()).is_err());
+
+    mem.commit(None)?;
+    assert!(mem.clear_ttl(id, "note"));
+    assert!(mem.expire(now())?.is_empty());
+    assert_eq!(mem.commits.len(), 1);
+    Ok(())
+}
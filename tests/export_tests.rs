@@ -0,0 +1,206 @@
+use myosotis::node::{NodeId, Value};
+use myosotis::{Memory, export};
+
+fn stream(mem: &Memory) -> Result<String, Box<dyn std::error::Error>> {
+    let mut out = Vec::new();
+    export::git_fast_import(mem, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn git_export_emits_one_commit_per_myosotis_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("c1".to_string()))?;
+    mem.set(id, "goal", Value::Str("Rest".to_string()))?;
+    mem.commit(Some("c2".to_string()))?;
+
+    let out = stream(&mem)?;
+    assert_eq!(out.matches("commit refs/heads/main").count(), 2);
+    assert!(out.contains("mark :1\n"));
+    assert!(out.contains("from :1\n"));
+    assert!(out.contains(&format!("M 644 inline nodes/{}.json", id)));
+    assert!(out.contains("Rest"));
+    Ok(())
+}
+
+#[test]
+fn git_export_removes_deleted_nodes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(Some("create".to_string()))?;
+    mem.delete_node(id)?;
+    mem.commit(Some("delete".to_string()))?;
+
+    let out = stream(&mem)?;
+    assert!(out.contains(&format!("D nodes/{}.json", id)));
+    Ok(())
+}
+
+#[test]
+fn git_export_is_deterministic() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "a", Value::Int(1))?;
+    mem.set(id, "b", Value::Int(2))?;
+    mem.set(id, "c", Value::Int(3))?;
+    mem.commit(Some("c1".to_string()))?;
+
+    assert_eq!(stream(&mem)?, stream(&mem)?);
+    Ok(())
+}
+
+fn build_graph() -> Result<(Memory, NodeId, NodeId, NodeId), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Goal");
+    let c = mem.create("Note");
+    mem.set(a, "goal", Value::Ref(b))?;
+    mem.set(a, "notes", Value::List(vec![Value::Ref(c), Value::Int(1)]))?;
+    mem.commit(Some("c1".to_string()))?;
+    mem.delete_node(c)?;
+    mem.commit(Some("c2".to_string()))?;
+    Ok((mem, a, b, c))
+}
+
+#[test]
+fn dot_renders_nodes_and_ref_edges() -> Result<(), Box<dyn std::error::Error>> {
+    let (mem, a, b, c) = build_graph()?;
+
+    let head = mem.to_dot(None)?;
+    assert!(head.starts_with("digraph myosotis {"));
+    assert!(head.contains(&format!("n{} [label=\"{}: Agent\"", a, a)));
+    assert!(head.contains(&format!("n{} -> n{} [label=\"goal\"]", a, b)));
+    assert!(!head.contains(&format!("n{} [", c)));
+
+    let historical = mem.to_dot(Some(1))?;
+    assert!(historical.contains(&format!("n{} -> n{} [label=\"notes\"]", a, c)));
+    Ok(())
+}
+
+#[test]
+fn graphml_renders_nodes_and_ref_edges() -> Result<(), Box<dyn std::error::Error>> {
+    let (mem, a, b, _) = build_graph()?;
+
+    let out = mem.to_graphml(None)?;
+    assert!(out.contains("<graphml"));
+    assert!(out.contains(&format!(
+        "<node id=\"n{}\"><data key=\"type\">Goal</data></node>",
+        b
+    )));
+    assert!(out.contains(&format!("source=\"n{}\" target=\"n{}\"", a, b)));
+    assert!(mem.to_graphml(Some(99)).is_err());
+    Ok(())
+}
+
+#[test]
+fn ntriples_uses_ontology_mapping() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut mem, a, b, c) = build_graph()?;
+    mem.set(b, "title", Value::Str("say \"hi\"".to_string()))?;
+
+    let ontology: export::Ontology = serde_json::from_str(
+        r#"{"base": "http://ex.org/n/", "types": {"Agent": "http://xmlns.com/foaf/0.1/Agent"},
+            "properties": {"goal": "http://ex.org/p/hasGoal"}}"#,
+    )?;
+    let out = mem.to_ntriples(None, &ontology)?;
+
+    assert!(out.contains(&format!(
+        "<http://ex.org/n/{}> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://xmlns.com/foaf/0.1/Agent> .",
+        a
+    )));
+    assert!(out.contains(&format!(
+        "<http://ex.org/n/{}> <http://ex.org/p/hasGoal> <http://ex.org/n/{}> .",
+        a, b
+    )));
+    assert!(out.contains(
+        "<urn:myosotis:vocab:notes> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> ."
+    ));
+    assert!(out.contains("<urn:myosotis:vocab:title> \"say \\\"hi\\\"\" ."));
+    assert!(!out.contains(&format!("/n/{}>", c)));
+    Ok(())
+}
+
+#[test]
+fn json_ld_graph_has_one_entry_per_live_node() -> Result<(), Box<dyn std::error::Error>> {
+    let (mem, a, b, _) = build_graph()?;
+    let doc = mem.to_json_ld(None, &export::Ontology::default())?;
+
+    let graph = doc["@graph"].as_array().ok_or("missing @graph")?;
+    assert_eq!(graph.len(), 2);
+    assert_eq!(graph[0]["@id"], format!("urn:myosotis:node:{}", a));
+    assert_eq!(graph[0]["@type"], "urn:myosotis:vocab:Agent");
+    assert_eq!(
+        graph[0]["urn:myosotis:vocab:goal"]["@id"],
+        format!("urn:myosotis:node:{}", b)
+    );
+    assert_eq!(graph[0]["urn:myosotis:vocab:notes"][0]["@value"], "1");
+    Ok(())
+}
+
+#[test]
+fn neo4j_csv_has_labels_typed_properties_and_relationships()
+-> Result<(), Box<dyn std::error::Error>> {
+    let (mut mem, a, b, _) = build_graph()?;
+    mem.set(a, "age", Value::Int(3))?;
+    mem.set(b, "title", Value::Str("win, \"big\"".to_string()))?;
+
+    let (nodes, relationships) = mem.to_neo4j_csv(None)?;
+    let mut lines = nodes.lines();
+    assert_eq!(lines.next(), Some("id:ID,:LABEL,age:long,title"));
+    assert_eq!(lines.next(), Some(format!("{},Agent,3,", a).as_str()));
+    assert_eq!(
+        lines.next(),
+        Some(format!("{},Goal,,\"win, \"\"big\"\"\"", b).as_str())
+    );
+    assert_eq!(lines.next(), None);
+
+    assert_eq!(
+        relationships,
+        format!(":START_ID,:END_ID,:TYPE\n{},{},goal\n", a, b)
+    );
+    Ok(())
+}
+
+#[test]
+fn extract_keeps_only_the_given_nodes_history() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let iris = mem.create("Agent");
+    let rowan = mem.create("Agent");
+    mem.set(iris, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("agents".to_string()))?;
+    mem.set(rowan, "goal", Value::Str("Rest".to_string()))?;
+    mem.commit(Some("rowan only".to_string()))?;
+    mem.set(iris, "peer", Value::List(vec![Value::Ref(iris)]))?;
+    mem.commit(Some("iris".to_string()))?;
+    mem.compact_in_memory(1)?;
+    mem.set(iris, "pending", Value::Bool(true))?;
+
+    let out = export::extract(&mem, &[iris])?;
+    assert_eq!(out.commits.len(), 1);
+    assert_eq!(out.commits[0].id, 1);
+    assert_eq!(out.commits[0].message.as_deref(), Some("iris"));
+    assert_eq!(out.genesis_state.as_ref().map(|g| g.len()), Some(1));
+    assert_eq!(out.head_state().len(), 1);
+    let node = out.head_state().get(&iris).ok_or("missing node")?;
+    assert_eq!(
+        node.fields.get("peer"),
+        Some(&Value::List(vec![Value::Ref(iris)]))
+    );
+    assert!(!node.fields.contains_key("pending"));
+    assert!(out.pending_mutations().is_empty());
+    out.validate()?;
+
+    assert!(matches!(
+        export::extract(&mem, &[42]),
+        Err(myosotis::MyosotisError::NodeNotFound(42))
+    ));
+    mem.set(iris, "peer", Value::Ref(rowan))?;
+    mem.commit(None)?;
+    assert!(matches!(
+        export::extract(&mem, &[iris]),
+        Err(myosotis::MyosotisError::InvalidInput(_))
+    ));
+    assert_eq!(export::extract(&mem, &[iris, rowan])?.commits.len(), 3);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use myosotis::commit::Mutation;
+use myosotis::float::FloatPolicy;
+use myosotis::node::{NodeId, Value};
+use myosotis::{Memory, MyosotisError, storage};
+use std::collections::HashMap;
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn float_bits(mem: &Memory, id: NodeId, key: &str) -> u64 {
+    match mem.get_field(id, key) {
+        Ok(Value::Float(v)) => v.to_bits(),
+        other => panic!("expected a float, got {:?}", other),
+    }
+}
+
+#[test]
+fn float_policy_is_applied_on_write() -> Result<(), Box<dyn std::error::Error>> {
+    let payload_nan = f64::from_bits(f64::NAN.to_bits() | 1);
+
+    let mut exact = Memory::new();
+    let id = exact.create("Reading");
+    exact.set(id, "value", Value::Float(-0.0))?;
+    assert_eq!(float_bits(&exact, id, "value"), (-0.0f64).to_bits());
+
+    let mut normalized = Memory::new();
+    normalized.set_float_policy(FloatPolicy::Normalize)?;
+    let id = normalized.create("Reading");
+    normalized.set(id, "value", Value::Float(-0.0))?;
+    normalized.set(id, "nan", Value::Float(payload_nan))?;
+    normalized.list_push(id, "series", Value::Float(-0.0))?;
+    assert_eq!(float_bits(&normalized, id, "value"), 0.0f64.to_bits());
+    assert_eq!(float_bits(&normalized, id, "nan"), f64::NAN.to_bits());
+    normalized.commit(None)?;
+
+    // Same hash as writing the canonical values in the first place.
+    let mut plain = Memory::new();
+    let id = plain.create("Reading");
+    plain.set(id, "value", Value::Float(0.0))?;
+    plain.set(id, "nan", Value::Float(f64::NAN))?;
+    plain.list_push(id, "series", Value::Float(0.0))?;
+    plain.commit(None)?;
+    assert_eq!(normalized.commits[0].hash, plain.commits[0].hash);
+
+    let mut strict = Memory::new();
+    strict.set_float_policy(FloatPolicy::Strict)?;
+    let id = strict.create("Reading");
+    let nested = Value::Map(HashMap::from([(
+        "samples".to_string(),
+        Value::List(vec![Value::Float(1.0), Value::Float(f64::NAN)]),
+    )]));
+    assert!(matches!(
+        strict.set(id, "stats", nested),
+        Err(MyosotisError::FloatPolicyViolation(_, policy)) if policy == "strict"
+    ));
+    assert!(
+        strict
+            .set(id, "embedding", Value::Vector(vec![0.5, f32::NAN]))
+            .is_err()
+    );
+    strict.set(id, "value", Value::Float(-0.0))?;
+    assert_eq!(float_bits(&strict, id, "value"), 0.0f64.to_bits());
+
+    // Batches previewed with `dry_run_commit` are checked the same way.
+    let batch = [Mutation::SetField {
+        id,
+        key: "nan".to_string(),
+        value: Value::Float(f64::NAN),
+    }];
+    assert!(matches!(
+        strict.dry_run_commit(None, &batch),
+        Err(MyosotisError::FloatPolicyViolation(..))
+    ));
+    Ok(())
+}
+
+#[test]
+fn float_policy_is_recorded_and_checked_on_load() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_float_policy_load.myo";
+    cleanup(path);
+
+    // History already holding -0.0 cannot switch to a normalizing policy.
+    let mut mem = Memory::new();
+    let id = mem.create("Reading");
+    mem.set(id, "value", Value::Float(-0.0))?;
+    mem.commit(None)?;
+    assert!(matches!(
+        mem.set_float_policy(FloatPolicy::Normalize),
+        Err(MyosotisError::FloatPolicyViolation(..))
+    ));
+    assert_eq!(mem.float_policy(), FloatPolicy::BitExact);
+
+    let mut mem = Memory::new();
+    mem.set_float_policy(FloatPolicy::Strict)?;
+    let id = mem.create("Reading");
+    mem.set(id, "value", Value::Float(-0.0))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    assert!(fs::read_to_string(path)?.contains("\"float_policy\": \"strict\""));
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.float_policy(), FloatPolicy::Strict);
+    assert_eq!(loaded.head_state(), mem.head_state());
+
+    // A file edited to hold -0.0 fails to load, even without hash checks.
+    let data = fs::read_to_string(path)?.replace("\"Float\": 0.0", "\"Float\": -0.0");
+    fs::write(path, data)?;
+    assert!(matches!(
+        storage::load_with_mode(path, storage::LoadMode::Unsafe),
+        Err(MyosotisError::FloatPolicyViolation(..))
+    ));
+    cleanup(path);
+    Ok(())
+}
@@ -1,6 +1,6 @@
-use myosotis::Memory;
 use myosotis::node::Value;
-use myosotis::storage::{self, FILE_MAGIC, FORMAT_VERSION, LoadMode};
+use myosotis::storage::{self, Durability, FILE_MAGIC, FORMAT_VERSION, LoadMode};
+use myosotis::{Memory, MyosotisError};
 use std::fs;
 
 fn cleanup(path: &str) {
@@ -61,7 +61,10 @@ fn format_version_test_and_legacy_migration_path() -> Result<(), Box<dyn std::er
     // too-new version should fail
     let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
     if let Some(obj) = json.as_object_mut() {
-        obj.insert("format_version".to_string(), serde_json::json!(2));
+        obj.insert(
+            "format_version".to_string(),
+            serde_json::json!(FORMAT_VERSION + 1),
+        );
     }
     fs::write(path, serde_json::to_string_pretty(&json)?)?;
     assert!(storage::load(path).is_err());
@@ -99,7 +102,7 @@ fn migration_preserves_hash_equivalence() -> Result<(), Box<dyn std::error::Erro
     mem.commit(Some("c1".to_string()))?;
     storage::save(path, &mem)?;
 
-    let before_hash = Memory::compute_state_hash(&mem.head_state);
+    let before_hash = Memory::compute_state_hash(mem.head_state());
 
     let mut legacy_json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
     if let Some(obj) = legacy_json.as_object_mut() {
@@ -109,7 +112,7 @@ fn migration_preserves_hash_equivalence() -> Result<(), Box<dyn std::error::Erro
     fs::write(path, serde_json::to_string_pretty(&legacy_json)?)?;
 
     let loaded = storage::load(path)?;
-    let migrated_hash = Memory::compute_state_hash(&loaded.head_state);
+    let migrated_hash = Memory::compute_state_hash(loaded.head_state());
     assert_eq!(before_hash, migrated_hash);
 
     cleanup(path);
@@ -129,10 +132,10 @@ fn strict_vs_unsafe_mode() -> Result<(), Box<dyn std::error::Error>> {
 
     // Tamper commit hash
     let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
-    if let Some(commits) = json.get_mut("commits").and_then(|v| v.as_array_mut()) {
-        if let Some(first) = commits.first_mut() {
-            first["hash"] = serde_json::json!(vec![0u8; 32]);
-        }
+    if let Some(commits) = json.get_mut("commits").and_then(|v| v.as_array_mut())
+        && let Some(first) = commits.first_mut()
+    {
+        first["hash"] = serde_json::json!(vec![0u8; 32]);
     }
     fs::write(path, serde_json::to_string_pretty(&json)?)?;
 
@@ -166,3 +169,59 @@ fn corrupt_genesis_detection() -> Result<(), Box<dyn std::error::Error>> {
     cleanup(path);
     Ok(())
 }
+
+#[test]
+fn save_replaces_the_file_instead_of_overwriting_it() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_atomic_save.myo";
+    let link = "test_atomic_save_link.myo";
+    cleanup(path);
+    let _ = fs::remove_file(link);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    let before = fs::read_to_string(path)?;
+    fs::hard_link(path, link)?;
+
+    for durability in [Durability::Atomic, Durability::Sync, Durability::Full] {
+        mem.set_durability(durability);
+        mem.set(id, "level", Value::Str(format!("{:?}", durability)))?;
+        mem.commit(None)?;
+        storage::save(path, &mem)?;
+        assert!(!fs::exists(format!("{}.tmp", path))?);
+        assert_eq!(storage::load(path)?.commits.len(), mem.commits.len());
+    }
+
+    // A write in place would have changed the linked file too.
+    assert_eq!(fs::read_to_string(link)?, before);
+
+    cleanup(path);
+    let _ = fs::remove_file(link);
+    Ok(())
+}
+
+#[test]
+fn failed_save_keeps_the_previous_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_failed_save.myo";
+    cleanup(path);
+    let _ = fs::remove_dir(format!("{}.tmp", path));
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    let before = fs::read_to_string(path)?;
+
+    // Nothing can be created at the temporary path.
+    fs::create_dir(format!("{}.tmp", path))?;
+    mem.set(id, "goal", Value::Int(1))?;
+    mem.commit(None)?;
+    let err = storage::save(path, &mem).unwrap_err();
+    assert!(matches!(err, MyosotisError::WriteFailed(..)));
+    assert_eq!(fs::read_to_string(path)?, before);
+
+    fs::remove_dir(format!("{}.tmp", path))?;
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use myosotis::maintenance::{self, Severity};
+use myosotis::node::Value;
+use myosotis::{Memory, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn build(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Goal");
+    mem.set(a, "goal", Value::Ref(b))?;
+    mem.commit(None)?;
+    mem.delete_node(b)?;
+    mem.commit(None)?;
+    for i in 0..50 {
+        mem.set(a, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+    Ok(())
+}
+
+#[test]
+fn fsck_reports_warnings_on_valid_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_fsck_valid.myo";
+    cleanup(path);
+    build(path)?;
+
+    let report = maintenance::fsck(path, true)?;
+    assert!(!report.has_errors());
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.issues[0].severity, Severity::Warning);
+    assert_eq!(report.issues[0].check, "dangling-ref");
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn fsck_collects_multiple_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_fsck_corrupt.myo";
+    cleanup(path);
+    build(path)?;
+
+    let mut root: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    root["next_node_id"] = 2.into();
+    root["checkpoints"][0]["commit_id"] = 999.into();
+    root["commits"][1]["mutations"] =
+        serde_json::json!([{ "CreateNode": { "id": 1, "ty": "Agent" } }]);
+    fs::write(path, serde_json::to_string(&root)?)?;
+
+    let report = maintenance::fsck(path, true)?;
+    assert!(report.has_errors());
+    let checks: Vec<&str> = report.issues.iter().map(|i| i.check).collect();
+    for expected in [
+        "validate",
+        "duplicate-id",
+        "orphan-checkpoint",
+        "next-node-id",
+    ] {
+        assert!(
+            checks.contains(&expected),
+            "missing {} in {:?}",
+            expected,
+            checks
+        );
+    }
+
+    fs::write(path, "not json")?;
+    let report = maintenance::fsck(path, false)?;
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.issues[0].check, "read");
+
+    cleanup(path);
+    Ok(())
+}
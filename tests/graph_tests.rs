@@ -0,0 +1,73 @@
+use myosotis::graph::RefIndex;
+use myosotis::node::{NodeId, Value};
+use myosotis::{Memory, MyosotisError};
+
+/// a -> b -> c, d -> c, e alone; `f` refers to `a` by alias.
+fn build() -> Result<(Memory, [NodeId; 6]), MyosotisError> {
+    let mut mem = Memory::new();
+    let a = mem.create_with_alias("Memory", "a")?;
+    let b = mem.create("Memory");
+    let c = mem.create("Memory");
+    let d = mem.create("Memory");
+    let e = mem.create("Memory");
+    let f = mem.create("Memory");
+    mem.set(a, "next", Value::Ref(b))?;
+    mem.set(b, "links", Value::List(vec![Value::Ref(c), Value::Int(1)]))?;
+    mem.set(d, "about", Value::Ref(c))?;
+    mem.set(f, "see", Value::AliasRef("a".to_string()))?;
+    mem.commit(Some("graph".to_string()))?;
+    Ok((mem, [a, b, c, d, e, f]))
+}
+
+#[test]
+fn ref_index_has_both_directions() -> Result<(), Box<dyn std::error::Error>> {
+    let (mem, [a, b, c, d, _, f]) = build()?;
+    let index = RefIndex::build(mem.head_state());
+    assert_eq!(index.refs_from(a), &[b]);
+    assert_eq!(index.refs_to(c), &[b, d]);
+    assert_eq!(index.refs_to(a), &[f]);
+    assert!(index.refs_from(c).is_empty());
+    Ok(())
+}
+
+#[test]
+fn reachable_follows_refs_forwards() -> Result<(), Box<dyn std::error::Error>> {
+    let (mem, [a, b, c, _, e, f]) = build()?;
+    assert_eq!(mem.reachable_from(a)?, vec![b, c]);
+    assert_eq!(mem.reachable_from(f)?, vec![a, b, c]);
+    assert!(mem.reachable_from(c)?.is_empty());
+    assert!(mem.reachable_from(e)?.is_empty());
+    assert!(matches!(
+        mem.reachable_from(99),
+        Err(MyosotisError::NodeNotFound(99))
+    ));
+    Ok(())
+}
+
+#[test]
+fn path_between_uses_refs_in_both_directions() -> Result<(), Box<dyn std::error::Error>> {
+    let (mem, [a, b, c, d, e, f]) = build()?;
+    assert_eq!(mem.path_between(a, d)?, Some(vec![a, b, c, d]));
+    assert_eq!(mem.path_between(d, f)?, Some(vec![d, c, b, a, f]));
+    assert_eq!(mem.path_between(b, b)?, Some(vec![b]));
+    assert_eq!(mem.path_between(a, e)?, None);
+    Ok(())
+}
+
+#[test]
+fn queries_at_a_historical_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut mem, [a, b, c, d, _, _]) = build()?;
+    let before = mem.commits.last().map(|c| c.id).ok_or("no commit")?;
+    mem.delete_node(b)?;
+    mem.commit(Some("cut".to_string()))?;
+
+    assert_eq!(mem.path_between(a, d)?, None);
+    assert!(mem.reachable_from(a)?.is_empty());
+    assert_eq!(mem.path_between_at(a, d, before)?, Some(vec![a, b, c, d]));
+    assert_eq!(mem.reachable_from_at(a, before)?, vec![b, c]);
+    assert!(matches!(
+        mem.path_between(a, b),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+    Ok(())
+}
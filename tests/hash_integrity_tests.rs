@@ -1,5 +1,6 @@
+use myosotis::commit::Mutation;
 use myosotis::node::Value;
-use myosotis::{Memory, storage};
+use myosotis::{Memory, MyosotisError, storage};
 use std::fs;
 
 fn cleanup(path: &str) {
@@ -50,12 +51,11 @@ fn parent_hash_corruption_detected() -> Result<(), Box<dyn std::error::Error>> {
 
     // Tamper with parent_hash of second commit in the saved JSON
     let mut data: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
-    if let Some(commits) = data.get_mut("commits").and_then(|c| c.as_array_mut()) {
-        if commits.len() >= 2 {
-            if let Some(obj) = commits[1].as_object_mut() {
-                obj.insert("parent_hash".to_string(), serde_json::Value::Null);
-            }
-        }
+    if let Some(commits) = data.get_mut("commits").and_then(|c| c.as_array_mut())
+        && commits.len() >= 2
+        && let Some(obj) = commits[1].as_object_mut()
+    {
+        obj.insert("parent_hash".to_string(), serde_json::Value::Null);
     }
 
     std::fs::write(path, serde_json::to_string_pretty(&data)?)?;
@@ -119,3 +119,40 @@ fn cross_restart_hash_stability() -> Result<(), Box<dyn std::error::Error>> {
     cleanup(path);
     Ok(())
 }
+
+#[test]
+fn verify_head_checks_latest_snapshot_and_tail() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(Some("c1".to_string()))?;
+    for i in 0..55 {
+        mem.set(id, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    assert_eq!(mem.checkpoints.len(), 1);
+    mem.set(id, "pending", Value::Bool(true))?;
+
+    let head_hash = mem.verify_head()?;
+    assert_eq!(head_hash, Memory::compute_state_hash(mem.head_state()));
+
+    let mut drifted = mem.clone();
+    if let Some(last) = drifted.commits.last_mut() {
+        last.mutations = vec![Mutation::SetField {
+            id,
+            key: "n".to_string(),
+            value: Value::Int(-1),
+        }];
+    }
+    assert!(matches!(
+        drifted.verify_head(),
+        Err(MyosotisError::HeadStateMismatch)
+    ));
+
+    let mut corrupt = mem.clone();
+    corrupt.checkpoints[0].state_hash = [0u8; 32];
+    assert!(matches!(
+        corrupt.verify_head(),
+        Err(MyosotisError::HeadSnapshotHashMismatch)
+    ));
+    Ok(())
+}
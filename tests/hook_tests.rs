@@ -0,0 +1,97 @@
+use myosotis::commit::Mutation;
+use myosotis::node::{Node, NodeId, Value};
+use myosotis::{Memory, MyosotisError};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn agents_keep_names(
+    _mutations: &[Mutation],
+    state: &HashMap<NodeId, Node>,
+) -> Result<(), MyosotisError> {
+    match state
+        .values()
+        .find(|n| !n.deleted && n.ty == "Agent" && n.get_str("name").is_none())
+    {
+        Some(node) => Err(MyosotisError::PolicyViolation(format!(
+            "agent {} has no name",
+            node.id
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[test]
+fn hook_rejects_commit_and_keeps_pending() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    mem.add_precommit_hook(agents_keep_names);
+
+    let id = mem.create("Agent");
+    mem.set(id, "name", Value::Str("Ada".to_string()))?;
+    mem.commit(Some("named".to_string()))?;
+
+    mem.delete_field(id, "name")?;
+    let err = mem.commit(Some("unnamed".to_string())).unwrap_err();
+    assert!(matches!(err, MyosotisError::PolicyViolation(ref m) if m.contains("no name")));
+    assert_eq!(mem.commits.len(), 1);
+    assert_eq!(mem.pending_mutations().len(), 1);
+
+    assert!(matches!(
+        mem.dry_run_commit(None, &[]),
+        Err(MyosotisError::PolicyViolation(_))
+    ));
+
+    mem.discard_pending()?;
+    mem.set(id, "role", Value::Str("planner".to_string()))?;
+    mem.commit(Some("role".to_string()))?;
+    assert_eq!(mem.commits.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn hooks_see_mutations_and_run_in_order() -> Result<(), Box<dyn std::error::Error>> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut mem = Memory::new();
+
+    let seen = calls.clone();
+    mem.add_precommit_hook(move |mutations, _| {
+        seen.fetch_add(mutations.len(), Ordering::SeqCst);
+        Ok(())
+    });
+    mem.add_precommit_hook(|mutations, _| {
+        if mutations.iter().any(|m| m.kind() == "delete_node") {
+            Err(MyosotisError::PolicyViolation(
+                "nodes are never deleted".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    });
+
+    let id = mem.create("Note");
+    mem.set(id, "text", Value::Str("hi".to_string()))?;
+    mem.commit(None)?;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    mem.delete_node(id)?;
+    assert!(mem.commit(None).is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    mem.clear_precommit_hooks();
+    mem.commit(None)?;
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    Ok(())
+}
+
+#[test]
+fn hooks_are_kept_on_clone() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    mem.add_precommit_hook(agents_keep_names);
+    let mut copy = mem.clone();
+    copy.create("Agent");
+    assert!(matches!(
+        copy.commit(None),
+        Err(MyosotisError::PolicyViolation(_))
+    ));
+    Ok(())
+}
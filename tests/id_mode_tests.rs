@@ -0,0 +1,155 @@
+use myosotis::canonical::{self, Canonicalization};
+use myosotis::node::{IdMode, Node, NodeId, Value};
+use myosotis::{Memory, MyosotisError, export, render, storage};
+use std::collections::HashMap;
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn uuid_memory() -> Memory {
+    let mut mem = Memory::new();
+    mem.id_mode = IdMode::Uuid;
+    mem
+}
+
+#[test]
+fn uuid_mode_allocates_random_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = uuid_memory();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.commit(Some("create".to_string()))?;
+
+    assert_ne!(a, b);
+    assert!(a > u64::MAX as NodeId && b > u64::MAX as NodeId);
+    assert_eq!(mem.next_node_id, 1);
+    mem.validate()?;
+    Ok(())
+}
+
+#[test]
+fn independent_files_do_not_collide() {
+    let mut left = uuid_memory();
+    let mut right = uuid_memory();
+    let left_ids: Vec<NodeId> = (0..10).map(|_| left.create("Agent")).collect();
+    let right_ids: Vec<NodeId> = (0..10).map(|_| right.create("Agent")).collect();
+    assert!(left_ids.iter().all(|id| !right_ids.contains(id)));
+}
+
+#[test]
+fn uuid_mode_survives_save_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_uuid_ids_roundtrip.myo";
+    cleanup(path);
+
+    let mut mem = uuid_memory();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.set(b, "peer", Value::Ref(a))?;
+    mem.commit(Some("create".to_string()))?;
+    storage::save(path, &mem)?;
+    let data = fs::read_to_string(path)?;
+    assert!(data.contains(r#""id_mode": "uuid""#));
+    assert!(data.contains(&format!(r#""Ref": "{}""#, a)));
+    assert!(!data.contains(&format!(": {}", a)));
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.id_mode, IdMode::Uuid);
+    assert_eq!(loaded.get_field(b, "peer")?, &Value::Ref(a));
+    assert_eq!(
+        Memory::compute_state_hash(loaded.head_state()),
+        Memory::compute_state_hash(mem.head_state())
+    );
+    let lazy = storage::open_lazy(path)?;
+    assert_eq!(lazy.id_mode, IdMode::Uuid);
+    lazy.validate()?;
+
+    let mut loaded = loaded;
+    let c = loaded.create("Agent");
+    assert!(c > u64::MAX as NodeId);
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn sequential_mode_omits_id_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_sequential_ids_header.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    assert_eq!(mem.create("Agent"), 1);
+    mem.commit(Some("create".to_string()))?;
+    storage::save(path, &mem)?;
+    assert!(!fs::read_to_string(path)?.contains("id_mode"));
+    assert_eq!(storage::load(path)?.id_mode, IdMode::Sequential);
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn wide_ids_hash_distinctly() {
+    let narrow = Memory::with_canonicalization(Canonicalization::Binary);
+    let mut a = narrow.clone();
+    a.id_mode = IdMode::Uuid;
+    let id = a.create("Agent");
+
+    let truncated: HashMap<NodeId, Node> = a
+        .head_state()
+        .values()
+        .map(|node| {
+            let mut node = node.clone();
+            node.id = id as u64 as NodeId;
+            (node.id, node)
+        })
+        .collect();
+    assert_ne!(
+        narrow.hash_state(a.head_state()),
+        narrow.hash_state(&truncated)
+    );
+
+    let jcs = String::from_utf8(canonical::jcs_state_bytes(a.head_state())).unwrap_or_default();
+    assert!(jcs.contains(&format!(r#""id":"{}""#, id)));
+}
+
+#[test]
+fn wide_ids_survive_json_values() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = uuid_memory();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.set(b, "peer", Value::Ref(a))?;
+    mem.commit(Some("create".to_string()))?;
+
+    let mut out = Vec::new();
+    export::git_fast_import(&mem, &mut out)?;
+    assert!(String::from_utf8(out)?.contains(&format!(r#""id": "{}""#, b)));
+    assert_eq!(
+        render::to_json(&Value::Ref(a), None),
+        serde_json::json!({ "ref": a.to_string() })
+    );
+    let value: Value = serde_json::from_value(serde_json::to_value(Value::Ref(a))?)?;
+    assert_eq!(value, Value::Ref(a));
+    Ok(())
+}
+
+#[test]
+fn version_1_files_refuse_uuid_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_uuid_ids_v1.myo";
+    cleanup(path);
+
+    let mut mem = uuid_memory();
+    mem.create("Agent");
+    mem.commit(Some("create".to_string()))?;
+    storage::save(path, &mem)?;
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    json["format_version"] = serde_json::json!(1);
+    fs::write(path, serde_json::to_string_pretty(&json)?)?;
+    assert!(matches!(
+        storage::load(path),
+        Err(MyosotisError::MalformedFileStructure)
+    ));
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use myosotis::blob::ValueLimits;
+use myosotis::import::{self, RemapPolicy};
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError};
+
+#[test]
+fn json_import_creates_nodes_in_order() -> Result<(), Box<dyn std::error::Error>> {
+    let data = r#"[
+        {"name": "Iris", "age": 3, "tags": ["a", "b"], "skip": null},
+        {"name": "Rowan", "score": 1.5, "active": true}
+    ]"#;
+
+    let mut mem = Memory::new();
+    let rows = import::rows_from_json(data)?;
+    let ids = import::stage_rows(&mut mem, "Agent", rows)?;
+    mem.commit(Some("import".to_string()))?;
+
+    assert_eq!(ids, vec![1, 2]);
+    assert_eq!(mem.commits.len(), 1);
+    let first = mem.head_state().get(&1).ok_or("missing node")?;
+    assert_eq!(first.ty, "Agent");
+    assert_eq!(first.fields.get("age"), Some(&Value::Int(3)));
+    assert!(!first.fields.contains_key("skip"));
+    let second = mem.head_state().get(&2).ok_or("missing node")?;
+    assert_eq!(second.fields.get("score"), Some(&Value::Float(1.5)));
+    Ok(())
+}
+
+#[test]
+fn csv_import_handles_quoting() -> Result<(), Box<dyn std::error::Error>> {
+    let data = "name,goal\r\nIris,\"Explore, then \"\"rest\"\"\"\r\nRowan,\n";
+    let rows = import::rows_from_csv(data)?;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0].get("goal"),
+        Some(&Value::Str("Explore, then \"rest\"".to_string()))
+    );
+    assert!(!rows[1].contains_key("goal"));
+    Ok(())
+}
+
+#[test]
+fn import_is_deterministic() -> Result<(), Box<dyn std::error::Error>> {
+    let data = r#"[{"b": 1, "a": 2, "c": 3}, {"z": "x"}]"#;
+
+    let mut first = Memory::new();
+    import::stage_rows(&mut first, "Row", import::rows_from_json(data)?)?;
+    first.commit(Some("import".to_string()))?;
+
+    let mut second = Memory::new();
+    import::stage_rows(&mut second, "Row", import::rows_from_json(data)?)?;
+    second.commit(Some("import".to_string()))?;
+
+    assert_eq!(first.commits[0].hash, second.commits[0].hash);
+    Ok(())
+}
+
+#[test]
+fn malformed_import_rejected() {
+    let err = import::rows_from_json(r#"{"name": "x"}"#).expect_err("object root should fail");
+    assert!(matches!(err, MyosotisError::InvalidInput(_)));
+
+    let err = import::rows_from_csv("a,b\n1\n").expect_err("ragged row should fail");
+    assert!(matches!(err, MyosotisError::InvalidInput(_)));
+}
+
+#[test]
+fn nested_nulls_rejected() {
+    for data in [
+        r#"[{"tags": ["a", null]}]"#,
+        r#"[{"meta": {"owner": null}}]"#,
+    ] {
+        let err = import::rows_from_json(data).expect_err("nested null should fail");
+        assert!(matches!(err, MyosotisError::InvalidInput(_)));
+    }
+}
+
+#[test]
+fn failed_import_stages_nothing() -> Result<(), Box<dyn std::error::Error>> {
+    let data = r#"[
+        {"name": "Iris"},
+        {"name": "Rowan"},
+        {"name": "a name far longer than the limit allows"}
+    ]"#;
+
+    let mut mem = Memory::new();
+    let kept = mem.create("Note");
+    mem.set_value_limits(ValueLimits {
+        max_bytes: Some(16),
+        ..ValueLimits::default()
+    });
+    let rows = import::rows_from_json(data)?;
+    let err = import::stage_rows(&mut mem, "Agent", rows).expect_err("long value should fail");
+    assert!(matches!(err, MyosotisError::ValueTooLarge(..)));
+
+    assert_eq!(mem.pending_summary().mutations, 1);
+    assert_eq!(mem.head_state().len(), 1);
+    assert!(mem.head_state().contains_key(&kept));
+    Ok(())
+}
+
+fn source_graph() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut other = Memory::new();
+    let unrelated = other.create("Note");
+    let agent = other.create("Agent");
+    let task = other.create("Task");
+    let tool = other.create("Tool");
+    other.set(unrelated, "text", Value::Str("skip me".to_string()))?;
+    other.set(agent, "task", Value::Ref(task))?;
+    other.set(task, "tools", Value::List(vec![Value::Ref(tool)]))?;
+    other.set(tool, "owner", Value::Ref(agent))?;
+    other.set_alias(agent, Some("agent:iris"))?;
+    other.set_namespace(tool, Some("tools"))?;
+    other.commit(None)?;
+    Ok(other)
+}
+
+#[test]
+fn import_subgraph_remaps_refs() -> Result<(), Box<dyn std::error::Error>> {
+    let other = source_graph()?;
+    let mut mem = Memory::new();
+    mem.create("Existing");
+    mem.commit(None)?;
+
+    let mapping = mem.import_subgraph(&other, &[2], RemapPolicy::Fresh)?;
+    mem.commit(Some("import".to_string()))?;
+
+    assert_eq!(
+        mapping.into_iter().collect::<Vec<_>>(),
+        vec![(2, 2), (3, 3), (4, 4)]
+    );
+    assert_eq!(mem.commits.len(), 2);
+    assert_eq!(mem.head_state().len(), 4);
+    assert_eq!(mem.resolve("agent:iris"), Some(2));
+    let tool = mem.head_state().get(&4).ok_or("missing tool")?;
+    assert_eq!(tool.fields.get("owner"), Some(&Value::Ref(2)));
+    assert_eq!(tool.namespace.as_deref(), Some("tools"));
+
+    let mut again = Memory::new();
+    again.create("Existing");
+    again.create("Existing");
+    let mapping = again.import_subgraph(&other, &[4], RemapPolicy::Fresh)?;
+    assert_eq!(
+        mapping.into_iter().collect::<Vec<_>>(),
+        vec![(2, 4), (3, 5), (4, 3)]
+    );
+    let task = again.head_state().get(&5).ok_or("missing task")?;
+    assert_eq!(
+        task.fields.get("tools"),
+        Some(&Value::List(vec![Value::Ref(3)]))
+    );
+    Ok(())
+}
+
+#[test]
+fn import_subgraph_keeps_free_ids_and_checks_first() -> Result<(), Box<dyn std::error::Error>> {
+    let mut other = source_graph()?;
+    let mut mem = Memory::new();
+    mem.create("Existing");
+    mem.create("Existing");
+    let mapping = mem.import_subgraph(&other, &[2], RemapPolicy::KeepIfFree)?;
+    assert_eq!(
+        mapping.into_iter().collect::<Vec<_>>(),
+        vec![(2, 5), (3, 3), (4, 4)]
+    );
+    assert_eq!(mem.create("Next"), 6);
+
+    let pending = mem.pending_mutations().len();
+    assert!(matches!(
+        mem.import_subgraph(&other, &[2], RemapPolicy::Fresh),
+        Err(MyosotisError::AliasTaken(_, 5))
+    ));
+    assert_eq!(mem.pending_mutations().len(), pending);
+
+    other.delete_node(4)?;
+    let mut fresh = Memory::new();
+    assert!(matches!(
+        fresh.import_subgraph(&other, &[3], RemapPolicy::Fresh),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        fresh.import_subgraph(&other, &[4], RemapPolicy::Fresh),
+        Err(MyosotisError::NodeDeleted(4))
+    ));
+    assert!(fresh.pending_mutations().is_empty());
+    Ok(())
+}
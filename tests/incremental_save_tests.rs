@@ -0,0 +1,109 @@
+use myosotis::memory::CHECKPOINT_INTERVAL;
+use myosotis::node::Value;
+use myosotis::{Memory, storage};
+use std::fs;
+use std::io::Write;
+use std::time::Duration;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(storage::segment_path(path));
+}
+
+fn add_commits(mem: &mut Memory, n: usize) -> Result<(), Box<dyn std::error::Error>> {
+    for _ in 0..n {
+        let id = mem.create("Note");
+        mem.set(id, "n", Value::Int(mem.commits.len() as i64))?;
+        mem.commit(None)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn incremental_save_appends_without_rewriting() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_incremental_append.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    add_commits(&mut mem, 3)?;
+    storage::save_incremental(path, &mem)?;
+    let base = fs::read(path)?;
+
+    // Crossing a checkpoint boundary appends the checkpoint as well.
+    for _ in 0..3 {
+        add_commits(&mut mem, CHECKPOINT_INTERVAL / 2)?;
+        storage::save_incremental(path, &mem)?;
+        assert_eq!(fs::read(path)?, base);
+    }
+    assert!(!mem.checkpoints.is_empty());
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.commits.len(), mem.commits.len());
+    assert_eq!(loaded.checkpoints.len(), mem.checkpoints.len());
+    assert_eq!(loaded.head_state(), mem.head_state());
+    assert_eq!(loaded.next_node_id, mem.next_node_id);
+
+    // A full save folds the log back into the file.
+    storage::save(path, &loaded)?;
+    assert!(!storage::exists(&storage::segment_path(path)));
+    assert_eq!(storage::load(path)?.commits.len(), mem.commits.len());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn torn_segment_append_is_dropped() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_incremental_torn.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    add_commits(&mut mem, 2)?;
+    storage::save_incremental(path, &mem)?;
+    add_commits(&mut mem, 1)?;
+    storage::save_incremental(path, &mem)?;
+
+    let mut seg = fs::OpenOptions::new()
+        .append(true)
+        .open(storage::segment_path(path))?;
+    seg.write_all(b"{\"record\":{\"commits\":[")?;
+    drop(seg);
+    assert_eq!(storage::load(path)?.commits.len(), 3);
+
+    add_commits(&mut mem, 1)?;
+    storage::save_incremental(path, &mem)?;
+    assert_eq!(storage::load(path)?.commits.len(), 4);
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn rewrites_and_setting_changes_fall_back_to_full_save() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_incremental_fallback.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    add_commits(&mut mem, 4)?;
+    storage::save_incremental(path, &mem)?;
+    let base = fs::read(path)?;
+
+    let id = mem.create("Note");
+    mem.commit(None)?;
+    mem.lock_node(id, "agent", Duration::from_secs(60))?;
+    storage::save_incremental(path, &mem)?;
+    assert_ne!(fs::read(path)?, base);
+    assert_eq!(storage::load(path)?.locks.len(), 1);
+
+    // Compaction rewrites the file; the old segment log no longer applies.
+    add_commits(&mut mem, 1)?;
+    storage::save_incremental(path, &mem)?;
+    storage::compact(path, Some(3))?;
+    let compacted = storage::load(path)?;
+    assert_eq!(compacted.head_state(), mem.head_state());
+    storage::save_incremental(path, &compacted)?;
+    assert_eq!(storage::load(path)?.commits.len(), compacted.commits.len());
+
+    cleanup(path);
+    Ok(())
+}
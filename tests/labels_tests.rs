@@ -0,0 +1,76 @@
+use myosotis::canonical::Canonicalization;
+use myosotis::node::{Node, NodeId, Value};
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn ids(nodes: Vec<&Node>) -> Vec<NodeId> {
+    nodes.iter().map(|n| n.id).collect()
+}
+
+#[test]
+fn labels_categorize_without_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Note");
+    let b = mem.create("Note");
+    mem.set(a, "text", Value::Str("remember".to_string()))?;
+    mem.add_label(a, "important")?;
+    mem.add_label(b, "important")?;
+    mem.add_label(b, "draft")?;
+    mem.commit(None)?;
+
+    assert!(mem.has_label(a, "important"));
+    assert_eq!(mem.head_state()[&a].fields.len(), 1);
+    assert_eq!(ids(mem.nodes_with_label("important")), vec![a, b]);
+    assert_eq!(mem.labels(), vec!["draft", "important"]);
+
+    assert!(matches!(
+        mem.add_label(a, "important"),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        mem.remove_label(a, "draft"),
+        Err(MyosotisError::LabelNotFound(_, _))
+    ));
+
+    mem.remove_label(b, "draft")?;
+    mem.delete_node(a)?;
+    mem.commit(None)?;
+    assert_eq!(ids(mem.nodes_with_label("important")), vec![b]);
+    assert_eq!(mem.labels(), vec!["important"]);
+
+    // Discarding a pending label brings the index back in line.
+    mem.add_label(b, "draft")?;
+    mem.discard_pending()?;
+    assert!(mem.nodes_with_label("draft").is_empty());
+    Ok(())
+}
+
+#[test]
+fn labels_are_hashed_and_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_labels_round_trip.myo";
+    cleanup(path);
+
+    for canonicalization in [Canonicalization::Binary, Canonicalization::Jcs] {
+        let mut mem = Memory::with_canonicalization(canonicalization);
+        let id = mem.create("Note");
+        mem.commit(None)?;
+        let unlabeled = mem.hash_state(mem.head_state());
+        mem.add_label(id, "important")?;
+        mem.commit(None)?;
+        assert_ne!(mem.hash_state(mem.head_state()), unlabeled);
+
+        let replayed = Memory::replay(&mem.commits)?;
+        assert!(replayed[&id].labels.contains("important"));
+
+        storage::save(path, &mem)?;
+        let loaded = storage::load(path)?;
+        assert_eq!(loaded.head_state(), mem.head_state());
+        assert_eq!(ids(loaded.nodes_with_label("important")), vec![id]);
+        cleanup(path);
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use myosotis::node::Value;
+use myosotis::{Memory, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn build(path: &str) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    for i in 0..120 {
+        mem.set(id, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+    Ok(mem)
+}
+
+#[test]
+fn lazy_head_matches_eager_load() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_lazy_head.myo";
+    cleanup(path);
+    let mem = build(path)?;
+
+    let lazy = storage::open_lazy(path)?;
+    assert_eq!(lazy.head_state(), mem.head_state());
+    assert_eq!(lazy.commits().len(), mem.commits.len());
+    assert_eq!(lazy.next_node_id, mem.next_node_id);
+
+    for commit_id in [1, 50, 75, 121] {
+        assert_eq!(
+            lazy.state_at_commit(commit_id)?,
+            mem.state_at_commit(commit_id)?
+        );
+    }
+    assert!(lazy.state_at_commit(500).is_err());
+
+    lazy.validate()?;
+    assert_eq!(lazy.to_memory()?.head_state(), mem.head_state());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn lazy_open_skips_old_history_but_validate_catches_it() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_lazy_defer.myo";
+    cleanup(path);
+    let mem = build(path)?;
+
+    // Corrupt a mutation body before the latest checkpoint (commit 100).
+    let mut root: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    root["commits"][10]["mutations"] = serde_json::json!("garbage");
+    fs::write(path, serde_json::to_string(&root)?)?;
+
+    let lazy = storage::open_lazy(path)?;
+    assert_eq!(lazy.head_state(), mem.head_state());
+    assert!(lazy.state_at_commit(20).is_err());
+    assert!(lazy.validate().is_err());
+    assert!(storage::load(path).is_err());
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use myosotis::node::Value;
+use myosotis::storage::{self, LoadLimits, LoadMode};
+use myosotis::{Memory, MyosotisError};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+/// The bound a load went over, as `(limit, actual, max)`.
+type Exceeded = Option<(&'static str, u64, u64)>;
+
+fn exceeded(path: &str, limits: LoadLimits) -> Result<Exceeded, Box<dyn std::error::Error>> {
+    match storage::load_with_limits(path, LoadMode::Strict, &limits) {
+        Ok(_) => Ok(None),
+        Err(MyosotisError::LoadLimitExceeded(limit, actual, max)) => Ok(Some((limit, actual, max))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[test]
+fn load_limits_refuse_oversized_files() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_load_limits.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    for i in 0..3 {
+        let id = mem.create("Agent");
+        mem.set(id, "note", Value::Str("x".repeat(10 * (i + 1))))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+    let size = fs::metadata(path)?.len();
+
+    assert_eq!(exceeded(path, LoadLimits::default())?, None);
+    let generous = LoadLimits {
+        max_file_bytes: Some(size),
+        max_commits: Some(3),
+        max_nodes: Some(3),
+        max_value_bytes: Some(50),
+    };
+    assert_eq!(exceeded(path, generous)?, None);
+
+    let limits = LoadLimits {
+        max_file_bytes: Some(size - 1),
+        ..Default::default()
+    };
+    assert_eq!(
+        exceeded(path, limits)?,
+        Some(("file bytes", size, size - 1))
+    );
+    let limits = LoadLimits {
+        max_commits: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(exceeded(path, limits)?, Some(("commits", 3, 2)));
+    let limits = LoadLimits {
+        max_nodes: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(exceeded(path, limits)?, Some(("nodes", 3, 2)));
+    // {"Str":"xxx..."} is 10 bytes plus the string.
+    let limits = LoadLimits {
+        max_value_bytes: Some(30),
+        ..Default::default()
+    };
+    assert_eq!(exceeded(path, limits)?, Some(("value bytes", 40, 30)));
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+use std::time::Duration;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn locks_block_other_owners() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+
+    mem.lock_node(id, "alice", Duration::from_secs(60))?;
+    assert!(matches!(
+        mem.lock_node(id, "bob", Duration::from_secs(60)),
+        Err(MyosotisError::NodeLocked(_, ref owner)) if owner == "alice"
+    ));
+
+    mem.act_as(Some("bob"));
+    assert!(matches!(
+        mem.set(id, "goal", Value::Str("x".to_string())),
+        Err(MyosotisError::NodeLocked(..))
+    ));
+    assert!(matches!(
+        mem.delete_node(id),
+        Err(MyosotisError::NodeLocked(..))
+    ));
+    assert!(mem.unlock_node(id, "bob").is_err());
+
+    mem.act_as(Some("alice"));
+    mem.set(id, "goal", Value::Str("x".to_string()))?;
+    mem.unlock_node(id, "alice")?;
+
+    mem.act_as(Some("bob"));
+    mem.set(id, "goal", Value::Str("y".to_string()))?;
+    Ok(())
+}
+
+#[test]
+fn expired_locks_are_ignored() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+
+    mem.lock_node(id, "alice", Duration::ZERO)?;
+    assert!(mem.active_lock(id).is_none());
+    mem.act_as(Some("bob"));
+    mem.set(id, "goal", Value::Str("x".to_string()))?;
+    mem.lock_node(id, "bob", Duration::from_secs(60))?;
+    assert_eq!(mem.locks.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn locks_persist_outside_hash_chain() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_locks_persist.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    let head_hash = mem.commits[0].hash;
+    mem.lock_node(id, "alice", Duration::from_secs(600))?;
+    storage::save(path, &mem)?;
+
+    let loaded = storage::load(path)?;
+    assert_eq!(
+        loaded.active_lock(id).map(|l| l.owner.as_str()),
+        Some("alice")
+    );
+    assert_eq!(loaded.commits[0].hash, head_hash);
+
+    cleanup(path);
+    Ok(())
+}
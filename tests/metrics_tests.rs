@@ -0,0 +1,46 @@
+use myosotis::node::Value;
+use myosotis::{Memory, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+#[test]
+fn metrics_count_commits_loads_and_saves() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_metrics.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    assert_eq!(mem.metrics(), Default::default());
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    mem.commit_allow_empty(Some("marker".to_string()))?;
+    let metrics = mem.metrics();
+    assert_eq!((metrics.commits, metrics.mutations_applied), (2, 2));
+    assert_eq!(metrics.load_time, None);
+
+    storage::save(path, &mem)?;
+    let size = fs::metadata(path)?.len();
+    assert_eq!(mem.metrics().file_size, Some(size));
+
+    let mut loaded = storage::load(path)?;
+    let metrics = loaded.metrics();
+    assert_eq!(metrics.commits, 0);
+    assert_eq!(metrics.file_size, Some(size));
+    let (load_time, validation_time) = (
+        metrics.load_time.ok_or("no load time")?,
+        metrics.validation_time.ok_or("no validation time")?,
+    );
+    assert!(validation_time <= load_time);
+
+    loaded.set(id, "goal", Value::Str("rest".to_string()))?;
+    loaded.commit(None)?;
+    assert_eq!(loaded.clone().metrics(), loaded.metrics());
+    assert_eq!(loaded.metrics().commits, 1);
+
+    cleanup(path);
+    Ok(())
+}
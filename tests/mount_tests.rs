@@ -0,0 +1,74 @@
+use myosotis::Memory;
+use myosotis::mount::{MountEntry, MountTree};
+use myosotis::node::Value;
+
+fn file_contents(tree: &mut MountTree, path: &str) -> Option<String> {
+    let ino = tree.resolve(path).ok()??;
+    match tree.get(ino).ok()?? {
+        MountEntry::File { data } => String::from_utf8(data.clone()).ok(),
+        MountEntry::Dir { .. } => None,
+    }
+}
+
+#[test]
+fn mount_tree_exposes_nodes_and_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.set(id, "a/b", Value::Int(1))?;
+    mem.commit(Some("c1".to_string()))?;
+
+    let mut tree = MountTree::build(&mem)?;
+    assert_eq!(
+        file_contents(&mut tree, &format!("{}/.type", id)).as_deref(),
+        Some("Agent\n")
+    );
+    assert!(
+        file_contents(&mut tree, &format!("{}/goal", id))
+            .ok_or("missing goal")?
+            .contains("Explore")
+    );
+    assert!(tree.resolve(&format!("{}/a%2Fb", id))?.is_some());
+    assert!(tree.resolve("999")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn mount_tree_history_and_tombstones() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("c1".to_string()))?;
+    mem.delete_node(id)?;
+    mem.commit(Some("c2".to_string()))?;
+
+    let mut tree = MountTree::build(&mem)?;
+    assert!(tree.resolve(&id.to_string())?.is_none());
+    assert!(tree.resolve(&format!(".history/1/{}/goal", id))?.is_some());
+    assert!(tree.resolve(&format!(".history/2/{}", id))?.is_none());
+    Ok(())
+}
+
+#[test]
+fn mount_tree_escapes_dot_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, ".type", Value::Str("shadow".to_string()))?;
+    mem.set(id, "..", Value::Int(1))?;
+    mem.commit(Some("c1".to_string()))?;
+
+    let mut tree = MountTree::build(&mem)?;
+    for dir in [id.to_string(), format!(".history/1/{}", id)] {
+        assert_eq!(
+            file_contents(&mut tree, &format!("{}/.type", dir)).as_deref(),
+            Some("Agent\n")
+        );
+        assert!(
+            file_contents(&mut tree, &format!("{}/%2Etype", dir))
+                .ok_or("missing .type field")?
+                .contains("shadow")
+        );
+        assert!(tree.resolve(&format!("{}/%2E.", dir))?.is_some());
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use myosotis::commit::Mutation;
-use myosotis::node::Value;
-use myosotis::{Memory, storage};
+use myosotis::memory::{CHECKPOINT_INTERVAL, PendingSummary};
+use myosotis::node::{NodeId, Value};
+use myosotis::{Memory, MyosotisError, storage};
 use std::fs;
 
 fn cleanup(path: &str) {
@@ -25,10 +26,10 @@ fn replay_determinism_and_head_equals_replay() -> Result<(), Box<dyn std::error:
 
     // head_state equals replay of commits
     let replayed = Memory::replay(&loaded.commits)?;
-    assert_eq!(loaded.head_state, replayed);
+    assert_eq!(loaded.head_state(), &replayed);
 
-    // also equal to original mem.head_state
-    assert_eq!(mem.head_state, replayed);
+    // also equal to original mem.head_state()
+    assert_eq!(mem.head_state(), &replayed);
 
     cleanup(path);
     Ok(())
@@ -52,12 +53,12 @@ fn multi_commit_evolution() -> Result<(), Box<dyn std::error::Error>> {
 
     // replay up to first commit: node exists but no 'goal'
     let state1 = Memory::replay(&loaded.commits[..1])?;
-    assert!(state1.get(&id).is_some());
+    assert!(state1.contains_key(&id));
     assert!(!state1.get(&id).unwrap().fields.contains_key("goal"));
 
     // replay up to second commit: has 'goal'
     let state2 = Memory::replay(&loaded.commits[..2])?;
-    assert!(state2.get(&id).is_some());
+    assert!(state2.contains_key(&id));
     assert!(state2.get(&id).unwrap().fields.contains_key("goal"));
 
     cleanup(path);
@@ -84,6 +85,8 @@ fn invalid_mutation_fails_on_load() -> Result<(), Box<dyn std::error::Error>> {
         parent_hash: None,
         hash,
         message: Some("bad".to_string()),
+        source: None,
+        state_hash: None,
         mutations,
     };
 
@@ -119,6 +122,8 @@ fn corrupt_parent_chain_fails_load() -> Result<(), Box<dyn std::error::Error>> {
         parent_hash: None,
         hash: h1,
         message: Some("c1".to_string()),
+        source: None,
+        state_hash: None,
         mutations: m1,
     };
 
@@ -134,6 +139,8 @@ fn corrupt_parent_chain_fails_load() -> Result<(), Box<dyn std::error::Error>> {
         parent_hash: Some(h1),
         hash: h2,
         message: Some("c2".to_string()),
+        source: None,
+        state_hash: None,
         mutations: m2,
     };
 
@@ -169,8 +176,372 @@ fn cross_restart_determinism() -> Result<(), Box<dyn std::error::Error>> {
 
     let reloaded = storage::load(path)?;
 
-    assert_eq!(loaded.head_state, reloaded.head_state);
+    assert_eq!(loaded.head_state(), reloaded.head_state());
 
     cleanup(path);
     Ok(())
 }
+
+#[test]
+fn undo_and_discard_pending() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    let committed = mem.head_state().clone();
+
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    let other = mem.create("Task");
+
+    let undone = mem.undo_last_pending()?;
+    assert!(matches!(undone, Some(Mutation::CreateNode { id, .. }) if id == other));
+    assert!(!mem.head_state().contains_key(&other));
+    assert_eq!(
+        mem.head_state()[&id].fields.get("goal"),
+        Some(&Value::Str("rest".to_string()))
+    );
+
+    assert_eq!(mem.discard_pending()?, 1);
+    assert_eq!(mem.head_state(), &committed);
+    assert!(mem.pending_mutations().is_empty());
+    assert!(mem.undo_last_pending()?.is_none());
+
+    // Discarded ids are not handed out again.
+    assert!(mem.create("Task") > other);
+    mem.commit(None)?;
+    mem.validate()?;
+    Ok(())
+}
+
+#[test]
+fn dry_run_commit_predicts_hash_without_committing() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    mem.commit(None)?;
+
+    let b = mem.next_node_id;
+    let batch = vec![
+        Mutation::CreateNode {
+            id: b,
+            ty: "Goal".to_string(),
+        },
+        Mutation::SetField {
+            id: a,
+            key: "goal".to_string(),
+            value: Value::Ref(b),
+        },
+    ];
+    let preview = mem.dry_run_commit(Some("plan".to_string()), &batch)?;
+    assert_eq!(mem.commits.len(), 1);
+    assert!(mem.pending_mutations().is_empty());
+
+    assert_eq!(mem.create("Goal"), b);
+    mem.set(a, "goal", Value::Ref(b))?;
+    mem.commit(Some("plan".to_string()))?;
+    let head = &mem.commits[1];
+    assert_eq!(preview.id, head.id);
+    assert_eq!(preview.parent_hash, head.parent_hash);
+    assert_eq!(preview.hash, head.hash);
+    assert_eq!(
+        preview.state_hash,
+        Memory::compute_state_hash(mem.head_state())
+    );
+
+    let dangling = [Mutation::SetField {
+        id: a,
+        key: "goal".to_string(),
+        value: Value::Ref(999),
+    }];
+    assert!(mem.dry_run_commit(None, &dangling).is_err());
+    let reused = [Mutation::CreateNode {
+        id: 1,
+        ty: "Agent".to_string(),
+    }];
+    assert!(mem.dry_run_commit(None, &reused).is_err());
+    assert!(mem.dry_run_commit(None, &[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn dry_run_commit_sees_mutations_staged_unchecked() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+
+    mem.stage_unchecked(Mutation::SetField {
+        id,
+        key: "goal".to_string(),
+        value: Value::Str("explore".to_string()),
+    });
+    let preview = mem.dry_run_commit(None, &[])?;
+    mem.commit(None)?;
+    assert_eq!(preview.hash, mem.commits[1].hash);
+    assert_eq!(
+        preview.state_hash,
+        Memory::compute_state_hash(mem.head_state())
+    );
+
+    // A batch `commit` would reject is rejected by the preview as well.
+    mem.stage_unchecked(Mutation::DeleteField {
+        id,
+        key: "missing".to_string(),
+    });
+    assert!(mem.dry_run_commit(None, &[]).is_err());
+    assert!(mem.commit(None).is_err());
+    Ok(())
+}
+
+#[test]
+fn empty_commits_only_when_allowed() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_empty_commit.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    assert!(mem.commit(Some("marker".to_string())).is_err());
+    mem.commit_allow_empty(Some("session start".to_string()))?;
+    let id = mem.create("Agent");
+    mem.commit_allow_empty(Some("with work".to_string()))?;
+    mem.commit_allow_empty(None)?;
+
+    assert_eq!(mem.commits.len(), 3);
+    assert!(mem.commits[0].mutations.is_empty());
+    assert_eq!(mem.commits[1].mutations.len(), 1);
+    assert!(mem.head_state().contains_key(&id));
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.commits.len(), 3);
+    assert_eq!(loaded.head_state(), mem.head_state());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn rename_field_keeps_value_and_replays() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_rename_field.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    let c = mem.create("Agent");
+    let t = mem.create("Task");
+    mem.set(a, "goal", Value::Str("explore".to_string()))?;
+    mem.set(b, "goal", Value::Str("rest".to_string()))?;
+    mem.set(t, "goal", Value::Int(1))?;
+    mem.commit(None)?;
+
+    mem.rename_field(t, "goal", "target")?;
+    assert_eq!(
+        mem.head_state()[&t].fields.get("target"),
+        Some(&Value::Int(1))
+    );
+    assert!(!mem.head_state()[&t].fields.contains_key("goal"));
+
+    assert_eq!(
+        mem.rename_field_for_type("Agent", "goal", "aim")?,
+        vec![a, b]
+    );
+    assert!(!mem.head_state()[&c].fields.contains_key("aim"));
+    assert_eq!(
+        mem.head_state()[&b].fields.get("aim"),
+        Some(&Value::Str("rest".to_string()))
+    );
+
+    assert!(matches!(
+        mem.rename_field(c, "goal", "aim"),
+        Err(MyosotisError::FieldNotFound(_))
+    ));
+    mem.set(c, "aim", Value::Bool(true))?;
+    mem.set(c, "goal", Value::Bool(false))?;
+    let pending = mem.pending_mutations().len();
+    assert!(mem.rename_field_for_type("Agent", "goal", "aim").is_err());
+    assert_eq!(mem.pending_mutations().len(), pending);
+
+    mem.commit(Some("rename".to_string()))?;
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.head_state(), mem.head_state());
+    assert_eq!(loaded.commits[1].mutations[0].kind(), "rename_field");
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn check_pending_reports_every_failing_mutation() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.commit(None)?;
+    mem.delete_node(b)?;
+    mem.commit(None)?;
+
+    mem.set(a, "goal", Value::Str("x".to_string()))?;
+    assert!(mem.check_pending()?.is_empty());
+
+    // Staged directly, bypassing the checks `set` and `create` make.
+    for mutation in [
+        Mutation::CreateNode {
+            id: a,
+            ty: "Agent".to_string(),
+        },
+        Mutation::SetField {
+            id: a,
+            key: "friend".to_string(),
+            value: Value::Ref(99),
+        },
+        Mutation::SetField {
+            id: b,
+            key: "goal".to_string(),
+            value: Value::Int(1),
+        },
+        Mutation::DeleteField {
+            id: a,
+            key: "goal".to_string(),
+        },
+    ] {
+        mem.stage_unchecked(mutation);
+    }
+    let issues = mem.check_pending()?;
+    let found: Vec<(usize, &str, NodeId)> = issues
+        .iter()
+        .map(|i| (i.index, i.kind, i.node_id))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (1, "create_node", a),
+            (2, "set_field", a),
+            (3, "set_field", b)
+        ]
+    );
+    assert!(issues[1].message.contains("missing node 99"));
+
+    let commits = mem.commits.len();
+    assert!(mem.commit(None).is_err());
+    assert_eq!(mem.commits.len(), commits);
+    Ok(())
+}
+
+#[test]
+fn commit_applies_mutations_staged_unchecked() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+
+    mem.stage_unchecked(Mutation::SetField {
+        id,
+        key: "goal".to_string(),
+        value: Value::Str("explore".to_string()),
+    });
+    mem.stage_unchecked(Mutation::SetAlias {
+        id,
+        alias: Some("scout".to_string()),
+    });
+    mem.commit(None)?;
+
+    assert_eq!(
+        mem.get_field(id, "goal")?,
+        &Value::Str("explore".to_string())
+    );
+    assert_eq!(mem.resolve("scout"), Some(id));
+    mem.validate()?;
+    mem.verify_head()?;
+
+    // A checkpoint written by such a commit holds the new state.
+    while mem.commits.len() < CHECKPOINT_INTERVAL {
+        mem.stage_unchecked(Mutation::SetField {
+            id,
+            key: "n".to_string(),
+            value: Value::Int(mem.commits.len() as i64),
+        });
+        mem.commit(None)?;
+    }
+    let checkpoint = mem.checkpoints.last().ok_or("no checkpoint")?;
+    assert_eq!(&checkpoint.state, mem.head_state());
+    mem.validate()?;
+    Ok(())
+}
+
+#[test]
+fn commit_does_not_replay_history() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    mem.set(id, "goal", Value::Str("x".to_string()))?;
+    mem.commit(None)?;
+
+    // History that no longer replays: commit only looks at what is staged.
+    mem.commits[1].mutations = vec![Mutation::DeleteField {
+        id,
+        key: "missing".to_string(),
+    }];
+    mem.set(id, "goal", Value::Str("y".to_string()))?;
+    mem.commit(None)?;
+    assert_eq!(mem.commits.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn set_type_replays_and_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_set_type.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "name", Value::Str("Iris".to_string()))?;
+    mem.commit(None)?;
+    let before = mem.commits[0].hash;
+
+    mem.set_type(id, "Person")?;
+    mem.commit(Some("retype".to_string()))?;
+    assert_eq!(mem.head_state()[&id].ty, "Person");
+    assert_eq!(mem.head_state()[&id].get_str("name"), Some("Iris"));
+    assert_eq!(&Memory::replay(&mem.commits)?, mem.head_state());
+    assert_eq!(mem.state_at_commit(1)?[&id].ty, "Agent");
+    assert_eq!(mem.commits[0].hash, before);
+
+    storage::save(path, &mem)?;
+    assert_eq!(storage::load(path)?.head_state(), mem.head_state());
+
+    assert!(matches!(
+        mem.set_type(99, "Person"),
+        Err(MyosotisError::NodeNotFound(99))
+    ));
+    mem.delete_node(id)?;
+    assert!(matches!(
+        mem.set_type(id, "Agent"),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn pending_summary_describes_the_next_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    assert!(!mem.is_dirty());
+    assert_eq!(mem.pending_summary(), PendingSummary::default());
+
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.set(b, "goal", Value::Str("Explore".to_string()))?;
+    mem.set(a, "goal", Value::Str("Rest".to_string()))?;
+    mem.set(a, "mood", Value::Str("Calm".to_string()))?;
+    assert!(mem.is_dirty());
+
+    let summary = mem.pending_summary();
+    assert_eq!(summary.mutations, 5);
+    assert_eq!(summary.by_kind.get("create_node"), Some(&2));
+    assert_eq!(summary.by_kind.get("set_field"), Some(&3));
+    assert_eq!(summary.created, vec![a, b]);
+    assert_eq!(summary.nodes, vec![a, b]);
+
+    mem.commit(None)?;
+    assert!(!mem.is_dirty());
+    assert_eq!(mem.pending_summary().mutations, 0);
+    Ok(())
+}
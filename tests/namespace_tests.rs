@@ -0,0 +1,112 @@
+use myosotis::canonical;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn namespaces_partition_nodes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let plain = mem.create("Note");
+    let event = mem.namespace("episodic").create("Event")?;
+    let fact = mem.namespace("semantic").create("Fact")?;
+    mem.namespace("semantic")
+        .set(fact, "text", Value::Str("sky is blue".to_string()))?;
+    mem.commit(Some("stores".to_string()))?;
+
+    assert_eq!(mem.namespaces(), vec!["episodic", "semantic"]);
+    let ids = |nodes: Vec<&myosotis::node::Node>| nodes.iter().map(|n| n.id).collect::<Vec<_>>();
+    assert_eq!(ids(mem.nodes_in(None)), vec![plain]);
+    assert_eq!(ids(mem.namespace("episodic").nodes()), vec![event]);
+    assert_eq!(
+        ids(mem.namespace("semantic").nodes_of_type("Fact")),
+        vec![fact]
+    );
+    assert!(mem.namespace("semantic").nodes_of_type("Event").is_empty());
+
+    // One shared chain.
+    assert_eq!(mem.commits.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn namespace_handle_rejects_foreign_nodes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let event = mem.namespace("episodic").create("Event")?;
+
+    let mut semantic = mem.namespace("semantic");
+    assert!(semantic.get(event).is_none());
+    assert!(matches!(
+        semantic.set(event, "k", Value::Int(1)),
+        Err(MyosotisError::NodeNotFound(id)) if id == event
+    ));
+    assert!(matches!(
+        semantic.delete_node(event),
+        Err(MyosotisError::NodeNotFound(_))
+    ));
+
+    mem.set_namespace(event, Some("semantic"))?;
+    assert!(mem.namespace("semantic").contains(event));
+    mem.set_namespace(event, None)?;
+    assert!(mem.namespaces().is_empty());
+    assert!(matches!(
+        mem.set_namespace(event, Some("")),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn deleted_nodes_leave_the_namespace() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let event = mem.namespace("episodic").create("Event")?;
+    mem.commit(Some("create".to_string()))?;
+    mem.namespace("episodic").delete_node(event)?;
+    mem.commit(Some("delete".to_string()))?;
+
+    assert!(mem.namespace("episodic").nodes().is_empty());
+    assert!(mem.namespaces().is_empty());
+    assert_eq!(
+        mem.head_state()[&event].namespace.as_deref(),
+        Some("episodic")
+    );
+    Ok(())
+}
+
+#[test]
+fn namespace_survives_save_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_namespace_roundtrip.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let event = mem.namespace("episodic").create("Event")?;
+    mem.commit(Some("create".to_string()))?;
+    storage::save(path, &mem)?;
+
+    let mut loaded = storage::load(path)?;
+    assert!(loaded.namespace("episodic").contains(event));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn namespace_changes_state_hash() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Event");
+    let before = Memory::compute_state_hash(mem.head_state());
+    let jcs_before = canonical::jcs_state_bytes(mem.head_state());
+
+    mem.set_namespace(id, Some("episodic"))?;
+    assert_ne!(before, Memory::compute_state_hash(mem.head_state()));
+    let jcs = String::from_utf8(canonical::jcs_state_bytes(mem.head_state()))?;
+    assert!(jcs.contains(r#""id":"1","namespace":"episodic","ty":"Event""#));
+
+    mem.set_namespace(id, None)?;
+    assert_eq!(before, Memory::compute_state_hash(mem.head_state()));
+    assert_eq!(jcs_before, canonical::jcs_state_bytes(mem.head_state()));
+    Ok(())
+}
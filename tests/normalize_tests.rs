@@ -0,0 +1,62 @@
+use myosotis::Memory;
+use myosotis::commit::Mutation;
+use myosotis::node::Value;
+
+#[test]
+fn normalize_collapses_overwritten_sets() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "mood", Value::Str("calm".to_string()))?;
+    mem.commit(None)?;
+
+    for step in 0..5 {
+        mem.set(id, "step", Value::Int(step))?;
+    }
+    // Set then delete of a field that did not exist leaves nothing.
+    mem.set(id, "scratch", Value::Bool(true))?;
+    mem.delete_field(id, "scratch")?;
+    // Set then delete of an existing field leaves only the delete.
+    mem.set(id, "mood", Value::Str("tense".to_string()))?;
+    mem.delete_field(id, "mood")?;
+    let head = mem.head_state().clone();
+
+    assert_eq!(mem.normalize_pending()?, 7);
+    assert_eq!(mem.head_state(), &head);
+    assert!(matches!(
+        mem.pending_mutations(),
+        [
+            Mutation::SetField { value: Value::Int(4), .. },
+            Mutation::DeleteField { key, .. },
+        ] if key == "mood"
+    ));
+    assert_eq!(mem.normalize_pending()?, 0);
+
+    mem.commit(None)?;
+    assert_eq!(mem.head_state(), &head);
+    mem.validate()?;
+    Ok(())
+}
+
+#[test]
+fn normalize_keeps_sets_that_are_observed() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.rename_field(id, "goal", "old_goal")?;
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    mem.set(id, "plan", Value::Map(Default::default()))?;
+    mem.set_path(id, "plan.first", Value::Int(1))?;
+    mem.set(id, "plan", Value::Bool(false))?;
+    let pending = mem.pending_mutations().len();
+    let head = mem.head_state().clone();
+
+    assert_eq!(mem.normalize_pending()?, 0);
+    assert_eq!(mem.pending_mutations().len(), pending);
+    mem.commit(None)?;
+    assert_eq!(mem.head_state(), &head);
+    assert_eq!(
+        mem.head_state()[&id].fields.get("old_goal"),
+        Some(&Value::Str("explore".to_string()))
+    );
+    Ok(())
+}
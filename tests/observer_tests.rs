@@ -0,0 +1,143 @@
+use myosotis::Memory;
+use myosotis::commit::{Commit, Mutation};
+use myosotis::memory::{CHECKPOINT_INTERVAL, Checkpoint};
+use myosotis::node::Value;
+use myosotis::observer::{CommitOrigin, Observer};
+use myosotis::stats::MemoryFootprint;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<String>>,
+}
+
+impl Observer for Recorder {
+    fn on_mutation(&self, mutation: &Mutation) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(format!("mutation {}", mutation.kind()));
+        }
+    }
+
+    fn on_commit(&self, commit: &Commit) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(format!("commit {}", commit.id));
+        }
+    }
+
+    fn on_checkpoint(&self, checkpoint: &Checkpoint) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(format!("checkpoint {}", checkpoint.commit_id));
+        }
+    }
+
+    fn on_footprint_exceeded(&self, footprint: &MemoryFootprint, limit: usize) {
+        if let Ok(mut events) = self.events.lock() {
+            assert!(footprint.total() > limit);
+            events.push(format!("footprint {}", limit));
+        }
+    }
+}
+
+#[test]
+fn observers_see_mutations_commits_and_checkpoints() -> Result<(), Box<dyn std::error::Error>> {
+    let recorder = Arc::new(Recorder::default());
+    let mut mem = Memory::new();
+    mem.add_observer(recorder.clone());
+
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("x".to_string()))?;
+    assert!(mem.set(99, "goal", Value::Int(1)).is_err());
+    mem.commit(None)?;
+    {
+        let events = recorder.events.lock().map_err(|_| "poisoned")?;
+        assert_eq!(
+            *events,
+            vec!["mutation create_node", "mutation set_field", "commit 1"]
+        );
+    }
+
+    for i in 1..CHECKPOINT_INTERVAL {
+        mem.set(id, "n", Value::Int(i as i64))?;
+        mem.commit(None)?;
+    }
+    let events = recorder.events.lock().map_err(|_| "poisoned")?;
+    let tail: Vec<&str> = events.iter().rev().take(2).map(String::as_str).collect();
+    assert_eq!(tail, vec!["checkpoint 50", "commit 50"]);
+    Ok(())
+}
+
+#[test]
+fn clones_do_not_notify() -> Result<(), Box<dyn std::error::Error>> {
+    let recorder = Arc::new(Recorder::default());
+    let mut mem = Memory::new();
+    mem.add_observer(recorder.clone());
+
+    let mut scratch = mem.clone();
+    scratch.create("Agent");
+    scratch.commit(None)?;
+    assert!(recorder.events.lock().map_err(|_| "poisoned")?.is_empty());
+
+    mem.clear_observers();
+    mem.create("Agent");
+    assert!(recorder.events.lock().map_err(|_| "poisoned")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn footprint_limit_warns_after_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let recorder = Arc::new(Recorder::default());
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    mem.add_observer(recorder.clone());
+
+    let limit = mem.memory_footprint().total() + 4096;
+    mem.set_footprint_limit(Some(limit));
+    mem.set(id, "note", Value::Str("short".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "note", Value::Str("x".repeat(8192)))?;
+    mem.commit(None)?;
+    mem.set_footprint_limit(None);
+    mem.set(id, "note", Value::Str("y".repeat(8192)))?;
+    mem.commit(None)?;
+
+    let events = recorder.events.lock().map_err(|_| "poisoned")?;
+    let warnings: Vec<&String> = events
+        .iter()
+        .filter(|e| e.starts_with("footprint"))
+        .collect();
+    assert_eq!(warnings, vec![&format!("footprint {}", limit)]);
+    Ok(())
+}
+
+#[test]
+fn subscribers_receive_commits_in_order() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let events = mem.subscribe();
+    let dropped = mem.subscribe();
+    drop(dropped);
+
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("first".to_string()))?;
+    let mut copy = mem.clone();
+    copy.set(id, "goal", Value::Str("Rest".to_string()))?;
+    copy.commit(None)?;
+    let first = events.try_recv()?;
+    assert_eq!(first.origin, CommitOrigin::Local);
+    assert_eq!(first.commit.message.as_deref(), Some("first"));
+    assert_eq!(first.commit.mutations.len(), 2);
+    assert!(events.try_recv().is_err());
+
+    mem.fetch(&copy)?;
+    let fetched = events.try_recv()?;
+    assert_eq!(fetched.commit.id, 2);
+    assert_eq!(fetched.origin, CommitOrigin::Fetched);
+    assert_eq!(fetched.commit.mutations.len(), 1);
+
+    mem.clear_observers();
+    mem.set(id, "goal", Value::Str("Sleep".to_string()))?;
+    mem.commit(None)?;
+    assert!(events.try_recv().is_err());
+    Ok(())
+}
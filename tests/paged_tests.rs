@@ -0,0 +1,199 @@
+use myosotis::memory::CHECKPOINT_INTERVAL;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, paged, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn memory_with_commits(n: usize) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Counter");
+    mem.commit(Some("create".to_string()))?;
+    for i in 1..n {
+        mem.set(id, "n", Value::Int(i as i64))?;
+        mem.commit(None)?;
+    }
+    Ok(mem)
+}
+
+/// Recording state hashes, with commit 3 claiming the state of commit 2.
+fn memory_with_tampered_state_hash() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    mem.record_state_hashes = true;
+    let id = mem.create("Counter");
+    mem.commit(None)?;
+    for i in 1..4 {
+        mem.set(id, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    mem.commits[2].state_hash = mem.commits[1].state_hash;
+    Ok(mem)
+}
+
+#[test]
+fn paged_queries_read_only_relevant_pages() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_paged_queries.myo";
+    cleanup(path);
+
+    let mem = memory_with_commits(2 * CHECKPOINT_INTERVAL + 10)?;
+    paged::save_with(path, &mem, 16)?;
+
+    let paged = paged::open(path)?;
+    assert_eq!(paged.pages_read(), 0);
+    assert_eq!(paged.page_commits(), 16);
+    assert_eq!(paged.pages().len(), mem.commits.len().div_ceil(16));
+    assert_eq!(paged.head_commit_id(), mem.commits.last().map(|c| c.id));
+
+    // The checkpoint at commit 100 covers everything before it.
+    let target = 2 * CHECKPOINT_INTERVAL as u64 + 5;
+    assert_eq!(paged.state_at_commit(target)?, mem.state_at_commit(target)?);
+    assert_eq!(paged.pages_read(), 1);
+
+    let early = paged.commits_between(3, 5)?;
+    assert_eq!(
+        early.iter().map(|c| c.id).collect::<Vec<_>>(),
+        vec![3, 4, 5]
+    );
+    assert_eq!(paged.commit(17)?.hash, mem.commits[16].hash);
+    assert_eq!(paged.pages_read(), 3);
+
+    assert!(matches!(
+        paged.state_at_commit(9999),
+        Err(MyosotisError::CommitNotFound(9999))
+    ));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn paged_file_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_paged_round_trip.myo";
+    cleanup(path);
+
+    let mut mem = memory_with_commits(40)?;
+    mem.compact_in_memory(10)?;
+    paged::save(path, &mem)?;
+
+    let paged = paged::open(path)?;
+    assert_eq!(paged.state_at_commit(12)?, mem.state_at_commit(12)?);
+    let loaded = paged.to_memory()?;
+    assert_eq!(loaded.head_state(), mem.head_state());
+    assert_eq!(loaded.commits.len(), mem.commits.len());
+    assert_eq!(loaded.genesis_state, mem.genesis_state);
+
+    // A regular file is not a paged one.
+    storage::save(path, &mem)?;
+    assert!(matches!(
+        paged::open(path),
+        Err(MyosotisError::MalformedFileStructure)
+    ));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn paged_save_refuses_write_once_rewrites() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_paged_write_once.myo";
+    cleanup(path);
+
+    let mut mem = memory_with_commits(20)?;
+    mem.write_once = true;
+    paged::save_with(path, &mem, 8)?;
+    let original = fs::read(path)?;
+
+    let mut truncated = mem.clone();
+    truncated.commits.pop();
+    assert!(matches!(
+        paged::save(path, &truncated),
+        Err(MyosotisError::WriteOnceViolation(_))
+    ));
+    let mut rewritten = mem.clone();
+    rewritten.commits[3].message = Some("edited".to_string());
+    assert!(matches!(
+        paged::save(path, &rewritten),
+        Err(MyosotisError::WriteOnceViolation(_))
+    ));
+    assert_eq!(fs::read(path)?, original);
+
+    mem.set(1, "n", Value::Int(-1))?;
+    mem.commit(None)?;
+    paged::save(path, &mem)?;
+    assert_eq!(paged::open(path)?.head_commit_id(), Some(21));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn paged_reads_check_recorded_state_hashes() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_paged_state_hash.myo";
+    cleanup(path);
+
+    paged::save(path, &memory_with_tampered_state_hash()?)?;
+    let paged = paged::open(path)?;
+    assert!(paged.state_at_commit(2).is_ok());
+    assert!(matches!(
+        paged.state_at_commit(3),
+        Err(MyosotisError::CommitStateHashMismatch(3))
+    ));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+mod mapped {
+    use super::{cleanup, memory_with_commits, memory_with_tampered_state_hash};
+    use myosotis::{MyosotisError, mmap, paged};
+    use std::borrow::Cow;
+
+    #[test]
+    fn mapped_reads_match_paged_reads() -> Result<(), Box<dyn std::error::Error>> {
+        let path = "test_paged_mapped.myo";
+        cleanup(path);
+
+        let mem = memory_with_commits(120)?;
+        paged::save_with(path, &mem, 16)?;
+        let mapped = mmap::open(path)?;
+        assert_eq!(mapped.pages().len(), paged::open(path)?.pages().len());
+
+        for id in [1, 50, 73, 120] {
+            assert_eq!(mapped.state_at_commit(id)?, mem.state_at_commit(id)?);
+        }
+
+        let frames = mapped.frames(&mapped.pages()[0])?;
+        assert_eq!(frames.len(), 16);
+        assert!(matches!(frames[0].message, Some(Cow::Borrowed("create"))));
+        assert_eq!(frames[3].to_commit()?.hash, mem.commits[3].hash);
+        assert!(mapped.frames_between(200, 300)?.is_empty());
+
+        assert!(matches!(
+            mapped.state_at_commit(500),
+            Err(MyosotisError::CommitNotFound(500))
+        ));
+
+        cleanup(path);
+        Ok(())
+    }
+
+    #[test]
+    fn mapped_reads_check_recorded_state_hashes() -> Result<(), Box<dyn std::error::Error>> {
+        let path = "test_paged_mapped_state_hash.myo";
+        cleanup(path);
+
+        paged::save(path, &memory_with_tampered_state_hash()?)?;
+        let mapped = mmap::open(path)?;
+        assert!(mapped.state_at_commit(4).is_ok());
+        assert!(matches!(
+            mapped.state_at_commit(3),
+            Err(MyosotisError::CommitStateHashMismatch(3))
+        ));
+
+        cleanup(path);
+        Ok(())
+    }
+}
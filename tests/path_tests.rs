@@ -0,0 +1,198 @@
+use myosotis::canonical::Canonicalization;
+use myosotis::commit::Mutation;
+use myosotis::node::{PathSeg, Value, parse_path};
+use myosotis::{Memory, MyosotisError, storage};
+use std::collections::HashMap;
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn map(entries: &[(&str, Value)]) -> Value {
+    Value::Map(
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+#[test]
+fn parse_path_splits_keys_and_indices() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        parse_path("profile.tags.0.name")?,
+        vec![
+            PathSeg::Key("profile".to_string()),
+            PathSeg::Key("tags".to_string()),
+            PathSeg::Index(0),
+            PathSeg::Key("name".to_string()),
+        ]
+    );
+    assert_eq!(parse_path("7")?, vec![PathSeg::Key("7".to_string())]);
+    assert!(matches!(
+        parse_path("profile..theme"),
+        Err(MyosotisError::InvalidPath(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn path_mutations_edit_nested_values_and_replay() -> Result<(), Box<dyn std::error::Error>> {
+    for canonicalization in [Canonicalization::Binary, Canonicalization::Jcs] {
+        let path = "test_path_mutations.myo";
+        cleanup(path);
+
+        let mut mem = Memory::with_canonicalization(canonicalization);
+        let id = mem.create("Agent");
+        mem.set(
+            id,
+            "profile",
+            map(&[(
+                "tags",
+                Value::List(vec![
+                    Value::Str("a".to_string()),
+                    Value::Str("b".to_string()),
+                ]),
+            )]),
+        )?;
+        mem.commit(None)?;
+
+        mem.set_path(id, "profile.settings.theme", Value::Str("dark".to_string()))?;
+        mem.set_path(id, "profile.tags.1", Value::Str("B".to_string()))?;
+        mem.set_path(id, "profile.tags.2", Value::Str("c".to_string()))?;
+        mem.delete_path(id, "profile.tags.0")?;
+        mem.commit(Some("nested".to_string()))?;
+        assert!(matches!(
+            mem.commits[1].mutations[0],
+            Mutation::SetPath { .. }
+        ));
+
+        let expected = map(&[
+            (
+                "settings",
+                map(&[("theme", Value::Str("dark".to_string()))]),
+            ),
+            (
+                "tags",
+                Value::List(vec![
+                    Value::Str("B".to_string()),
+                    Value::Str("c".to_string()),
+                ]),
+            ),
+        ]);
+        assert_eq!(mem.head_state()[&id].fields.get("profile"), Some(&expected));
+
+        storage::save(path, &mem)?;
+        let loaded = storage::load(path)?;
+        assert_eq!(
+            loaded.head_state()[&id].fields.get("profile"),
+            Some(&expected)
+        );
+        assert_eq!(loaded.commits[1].hash, mem.commits[1].hash);
+
+        mem.delete_path(id, "profile")?;
+        assert!(!mem.head_state()[&id].fields.contains_key("profile"));
+        cleanup(path);
+    }
+    Ok(())
+}
+
+#[test]
+fn invalid_paths_are_rejected_without_staging() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "name", Value::Str("iris".to_string()))?;
+    mem.set(id, "tags", Value::List(vec![]))?;
+    mem.commit(None)?;
+
+    for (path, value) in [
+        ("name.first", Value::Int(1)),
+        ("tags.3", Value::Int(1)),
+        ("tags.key", Value::Int(1)),
+    ] {
+        assert!(matches!(
+            mem.set_path(id, path, value),
+            Err(MyosotisError::InvalidPath(_))
+        ));
+    }
+    assert!(matches!(
+        mem.set_path(id, "tags.0", Value::Ref(99)),
+        Err(MyosotisError::Invariant(_))
+    ));
+    assert!(matches!(
+        mem.delete_path(id, "missing.key"),
+        Err(MyosotisError::FieldNotFound(_))
+    ));
+    assert!(matches!(
+        mem.delete_path(id, "tags.0"),
+        Err(MyosotisError::InvalidPath(_))
+    ));
+    assert!(mem.pending_mutations().is_empty());
+
+    mem.delete_node(id)?;
+    assert!(matches!(
+        mem.set_path(id, "tags.0", Value::Int(1)),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn list_mutations_record_only_the_element() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_list_mutations.myo";
+    cleanup(path);
+
+    let mut mem = Memory::with_canonicalization(Canonicalization::Jcs);
+    let id = mem.create("Agent");
+    mem.list_push(id, "log", Value::Str("boot".to_string()))?;
+    mem.commit(None)?;
+    for i in 0..3 {
+        mem.list_push(id, "log", Value::Int(i))?;
+    }
+    mem.list_insert(id, "log", 1, Value::Str("wake".to_string()))?;
+    mem.list_remove(id, "log", 0)?;
+    mem.list_push(id, "profile.seen", Value::Ref(id))?;
+    mem.commit(None)?;
+    assert!(
+        mem.commits[1]
+            .mutations
+            .iter()
+            .all(|m| !matches!(m, Mutation::SetField { .. }))
+    );
+
+    let log = Value::List(vec![
+        Value::Str("wake".to_string()),
+        Value::Int(0),
+        Value::Int(1),
+        Value::Int(2),
+    ]);
+    let profile = map(&[("seen", Value::List(vec![Value::Ref(id)]))]);
+    assert_eq!(mem.head_state()[&id].fields.get("log"), Some(&log));
+    assert_eq!(mem.head_state()[&id].fields.get("profile"), Some(&profile));
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.head_state()[&id].fields.get("log"), Some(&log));
+
+    assert!(matches!(
+        mem.list_insert(id, "log", 9, Value::Int(0)),
+        Err(MyosotisError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        mem.list_remove(id, "missing", 0),
+        Err(MyosotisError::FieldNotFound(_))
+    ));
+    assert!(matches!(
+        mem.list_push(id, "profile", Value::Int(0)),
+        Err(MyosotisError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        mem.list_push(id, "log", Value::Ref(99)),
+        Err(MyosotisError::Invariant(_))
+    ));
+    assert!(mem.pending_mutations().is_empty());
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use myosotis::prelude::*;
+use std::fs;
+
+#[test]
+fn prelude_covers_a_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_prelude.myo";
+    let _ = fs::remove_file(path);
+
+    let mut mem = Memory::new();
+    let id: NodeId = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    assert!(matches!(
+        mem.pending_mutations(),
+        [Mutation::CreateNode { .. }, Mutation::SetField { .. }]
+    ));
+    mem.commit(None)?;
+    assert!(mem.pending_mutations().is_empty());
+    save(path, &mem)?;
+
+    let loaded = load_with_mode(path, LoadMode::Strict)?;
+    let node: &Node = &loaded.head_state()[&id];
+    assert_eq!(node.fields.get("goal"), mem.get_field(id, "goal").ok());
+    let commit: &Commit = loaded.commits.last().ok_or("no commit")?;
+    assert_eq!(commit.mutations.len(), 2);
+    assert!(matches!(
+        load("missing_prelude.myo"),
+        Err(MyosotisError::ReadFailed(..))
+    ));
+    assert!(exists(path));
+
+    let _ = fs::remove_file(path);
+    Ok(())
+}
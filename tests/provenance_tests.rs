@@ -0,0 +1,67 @@
+use myosotis::canonical::Canonicalization;
+use myosotis::commit::Mutation;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+#[test]
+fn mutations_are_queryable_by_source() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_provenance.myo";
+    let _ = fs::remove_file(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit_with_source(None, "planner")?;
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(Some("untraced".to_string()))?;
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    mem.commit_with_source(None, "tool:search")?;
+    mem.set(id, "plan", Value::Int(1))?;
+    mem.commit_with_source(None, "planner")?;
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    let _ = fs::remove_file(path);
+
+    let planned = loaded.mutations_by_source("planner");
+    assert_eq!(planned.len(), 2);
+    assert!(matches!(planned[0], (1, Mutation::CreateNode { .. })));
+    assert!(matches!(planned[1], (4, Mutation::SetField { key, .. }) if key == "plan"));
+    assert_eq!(loaded.commits_by_source("tool:search")[0].id, 3);
+    assert!(loaded.mutations_by_source("nobody").is_empty());
+    assert_eq!(loaded.commits[1].source, None);
+    Ok(())
+}
+
+#[test]
+fn source_is_part_of_the_commit_hash() -> Result<(), Box<dyn std::error::Error>> {
+    for canonicalization in [Canonicalization::Binary, Canonicalization::Jcs] {
+        let mut plain = Memory::with_canonicalization(canonicalization);
+        plain.create("Agent");
+        plain.commit(None)?;
+        let mut traced = Memory::with_canonicalization(canonicalization);
+        traced.create("Agent");
+        traced.commit_with_source(None, "planner")?;
+
+        // Commits without a source hash as they always have.
+        let commit = &plain.commits[0];
+        assert_eq!(
+            commit.hash,
+            Memory::compute_commit_hash_with(
+                canonicalization,
+                None,
+                &commit.message,
+                &commit.mutations
+            )
+        );
+        assert_ne!(traced.commits[0].hash, commit.hash);
+        traced.validate()?;
+
+        traced.commits[0].source = Some("impostor".to_string());
+        assert!(matches!(
+            traced.validate(),
+            Err(MyosotisError::CorruptCommitHash)
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use myosotis::maintenance;
+use myosotis::memory::CHECKPOINT_INTERVAL;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+fn placeholder(value: Option<&Value>) -> bool {
+    matches!(value, Some(Value::Str(s)) if s.starts_with("redacted:"))
+}
+
+#[test]
+fn redaction_rewrites_history_and_keeps_structure() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_redact.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let user = mem.create("User");
+    mem.set(user, "email", Value::Str("iris@example.com".to_string()))?;
+    mem.set(user, "name", Value::Str("Iris".to_string()))?;
+    mem.commit(None)?;
+    for i in 0..CHECKPOINT_INTERVAL {
+        mem.set(user, "visits", Value::Int(i as i64))?;
+        mem.commit(None)?;
+    }
+    mem.set(user, "email", Value::Str("iris@example.org".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+
+    let redaction = maintenance::redact(path, user, "email")?;
+    assert_eq!(redaction.mutations, 2);
+    assert_eq!(redaction.snapshots, 1);
+    assert_eq!(redaction.marker_commit, mem.commits.len() as u64 + 1);
+
+    let raw = fs::read_to_string(path)?;
+    assert!(!raw.contains("iris@example"));
+    let redacted = storage::load(path)?;
+    assert_eq!(redacted.commits.len(), mem.commits.len() + 1);
+    assert!(placeholder(
+        redacted.head_state()[&user].fields.get("email")
+    ));
+    assert_eq!(
+        redacted.head_state()[&user].fields.get("name"),
+        Some(&Value::Str("Iris".to_string()))
+    );
+    assert!(placeholder(
+        redacted.state_at_commit(1)?[&user].fields.get("email")
+    ));
+    assert!(
+        maintenance::fsck(path, true)?
+            .issues
+            .iter()
+            .all(|i| i.check == "next-node-id")
+    );
+
+    assert!(matches!(
+        maintenance::redact(path, user, "missing").unwrap_err(),
+        MyosotisError::FieldNotFound(_)
+    ));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn redaction_is_refused_for_write_once() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    mem.write_once = true;
+    let user = mem.create("User");
+    mem.set(user, "email", Value::Str("iris@example.com".to_string()))?;
+    mem.commit(None)?;
+    assert!(matches!(
+        mem.redact_in_memory(user, "email"),
+        Err(MyosotisError::WriteOnceViolation(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn redaction_covers_path_writes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let user = mem.create("User");
+    mem.set_path(
+        user,
+        "contact.email",
+        Value::Str("iris@example.com".to_string()),
+    )?;
+    mem.set_path(user, "contact.phone", Value::Str("555".to_string()))?;
+    mem.commit(None)?;
+    mem.delete_path(user, "contact.phone")?;
+    mem.list_push(
+        user,
+        "contact.old",
+        Value::Str("iris@example.net".to_string()),
+    )?;
+    mem.commit(None)?;
+
+    let redaction = mem.redact_in_memory(user, "contact")?;
+    assert_eq!(redaction.mutations, 4);
+    assert!(placeholder(mem.head_state()[&user].fields.get("contact")));
+    assert!(!serde_json::to_string(&mem.commits)?.contains("iris@example"));
+    mem.validate()?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use myosotis::node::Value;
+use myosotis::render;
+use std::collections::HashMap;
+
+#[test]
+fn scalars_render_inline() {
+    assert_eq!(render::pretty(&Value::Int(-3), None, ""), "-3");
+    assert_eq!(render::pretty(&Value::Bool(true), None, ""), "true");
+    assert_eq!(
+        render::pretty(&Value::Str("say \"hi\"".to_string()), None, ""),
+        r#""say \"hi\"""#
+    );
+    assert_eq!(render::pretty(&Value::Ref(7), None, ""), r#"{"ref":7}"#);
+}
+
+#[test]
+fn collections_render_as_indented_json() {
+    let mut map = HashMap::new();
+    map.insert("b".to_string(), Value::Float(0.5));
+    map.insert("a".to_string(), Value::List(vec![Value::Int(1)]));
+    assert_eq!(
+        render::pretty(&Value::Map(map), None, "  "),
+        "{\n    \"a\": [\n      1\n    ],\n    \"b\": 0.5\n  }"
+    );
+}
+
+#[test]
+fn long_values_are_truncated_with_markers() {
+    let long = Value::Str("é".repeat(10));
+    assert_eq!(
+        render::to_json(&long, Some(4)),
+        serde_json::json!("éééé… (6 more chars)")
+    );
+    assert_eq!(
+        render::to_json(&long, None),
+        serde_json::json!("é".repeat(10))
+    );
+
+    let list = Value::List((0..5).map(Value::Int).collect());
+    assert_eq!(
+        render::to_json(&list, Some(2)),
+        serde_json::json!([0, 1, "… 3 more items"])
+    );
+
+    let map: HashMap<String, Value> = (0..3).map(|i| (format!("k{}", i), Value::Int(i))).collect();
+    assert_eq!(
+        render::to_json(&Value::Map(map), Some(1)),
+        serde_json::json!({ "k0": 0, "…": "2 more entries" })
+    );
+}
@@ -0,0 +1,67 @@
+use myosotis::commit::Mutation;
+use myosotis::diff::Change;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError};
+
+fn history() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    mem.set(a, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    mem.set(a, "goal", Value::Str("map".to_string()))?;
+    mem.commit(None)?;
+    mem.set(a, "goal", Value::Str("rest".to_string()))?;
+    let b = mem.create("Task");
+    mem.commit(None)?;
+    mem.delete_node(b)?;
+    mem.commit(None)?;
+    Ok(mem)
+}
+
+#[test]
+fn mutations_between_lists_recorded_mutations() -> Result<(), Box<dyn std::error::Error>> {
+    let mem = history()?;
+    let mutations = mem.mutations_between(1, 3)?;
+    assert_eq!(mutations.len(), 3);
+    assert!(matches!(mutations[2], Mutation::CreateNode { id: 2, .. }));
+
+    let recorded: Vec<_> = mem.commits[1..3]
+        .iter()
+        .flat_map(|c| c.mutations.clone())
+        .collect();
+    assert_eq!(
+        serde_json::to_value(&mutations)?,
+        serde_json::to_value(&recorded)?
+    );
+
+    assert_eq!(mem.mutations_between(0, 4)?.len(), 6);
+    assert!(mem.mutations_between(2, 2)?.is_empty());
+    assert!(matches!(
+        mem.mutations_between(3, 1),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        mem.mutations_between(1, 9),
+        Err(MyosotisError::CommitNotFound(9))
+    ));
+    Ok(())
+}
+
+#[test]
+fn replay_range_reports_the_net_delta() -> Result<(), Box<dyn std::error::Error>> {
+    let mem = history()?;
+    // The goal changed twice and the task came and went: only the net
+    // goal change remains.
+    assert_eq!(
+        mem.replay_range(1, 4)?,
+        vec![Change::FieldSet {
+            id: 1,
+            key: "goal".to_string(),
+            old: Some(Value::Str("explore".to_string())),
+            new: Value::Str("rest".to_string()),
+        }]
+    );
+    assert_eq!(mem.replay_range(0, 1)?, mem.diff(0, Some(1))?);
+    assert_eq!(mem.diff(4, Some(1))?.len(), 1);
+    Ok(())
+}
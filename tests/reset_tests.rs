@@ -0,0 +1,67 @@
+use myosotis::node::Value;
+use myosotis::storage;
+use myosotis::{Memory, MyosotisError};
+use std::fs;
+
+fn three_commits() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set_alias(id, Some("agent:iris"))?;
+    mem.commit(None)?;
+    for goal in ["Explore", "Loop"] {
+        mem.set(id, "goal", Value::Str(goal.to_string()))?;
+        mem.commit(Some(goal.to_string()))?;
+    }
+    Ok(mem)
+}
+
+#[test]
+fn reset_drops_later_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_reset_drops.myo";
+    let _ = fs::remove_file(path);
+    let mut mem = three_commits()?;
+    let at_two = mem.state_at_commit(2)?;
+    mem.set(1, "pending", Value::Bool(true))?;
+
+    let dropped = mem.reset_to(2)?;
+    assert_eq!(dropped.iter().map(|c| c.id).collect::<Vec<_>>(), vec![3]);
+    assert_eq!(mem.commits.len(), 2);
+    assert!(mem.pending_mutations().is_empty());
+    assert_eq!(*mem.head_state(), at_two);
+    mem.validate()?;
+
+    mem.set(1, "goal", Value::Str("Rest".to_string()))?;
+    mem.commit(None)?;
+    assert_eq!(mem.commits.last().map(|c| c.id), Some(3));
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(
+        loaded.get_field(1, "goal")?,
+        &Value::Str("Rest".to_string())
+    );
+
+    assert_eq!(mem.reset_to(0)?.len(), 3);
+    assert!(mem.head_state().is_empty());
+    assert_eq!(mem.resolve("agent:iris"), None);
+
+    let _ = fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn reset_is_refused_for_unknown_commits_and_write_once() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = three_commits()?;
+    assert!(matches!(
+        mem.reset_to(9),
+        Err(MyosotisError::CommitNotFound(9))
+    ));
+    assert_eq!(mem.commits.len(), 3);
+
+    mem.write_once = true;
+    assert!(matches!(
+        mem.reset_to(1),
+        Err(MyosotisError::WriteOnceViolation(_))
+    ));
+    assert_eq!(mem.commits.len(), 3);
+    Ok(())
+}
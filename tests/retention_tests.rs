@@ -0,0 +1,116 @@
+use myosotis::node::{NodeId, Value};
+use myosotis::retention::RetentionPolicy;
+use myosotis::{Memory, MyosotisError, maintenance, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+fn memory_with_commits(count: i64) -> Result<(Memory, NodeId), MyosotisError> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(Some("create".to_string()))?;
+    for i in 1..count {
+        mem.set(id, "step", Value::Int(i))?;
+        mem.commit(Some(format!("step {}", i)))?;
+    }
+    Ok((mem, id))
+}
+
+#[test]
+fn max_commits_and_keep_tags_bound_the_target() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut mem, _) = memory_with_commits(10)?;
+    assert_eq!(mem.retention_target(0), None);
+
+    mem.retention = Some(RetentionPolicy {
+        max_commits: Some(4),
+        ..Default::default()
+    });
+    assert_eq!(mem.retention_target(0), Some(6));
+
+    mem.commits[2].message = Some("release:v1".to_string());
+    mem.retention.as_mut().unwrap().keep_tags = vec!["release".to_string()];
+    assert_eq!(mem.retention_target(0), Some(2));
+
+    mem.commits[0].message = Some("release".to_string());
+    assert_eq!(mem.retention_target(0), None);
+
+    mem.commits[0].message = Some("released".to_string());
+    mem.retention.as_mut().unwrap().max_commits = Some(20);
+    assert_eq!(mem.retention_target(0), None);
+    Ok(())
+}
+
+#[test]
+fn max_age_uses_recorded_commit_times() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut mem, id) = memory_with_commits(3)?;
+    assert!(mem.commit_times.is_empty());
+
+    mem.retention = Some(RetentionPolicy {
+        max_age: Some(60),
+        ..Default::default()
+    });
+    mem.set(id, "step", Value::Int(3))?;
+    mem.commit(None)?;
+    mem.set(id, "step", Value::Int(4))?;
+    mem.commit(None)?;
+    assert_eq!(
+        mem.commit_times.keys().copied().collect::<Vec<_>>(),
+        vec![4, 5]
+    );
+
+    let made = mem.commit_times[&5];
+    assert_eq!(mem.retention_target(made), None);
+    mem.commit_times.insert(4, made - 120);
+    assert_eq!(mem.retention_target(made), Some(4));
+
+    let head = mem.head_state().clone();
+    assert_eq!(mem.apply_retention(made)?, Some(4));
+    assert_eq!(mem.commits.len(), 1);
+    assert_eq!(mem.head_state(), &head);
+    assert_eq!(
+        mem.commit_times.keys().copied().collect::<Vec<_>>(),
+        vec![5]
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_retention_compacts_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_retention_apply.myo";
+    cleanup(path);
+
+    let (mut mem, _) = memory_with_commits(8)?;
+    storage::save(path, &mem)?;
+    assert_eq!(maintenance::apply_retention(path)?, None);
+
+    mem.retention = Some(RetentionPolicy {
+        max_commits: Some(3),
+        max_age: None,
+        keep_tags: vec!["step 7".to_string()],
+    });
+    storage::save(path, &mem)?;
+    assert_eq!(maintenance::apply_retention(path)?, Some(5));
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.retention, mem.retention);
+    assert_eq!(
+        loaded.commits.iter().map(|c| c.id).collect::<Vec<_>>(),
+        vec![6, 7, 8]
+    );
+    assert_eq!(
+        Memory::compute_state_hash(loaded.head_state()),
+        Memory::compute_state_hash(mem.head_state())
+    );
+    assert_eq!(maintenance::apply_retention(path)?, None);
+
+    mem.write_once = true;
+    storage::save(path, &mem)?;
+    let err = maintenance::apply_retention(path).unwrap_err();
+    assert!(matches!(err, MyosotisError::WriteOnceViolation(_)));
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use myosotis::node::Value;
+use myosotis::script::{self, NodeRef, Step};
+use myosotis::{Memory, MyosotisError};
+
+#[test]
+fn script_binds_names_and_refs() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let existing = mem.create("Agent");
+    mem.commit(None)?;
+
+    let steps = script::parse(
+        r#"[
+            {"op": "create", "type": "Task", "as": "task"},
+            {"op": "set", "node": "task", "key": "owner", "value": {"Ref": "1"}},
+            {"op": "set", "node": 1, "key": "todo", "value": {"List": [{"Ref": "task"}]}},
+            {"op": "set", "node": "task", "key": "title", "value": {"Str": "map"}},
+            {"op": "delete_field", "node": "task", "key": "title"}
+        ]"#,
+    )?;
+    assert_eq!(
+        steps[2],
+        Step::Set {
+            node: NodeRef::Id(1),
+            key: "todo".to_string(),
+            value: serde_json::json!({ "List": [{ "Ref": "task" }] }),
+        }
+    );
+
+    let names = mem.apply_script(&steps)?;
+    let task = names["task"];
+    assert_eq!(mem.get_field(task, "owner")?, &Value::Ref(existing));
+    assert_eq!(
+        mem.get_field(existing, "todo")?,
+        &Value::List(vec![Value::Ref(task)])
+    );
+    assert!(mem.get_field(task, "title").is_err());
+    assert_eq!(mem.pending_mutations().len(), 5);
+    Ok(())
+}
+
+#[test]
+fn failing_step_rolls_back_the_script() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    let before = mem.head_state().clone();
+
+    let steps = script::parse(
+        r#"[
+            {"op": "create", "type": "Task", "as": "task"},
+            {"op": "set", "node": 1, "key": "goal", "value": {"Str": "rest"}},
+            {"op": "delete", "node": "missing"}
+        ]"#,
+    )?;
+    let err = mem.apply_script(&steps).unwrap_err();
+    assert!(matches!(err, MyosotisError::InvalidInput(_)));
+    assert_eq!(mem.head_state(), &before);
+    assert_eq!(mem.pending_mutations().len(), 2);
+
+    assert!(script::parse(r#"[{"op": "rename", "node": 1}]"#).is_err());
+    assert!(script::parse(r#"{"op": "delete", "node": 1}"#).is_err());
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use myosotis::node::Value;
+use myosotis::storage::{self, LoadMode};
+use myosotis::{Memory, MyosotisError};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+}
+
+fn compacted() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "secret", Value::Str("hunter2".to_string()))?;
+    mem.commit(None)?;
+    let head = mem.commits.last().map(|c| c.id).ok_or("no commits")?;
+    mem.compact_in_memory(head)?;
+    Ok(mem)
+}
+
+/// Rewrites the genesis goal and its hash the way a careful hand edit
+/// would, leaving the seal alone.
+fn tamper(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    raw["genesis_state"]["1"]["fields"]["goal"] = serde_json::json!({ "Str": "Obey" });
+    let state = serde_json::from_value(raw["genesis_state"].clone())?;
+    raw["genesis_state_hash"] = serde_json::to_value(Memory::compute_state_hash(&state))?;
+    fs::write(path, serde_json::to_string_pretty(&raw)?)?;
+    Ok(())
+}
+
+#[test]
+fn compaction_seals_genesis_and_catches_edits() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_seal_tamper.myo";
+    cleanup(path);
+
+    let mut mem = compacted()?;
+    let seal = mem.seal.clone().ok_or("compaction did not seal")?;
+    assert_eq!(seal.algorithm, "sha256");
+    mem.verify_seal(None)?;
+    storage::save(path, &mem)?;
+    assert_eq!(storage::load(path)?.seal, Some(seal));
+
+    tamper(path)?;
+    let err = storage::load_with_mode(path, LoadMode::Unsafe).unwrap_err();
+    assert!(matches!(err, MyosotisError::SealMismatch));
+
+    // Without a seal the same edit goes unnoticed.
+    mem.unseal();
+    storage::save(path, &mem)?;
+    tamper(path)?;
+    let loaded = storage::load_with_mode(path, LoadMode::Unsafe)?;
+    assert_eq!(
+        loaded.get_field(1, "goal")?,
+        &Value::Str("Obey".to_string())
+    );
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn keyed_seals_need_the_key() -> Result<(), Box<dyn std::error::Error>> {
+    let key = b"seal key".to_vec();
+    let mut mem = compacted()?;
+    assert!(Memory::new().seal(None).is_err());
+
+    mem.seal(Some(&key))?;
+    mem.verify_seal(Some(&key))?;
+    assert!(matches!(
+        mem.verify_seal(Some(b"other")),
+        Err(MyosotisError::SealMismatch)
+    ));
+    assert!(matches!(
+        mem.verify_seal(None),
+        Err(MyosotisError::SealMismatch)
+    ));
+
+    mem.set(1, "goal", Value::Str("Rest".to_string()))?;
+    mem.commit(None)?;
+    let head = mem.commits.last().map(|c| c.id).ok_or("no commits")?;
+    let err = mem.compact_in_memory(head).unwrap_err();
+    assert!(matches!(err, MyosotisError::InvalidInput(_)));
+    assert_eq!(mem.commits.len(), 1);
+
+    mem.set_seal_key(Some(key.clone()));
+    mem.compact_in_memory(head)?;
+    mem.verify_seal(Some(&key))?;
+    Ok(())
+}
+
+#[test]
+fn redaction_renews_the_seal() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = compacted()?;
+    let before = mem.seal.clone();
+    mem.redact_in_memory(1, "secret")?;
+    assert_ne!(mem.seal, before);
+    mem.verify_seal(None)?;
+    mem.validate()?;
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use myosotis::Memory;
+use myosotis::node::Value;
+use myosotis::shared::SharedMemory;
+use std::thread;
+
+#[test]
+fn shared_memory_serializes_writers() -> Result<(), Box<dyn std::error::Error>> {
+    let shared = SharedMemory::new(Memory::new());
+    let id = shared.write(|mem| mem.create("Counter"));
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                for _ in 0..25 {
+                    shared.write(|mem| {
+                        let n = mem
+                            .get_field(id, "n")
+                            .ok()
+                            .and_then(Value::as_int)
+                            .unwrap_or(0);
+                        mem.set(id, "n", Value::Int(n + 1))
+                    })?;
+                }
+                Ok::<_, myosotis::MyosotisError>(())
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().map_err(|_| "writer panicked")??;
+    }
+
+    let snapshot = shared.snapshot();
+    assert_eq!(snapshot.get_field(id, "n")?, &Value::Int(100));
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+mod grpc {
+    use myosotis::node::Value;
+    use myosotis::server::MemoryService;
+    use myosotis::server::proto::memory_client::MemoryClient;
+    use myosotis::server::proto::operation::Kind;
+    use myosotis::server::proto::{
+        CommitRequest, CompactRequest, CreateNode, DeleteNode, DryRunRequest, GetNodeRequest,
+        HistoryRequest, MutateRequest, Operation, SetField, StateAtRequest,
+    };
+    use myosotis::shared::SharedMemory;
+    use myosotis::{Memory, storage};
+    use std::error::Error;
+    use std::net::SocketAddr;
+    use tonic::Code;
+    use tonic::transport::Channel;
+    use tonic::transport::server::TcpIncoming;
+
+    fn op(kind: Kind) -> Operation {
+        Operation { kind: Some(kind) }
+    }
+
+    fn set(id: &str, key: &str, value: &str) -> Operation {
+        op(Kind::SetField(SetField {
+            id: id.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        }))
+    }
+
+    async fn start(shared: SharedMemory) -> Result<MemoryClient<Channel>, Box<dyn Error>> {
+        start_service(MemoryService::new(shared)).await
+    }
+
+    async fn start_service(
+        service: MemoryService,
+    ) -> Result<MemoryClient<Channel>, Box<dyn Error>> {
+        let incoming = TcpIncoming::bind(SocketAddr::from(([127, 0, 0, 1], 0)))?;
+        let addr = incoming.local_addr()?;
+        let server = service.into_server();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(server)
+                .serve_with_incoming(incoming),
+        );
+        Ok(MemoryClient::connect(format!("http://{}", addr)).await?)
+    }
+
+    fn runtime() -> std::io::Result<tokio::runtime::Runtime> {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+    }
+
+    #[test]
+    fn mutate_commit_and_read_back() -> Result<(), Box<dyn Error>> {
+        runtime()?.block_on(async {
+            let shared = SharedMemory::new(Memory::new());
+            let mut client = start(shared.clone()).await?;
+
+            let created = client
+                .mutate(MutateRequest {
+                    operations: vec![op(Kind::CreateNode(CreateNode {
+                        ty: "Agent".to_string(),
+                        namespace: None,
+                    }))],
+                })
+                .await?
+                .into_inner();
+            let id = created.created_ids[0].clone();
+            client
+                .mutate(MutateRequest {
+                    operations: vec![set(&id, "goal", r#"{"Str":"Explore"}"#)],
+                })
+                .await?;
+            let commit = client
+                .commit(CommitRequest {
+                    message: Some("first".to_string()),
+                })
+                .await?
+                .into_inner()
+                .commit
+                .ok_or("commit missing")?;
+            assert_eq!(commit.mutations.len(), 2);
+
+            let node = client
+                .get_node(GetNodeRequest { id: id.clone() })
+                .await?
+                .into_inner();
+            assert_eq!(node.ty, "Agent");
+            assert_eq!(node.fields["goal"], r#"{"Str":"Explore"}"#);
+
+            client
+                .mutate(MutateRequest {
+                    operations: vec![op(Kind::DeleteNode(DeleteNode { id: id.clone() }))],
+                })
+                .await?;
+            client.commit(CommitRequest { message: None }).await?;
+
+            let history = client
+                .history(HistoryRequest { after: Some(1) })
+                .await?
+                .into_inner();
+            assert_eq!(history.commits.len(), 1);
+            assert_eq!(history.commits[0].parent, Some(1));
+
+            let state = client
+                .state_at(StateAtRequest { commit_id: 1 })
+                .await?
+                .into_inner();
+            assert_eq!(state.nodes.len(), 1);
+            assert!(!state.nodes[0].deleted);
+            assert!(shared.read(|mem| mem.head_state().values().all(|n| n.deleted)));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn dry_run_predicts_the_commit_hash() -> Result<(), Box<dyn Error>> {
+        runtime()?.block_on(async {
+            let mut mem = Memory::new();
+            let id = mem.create("Agent");
+            mem.commit(None)?;
+            let shared = SharedMemory::new(mem);
+            let mut client = start(shared.clone()).await?;
+
+            let goal = format!(
+                r#"{{"SetField":{{"id":{},"key":"goal","value":{{"Str":"Explore"}}}}}}"#,
+                id
+            );
+            let preview = client
+                .dry_run(DryRunRequest {
+                    message: Some("plan".to_string()),
+                    mutations: vec![goal],
+                })
+                .await?
+                .into_inner();
+            assert_eq!(preview.id, 2);
+            assert!(shared.read(|mem| mem.pending_mutations().is_empty()));
+
+            client
+                .mutate(MutateRequest {
+                    operations: vec![set(&id.to_string(), "goal", r#"{"Str":"Explore"}"#)],
+                })
+                .await?;
+            let commit = client
+                .commit(CommitRequest {
+                    message: Some("plan".to_string()),
+                })
+                .await?
+                .into_inner()
+                .commit
+                .ok_or("commit missing")?;
+            assert_eq!(commit.hash, preview.hash);
+
+            let missing = r#"{"DeleteNode":{"id":99}}"#.to_string();
+            let err = client
+                .dry_run(DryRunRequest {
+                    message: None,
+                    mutations: vec![missing],
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::NotFound);
+            let err = client
+                .dry_run(DryRunRequest {
+                    message: None,
+                    mutations: vec!["not json".to_string()],
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn compact_while_serving() -> Result<(), Box<dyn Error>> {
+        let path = "test_server_compact.myo";
+        let _ = std::fs::remove_file(path);
+        runtime()?.block_on(async {
+            let mut mem = Memory::new();
+            let id = mem.create("Counter");
+            for n in 0..30 {
+                mem.set(id, "n", Value::Int(n))?;
+                mem.commit(None)?;
+            }
+            storage::save(path, &mem)?;
+            let shared = SharedMemory::new(mem);
+            let mut client =
+                start_service(MemoryService::new(shared.clone()).persist_to(path)).await?;
+
+            let compacted = client
+                .compact(CompactRequest { at: Some(20) })
+                .await?
+                .into_inner();
+            assert_eq!(compacted.target_commit_id, 20);
+            assert_eq!(compacted.remaining_commits, 10);
+            assert_eq!(compacted.rebased_commits, 0);
+            assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+            let from_file = storage::load(path)?;
+            assert!(from_file.genesis_state.is_some());
+            assert!(shared.read(|mem| mem.head_state() == from_file.head_state()));
+
+            client
+                .mutate(MutateRequest {
+                    operations: vec![set(&id.to_string(), "n", r#"{"Int":30}"#)],
+                })
+                .await?;
+            let commit = client
+                .commit(CommitRequest { message: None })
+                .await?
+                .into_inner()
+                .commit
+                .ok_or("commit missing")?;
+            assert_eq!(commit.id, 31);
+            let from_file = storage::load(path)?;
+            from_file.validate()?;
+            assert_eq!(from_file.commits.len(), 11);
+
+            let err = client
+                .compact(CompactRequest { at: Some(5) })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+            Ok::<_, Box<dyn Error>>(())
+        })?;
+        let _ = std::fs::remove_file(path);
+        Ok(())
+    }
+
+    #[test]
+    fn failed_batch_is_rolled_back() -> Result<(), Box<dyn Error>> {
+        runtime()?.block_on(async {
+            let shared = SharedMemory::new(Memory::new());
+            let mut client = start(shared.clone()).await?;
+
+            let err = client
+                .mutate(MutateRequest {
+                    operations: vec![
+                        op(Kind::CreateNode(CreateNode {
+                            ty: "Agent".to_string(),
+                            namespace: None,
+                        })),
+                        set("99", "goal", r#"{"Int":1}"#),
+                    ],
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::NotFound);
+            assert!(shared.read(|mem| mem.pending_mutations().is_empty()));
+
+            let err = client
+                .get_node(GetNodeRequest {
+                    id: "not-a-number".to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+
+            let err = client
+                .commit(CommitRequest { message: None })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,73 @@
+use myosotis::node::Value;
+use myosotis::session::SessionMarker;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn sessions_bound_commits_and_states() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_sessions.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(Some("setup".to_string()))?;
+
+    assert_eq!(mem.begin_session()?, 1);
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    mem.commit(None)?;
+    assert_eq!(mem.end_session()?, 1);
+    let after_first = mem.head_state().clone();
+
+    assert_eq!(mem.begin_session()?, 2);
+    mem.delete_field(id, "goal")?;
+    mem.commit(None)?;
+    assert_eq!(mem.current_session(), Some(2));
+
+    let ids: Vec<u64> = mem.commits_in_session(1)?.iter().map(|c| c.id).collect();
+    assert_eq!(ids, vec![3, 4]);
+    assert_eq!(mem.commits_in_session(2)?.len(), 1);
+    assert_eq!(mem.state_at_session_end(1)?, after_first);
+    assert!(matches!(
+        mem.state_at_session_end(2),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        mem.commits_in_session(9),
+        Err(MyosotisError::SessionNotFound(9))
+    ));
+
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.sessions(), mem.sessions());
+    assert_eq!(loaded.sessions()[0].end_commit, Some(5));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn session_boundaries_are_guarded() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    assert!(mem.end_session().is_err());
+    mem.begin_session()?;
+    assert!(mem.begin_session().is_err());
+
+    mem.create("Agent");
+    assert!(mem.end_session().is_err());
+    mem.commit(None)?;
+    mem.end_session()?;
+
+    // Only empty commits count as markers.
+    mem.create("Agent");
+    mem.commit(Some("session:begin 7".to_string()))?;
+    assert_eq!(SessionMarker::of(&mem.commits[3]), None);
+    assert_eq!(mem.sessions().len(), 1);
+    assert_eq!(mem.current_session(), None);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use myosotis::Memory;
+use myosotis::diff::Change;
+use myosotis::node::Value;
+use myosotis::shell::{Outcome, Session};
+
+fn run(session: &mut Session, line: &str) -> Result<String, Box<dyn std::error::Error>> {
+    match session.execute(line)? {
+        Outcome::Output(text) => Ok(text),
+        Outcome::Exit => Ok("<exit>".to_string()),
+    }
+}
+
+#[test]
+fn session_stages_across_commands() -> Result<(), Box<dyn std::error::Error>> {
+    let mut session = Session::new(Memory::new());
+    run(&mut session, "create Agent")?;
+    run(&mut session, "set 1 goal explore the map")?;
+    run(&mut session, "commit first")?;
+    run(&mut session, "set 1 goal rest")?;
+    run(&mut session, "set 1 mood ok")?;
+    run(&mut session, "undo")?;
+
+    let node = &session.mem.head_state()[&1];
+    assert_eq!(
+        node.fields.get("goal"),
+        Some(&Value::Str("rest".to_string()))
+    );
+    assert!(!node.fields.contains_key("mood"));
+    assert_eq!(session.mem.pending_mutations().len(), 1);
+
+    run(&mut session, "commit")?;
+    assert_eq!(session.mem.commits.len(), 2);
+    assert_eq!(session.mem.commits[1].message, None);
+    assert!(run(&mut session, "show 1 1")?.contains("explore the map"));
+    assert!(run(&mut session, "undo").is_err());
+    assert!(run(&mut session, "frobnicate").is_err());
+    assert_eq!(run(&mut session, "quit")?, "<exit>");
+    Ok(())
+}
+
+#[test]
+fn completions_cover_commands_ids_and_keys() -> Result<(), Box<dyn std::error::Error>> {
+    let mut session = Session::new(Memory::new());
+    run(&mut session, "create Agent")?;
+    run(&mut session, "set 1 goal x")?;
+    run(&mut session, "set 1 gauge y")?;
+
+    assert_eq!(session.completions(true, "co"), vec!["commit"]);
+    assert_eq!(session.completions(false, "g"), vec!["gauge", "goal"]);
+    assert_eq!(session.completions(false, "1"), vec!["1"]);
+    Ok(())
+}
+
+#[test]
+fn diff_reports_node_and_field_changes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    mem.set(a, "goal", Value::Str("explore".to_string()))?;
+    mem.set(a, "mood", Value::Str("ok".to_string()))?;
+    mem.commit(None)?;
+
+    let b = mem.create("Task");
+    mem.set(a, "goal", Value::Str("rest".to_string()))?;
+    mem.delete_field(a, "mood")?;
+    mem.commit(None)?;
+    mem.delete_node(b)?;
+
+    assert_eq!(
+        mem.diff(1, Some(2))?,
+        vec![
+            Change::FieldSet {
+                id: a,
+                key: "goal".to_string(),
+                old: Some(Value::Str("explore".to_string())),
+                new: Value::Str("rest".to_string()),
+            },
+            Change::FieldRemoved {
+                id: a,
+                key: "mood".to_string(),
+                old: Value::Str("ok".to_string()),
+            },
+            Change::NodeCreated {
+                id: b,
+                ty: "Task".to_string(),
+            },
+        ]
+    );
+    assert_eq!(mem.diff(2, None)?, vec![Change::NodeDeleted { id: b }]);
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use myosotis::node::Value;
+use myosotis::shared::SharedMemory;
+use myosotis::{Memory, MyosotisError};
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn snapshot_stays_as_taken() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    mem.set(a, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(None)?;
+    let b = mem.create("Task");
+
+    let before = mem.snapshot();
+    assert_eq!(before.head_commit(), Some(1));
+    assert_eq!(before.pending(), 1);
+    assert_eq!(before.len(), 2);
+
+    mem.set(a, "goal", Value::Str("Rest".to_string()))?;
+    mem.delete_node(b)?;
+    mem.commit(None)?;
+
+    assert_eq!(
+        before.get_field(a, "goal")?,
+        &Value::Str("Explore".to_string())
+    );
+    assert!(!before.get(b).unwrap().deleted);
+
+    let after = mem.snapshot();
+    assert_eq!(after.get_field(a, "goal")?, &Value::Str("Rest".to_string()));
+    assert!(matches!(
+        after.get_field(b, "x"),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+    assert_eq!(after.to_state(), *mem.head_state());
+
+    mem.set(a, "goal", Value::Str("Sleep".to_string()))?;
+    mem.snapshot();
+    mem.discard_pending()?;
+    assert_eq!(mem.snapshot().to_state(), *mem.head_state());
+    mem.reset_to(1)?;
+    assert_eq!(mem.snapshot().to_state(), *mem.head_state());
+    Ok(())
+}
+
+#[test]
+fn snapshots_share_unchanged_nodes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.commit(None)?;
+
+    let first = mem.snapshot();
+    let again = mem.snapshot();
+    assert!(Arc::ptr_eq(
+        &first.node(a).unwrap(),
+        &again.node(a).unwrap()
+    ));
+
+    mem.set(b, "goal", Value::Str("Explore".to_string()))?;
+    let staged = mem.snapshot();
+    assert!(Arc::ptr_eq(
+        &first.node(a).unwrap(),
+        &staged.node(a).unwrap()
+    ));
+    assert!(!Arc::ptr_eq(
+        &first.node(b).unwrap(),
+        &staged.node(b).unwrap()
+    ));
+
+    mem.commit(None)?;
+    let committed = mem.snapshot();
+    assert!(Arc::ptr_eq(
+        &first.node(a).unwrap(),
+        &committed.node(a).unwrap()
+    ));
+    assert_eq!(committed.to_state(), *mem.head_state());
+    Ok(())
+}
+
+#[test]
+fn views_outlive_concurrent_writes() -> Result<(), Box<dyn std::error::Error>> {
+    let shared = SharedMemory::new(Memory::new());
+    let id = shared.write(|mem| {
+        let id = mem.create("Counter");
+        mem.set(id, "n", Value::Int(0)).map(|()| id)
+    })?;
+    let view = shared.view();
+
+    let writer = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for n in 1..=50 {
+                shared.write(|mem| mem.set(id, "n", Value::Int(n))).unwrap();
+            }
+        })
+    };
+    let reader = thread::spawn(move || view.get_field(id, "n").cloned());
+    writer.join().unwrap();
+
+    assert_eq!(reader.join().unwrap()?, Value::Int(0));
+    assert_eq!(shared.view().get_field(id, "n")?, &Value::Int(50));
+    Ok(())
+}
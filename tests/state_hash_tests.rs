@@ -0,0 +1,77 @@
+use myosotis::canonical::Canonicalization;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn commits_record_the_state_they_leave() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    assert_eq!(mem.commits[0].state_hash, None);
+
+    mem.record_state_hashes = true;
+    for step in 0..3 {
+        mem.set(id, "step", Value::Int(step))?;
+        let preview = mem.dry_run_commit(None, &[])?;
+        mem.commit(None)?;
+        assert_eq!(mem.commits.last().map(|c| c.hash), Some(preview.hash));
+    }
+    for commit in &mem.commits[1..] {
+        let state = mem.state_at_commit(commit.id)?;
+        assert_eq!(commit.state_hash, Some(mem.hash_state(&state)));
+    }
+    assert_eq!(mem.verify_state_hashes()?, 3);
+
+    // The recorded hash is part of the commit hash.
+    let mut unrecorded = mem.commits[1].clone();
+    unrecorded.state_hash = None;
+    assert_ne!(
+        unrecorded.compute_hash_with(mem.canonicalization),
+        mem.commits[1].hash
+    );
+
+    // A reconstruction that disagrees with the record is refused.
+    let mut tampered = mem.clone();
+    tampered.commits[2].state_hash = mem.commits[1].state_hash;
+    assert!(matches!(
+        tampered.state_at_commit(3),
+        Err(MyosotisError::CommitStateHashMismatch(3))
+    ));
+    assert!(tampered.verify_state_hashes().is_err());
+    Ok(())
+}
+
+#[test]
+fn state_hashes_round_trip_and_survive_redaction() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_state_hash_round_trip.myo";
+    cleanup(path);
+
+    for canonicalization in [Canonicalization::Binary, Canonicalization::Jcs] {
+        let mut mem = Memory::with_canonicalization(canonicalization);
+        mem.record_state_hashes = true;
+        let id = mem.create("Agent");
+        mem.set(id, "secret", Value::Str("hunter2".to_string()))?;
+        mem.commit(None)?;
+        mem.set(id, "name", Value::Str("scout".to_string()))?;
+        mem.commit(None)?;
+
+        storage::save(path, &mem)?;
+        let loaded = storage::load(path)?;
+        assert!(loaded.record_state_hashes);
+        assert_eq!(loaded.commits[1].state_hash, mem.commits[1].state_hash);
+        assert_eq!(loaded.verify_state_hashes()?, 2);
+
+        // Redaction changes the states, so their hashes are re-recorded.
+        mem.redact_in_memory(id, "secret")?;
+        assert_ne!(loaded.commits[0].state_hash, mem.commits[0].state_hash);
+        assert_eq!(mem.verify_state_hashes()?, 3);
+        mem.validate()?;
+        cleanup(path);
+    }
+    Ok(())
+}
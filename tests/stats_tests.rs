@@ -0,0 +1,85 @@
+use myosotis::Memory;
+use myosotis::node::Value;
+
+#[test]
+fn stats_count_nodes_commits_and_mutations() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    let t = mem.create("Task");
+    mem.set(a, "goal", Value::Str("x".to_string()))?;
+    mem.commit(None)?;
+    mem.delete_node(b)?;
+    mem.commit(None)?;
+    mem.set(t, "done", Value::Bool(false))?;
+
+    let stats = mem.stats()?;
+    assert_eq!(stats.live_nodes, 2);
+    assert_eq!(stats.deleted_nodes, 1);
+    assert_eq!(stats.nodes_by_type.get("Agent"), Some(&1));
+    assert_eq!(stats.nodes_by_type.get("Task"), Some(&1));
+    assert_eq!(stats.commits, 2);
+    assert_eq!(stats.checkpoints, 0);
+    assert_eq!(stats.mutations_by_kind.get("create_node"), Some(&3));
+    assert_eq!(stats.mutations_by_kind.get("set_field"), Some(&1));
+    assert_eq!(stats.mutations_by_kind.get("delete_node"), Some(&1));
+    assert_eq!(stats.pending_mutations, 1);
+    assert!(stats.approx_size_bytes > 0);
+    Ok(())
+}
+
+#[test]
+fn stats_report_hash_input_sizes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    mem.set(id, "blob", Value::Str("x".repeat(10_000)))?;
+    mem.commit(None)?;
+    for i in 0..48 {
+        mem.set(id, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+
+    let sizes = mem.commit_hash_input_sizes();
+    assert_eq!(sizes.len(), 50);
+    assert!(sizes[1].1 > 10_000);
+
+    let stats = mem.stats()?;
+    assert_eq!(stats.commit_hash_inputs.count, 50);
+    assert_eq!(stats.commit_hash_inputs.max_commit_id, Some(2));
+    assert_eq!(stats.commit_hash_inputs.max_bytes, sizes[1].1);
+    assert_eq!(stats.checkpoint_hash_inputs.count, 1);
+    assert_eq!(stats.checkpoint_hash_inputs.max_commit_id, Some(50));
+    assert!(stats.checkpoint_hash_inputs.max_bytes > 10_000);
+    Ok(())
+}
+
+#[test]
+fn footprint_tracks_state_history_and_pending() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let empty = mem.memory_footprint();
+    assert_eq!(empty.total(), 0);
+
+    let id = mem.create("Agent");
+    mem.set(id, "bio", Value::Str("z".repeat(1000)))?;
+    let staged = mem.memory_footprint();
+    assert!(staged.pending_mutations >= 1000);
+    assert!(staged.head_state >= 1000);
+    assert_eq!(staged.commits, 0);
+
+    mem.commit(None)?;
+    let committed = mem.memory_footprint();
+    // The cleared queue keeps its capacity, but not the staged string.
+    assert!(committed.pending_mutations < 1000);
+    assert!(committed.commits >= 1000);
+    assert_eq!(
+        committed.total(),
+        committed.head_state
+            + committed.genesis_state
+            + committed.commits
+            + committed.checkpoints
+            + committed.pending_mutations
+            + committed.other
+    );
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use myosotis::memory::CHECKPOINT_INTERVAL;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage, sync};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn base() -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    Ok(mem)
+}
+
+#[test]
+fn fetch_appends_missing_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let mut laptop = base()?;
+    let mut server = laptop.clone();
+    for i in 0..CHECKPOINT_INTERVAL {
+        server.set(1, "n", Value::Int(i as i64))?;
+        server.commit(None)?;
+    }
+    let task = server.create("Task");
+    server.commit(None)?;
+
+    assert_eq!(laptop.fetch(&server)?, CHECKPOINT_INTERVAL + 1);
+    assert_eq!(laptop.head_state(), server.head_state());
+    assert_eq!(
+        laptop.commits.last().map(|c| c.hash),
+        server.commits.last().map(|c| c.hash)
+    );
+    assert_eq!(laptop.checkpoints.len(), 1);
+    laptop.validate()?;
+
+    assert_eq!(laptop.fetch(&server)?, 0);
+    assert!(laptop.create("Task") > task);
+    Ok(())
+}
+
+#[test]
+fn fetch_rejects_divergence_and_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let mut laptop = base()?;
+    let mut server = laptop.clone();
+    server.set(1, "goal", Value::Str("rest".to_string()))?;
+    server.commit(None)?;
+
+    let mut forked = laptop.clone();
+    forked.set(1, "goal", Value::Str("plan".to_string()))?;
+    forked.commit(None)?;
+    assert!(matches!(
+        forked.fetch(&server),
+        Err(MyosotisError::HistoriesDiverged(_))
+    ));
+
+    let mut tampered = server.clone();
+    tampered.commits[1].message = Some("edited".to_string());
+    assert!(matches!(
+        laptop.fetch(&tampered),
+        Err(MyosotisError::CorruptCommitHash)
+    ));
+    assert_eq!(laptop.commits.len(), 1);
+
+    laptop.create("Task");
+    assert!(laptop.fetch(&server).is_err());
+    Ok(())
+}
+
+#[test]
+fn pull_and_push_between_files() -> Result<(), Box<dyn std::error::Error>> {
+    let local = "test_sync_local.myo";
+    let remote = "test_sync_remote.myo";
+    cleanup(local);
+    cleanup(remote);
+
+    let mut mem = base()?;
+    storage::save(remote, &mem)?;
+    mem.set(1, "goal", Value::Str("rest".to_string()))?;
+    mem.commit(None)?;
+    storage::save(local, &mem)?;
+
+    assert_eq!(sync::pull(local, remote)?, 0);
+    assert_eq!(sync::push(local, remote)?, 1);
+    assert_eq!(storage::load(remote)?.head_state(), mem.head_state());
+
+    cleanup(local);
+    cleanup(remote);
+    Ok(())
+}
+
+#[test]
+fn bundles_carry_incremental_history() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_bundle_source.myo";
+    let out = "test_bundle.myobundle";
+    let target = "test_bundle_target.myo";
+    cleanup(path);
+    cleanup(out);
+    cleanup(target);
+
+    let mut mem = base()?;
+    storage::save(target, &mem)?;
+    for i in 0..3 {
+        mem.set(1, "n", Value::Int(i))?;
+        mem.commit(None)?;
+    }
+    storage::save(path, &mem)?;
+
+    assert_eq!(sync::write_bundle(path, out, 1)?, 3);
+    let bundle = sync::read_bundle(out)?;
+    assert_eq!(bundle.base_commit, 1);
+    assert_eq!(bundle.base_hash, Some(mem.commits[0].hash));
+
+    assert_eq!(sync::apply_bundle(target, out)?, 3);
+    assert_eq!(sync::apply_bundle(target, out)?, 0);
+    assert_eq!(storage::load(target)?.head_state(), mem.head_state());
+
+    // A bundle whose base is missing is refused.
+    let later = mem.bundle(3)?;
+    let mut short = base()?;
+    assert!(matches!(
+        short.unbundle(&later),
+        Err(MyosotisError::HistoriesDiverged(_))
+    ));
+    assert_eq!(short.unbundle(&mem.bundle(0)?)?, 3);
+    assert!(mem.bundle(9).is_err());
+
+    cleanup(path);
+    cleanup(out);
+    cleanup(target);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use myosotis::node::Value;
+use myosotis::typed::{MyoNode, NodeRef};
+use myosotis::{Memory, MyosotisError};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, MyoNode)]
+struct Agent {
+    name: String,
+    goal: String,
+}
+
+#[derive(Debug, PartialEq, MyoNode)]
+#[myo(type = "Agent")]
+struct AgentProfile {
+    name: String,
+    #[myo(rename = "goal")]
+    objective: Option<String>,
+    score: Option<i64>,
+    tags: Vec<String>,
+    weights: HashMap<String, f64>,
+    parent: Option<NodeRef>,
+    active: bool,
+}
+
+#[test]
+fn insert_and_get_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let agent = Agent {
+        name: "Iris".to_string(),
+        goal: "Explore".to_string(),
+    };
+    let id = mem.insert_typed(&agent)?;
+    mem.commit(None)?;
+
+    assert_eq!(mem.head_state()[&id].ty, "Agent");
+    assert_eq!(
+        mem.head_state()[&id].fields.get("goal"),
+        Some(&Value::Str("Explore".to_string()))
+    );
+    assert_eq!(mem.get_typed::<Agent>(id)?, agent);
+    assert_eq!(agent.to_node(id), mem.head_state()[&id]);
+    Ok(())
+}
+
+#[test]
+fn attributes_options_and_containers() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let parent = mem.insert_typed(&Agent {
+        name: "Root".to_string(),
+        goal: "Lead".to_string(),
+    })?;
+    let profile = AgentProfile {
+        name: "Iris".to_string(),
+        objective: None,
+        score: Some(3),
+        tags: vec!["a".to_string(), "b".to_string()],
+        weights: HashMap::from([("x".to_string(), 0.5)]),
+        parent: Some(NodeRef(parent)),
+        active: true,
+    };
+    let id = mem.insert_typed(&profile)?;
+    assert!(!mem.head_state()[&id].fields.contains_key("goal"));
+    assert_eq!(mem.get_typed::<AgentProfile>(id)?, profile);
+
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    let loaded = mem.get_typed::<AgentProfile>(id)?;
+    assert_eq!(loaded.objective.as_deref(), Some("Explore"));
+    assert_eq!(
+        mem.get_typed::<AgentProfile>(parent)
+            .unwrap_err()
+            .to_string(),
+        "Field not found: tags"
+    );
+    Ok(())
+}
+
+#[test]
+fn wrong_type_and_shape_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Task");
+    mem.set(id, "name", Value::Str("x".to_string()))?;
+    mem.set(id, "goal", Value::Int(1))?;
+    assert!(matches!(
+        mem.get_typed::<Agent>(id),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        mem.get_typed::<Agent>(99),
+        Err(MyosotisError::NodeNotFound(99))
+    ));
+
+    let agent = mem.create("Agent");
+    mem.set(agent, "name", Value::Str("x".to_string()))?;
+    mem.set(agent, "goal", Value::Int(1))?;
+    assert!(matches!(
+        mem.get_typed::<Agent>(agent),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+
+    mem.delete_node(agent)?;
+    assert!(matches!(
+        mem.get_typed::<Agent>(agent),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn node_getters_and_get_field() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let other = mem.create("Agent");
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.set(id, "steps", Value::Int(3))?;
+    mem.set(id, "peer", Value::Ref(other))?;
+    mem.set(id, "tags", Value::List(vec![Value::Bool(true)]))?;
+
+    let node = &mem.head_state()[&id];
+    assert_eq!(node.get_str("goal"), Some("Explore"));
+    assert_eq!(node.get_int("steps"), Some(3));
+    assert_eq!(node.get_ref("peer"), Some(other));
+    assert_eq!(node.get_list("tags"), Some(&[Value::Bool(true)][..]));
+    assert_eq!(node.get_int("goal"), None);
+    assert_eq!(node.get_str("missing"), None);
+    assert_eq!(node.get_map("tags"), None);
+
+    assert_eq!(mem.get_field(id, "steps")?.as_int(), Some(3));
+    assert!(matches!(
+        mem.get_field(id, "missing"),
+        Err(MyosotisError::FieldNotFound(_))
+    ));
+    assert!(matches!(
+        mem.get_field(99, "goal"),
+        Err(MyosotisError::NodeNotFound(99))
+    ));
+    mem.delete_node(other)?;
+    assert!(matches!(
+        mem.get_field(other, "goal"),
+        Err(MyosotisError::NodeDeleted(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn name(s: &str) -> Value {
+    Value::Str(s.to_string())
+}
+
+#[test]
+fn unique_constraint_rejects_duplicate_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    mem.add_unique_constraint("Agent", "name")?;
+    let a = mem.create("Agent");
+    mem.set(a, "name", name("scout"))?;
+    // Other types may share the value.
+    let task = mem.create("Task");
+    mem.set(task, "name", name("scout"))?;
+    mem.commit(None)?;
+    assert_eq!(mem.find_unique("Agent", "name", &name("scout")), Some(a));
+
+    let b = mem.create("Agent");
+    mem.set(b, "name", name("scout"))?;
+    assert!(matches!(
+        mem.dry_run_commit(None, &[]),
+        Err(MyosotisError::UniqueViolation(..))
+    ));
+    assert!(matches!(
+        mem.commit(None),
+        Err(MyosotisError::UniqueViolation(field, x, y)) if field == "Agent.name" && (x, y) == (a, b)
+    ));
+    assert_eq!(mem.commits.len(), 1);
+
+    // Renaming the holder in the same commit frees the value.
+    mem.set(a, "name", name("planner"))?;
+    mem.commit(None)?;
+    assert_eq!(mem.find_unique("Agent", "name", &name("scout")), Some(b));
+    assert_eq!(mem.find_unique("Agent", "name", &name("planner")), Some(a));
+
+    // Deleted nodes do not hold values.
+    mem.delete_node(b)?;
+    let c = mem.create("Agent");
+    mem.set(c, "name", name("scout"))?;
+    mem.commit(None)?;
+    assert_eq!(mem.find_unique("Agent", "name", &name("scout")), Some(c));
+    Ok(())
+}
+
+#[test]
+fn unique_constraints_are_checked_when_added_and_loaded() -> Result<(), Box<dyn std::error::Error>>
+{
+    let path = "test_unique_load.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let a = mem.create("Agent");
+    let b = mem.create("Agent");
+    mem.set(a, "name", name("scout"))?;
+    mem.set(b, "name", name("scout"))?;
+    mem.commit(None)?;
+    assert!(matches!(
+        mem.add_unique_constraint("Agent", "name"),
+        Err(MyosotisError::UniqueViolation(..))
+    ));
+    assert!(mem.unique_constraints().is_empty());
+
+    mem.set(b, "name", name("planner"))?;
+    mem.commit(None)?;
+    mem.add_unique_constraint("Agent", "name")?;
+    storage::save(path, &mem)?;
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.unique_constraints(), mem.unique_constraints());
+    assert_eq!(
+        loaded.find_unique("Agent", "name", &name("planner")),
+        Some(b)
+    );
+
+    // Resetting to a state that breaks the constraint is refused.
+    let mut reset = loaded.clone();
+    assert!(matches!(
+        reset.reset_to(1),
+        Err(MyosotisError::UniqueViolation(..))
+    ));
+    assert_eq!(reset.commits.len(), 2);
+
+    // A file edited to break the constraint fails to load, even without
+    // hash checks.
+    let data = fs::read_to_string(path)?.replace("\"planner\"", "\"scout\"");
+    fs::write(path, data)?;
+    assert!(matches!(
+        storage::load_with_mode(path, storage::LoadMode::Unsafe),
+        Err(MyosotisError::UniqueViolation(..))
+    ));
+    cleanup(path);
+    Ok(())
+}
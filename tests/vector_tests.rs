@@ -0,0 +1,110 @@
+use myosotis::node::{NodeId, Value};
+use myosotis::vector::{self, Match};
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn embedded(mem: &mut Memory, v: Vec<f32>) -> Result<NodeId, MyosotisError> {
+    let id = mem.create("Memory");
+    mem.set(id, "embedding", Value::Vector(v))?;
+    Ok(id)
+}
+
+#[test]
+fn similar_ranks_by_cosine() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let east = embedded(&mut mem, vec![1.0, 0.0])?;
+    let north = embedded(&mut mem, vec![0.0, 1.0])?;
+    let north_east = embedded(&mut mem, vec![2.0, 2.0])?;
+    let west = embedded(&mut mem, vec![-1.0, 0.0])?;
+    embedded(&mut mem, vec![1.0, 0.0, 0.0])?;
+    let plain = mem.create("Memory");
+    mem.set(plain, "embedding", Value::Str("not a vector".to_string()))?;
+    mem.commit(Some("memories".to_string()))?;
+
+    let found = mem.similar("embedding", &[1.0, 0.1], 3)?;
+    let ids: Vec<_> = found.iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![east, north_east, north]);
+    assert!(found[0].score > 0.99 && found[0].score <= 1.0);
+
+    let all = mem.similar("embedding", &[1.0, 0.0], 10)?;
+    assert_eq!(all.len(), 4);
+    assert_eq!(
+        all.last(),
+        Some(&Match {
+            id: west,
+            score: -1.0
+        })
+    );
+    assert!(mem.similar("missing", &[1.0, 0.0], 3)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn similar_skips_deleted_and_breaks_ties_by_id() -> Result<(), Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let a = embedded(&mut mem, vec![1.0, 1.0])?;
+    let b = embedded(&mut mem, vec![3.0, 3.0])?;
+    let c = embedded(&mut mem, vec![2.0, 2.0])?;
+    mem.delete_node(a)?;
+
+    let ids: Vec<_> = mem
+        .similar("embedding", &[1.0, 1.0], 5)?
+        .iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(ids, vec![b, c]);
+    Ok(())
+}
+
+#[test]
+fn similar_rejects_zero_query() {
+    let mem = Memory::new();
+    assert!(matches!(
+        mem.similar("embedding", &[], 3),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        mem.similar("embedding", &[0.0, 0.0], 3),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn cosine_similarity_edge_cases() {
+    assert_eq!(vector::cosine_similarity(&[1.0, 0.0], &[1.0]), None);
+    assert_eq!(vector::cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+    assert_eq!(
+        vector::cosine_similarity(&[f32::NAN, 1.0], &[1.0, 0.0]),
+        None
+    );
+    assert_eq!(
+        vector::cosine_similarity(&[0.0, 3.0], &[0.0, 0.5]),
+        Some(1.0)
+    );
+}
+
+#[test]
+fn vectors_survive_save_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_vector_roundtrip.myo";
+    cleanup(path);
+
+    let mut mem = Memory::new();
+    let id = embedded(&mut mem, vec![0.1, -0.25, 3.5])?;
+    mem.commit(Some("embed".to_string()))?;
+    storage::save(path, &mem)?;
+
+    let loaded = storage::load(path)?;
+    let node = &loaded.head_state()[&id];
+    assert_eq!(node.get_vector("embedding"), Some(&[0.1, -0.25, 3.5][..]));
+    assert_eq!(
+        Memory::compute_state_hash(loaded.head_state()),
+        Memory::compute_state_hash(mem.head_state())
+    );
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use myosotis::node::Value;
+use myosotis::{Memory, storage, verify};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn build(path: &str) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    Ok(mem)
+}
+
+#[test]
+fn report_describes_clean_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_verify_clean.myo";
+    cleanup(path);
+    let mem = build(path)?;
+
+    let report = verify::verify(path, true)?;
+    assert!(report.passed());
+    assert_eq!(report.head_commit, Some(1));
+    let head: String = mem.commits[0]
+        .hash
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    assert_eq!(report.head_hash, Some(head));
+    assert_eq!(report.file_sha256.len(), 64);
+    assert!(report.checks.iter().any(|c| c.name == "checkpoint-state"));
+    assert!(report.checks.iter().all(|c| c.passed));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn report_records_failures_with_locations() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_verify_failures.myo";
+    cleanup(path);
+    let mut mem = build(path)?;
+    mem.commits[0]
+        .mutations
+        .push(myosotis::commit::Mutation::CreateNode {
+            id: 1,
+            ty: "Agent".to_string(),
+        });
+    storage::save(path, &mem)?;
+
+    let report = verify::verify(path, false)?;
+    assert!(!report.passed());
+    let dup = report
+        .failures
+        .iter()
+        .find(|f| f.check == "duplicate-id")
+        .ok_or("missing duplicate-id failure")?;
+    assert_eq!((dup.commit, dup.node), (Some(1), Some(1)));
+    assert!(
+        report
+            .checks
+            .iter()
+            .any(|c| c.name == "duplicate-id" && !c.passed)
+    );
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn signatures_detect_tampering() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_verify_signature.myo";
+    cleanup(path);
+    build(path)?;
+
+    let mut report = verify::verify(path, false)?;
+    report.sign(Some(b"secret"))?;
+    assert_eq!(
+        report.signature.as_ref().map(|s| s.algorithm.as_str()),
+        Some("hmac-sha256")
+    );
+    assert!(report.signature_valid(Some(b"secret")));
+    assert!(!report.signature_valid(Some(b"other")));
+
+    let json = serde_json::to_string(&report)?;
+    let mut reloaded: verify::VerificationReport = serde_json::from_str(&json)?;
+    assert!(reloaded.signature_valid(Some(b"secret")));
+    reloaded.failures.clear();
+    reloaded.head_commit = Some(9);
+    assert!(!reloaded.signature_valid(Some(b"secret")));
+
+    report.sign(None)?;
+    assert!(report.signature_valid(None));
+
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use myosotis::node::Value;
+use myosotis::wal::{self, WalRecovery};
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+use std::io::Write;
+
+fn setup(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(wal::wal_path(path));
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("Explore".to_string()))?;
+    mem.commit(Some("init".to_string()))?;
+    storage::save(path, &mem)?;
+    Ok(())
+}
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(wal::wal_path(path));
+}
+
+#[test]
+fn staged_mutations_survive_a_crash() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_wal_restage.myo";
+    setup(path)?;
+
+    let mut mem = wal::load(path, WalRecovery::Restage)?;
+    let id = mem.create("Task");
+    mem.set(id, "title", Value::Str("Map the cave".to_string()))?;
+    mem.set(1, "goal", Value::Str("Rest".to_string()))?;
+    // The process dies here without committing.
+    drop(mem);
+
+    assert_eq!(wal::pending(path)?.len(), 3);
+    let mut mem = wal::load(path, WalRecovery::Restage)?;
+    assert_eq!(mem.pending_mutations().len(), 3);
+    assert_eq!(mem.get_field(1, "goal")?, &Value::Str("Rest".to_string()));
+    assert_eq!(mem.create("Task"), id + 1);
+
+    mem.commit(Some("recovered".to_string()))?;
+    storage::save(path, &mem)?;
+    assert!(wal::pending(path)?.is_empty());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn discard_undo_and_commit_rewrite_the_log() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_wal_rewrite.myo";
+    setup(path)?;
+
+    let mut mem = wal::load(path, WalRecovery::Restage)?;
+    assert_eq!(mem.wal_path(), Some(wal::wal_path(path).as_str()));
+    mem.set(1, "a", Value::Int(1))?;
+    mem.set(1, "b", Value::Int(2))?;
+    mem.undo_last_pending()?;
+    assert_eq!(wal::pending(path)?.len(), 1);
+    mem.discard_pending()?;
+    assert!(wal::pending(path)?.is_empty());
+
+    mem.set(1, "c", Value::Int(3))?;
+    drop(mem);
+    let mem = wal::load(path, WalRecovery::Discard)?;
+    assert!(mem.pending_mutations().is_empty());
+    assert!(wal::pending(path)?.is_empty());
+
+    // Clones do not write to the log.
+    let mut copy = mem.clone();
+    copy.set(1, "d", Value::Int(4))?;
+    assert!(wal::pending(path)?.is_empty());
+
+    let mut mem = mem;
+    mem.disable_wal()?;
+    assert!(!fs::exists(wal::wal_path(path))?);
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn stale_log_is_refused() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_wal_stale.myo";
+    setup(path)?;
+
+    let mut mem = wal::load(path, WalRecovery::Restage)?;
+    mem.set(1, "goal", Value::Str("Rest".to_string()))?;
+    drop(mem);
+
+    // Another writer moves the file on.
+    let mut other = storage::load(path)?;
+    other.set(1, "goal", Value::Str("Hide".to_string()))?;
+    other.commit(None)?;
+    storage::save(path, &other)?;
+
+    let err = wal::load(path, WalRecovery::Restage).unwrap_err();
+    assert!(matches!(err, MyosotisError::StaleWal(_)));
+    assert_eq!(wal::pending(path)?.len(), 1);
+
+    let mem = wal::load(path, WalRecovery::Discard)?;
+    assert!(mem.pending_mutations().is_empty());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn torn_last_entry_is_ignored() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_wal_torn.myo";
+    setup(path)?;
+
+    let mut mem = wal::load(path, WalRecovery::Restage)?;
+    mem.set(1, "step", Value::Int(1))?;
+    drop(mem);
+    let mut log = fs::OpenOptions::new()
+        .append(true)
+        .open(wal::wal_path(path))?;
+    log.write_all(br#"{"SetField":{"id":1,"key":"st"#)?;
+
+    let mem = wal::load(path, WalRecovery::Restage)?;
+    assert_eq!(mem.pending_mutations().len(), 1);
+    assert_eq!(mem.get_field(1, "step")?, &Value::Int(1));
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn staging_area_accumulates_until_commit() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_wal_staged.myo";
+    setup(path)?;
+    let _ = fs::remove_file(wal::staged_path(path));
+
+    let mut mem = wal::load_staged(path)?;
+    let id = mem.create("Task");
+    drop(mem);
+    let mut mem = wal::load_staged(path)?;
+    mem.set(id, "title", Value::Str("Map".to_string()))?;
+    drop(mem);
+    assert_eq!(wal::staged(path)?.len(), 2);
+    assert!(storage::load(path)?.head_state().get(&id).is_none());
+
+    let mem = wal::load_staged(path)?;
+    assert_eq!(mem.pending_summary().nodes, vec![id]);
+    let mut copy = mem.clone();
+    copy.commit(Some("staged".to_string()))?;
+    storage::save(path, &copy)?;
+    assert_eq!(wal::clear_staged(path)?, 2);
+    assert!(wal::staged(path)?.is_empty());
+    assert!(!fs::exists(wal::staged_path(path))?);
+
+    let loaded = storage::load(path)?;
+    assert_eq!(loaded.commits.len(), 2);
+    assert_eq!(
+        loaded.get_field(id, "title")?,
+        &Value::Str("Map".to_string())
+    );
+
+    let mut stale = wal::load_staged(path)?;
+    stale.create("Note");
+    drop(stale);
+    let mut other = storage::load(path)?;
+    other.commit_allow_empty(None)?;
+    storage::save(path, &other)?;
+    assert!(matches!(
+        wal::load_staged(path),
+        Err(MyosotisError::StaleWal(_))
+    ));
+    assert_eq!(wal::clear_staged(path)?, 1);
+    wal::load_staged(path)?;
+
+    let _ = fs::remove_file(wal::staged_path(path));
+    cleanup(path);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use myosotis::node::Value;
+use myosotis::{Memory, storage};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+}
+
+fn saved_with_commit(path: &str) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    let id = mem.create("Agent");
+    mem.set(id, "step", Value::Int(0))?;
+    mem.commit(Some("start".to_string()))?;
+    storage::save(path, &mem)?;
+    Ok(mem)
+}
+
+#[test]
+fn watch_reports_appended_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_watch_append.myo";
+    cleanup(path);
+    let mut mem = saved_with_commit(path)?;
+
+    let writer = thread::spawn(move || -> Result<(), String> {
+        thread::sleep(Duration::from_millis(200));
+        for step in 1..=2 {
+            mem.set(1, "step", Value::Int(step))
+                .map_err(|e| e.to_string())?;
+            mem.commit(Some(format!("step {}", step)))
+                .map_err(|e| e.to_string())?;
+        }
+        storage::save(path, &mem).map_err(|e| e.to_string())
+    });
+
+    let mut seen = Vec::new();
+    storage::watch(path, |update| {
+        assert!(!update.rewritten);
+        seen.extend(update.new_commits.iter().map(|c| c.message.clone()));
+        seen.len() < 2
+    })?;
+    writer.join().map_err(|_| "writer panicked")??;
+
+    assert_eq!(
+        seen,
+        vec![Some("step 1".to_string()), Some("step 2".to_string())]
+    );
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn watch_flags_rewritten_history() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_watch_rewrite.myo";
+    cleanup(path);
+    saved_with_commit(path)?;
+
+    let writer = thread::spawn(move || -> Result<(), String> {
+        thread::sleep(Duration::from_millis(200));
+        let mut other = Memory::new();
+        other.create("Other");
+        other
+            .commit(Some("unrelated".to_string()))
+            .map_err(|e| e.to_string())?;
+        storage::save(path, &other).map_err(|e| e.to_string())
+    });
+
+    let mut rewritten = None;
+    storage::watch(path, |update| {
+        rewritten = Some((update.rewritten, update.new_commits.len()));
+        false
+    })?;
+    writer.join().map_err(|_| "writer panicked")??;
+
+    assert_eq!(rewritten, Some((true, 1)));
+    cleanup(path);
+    Ok(())
+}
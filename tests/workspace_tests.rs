@@ -0,0 +1,121 @@
+use myosotis::node::Value;
+use myosotis::workspace::{DanglingRef, Workspace};
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(paths: &[&str]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn world_ref(node_id: myosotis::node::NodeId) -> Value {
+    Value::ExternalRef {
+        file_alias: "world".to_string(),
+        node_id,
+    }
+}
+
+#[test]
+fn external_refs_resolve_across_files() -> Result<(), Box<dyn std::error::Error>> {
+    let (world_path, agent_path) = ("test_ws_world.myo", "test_ws_agent.myo");
+    cleanup(&[world_path, agent_path]);
+
+    let mut world = Memory::new();
+    let city = world.create("City");
+    world.set(city, "name", Value::Str("Lisbon".to_string()))?;
+    world.commit(Some("world".to_string()))?;
+    storage::save(world_path, &world)?;
+
+    let mut ws = Workspace::new();
+    ws.open("world", world_path)?;
+    ws.add("agent", agent_path, Memory::new())?;
+    let agent = ws.memory_mut("agent").ok_or("agent missing")?;
+    let me = agent.create("Agent");
+    agent.set(me, "home", world_ref(city))?;
+    agent.commit(Some("home".to_string()))?;
+
+    let home = ws
+        .memory("agent")
+        .ok_or("agent missing")?
+        .get_field(me, "home")?;
+    let node = ws.resolve(home).ok_or("unresolved")?;
+    assert_eq!(node.get_str("name"), Some("Lisbon"));
+    assert!(ws.dangling_refs().is_empty());
+    ws.save()?;
+
+    let mut reopened = Workspace::new();
+    reopened.open("agent", agent_path)?;
+    reopened.open("world", world_path)?;
+    assert_eq!(
+        reopened.aliases().collect::<Vec<_>>(),
+        vec!["agent", "world"]
+    );
+    assert!(reopened.dangling_refs().is_empty());
+
+    cleanup(&[world_path, agent_path]);
+    Ok(())
+}
+
+#[test]
+fn save_refuses_dangling_refs() -> Result<(), Box<dyn std::error::Error>> {
+    let (world_path, agent_path) = ("test_ws_dangling_world.myo", "test_ws_dangling_agent.myo");
+    cleanup(&[world_path, agent_path]);
+
+    let mut world = Memory::new();
+    let city = world.create("City");
+    world.commit(Some("world".to_string()))?;
+
+    let mut agent = Memory::new();
+    let me = agent.create("Agent");
+    agent.set(
+        me,
+        "places",
+        Value::List(vec![world_ref(city), world_ref(city + 1)]),
+    )?;
+    agent.commit(Some("places".to_string()))?;
+
+    let mut ws = Workspace::new();
+    ws.add("world", world_path, world)?;
+    ws.add("agent", agent_path, agent)?;
+    assert_eq!(
+        ws.dangling_refs(),
+        vec![DanglingRef {
+            file: "agent".to_string(),
+            node_id: me,
+            key: "places".to_string(),
+            target_file: "world".to_string(),
+            target_id: city + 1,
+        }]
+    );
+    assert!(matches!(
+        ws.save(),
+        Err(MyosotisError::DanglingExternalRef(ref file, id)) if file == "world" && id == city + 1
+    ));
+    assert!(!storage::exists(world_path) && !storage::exists(agent_path));
+
+    // Deleting the target makes the other ref dangle too.
+    let world = ws.memory_mut("world").ok_or("world missing")?;
+    world.delete_node(city)?;
+    world.commit(Some("gone".to_string()))?;
+    assert_eq!(ws.dangling_refs().len(), 2);
+
+    cleanup(&[world_path, agent_path]);
+    Ok(())
+}
+
+#[test]
+fn unknown_file_alias_dangles() -> Result<(), Box<dyn std::error::Error>> {
+    let mut agent = Memory::new();
+    let me = agent.create("Agent");
+    agent.set(me, "home", world_ref(1))?;
+
+    let mut ws = Workspace::new();
+    ws.add("agent", "unused.myo", agent)?;
+    assert_eq!(ws.dangling_refs().len(), 1);
+    assert!(matches!(
+        ws.add("agent", "other.myo", Memory::new()),
+        Err(MyosotisError::InvalidInput(_))
+    ));
+    Ok(())
+}
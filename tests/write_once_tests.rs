@@ -0,0 +1,123 @@
+use myosotis::maintenance;
+use myosotis::node::Value;
+use myosotis::{Memory, MyosotisError, storage};
+use std::fs;
+
+fn cleanup(path: &str) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(format!("{}.tmp", path));
+    let _ = fs::remove_file(storage::segment_path(path));
+}
+
+fn is_violation(err: &MyosotisError) -> bool {
+    matches!(err, MyosotisError::WriteOnceViolation(_))
+}
+
+fn build(path: &str) -> Result<Memory, Box<dyn std::error::Error>> {
+    let mut mem = Memory::new();
+    mem.write_once = true;
+    let id = mem.create("Agent");
+    mem.set(id, "goal", Value::Str("explore".to_string()))?;
+    mem.commit(None)?;
+    mem.set(id, "goal", Value::Str("rest".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    Ok(mem)
+}
+
+#[test]
+fn write_once_flag_persists_and_allows_appends() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_write_once_append.myo";
+    cleanup(path);
+    build(path)?;
+
+    let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    assert_eq!(raw["write_once"], serde_json::json!(true));
+
+    let mut mem = storage::load(path)?;
+    assert!(mem.write_once);
+    mem.set(1, "goal", Value::Str("plan".to_string()))?;
+    mem.commit(None)?;
+    storage::save(path, &mem)?;
+    assert_eq!(storage::load(path)?.commits.len(), 3);
+    assert!(storage::open_lazy(path)?.write_once);
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn write_once_refuses_rewrites() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_write_once_rewrite.myo";
+    cleanup(path);
+    let mem = build(path)?;
+    let original = fs::read_to_string(path)?;
+
+    let err = maintenance::compact(path, None).unwrap_err();
+    assert!(is_violation(&err));
+
+    let mut truncated = mem.clone();
+    truncated.commits.pop();
+    assert!(is_violation(&storage::save(path, &truncated).unwrap_err()));
+
+    let mut rewritten = mem.clone();
+    rewritten.commits[1].message = Some("edited".to_string());
+    assert!(is_violation(&storage::save(path, &rewritten).unwrap_err()));
+
+    let mut cleared = mem.clone();
+    cleared.write_once = false;
+    assert!(is_violation(&storage::save(path, &cleared).unwrap_err()));
+
+    assert!(is_violation(
+        &storage::save(path, &Memory::new()).unwrap_err()
+    ));
+    assert_eq!(fs::read_to_string(path)?, original);
+    assert!(maintenance::advise(path)?.recommendation.is_none());
+
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn write_once_covers_commits_in_the_segment_log() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_write_once_segments.myo";
+    cleanup(path);
+    let mut mem = Memory::new();
+    mem.write_once = true;
+    let id = mem.create("Agent");
+    mem.commit(None)?;
+    storage::save_incremental(path, &mem)?;
+    let mut divergent = mem.clone();
+
+    // Commits 2 and 3 are only in the segment log.
+    for k in 0..2 {
+        mem.set(id, "k", Value::Int(k))?;
+        mem.commit(None)?;
+    }
+    storage::save_incremental(path, &mem)?;
+    assert_eq!(storage::stream_commits(path)?.count(), 1);
+
+    divergent.set(id, "k", Value::Int(99))?;
+    divergent.commit(None)?;
+    assert!(is_violation(&storage::save(path, &divergent).unwrap_err()));
+    assert_eq!(storage::load(path)?.commits.len(), 3);
+
+    storage::save(path, &mem)?;
+    assert_eq!(storage::load(path)?.commits.len(), 3);
+    cleanup(path);
+    Ok(())
+}
+
+#[test]
+fn regular_files_omit_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let path = "test_write_once_default.myo";
+    cleanup(path);
+
+    storage::save(path, &Memory::new())?;
+    let raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    assert!(raw.get("write_once").is_none());
+    storage::save(path, &Memory::new())?;
+
+    cleanup(path);
+    Ok(())
+}